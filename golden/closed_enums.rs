@@ -0,0 +1,143 @@
+// This file is @generated by protoc-gen-ks. Do not edit.
+
+#[allow(unused_imports)]
+use ::ks_protobuf_v3::{
+    Fixed32 as _, Fixed32Field as _, Fixed64 as _, Fixed64Field as _, Group as _, GroupField as _,
+    LengthDelimited as _, LengthDelimitedField as _, MapField as _, Message as _, Oneof as _,
+    RepeatedField as _, RepeatedGroupField as _, SignExtendedVarint as _,
+    SignExtendedVarintField as _, Varint as _, VarintField as _,
+};
+/// The encoded `FileDescriptorSet` of the files this module was generated from and the files they import.
+pub const FILE_DESCRIPTOR_SET: &[u8] = b"\n\xb9\x02\n\x12closed_enums.proto\x12\x0cclosed_enums\"\xee\x01\n\x05Entry\x12)\n\x05level\x18\x01 \x01(\x0e2\x13.closed_enums.LevelR\x05level\x12+\n\x06levels\x18\x02 \x03(\x0e2\x13.closed_enums.LevelR\x06levels\x12<\n\rpacked_levels\x18\x03 \x03(\x0e2\x13.closed_enums.LevelB\x02\x10\x01R\x0cpackedLevels\x12-\n\x06chosen\x18\x04 \x01(\x0e2\x13.closed_enums.LevelH\x00R\x06chosen\x12\x16\n\x05other\x18\x05 \x01(\tH\x00R\x05otherB\x08\n\x06choice*$\n\x05Level\x12\x08\n\x04NONE\x10\x00\x12\x07\n\x03LOW\x10\x01\x12\x08\n\x04HIGH\x10\x02";
+
+/// Returns the pool holding the descriptors of this module, which its messages return through `ReflectMessage`.
+pub fn descriptor_pool() -> &'static ::ks_protobuf_v3::DescriptorPool {
+    static POOL: ::std::sync::OnceLock<::ks_protobuf_v3::DescriptorPool> = ::std::sync::OnceLock::new();
+    POOL.get_or_init(|| ::ks_protobuf_v3::DescriptorPool::decode(FILE_DESCRIPTOR_SET).expect("the embedded descriptors are valid"))
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Entry {
+    pub level: Option<::ks_protobuf_v3::OpenEnum<Level>>,
+    pub levels: Vec<::ks_protobuf_v3::OpenEnum<Level>>,
+    pub packed_levels: Vec<::ks_protobuf_v3::OpenEnum<Level>>,
+    pub choice: Option<entry::Choice>,
+    pub unknown_fields: ::ks_protobuf_v3::UnknownFields,
+}
+
+impl Entry {
+    /// Returns the value of the `level` field, or its default when it is not set.
+    pub fn level(&self) -> Level {
+        self.level.and_then(|v| v.known()).unwrap_or(Level::None)
+    }
+}
+
+impl ::ks_protobuf_v3::Message for Entry {
+    fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+        let mut size = 0;
+        size += self.level.to_varint_field::<1>(buffer);
+        size += self.levels.to_unpacked_field::<::ks_protobuf_v3::VarintCodec, 2>(buffer);
+        size += self.packed_levels.to_packed_field::<::ks_protobuf_v3::VarintCodec, 3>(buffer);
+        if let Some(oneof) = &self.choice { size += oneof.encode(buffer); }
+        size += self.unknown_fields.encode(buffer);
+        size
+    }
+
+    fn decode_field(&mut self, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, ::ks_protobuf_v3::WireType::Varint) => ::ks_protobuf_v3::decode_closed_enum(buffer, |v| self.level = Some(v)),
+            (2, _) => ::ks_protobuf_v3::decode_closed_enums(&mut self.levels, &mut self.unknown_fields, tag, buffer),
+            (3, _) => ::ks_protobuf_v3::decode_closed_enums(&mut self.packed_levels, &mut self.unknown_fields, tag, buffer),
+            (4 | 5, _) => entry::Choice::decode_field(&mut self.choice, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+
+    fn is_singular_field(&self, field_number: u32) -> bool { matches!(field_number, 1 | 4 | 5) }
+
+    fn unknown_fields_mut(&mut self) -> Option<&mut ::ks_protobuf_v3::UnknownFields> { Some(&mut self.unknown_fields) }
+
+    fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        if ::ks_protobuf_v3::Presence::is_present(&other.level) { self.level = other.level; }
+        self.levels.extend(other.levels);
+        self.packed_levels.extend(other.packed_levels);
+        ::ks_protobuf_v3::merge_oneof(&mut self.choice, other.choice)?;
+        self.unknown_fields.merge(other.unknown_fields);
+        Ok(())
+    }
+}
+
+impl ::ks_protobuf_v3::ReflectMessage for Entry {
+    fn descriptor(&self) -> ::ks_protobuf_v3::MessageDescriptor {
+        descriptor_pool().get_message_by_name("closed_enums.Entry").expect("the pool holds the message")
+    }
+
+    fn transcode_to_dynamic(&self) -> Result<::ks_protobuf_v3::DynamicMessage, ::ks_protobuf_v3::DecodeError> {
+        ::ks_protobuf_v3::DynamicMessage::transcode_from(::ks_protobuf_v3::ReflectMessage::descriptor(self), self)
+    }
+
+    fn transcode_from_dynamic(&mut self, message: &::ks_protobuf_v3::DynamicMessage) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        *self = message.transcode_to()?;
+        Ok(())
+    }
+}
+
+/// Nested types and oneof groups of `Entry`.
+pub mod entry {
+    #[allow(unused_imports)]
+    use ::ks_protobuf_v3::{
+        Fixed32 as _, Fixed32Field as _, Fixed64 as _, Fixed64Field as _, Group as _, GroupField as _,
+        LengthDelimited as _, LengthDelimitedField as _, MapField as _, Message as _, Oneof as _,
+        RepeatedField as _, RepeatedGroupField as _, SignExtendedVarint as _,
+        SignExtendedVarintField as _, Varint as _, VarintField as _,
+    };
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Choice {
+        Chosen(::ks_protobuf_v3::OpenEnum<super::Level>),
+        Other(String),
+    }
+
+    #[allow(unreachable_patterns)]
+    impl ::ks_protobuf_v3::Oneof for Choice {
+        fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+            match self {
+                Choice::Chosen(v) => v.to_varint_field_always::<4>(buffer),
+                Choice::Other(v) => v.to_length_delimited_field_always::<5>(buffer),
+            }
+        }
+
+        fn decode_field(oneof: &mut Option<Self>, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+            match (tag.field_number(), tag.wire_type()) {
+                (4, ::ks_protobuf_v3::WireType::Varint) => ::ks_protobuf_v3::decode_closed_enum(buffer, |v| *oneof = Some(Choice::Chosen(v))),
+                (5, ::ks_protobuf_v3::WireType::LengthDelimited) => ::ks_protobuf_v3::decode_oneof_member(oneof, buffer, <String as ::ks_protobuf_v3::LengthDelimited>::from_length_delimited, Choice::Other).map(Some),
+                _ => Ok(None),
+            }
+        }
+    }
+
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Level {
+    None = 0,
+    Low = 1,
+    High = 2,
+}
+
+impl ::ks_protobuf_v3::Enumeration for Level {
+    const VALUES: &'static [Self] = &[Level::None, Level::Low, Level::High];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Level::None => "NONE",
+            Level::Low => "LOW",
+            Level::High => "HIGH",
+        }
+    }
+}
+
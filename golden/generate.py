@@ -0,0 +1,131 @@
+"""Regenerates the golden payloads with the reference Python implementation of protobuf.
+
+Usage: protoc --descriptor_set_out=golden.pb golden.proto && python3 generate.py golden.pb
+
+This needs protoc 21.5 and the protobuf 4.21.5 Python package, see README.md.
+"""
+
+import sys
+
+from google.protobuf import descriptor_pb2, descriptor_pool, message_factory
+
+file_set = descriptor_pb2.FileDescriptorSet()
+with open(sys.argv[1], "rb") as f:
+    file_set.ParseFromString(f.read())
+messages = message_factory.GetMessages(list(file_set.file), pool=descriptor_pool.DescriptorPool())
+
+
+def message(type_name, **fields):
+    return messages["golden." + type_name](**fields)
+
+
+def scalars():
+    return message(
+        "Scalars",
+        int32_value=2147483647,
+        int64_value=9223372036854775807,
+        uint32_value=4294967295,
+        uint64_value=18446744073709551615,
+        sint32_value=-2147483648,
+        sint64_value=-9223372036854775808,
+        fixed32_value=4294967295,
+        fixed64_value=18446744073709551615,
+        sfixed32_value=-2147483648,
+        sfixed64_value=-9223372036854775808,
+        float_value=3.5,
+        double_value=-1.25e-300,
+        bool_value=True,
+        string_value="héllo €\U0001f600",
+        bytes_value=b"\x00\x01\xfe\xff",
+        enum_value=2,
+    )
+
+
+def negatives():
+    return message(
+        "Scalars",
+        int32_value=-1,
+        int64_value=-1,
+        sint32_value=-1,
+        sint64_value=-1,
+        sfixed32_value=-1,
+        sfixed64_value=-1,
+        float_value=-0.0,
+        double_value=-1e300,
+        enum_value=-1,
+    )
+
+
+def repeated(name):
+    fields = dict(
+        int32_values=[0, 1, -1, 2147483647, -2147483648],
+        int64_values=[0, 300, -300, 9223372036854775807, -9223372036854775808],
+        uint32_values=[0, 127, 128, 4294967295],
+        uint64_values=[0, 16383, 16384, 18446744073709551615],
+        sint32_values=[0, -1, 1, -2147483648, 2147483647],
+        sint64_values=[0, -1, 1, -9223372036854775808, 9223372036854775807],
+        fixed32_values=[0, 1, 4294967295],
+        fixed64_values=[0, 1, 18446744073709551615],
+        sfixed32_values=[0, -1, 2147483647],
+        sfixed64_values=[0, -1, 9223372036854775807],
+        float_values=[0.0, -1.5, 1e30],
+        double_values=[0.0, 2.5, -1e-300],
+        bool_values=[True, False, True],
+        enum_values=[0, 1, -1, 2],
+    )
+    if name == "Unpacked":
+        fields.update(string_values=["", "a", "é"], bytes_values=[b"", b"\x00\xff"])
+    return message(name, **fields)
+
+
+def maps():
+    value = message("Maps")
+    value.string_to_int32.update({"": 0, "a": -1, "b": 2147483647})
+    value.int32_to_string.update({-5: "minus five", 0: "", 7: "seven"})
+    value.int64_to_message[-9223372036854775808].name = "min"
+    value.int64_to_message[1].child.name = "child"
+    value.uint32_to_enum.update({0: 1, 4294967295: -1})
+    value.sint64_to_double.update({-1: 0.5, 1: -0.5})
+    value.bool_to_bytes.update({False: b"", True: b"\xff"})
+    value.fixed32_to_fixed64.update({1: 18446744073709551615, 4294967295: 0})
+    return value
+
+
+def nested():
+    value = message("Nested", name="root")
+    value.child.name = "child"
+    value.child.child.child.name = "great grandchild"
+    value.child.number = 5
+    value.children.add(name="first", text="text")
+    value.children.add()
+    value.children.add(name="third").other.name = "other"
+    value.scalars.CopyFrom(negatives())
+    return value
+
+
+# Parsing Scalars as PartialScalars keeps the fields it does not know, which are serialized after
+# the known fields.
+def unknown():
+    value = message("PartialScalars")
+    value.ParseFromString(scalars().SerializeToString())
+    return value
+
+
+PAYLOADS = {
+    "scalars": (scalars, "Scalars"),
+    "negatives": (negatives, "Scalars"),
+    "packed": (lambda: repeated("Packed"), "Packed"),
+    "unpacked": (lambda: repeated("Unpacked"), "Unpacked"),
+    "maps": (maps, "Maps"),
+    "nested": (nested, "Nested"),
+    "unknown": (unknown, "PartialScalars"),
+}
+
+for name, (build, read_as) in PAYLOADS.items():
+    payload = build().SerializeToString(deterministic=True)
+    with open(name + ".bin", "wb") as f:
+        f.write(payload)
+
+    decoded = messages["golden." + read_as]()
+    decoded.ParseFromString(payload)
+    assert decoded.SerializeToString(deterministic=True) == payload
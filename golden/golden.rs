@@ -0,0 +1,556 @@
+// This file is @generated by protoc-gen-ks. Do not edit.
+
+#[allow(unused_imports)]
+use ::ks_protobuf_v3::{
+    Fixed32 as _, Fixed32Field as _, Fixed64 as _, Fixed64Field as _, Group as _, GroupField as _,
+    LengthDelimited as _, LengthDelimitedField as _, MapField as _, Message as _, Oneof as _,
+    RepeatedField as _, RepeatedGroupField as _, SignExtendedVarint as _,
+    SignExtendedVarintField as _, Varint as _, VarintField as _,
+};
+/// The encoded `FileDescriptorSet` of the files this module was generated from and the files they import.
+pub const FILE_DESCRIPTOR_SET: &[u8] = b"\n\xe8\x19\n\x0cgolden.proto\x12\x06golden\"\xc5\x04\n\x07Scalars\x12\x1f\n\x0bint32_value\x18\x01 \x01(\x05R\nint32Value\x12\x1f\n\x0bint64_value\x18\x02 \x01(\x03R\nint64Value\x12!\n\x0cuint32_value\x18\x03 \x01(\rR\x0buint32Value\x12!\n\x0cuint64_value\x18\x04 \x01(\x04R\x0buint64Value\x12!\n\x0csint32_value\x18\x05 \x01(\x11R\x0bsint32Value\x12!\n\x0csint64_value\x18\x06 \x01(\x12R\x0bsint64Value\x12#\n\rfixed32_value\x18\x07 \x01(\x07R\x0cfixed32Value\x12#\n\rfixed64_value\x18\x08 \x01(\x06R\x0cfixed64Value\x12%\n\x0esfixed32_value\x18\t \x01(\x0fR\rsfixed32Value\x12%\n\x0esfixed64_value\x18\n \x01(\x10R\rsfixed64Value\x12\x1f\n\x0bfloat_value\x18\x0b \x01(\x02R\nfloatValue\x12!\n\x0cdouble_value\x18\x0c \x01(\x01R\x0bdoubleValue\x12\x1d\n\nbool_value\x18\r \x01(\x08R\tboolValue\x12!\n\x0cstring_value\x18\x0e \x01(\tR\x0bstringValue\x12\x1f\n\x0bbytes_value\x18\x0f \x01(\x0cR\nbytesValue\x12-\n\nenum_value\x18\x10 \x01(\x0e2\x0e.golden.ColourR\tenumValue\"\x9c\x04\n\x06Packed\x12!\n\x0cint32_values\x18\x01 \x03(\x05R\x0bint32Values\x12!\n\x0cint64_values\x18\x02 \x03(\x03R\x0bint64Values\x12#\n\ruint32_values\x18\x03 \x03(\rR\x0cuint32Values\x12#\n\ruint64_values\x18\x04 \x03(\x04R\x0cuint64Values\x12#\n\rsint32_values\x18\x05 \x03(\x11R\x0csint32Values\x12#\n\rsint64_values\x18\x06 \x03(\x12R\x0csint64Values\x12%\n\x0efixed32_values\x18\x07 \x03(\x07R\rfixed32Values\x12%\n\x0efixed64_values\x18\x08 \x03(\x06R\rfixed64Values\x12\'\n\x0fsfixed32_values\x18\t \x03(\x0fR\x0esfixed32Values\x12\'\n\x0fsfixed64_values\x18\n \x03(\x10R\x0esfixed64Values\x12!\n\x0cfloat_values\x18\x0b \x03(\x02R\x0bfloatValues\x12#\n\rdouble_values\x18\x0c \x03(\x01R\x0cdoubleValues\x12\x1f\n\x0bbool_values\x18\r \x03(\x08R\nboolValues\x12/\n\x0benum_values\x18\x0e \x03(\x0e2\x0e.golden.ColourR\nenumValues\"\x9e\x05\n\x08Unpacked\x12%\n\x0cint32_values\x18\x01 \x03(\x05B\x02\x10\x00R\x0bint32Values\x12%\n\x0cint64_values\x18\x02 \x03(\x03B\x02\x10\x00R\x0bint64Values\x12\'\n\ruint32_values\x18\x03 \x03(\rB\x02\x10\x00R\x0cuint32Values\x12\'\n\ruint64_values\x18\x04 \x03(\x04B\x02\x10\x00R\x0cuint64Values\x12\'\n\rsint32_values\x18\x05 \x03(\x11B\x02\x10\x00R\x0csint32Values\x12\'\n\rsint64_values\x18\x06 \x03(\x12B\x02\x10\x00R\x0csint64Values\x12)\n\x0efixed32_values\x18\x07 \x03(\x07B\x02\x10\x00R\rfixed32Values\x12)\n\x0efixed64_values\x18\x08 \x03(\x06B\x02\x10\x00R\rfixed64Values\x12+\n\x0fsfixed32_values\x18\t \x03(\x0fB\x02\x10\x00R\x0esfixed32Values\x12+\n\x0fsfixed64_values\x18\n \x03(\x10B\x02\x10\x00R\x0esfixed64Values\x12%\n\x0cfloat_values\x18\x0b \x03(\x02B\x02\x10\x00R\x0bfloatValues\x12\'\n\rdouble_values\x18\x0c \x03(\x01B\x02\x10\x00R\x0cdoubleValues\x12#\n\x0bbool_values\x18\r \x03(\x08B\x02\x10\x00R\nboolValues\x123\n\x0benum_values\x18\x0e \x03(\x0e2\x0e.golden.ColourB\x02\x10\x00R\nenumValues\x12#\n\rstring_values\x18\x0f \x03(\tR\x0cstringValues\x12!\n\x0cbytes_values\x18\x10 \x03(\x0cR\x0bbytesValues\"\xfb\x07\n\x04Maps\x12G\n\x0fstring_to_int32\x18\x01 \x03(\x0b2\x1f.golden.Maps.StringToInt32EntryR\rstringToInt32\x12G\n\x0fint32_to_string\x18\x02 \x03(\x0b2\x1f.golden.Maps.Int32ToStringEntryR\rint32ToString\x12J\n\x10int64_to_message\x18\x03 \x03(\x0b2 .golden.Maps.Int64ToMessageEntryR\x0eint64ToMessage\x12D\n\x0euint32_to_enum\x18\x04 \x03(\x0b2\x1e.golden.Maps.Uint32ToEnumEntryR\x0cuint32ToEnum\x12J\n\x10sint64_to_double\x18\x05 \x03(\x0b2 .golden.Maps.Sint64ToDoubleEntryR\x0esint64ToDouble\x12A\n\rbool_to_bytes\x18\x06 \x03(\x0b2\x1d.golden.Maps.BoolToBytesEntryR\x0bboolToBytes\x12P\n\x12fixed32_to_fixed64\x18\x07 \x03(\x0b2\".golden.Maps.Fixed32ToFixed64EntryR\x10fixed32ToFixed64\x1a@\n\x12StringToInt32Entry\x12\x10\n\x03key\x18\x01 \x01(\tR\x03key\x12\x14\n\x05value\x18\x02 \x01(\x05R\x05value:\x028\x01\x1a@\n\x12Int32ToStringEntry\x12\x10\n\x03key\x18\x01 \x01(\x05R\x03key\x12\x14\n\x05value\x18\x02 \x01(\tR\x05value:\x028\x01\x1aQ\n\x13Int64ToMessageEntry\x12\x10\n\x03key\x18\x01 \x01(\x03R\x03key\x12$\n\x05value\x18\x02 \x01(\x0b2\x0e.golden.NestedR\x05value:\x028\x01\x1aO\n\x11Uint32ToEnumEntry\x12\x10\n\x03key\x18\x01 \x01(\rR\x03key\x12$\n\x05value\x18\x02 \x01(\x0e2\x0e.golden.ColourR\x05value:\x028\x01\x1aA\n\x13Sint64ToDoubleEntry\x12\x10\n\x03key\x18\x01 \x01(\x12R\x03key\x12\x14\n\x05value\x18\x02 \x01(\x01R\x05value:\x028\x01\x1a>\n\x10BoolToBytesEntry\x12\x10\n\x03key\x18\x01 \x01(\x08R\x03key\x12\x14\n\x05value\x18\x02 \x01(\x0cR\x05value:\x028\x01\x1aC\n\x15Fixed32ToFixed64Entry\x12\x10\n\x03key\x18\x01 \x01(\x07R\x03key\x12\x14\n\x05value\x18\x02 \x01(\x06R\x05value:\x028\x01\"\xfb\x01\n\x06Nested\x12\x12\n\x04name\x18\x01 \x01(\tR\x04name\x12$\n\x05child\x18\x02 \x01(\x0b2\x0e.golden.NestedR\x05child\x12*\n\x08children\x18\x03 \x03(\x0b2\x0e.golden.NestedR\x08children\x12)\n\x07scalars\x18\x04 \x01(\x0b2\x0f.golden.ScalarsR\x07scalars\x12\x18\n\x06number\x18\x05 \x01(\x05H\x00R\x06number\x12\x14\n\x04text\x18\x06 \x01(\tH\x00R\x04text\x12&\n\x05other\x18\x07 \x01(\x0b2\x0e.golden.NestedH\x00R\x05otherB\x08\n\x06choice\"w\n\x0ePartialScalars\x12\x1f\n\x0bint32_value\x18\x01 \x01(\x05R\nint32Value\x12!\n\x0csint64_value\x18\x06 \x01(\x12R\x0bsint64Value\x12!\n\x0cstring_value\x18\x0e \x01(\tR\x0bstringValue*K\n\x06Colour\x12\x16\n\x12COLOUR_UNSPECIFIED\x10\x00\x12\x07\n\x03RED\x10\x01\x12\t\n\x05GREEN\x10\x02\x12\x15\n\x08NEGATIVE\x10\xff\xff\xff\xff\xff\xff\xff\xff\xff\x01b\x06proto3";
+
+/// Returns the pool holding the descriptors of this module, which its messages return through `ReflectMessage`.
+pub fn descriptor_pool() -> &'static ::ks_protobuf_v3::DescriptorPool {
+    static POOL: ::std::sync::OnceLock<::ks_protobuf_v3::DescriptorPool> = ::std::sync::OnceLock::new();
+    POOL.get_or_init(|| ::ks_protobuf_v3::DescriptorPool::decode(FILE_DESCRIPTOR_SET).expect("the embedded descriptors are valid"))
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Scalars {
+    pub int32_value: i32,
+    pub int64_value: i64,
+    pub uint32_value: u32,
+    pub uint64_value: u64,
+    pub sint32_value: i32,
+    pub sint64_value: i64,
+    pub fixed32_value: u32,
+    pub fixed64_value: u64,
+    pub sfixed32_value: i32,
+    pub sfixed64_value: i64,
+    pub float_value: f32,
+    pub double_value: f64,
+    pub bool_value: bool,
+    pub string_value: String,
+    pub bytes_value: Vec<u8>,
+    pub enum_value: ::ks_protobuf_v3::OpenEnum<Colour>,
+}
+
+impl ::ks_protobuf_v3::Message for Scalars {
+    fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+        let mut size = 0;
+        size += self.int32_value.to_sign_extended_varint_field::<1>(buffer);
+        size += self.int64_value.to_sign_extended_varint_field::<2>(buffer);
+        size += self.uint32_value.to_varint_field::<3>(buffer);
+        size += self.uint64_value.to_varint_field::<4>(buffer);
+        size += self.sint32_value.to_varint_field::<5>(buffer);
+        size += self.sint64_value.to_varint_field::<6>(buffer);
+        size += self.fixed32_value.to_fixed32_field::<7>(buffer);
+        size += self.fixed64_value.to_fixed64_field::<8>(buffer);
+        size += self.sfixed32_value.to_fixed32_field::<9>(buffer);
+        size += self.sfixed64_value.to_fixed64_field::<10>(buffer);
+        size += self.float_value.to_fixed32_field::<11>(buffer);
+        size += self.double_value.to_fixed64_field::<12>(buffer);
+        size += self.bool_value.to_varint_field::<13>(buffer);
+        size += self.string_value.to_length_delimited_field::<14>(buffer);
+        size += self.bytes_value.to_length_delimited_field::<15>(buffer);
+        size += self.enum_value.to_varint_field::<16>(buffer);
+        size
+    }
+
+    fn decode_field(&mut self, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, ::ks_protobuf_v3::WireType::Varint) => self.int32_value.from_sign_extended_varint(buffer).map(Some),
+            (2, ::ks_protobuf_v3::WireType::Varint) => self.int64_value.from_sign_extended_varint(buffer).map(Some),
+            (3, ::ks_protobuf_v3::WireType::Varint) => self.uint32_value.from_varint(buffer).map(Some),
+            (4, ::ks_protobuf_v3::WireType::Varint) => self.uint64_value.from_varint(buffer).map(Some),
+            (5, ::ks_protobuf_v3::WireType::Varint) => self.sint32_value.from_varint(buffer).map(Some),
+            (6, ::ks_protobuf_v3::WireType::Varint) => self.sint64_value.from_varint(buffer).map(Some),
+            (7, ::ks_protobuf_v3::WireType::Fixed32) => self.fixed32_value.from_fixed32(buffer).map(Some),
+            (8, ::ks_protobuf_v3::WireType::Fixed64) => self.fixed64_value.from_fixed64(buffer).map(Some),
+            (9, ::ks_protobuf_v3::WireType::Fixed32) => self.sfixed32_value.from_fixed32(buffer).map(Some),
+            (10, ::ks_protobuf_v3::WireType::Fixed64) => self.sfixed64_value.from_fixed64(buffer).map(Some),
+            (11, ::ks_protobuf_v3::WireType::Fixed32) => self.float_value.from_fixed32(buffer).map(Some),
+            (12, ::ks_protobuf_v3::WireType::Fixed64) => self.double_value.from_fixed64(buffer).map(Some),
+            (13, ::ks_protobuf_v3::WireType::Varint) => self.bool_value.from_varint(buffer).map(Some),
+            (14, ::ks_protobuf_v3::WireType::LengthDelimited) => self.string_value.from_length_delimited(buffer).map(Some),
+            (15, ::ks_protobuf_v3::WireType::LengthDelimited) => self.bytes_value.from_length_delimited(buffer).map(Some),
+            (16, ::ks_protobuf_v3::WireType::Varint) => self.enum_value.from_varint(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn is_singular_field(&self, field_number: u32) -> bool { matches!(field_number, 1 | 2 | 3 | 4 | 5 | 6 | 7 | 8 | 9 | 10 | 11 | 12 | 13 | 14 | 15 | 16) }
+
+    fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        if ::ks_protobuf_v3::Presence::is_present(&other.int32_value) { self.int32_value = other.int32_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.int64_value) { self.int64_value = other.int64_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.uint32_value) { self.uint32_value = other.uint32_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.uint64_value) { self.uint64_value = other.uint64_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.sint32_value) { self.sint32_value = other.sint32_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.sint64_value) { self.sint64_value = other.sint64_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.fixed32_value) { self.fixed32_value = other.fixed32_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.fixed64_value) { self.fixed64_value = other.fixed64_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.sfixed32_value) { self.sfixed32_value = other.sfixed32_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.sfixed64_value) { self.sfixed64_value = other.sfixed64_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.float_value) { self.float_value = other.float_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.double_value) { self.double_value = other.double_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.bool_value) { self.bool_value = other.bool_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.string_value) { self.string_value = other.string_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.bytes_value) { self.bytes_value = other.bytes_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.enum_value) { self.enum_value = other.enum_value; }
+        Ok(())
+    }
+}
+
+impl ::ks_protobuf_v3::ReflectMessage for Scalars {
+    fn descriptor(&self) -> ::ks_protobuf_v3::MessageDescriptor {
+        descriptor_pool().get_message_by_name("golden.Scalars").expect("the pool holds the message")
+    }
+
+    fn transcode_to_dynamic(&self) -> Result<::ks_protobuf_v3::DynamicMessage, ::ks_protobuf_v3::DecodeError> {
+        ::ks_protobuf_v3::DynamicMessage::transcode_from(::ks_protobuf_v3::ReflectMessage::descriptor(self), self)
+    }
+
+    fn transcode_from_dynamic(&mut self, message: &::ks_protobuf_v3::DynamicMessage) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        *self = message.transcode_to()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Packed {
+    pub int32_values: Vec<i32>,
+    pub int64_values: Vec<i64>,
+    pub uint32_values: Vec<u32>,
+    pub uint64_values: Vec<u64>,
+    pub sint32_values: Vec<i32>,
+    pub sint64_values: Vec<i64>,
+    pub fixed32_values: Vec<u32>,
+    pub fixed64_values: Vec<u64>,
+    pub sfixed32_values: Vec<i32>,
+    pub sfixed64_values: Vec<i64>,
+    pub float_values: Vec<f32>,
+    pub double_values: Vec<f64>,
+    pub bool_values: Vec<bool>,
+    pub enum_values: Vec<::ks_protobuf_v3::OpenEnum<Colour>>,
+}
+
+impl ::ks_protobuf_v3::Message for Packed {
+    fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+        let mut size = 0;
+        size += self.int32_values.to_packed_field::<::ks_protobuf_v3::SignExtendedVarintCodec, 1>(buffer);
+        size += self.int64_values.to_packed_field::<::ks_protobuf_v3::SignExtendedVarintCodec, 2>(buffer);
+        size += self.uint32_values.to_packed_field::<::ks_protobuf_v3::VarintCodec, 3>(buffer);
+        size += self.uint64_values.to_packed_field::<::ks_protobuf_v3::VarintCodec, 4>(buffer);
+        size += self.sint32_values.to_packed_field::<::ks_protobuf_v3::VarintCodec, 5>(buffer);
+        size += self.sint64_values.to_packed_field::<::ks_protobuf_v3::VarintCodec, 6>(buffer);
+        size += self.fixed32_values.to_packed_field::<::ks_protobuf_v3::Fixed32Codec, 7>(buffer);
+        size += self.fixed64_values.to_packed_field::<::ks_protobuf_v3::Fixed64Codec, 8>(buffer);
+        size += self.sfixed32_values.to_packed_field::<::ks_protobuf_v3::Fixed32Codec, 9>(buffer);
+        size += self.sfixed64_values.to_packed_field::<::ks_protobuf_v3::Fixed64Codec, 10>(buffer);
+        size += self.float_values.to_packed_field::<::ks_protobuf_v3::Fixed32Codec, 11>(buffer);
+        size += self.double_values.to_packed_field::<::ks_protobuf_v3::Fixed64Codec, 12>(buffer);
+        size += self.bool_values.to_packed_field::<::ks_protobuf_v3::VarintCodec, 13>(buffer);
+        size += self.enum_values.to_packed_field::<::ks_protobuf_v3::VarintCodec, 14>(buffer);
+        size
+    }
+
+    fn decode_field(&mut self, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, wire_type) => self.int32_values.from_repeated_field::<::ks_protobuf_v3::SignExtendedVarintCodec>(wire_type, buffer),
+            (2, wire_type) => self.int64_values.from_repeated_field::<::ks_protobuf_v3::SignExtendedVarintCodec>(wire_type, buffer),
+            (3, wire_type) => self.uint32_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (4, wire_type) => self.uint64_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (5, wire_type) => self.sint32_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (6, wire_type) => self.sint64_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (7, wire_type) => self.fixed32_values.from_repeated_field::<::ks_protobuf_v3::Fixed32Codec>(wire_type, buffer),
+            (8, wire_type) => self.fixed64_values.from_repeated_field::<::ks_protobuf_v3::Fixed64Codec>(wire_type, buffer),
+            (9, wire_type) => self.sfixed32_values.from_repeated_field::<::ks_protobuf_v3::Fixed32Codec>(wire_type, buffer),
+            (10, wire_type) => self.sfixed64_values.from_repeated_field::<::ks_protobuf_v3::Fixed64Codec>(wire_type, buffer),
+            (11, wire_type) => self.float_values.from_repeated_field::<::ks_protobuf_v3::Fixed32Codec>(wire_type, buffer),
+            (12, wire_type) => self.double_values.from_repeated_field::<::ks_protobuf_v3::Fixed64Codec>(wire_type, buffer),
+            (13, wire_type) => self.bool_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (14, wire_type) => self.enum_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+
+    fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        self.int32_values.extend(other.int32_values);
+        self.int64_values.extend(other.int64_values);
+        self.uint32_values.extend(other.uint32_values);
+        self.uint64_values.extend(other.uint64_values);
+        self.sint32_values.extend(other.sint32_values);
+        self.sint64_values.extend(other.sint64_values);
+        self.fixed32_values.extend(other.fixed32_values);
+        self.fixed64_values.extend(other.fixed64_values);
+        self.sfixed32_values.extend(other.sfixed32_values);
+        self.sfixed64_values.extend(other.sfixed64_values);
+        self.float_values.extend(other.float_values);
+        self.double_values.extend(other.double_values);
+        self.bool_values.extend(other.bool_values);
+        self.enum_values.extend(other.enum_values);
+        Ok(())
+    }
+}
+
+impl ::ks_protobuf_v3::ReflectMessage for Packed {
+    fn descriptor(&self) -> ::ks_protobuf_v3::MessageDescriptor {
+        descriptor_pool().get_message_by_name("golden.Packed").expect("the pool holds the message")
+    }
+
+    fn transcode_to_dynamic(&self) -> Result<::ks_protobuf_v3::DynamicMessage, ::ks_protobuf_v3::DecodeError> {
+        ::ks_protobuf_v3::DynamicMessage::transcode_from(::ks_protobuf_v3::ReflectMessage::descriptor(self), self)
+    }
+
+    fn transcode_from_dynamic(&mut self, message: &::ks_protobuf_v3::DynamicMessage) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        *self = message.transcode_to()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Unpacked {
+    pub int32_values: Vec<i32>,
+    pub int64_values: Vec<i64>,
+    pub uint32_values: Vec<u32>,
+    pub uint64_values: Vec<u64>,
+    pub sint32_values: Vec<i32>,
+    pub sint64_values: Vec<i64>,
+    pub fixed32_values: Vec<u32>,
+    pub fixed64_values: Vec<u64>,
+    pub sfixed32_values: Vec<i32>,
+    pub sfixed64_values: Vec<i64>,
+    pub float_values: Vec<f32>,
+    pub double_values: Vec<f64>,
+    pub bool_values: Vec<bool>,
+    pub enum_values: Vec<::ks_protobuf_v3::OpenEnum<Colour>>,
+    pub string_values: Vec<String>,
+    pub bytes_values: Vec<Vec<u8>>,
+}
+
+impl ::ks_protobuf_v3::Message for Unpacked {
+    fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+        let mut size = 0;
+        size += self.int32_values.to_unpacked_field::<::ks_protobuf_v3::SignExtendedVarintCodec, 1>(buffer);
+        size += self.int64_values.to_unpacked_field::<::ks_protobuf_v3::SignExtendedVarintCodec, 2>(buffer);
+        size += self.uint32_values.to_unpacked_field::<::ks_protobuf_v3::VarintCodec, 3>(buffer);
+        size += self.uint64_values.to_unpacked_field::<::ks_protobuf_v3::VarintCodec, 4>(buffer);
+        size += self.sint32_values.to_unpacked_field::<::ks_protobuf_v3::VarintCodec, 5>(buffer);
+        size += self.sint64_values.to_unpacked_field::<::ks_protobuf_v3::VarintCodec, 6>(buffer);
+        size += self.fixed32_values.to_unpacked_field::<::ks_protobuf_v3::Fixed32Codec, 7>(buffer);
+        size += self.fixed64_values.to_unpacked_field::<::ks_protobuf_v3::Fixed64Codec, 8>(buffer);
+        size += self.sfixed32_values.to_unpacked_field::<::ks_protobuf_v3::Fixed32Codec, 9>(buffer);
+        size += self.sfixed64_values.to_unpacked_field::<::ks_protobuf_v3::Fixed64Codec, 10>(buffer);
+        size += self.float_values.to_unpacked_field::<::ks_protobuf_v3::Fixed32Codec, 11>(buffer);
+        size += self.double_values.to_unpacked_field::<::ks_protobuf_v3::Fixed64Codec, 12>(buffer);
+        size += self.bool_values.to_unpacked_field::<::ks_protobuf_v3::VarintCodec, 13>(buffer);
+        size += self.enum_values.to_unpacked_field::<::ks_protobuf_v3::VarintCodec, 14>(buffer);
+        size += self.string_values.to_unpacked_field::<::ks_protobuf_v3::LengthDelimitedCodec, 15>(buffer);
+        size += self.bytes_values.to_unpacked_field::<::ks_protobuf_v3::LengthDelimitedCodec, 16>(buffer);
+        size
+    }
+
+    fn decode_field(&mut self, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, wire_type) => self.int32_values.from_repeated_field::<::ks_protobuf_v3::SignExtendedVarintCodec>(wire_type, buffer),
+            (2, wire_type) => self.int64_values.from_repeated_field::<::ks_protobuf_v3::SignExtendedVarintCodec>(wire_type, buffer),
+            (3, wire_type) => self.uint32_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (4, wire_type) => self.uint64_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (5, wire_type) => self.sint32_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (6, wire_type) => self.sint64_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (7, wire_type) => self.fixed32_values.from_repeated_field::<::ks_protobuf_v3::Fixed32Codec>(wire_type, buffer),
+            (8, wire_type) => self.fixed64_values.from_repeated_field::<::ks_protobuf_v3::Fixed64Codec>(wire_type, buffer),
+            (9, wire_type) => self.sfixed32_values.from_repeated_field::<::ks_protobuf_v3::Fixed32Codec>(wire_type, buffer),
+            (10, wire_type) => self.sfixed64_values.from_repeated_field::<::ks_protobuf_v3::Fixed64Codec>(wire_type, buffer),
+            (11, wire_type) => self.float_values.from_repeated_field::<::ks_protobuf_v3::Fixed32Codec>(wire_type, buffer),
+            (12, wire_type) => self.double_values.from_repeated_field::<::ks_protobuf_v3::Fixed64Codec>(wire_type, buffer),
+            (13, wire_type) => self.bool_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (14, wire_type) => self.enum_values.from_repeated_field::<::ks_protobuf_v3::VarintCodec>(wire_type, buffer),
+            (15, wire_type) => self.string_values.from_repeated_field::<::ks_protobuf_v3::LengthDelimitedCodec>(wire_type, buffer),
+            (16, wire_type) => self.bytes_values.from_repeated_field::<::ks_protobuf_v3::LengthDelimitedCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+
+    fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        self.int32_values.extend(other.int32_values);
+        self.int64_values.extend(other.int64_values);
+        self.uint32_values.extend(other.uint32_values);
+        self.uint64_values.extend(other.uint64_values);
+        self.sint32_values.extend(other.sint32_values);
+        self.sint64_values.extend(other.sint64_values);
+        self.fixed32_values.extend(other.fixed32_values);
+        self.fixed64_values.extend(other.fixed64_values);
+        self.sfixed32_values.extend(other.sfixed32_values);
+        self.sfixed64_values.extend(other.sfixed64_values);
+        self.float_values.extend(other.float_values);
+        self.double_values.extend(other.double_values);
+        self.bool_values.extend(other.bool_values);
+        self.enum_values.extend(other.enum_values);
+        self.string_values.extend(other.string_values);
+        self.bytes_values.extend(other.bytes_values);
+        Ok(())
+    }
+}
+
+impl ::ks_protobuf_v3::ReflectMessage for Unpacked {
+    fn descriptor(&self) -> ::ks_protobuf_v3::MessageDescriptor {
+        descriptor_pool().get_message_by_name("golden.Unpacked").expect("the pool holds the message")
+    }
+
+    fn transcode_to_dynamic(&self) -> Result<::ks_protobuf_v3::DynamicMessage, ::ks_protobuf_v3::DecodeError> {
+        ::ks_protobuf_v3::DynamicMessage::transcode_from(::ks_protobuf_v3::ReflectMessage::descriptor(self), self)
+    }
+
+    fn transcode_from_dynamic(&mut self, message: &::ks_protobuf_v3::DynamicMessage) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        *self = message.transcode_to()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Maps {
+    pub string_to_int32: ::std::collections::HashMap<String, i32>,
+    pub int32_to_string: ::std::collections::HashMap<i32, String>,
+    pub int64_to_message: ::std::collections::HashMap<i64, Nested>,
+    pub uint32_to_enum: ::std::collections::HashMap<u32, ::ks_protobuf_v3::OpenEnum<Colour>>,
+    pub sint64_to_double: ::std::collections::HashMap<i64, f64>,
+    pub bool_to_bytes: ::std::collections::HashMap<bool, Vec<u8>>,
+    pub fixed32_to_fixed64: ::std::collections::HashMap<u32, u64>,
+}
+
+impl ::ks_protobuf_v3::Message for Maps {
+    fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+        let mut size = 0;
+        size += self.string_to_int32.to_map_field::<::ks_protobuf_v3::LengthDelimitedCodec, ::ks_protobuf_v3::SignExtendedVarintCodec, 1>(buffer);
+        size += self.int32_to_string.to_map_field::<::ks_protobuf_v3::SignExtendedVarintCodec, ::ks_protobuf_v3::LengthDelimitedCodec, 2>(buffer);
+        size += self.int64_to_message.to_map_field::<::ks_protobuf_v3::SignExtendedVarintCodec, ::ks_protobuf_v3::LengthDelimitedCodec, 3>(buffer);
+        size += self.uint32_to_enum.to_map_field::<::ks_protobuf_v3::VarintCodec, ::ks_protobuf_v3::VarintCodec, 4>(buffer);
+        size += self.sint64_to_double.to_map_field::<::ks_protobuf_v3::VarintCodec, ::ks_protobuf_v3::Fixed64Codec, 5>(buffer);
+        size += self.bool_to_bytes.to_map_field::<::ks_protobuf_v3::VarintCodec, ::ks_protobuf_v3::LengthDelimitedCodec, 6>(buffer);
+        size += self.fixed32_to_fixed64.to_map_field::<::ks_protobuf_v3::Fixed32Codec, ::ks_protobuf_v3::Fixed64Codec, 7>(buffer);
+        size
+    }
+
+    fn decode_field(&mut self, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, ::ks_protobuf_v3::WireType::LengthDelimited) => self.string_to_int32.from_map_entry::<::ks_protobuf_v3::LengthDelimitedCodec, ::ks_protobuf_v3::SignExtendedVarintCodec>(buffer).map(Some),
+            (2, ::ks_protobuf_v3::WireType::LengthDelimited) => self.int32_to_string.from_map_entry::<::ks_protobuf_v3::SignExtendedVarintCodec, ::ks_protobuf_v3::LengthDelimitedCodec>(buffer).map(Some),
+            (3, ::ks_protobuf_v3::WireType::LengthDelimited) => self.int64_to_message.from_map_entry::<::ks_protobuf_v3::SignExtendedVarintCodec, ::ks_protobuf_v3::LengthDelimitedCodec>(buffer).map(Some),
+            (4, ::ks_protobuf_v3::WireType::LengthDelimited) => self.uint32_to_enum.from_map_entry::<::ks_protobuf_v3::VarintCodec, ::ks_protobuf_v3::VarintCodec>(buffer).map(Some),
+            (5, ::ks_protobuf_v3::WireType::LengthDelimited) => self.sint64_to_double.from_map_entry::<::ks_protobuf_v3::VarintCodec, ::ks_protobuf_v3::Fixed64Codec>(buffer).map(Some),
+            (6, ::ks_protobuf_v3::WireType::LengthDelimited) => self.bool_to_bytes.from_map_entry::<::ks_protobuf_v3::VarintCodec, ::ks_protobuf_v3::LengthDelimitedCodec>(buffer).map(Some),
+            (7, ::ks_protobuf_v3::WireType::LengthDelimited) => self.fixed32_to_fixed64.from_map_entry::<::ks_protobuf_v3::Fixed32Codec, ::ks_protobuf_v3::Fixed64Codec>(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        self.string_to_int32.extend(other.string_to_int32);
+        self.int32_to_string.extend(other.int32_to_string);
+        self.int64_to_message.extend(other.int64_to_message);
+        self.uint32_to_enum.extend(other.uint32_to_enum);
+        self.sint64_to_double.extend(other.sint64_to_double);
+        self.bool_to_bytes.extend(other.bool_to_bytes);
+        self.fixed32_to_fixed64.extend(other.fixed32_to_fixed64);
+        Ok(())
+    }
+}
+
+impl ::ks_protobuf_v3::ReflectMessage for Maps {
+    fn descriptor(&self) -> ::ks_protobuf_v3::MessageDescriptor {
+        descriptor_pool().get_message_by_name("golden.Maps").expect("the pool holds the message")
+    }
+
+    fn transcode_to_dynamic(&self) -> Result<::ks_protobuf_v3::DynamicMessage, ::ks_protobuf_v3::DecodeError> {
+        ::ks_protobuf_v3::DynamicMessage::transcode_from(::ks_protobuf_v3::ReflectMessage::descriptor(self), self)
+    }
+
+    fn transcode_from_dynamic(&mut self, message: &::ks_protobuf_v3::DynamicMessage) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        *self = message.transcode_to()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Nested {
+    pub name: String,
+    pub child: Option<Box<Nested>>,
+    pub children: Vec<Nested>,
+    pub scalars: Option<Scalars>,
+    pub choice: Option<nested::Choice>,
+}
+
+impl ::ks_protobuf_v3::Message for Nested {
+    fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+        let mut size = 0;
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self.child.to_length_delimited_field::<2>(buffer);
+        size += self.children.to_unpacked_field::<::ks_protobuf_v3::LengthDelimitedCodec, 3>(buffer);
+        size += self.scalars.to_length_delimited_field::<4>(buffer);
+        if let Some(oneof) = &self.choice { size += oneof.encode(buffer); }
+        size
+    }
+
+    fn decode_field(&mut self, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, ::ks_protobuf_v3::WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, ::ks_protobuf_v3::WireType::LengthDelimited) => self.child.from_length_delimited(buffer).map(Some),
+            (3, wire_type) => self.children.from_repeated_field::<::ks_protobuf_v3::LengthDelimitedCodec>(wire_type, buffer),
+            (4, ::ks_protobuf_v3::WireType::LengthDelimited) => self.scalars.from_length_delimited(buffer).map(Some),
+            (5 | 6 | 7, _) => nested::Choice::decode_field(&mut self.choice, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+
+    fn is_singular_field(&self, field_number: u32) -> bool { matches!(field_number, 1 | 2 | 4 | 5 | 6 | 7) }
+
+    fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        if ::ks_protobuf_v3::Presence::is_present(&other.name) { self.name = other.name; }
+        ::ks_protobuf_v3::merge_message(&mut self.child, other.child)?;
+        self.children.extend(other.children);
+        ::ks_protobuf_v3::merge_message(&mut self.scalars, other.scalars)?;
+        ::ks_protobuf_v3::merge_oneof(&mut self.choice, other.choice)?;
+        Ok(())
+    }
+}
+
+impl ::ks_protobuf_v3::ReflectMessage for Nested {
+    fn descriptor(&self) -> ::ks_protobuf_v3::MessageDescriptor {
+        descriptor_pool().get_message_by_name("golden.Nested").expect("the pool holds the message")
+    }
+
+    fn transcode_to_dynamic(&self) -> Result<::ks_protobuf_v3::DynamicMessage, ::ks_protobuf_v3::DecodeError> {
+        ::ks_protobuf_v3::DynamicMessage::transcode_from(::ks_protobuf_v3::ReflectMessage::descriptor(self), self)
+    }
+
+    fn transcode_from_dynamic(&mut self, message: &::ks_protobuf_v3::DynamicMessage) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        *self = message.transcode_to()?;
+        Ok(())
+    }
+}
+
+/// Nested types and oneof groups of `Nested`.
+pub mod nested {
+    #[allow(unused_imports)]
+    use ::ks_protobuf_v3::{
+        Fixed32 as _, Fixed32Field as _, Fixed64 as _, Fixed64Field as _, Group as _, GroupField as _,
+        LengthDelimited as _, LengthDelimitedField as _, MapField as _, Message as _, Oneof as _,
+        RepeatedField as _, RepeatedGroupField as _, SignExtendedVarint as _,
+        SignExtendedVarintField as _, Varint as _, VarintField as _,
+    };
+
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Choice {
+        Number(i32),
+        Text(String),
+        Other(Box<super::Nested>),
+    }
+
+    #[allow(unreachable_patterns)]
+    impl ::ks_protobuf_v3::Oneof for Choice {
+        fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+            match self {
+                Choice::Number(v) => v.to_sign_extended_varint_field_always::<5>(buffer),
+                Choice::Text(v) => v.to_length_delimited_field_always::<6>(buffer),
+                Choice::Other(v) => v.to_length_delimited_field_always::<7>(buffer),
+            }
+        }
+
+        fn decode_field(oneof: &mut Option<Self>, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+            match (tag.field_number(), tag.wire_type()) {
+                (5, ::ks_protobuf_v3::WireType::Varint) => ::ks_protobuf_v3::decode_oneof_member(oneof, buffer, <i32 as ::ks_protobuf_v3::SignExtendedVarint>::from_sign_extended_varint, Choice::Number).map(Some),
+                (6, ::ks_protobuf_v3::WireType::LengthDelimited) => ::ks_protobuf_v3::decode_oneof_member(oneof, buffer, <String as ::ks_protobuf_v3::LengthDelimited>::from_length_delimited, Choice::Text).map(Some),
+                (7, ::ks_protobuf_v3::WireType::LengthDelimited) => ::ks_protobuf_v3::merge_oneof_message(oneof, buffer, |v| match v { Choice::Other(v) => Some(v), _ => None }, Choice::Other).map(Some),
+                _ => Ok(None),
+            }
+        }
+
+        fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+            match (self, other) {
+                (Choice::Other(v), Choice::Other(o)) => ::ks_protobuf_v3::Message::merge(v, o),
+                (this, other) => { *this = other; Ok(()) }
+            }
+        }
+    }
+
+}
+
+/// A view of Scalars that only knows some of its fields, so that the rest are unknown fields.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PartialScalars {
+    pub int32_value: i32,
+    pub sint64_value: i64,
+    pub string_value: String,
+}
+
+impl ::ks_protobuf_v3::Message for PartialScalars {
+    fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {
+        let mut size = 0;
+        size += self.int32_value.to_sign_extended_varint_field::<1>(buffer);
+        size += self.sint64_value.to_varint_field::<6>(buffer);
+        size += self.string_value.to_length_delimited_field::<14>(buffer);
+        size
+    }
+
+    fn decode_field(&mut self, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, ::ks_protobuf_v3::WireType::Varint) => self.int32_value.from_sign_extended_varint(buffer).map(Some),
+            (6, ::ks_protobuf_v3::WireType::Varint) => self.sint64_value.from_varint(buffer).map(Some),
+            (14, ::ks_protobuf_v3::WireType::LengthDelimited) => self.string_value.from_length_delimited(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn is_singular_field(&self, field_number: u32) -> bool { matches!(field_number, 1 | 6 | 14) }
+
+    fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        if ::ks_protobuf_v3::Presence::is_present(&other.int32_value) { self.int32_value = other.int32_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.sint64_value) { self.sint64_value = other.sint64_value; }
+        if ::ks_protobuf_v3::Presence::is_present(&other.string_value) { self.string_value = other.string_value; }
+        Ok(())
+    }
+}
+
+impl ::ks_protobuf_v3::ReflectMessage for PartialScalars {
+    fn descriptor(&self) -> ::ks_protobuf_v3::MessageDescriptor {
+        descriptor_pool().get_message_by_name("golden.PartialScalars").expect("the pool holds the message")
+    }
+
+    fn transcode_to_dynamic(&self) -> Result<::ks_protobuf_v3::DynamicMessage, ::ks_protobuf_v3::DecodeError> {
+        ::ks_protobuf_v3::DynamicMessage::transcode_from(::ks_protobuf_v3::ReflectMessage::descriptor(self), self)
+    }
+
+    fn transcode_from_dynamic(&mut self, message: &::ks_protobuf_v3::DynamicMessage) -> Result<(), ::ks_protobuf_v3::DecodeError> {
+        *self = message.transcode_to()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Colour {
+    Unspecified = 0,
+    Red = 1,
+    Green = 2,
+    Negative = -1,
+}
+
+impl ::ks_protobuf_v3::Enumeration for Colour {
+    const VALUES: &'static [Self] = &[Colour::Unspecified, Colour::Red, Colour::Green, Colour::Negative];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Colour::Unspecified => "COLOUR_UNSPECIFIED",
+            Colour::Red => "RED",
+            Colour::Green => "GREEN",
+            Colour::Negative => "NEGATIVE",
+        }
+    }
+}
+
@@ -0,0 +1,210 @@
+//! A testee for the Protocol Buffers conformance suite, which checks the binary, JSON and text
+//! formats against `TestAllTypesProto3` and `TestAllTypesProto2`.
+//!
+//! The conformance runner starts the testee and writes each `ConformanceRequest` to its stdin,
+//! preceded by its length as a little endian `u32`, and reads each `ConformanceResponse` from its
+//! stdout in the same way. The tests that are known to fail are listed in `conformance/`:
+//!
+//! ```text
+//! conformance-test-runner --failure_list conformance/failure_list_ks.txt \
+//!     --text_format_failure_list conformance/text_format_failure_list_ks.txt \
+//!     target/debug/conformance
+//! ```
+
+use ks_protobuf_v3::{
+    DescriptorPool, DynamicMessage, FieldValue, MessageDescriptor, ProtoCompiler,
+};
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+const CONFORMANCE_PROTO: &str = include_str!("../../conformance/conformance.proto");
+const TEST_MESSAGES_PROTO2: &str = include_str!("../../conformance/test_messages_proto2.proto");
+const TEST_MESSAGES_PROTO3: &str = include_str!("../../conformance/test_messages_proto3.proto");
+
+// The values of the `WireFormat` enum.
+const PROTOBUF: i32 = 1;
+const JSON: i32 = 2;
+const TEXT_FORMAT: i32 = 4;
+
+// The value of the `TestCategory` enum for JSON tests that ignore unknown fields.
+const JSON_IGNORE_UNKNOWN_PARSING_TEST: i32 = 3;
+
+fn main() -> ExitCode {
+    let pool = match load_pool() {
+        Ok(pool) => pool,
+        Err(error) => {
+            eprintln!(
+                "conformance: The test messages could not be compiled: {}",
+                error
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    loop {
+        match serve_request(&pool, &mut stdin, &mut stdout) {
+            Ok(true) => continue,
+            Ok(false) => return ExitCode::SUCCESS,
+            Err(error) => {
+                eprintln!("conformance: {}", error);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+}
+
+fn load_pool() -> Result<DescriptorPool, String> {
+    let mut compiler = ProtoCompiler::new();
+    compiler.include_imports(true);
+    compiler.add_source("conformance.proto", CONFORMANCE_PROTO);
+    compiler.add_source("test_messages_proto2.proto", TEST_MESSAGES_PROTO2);
+    compiler.add_source("test_messages_proto3.proto", TEST_MESSAGES_PROTO3);
+
+    let set = compiler
+        .compile(&[
+            "conformance.proto",
+            "test_messages_proto2.proto",
+            "test_messages_proto3.proto",
+        ])
+        .map_err(|e| e.to_string())?;
+    DescriptorPool::from_file_descriptor_set(set).map_err(|e| e.to_string())
+}
+
+// Reads a request and writes its response, returning false once the runner has closed stdin.
+fn serve_request(
+    pool: &DescriptorPool,
+    input: &mut impl Read,
+    output: &mut impl Write,
+) -> Result<bool, String> {
+    let mut length = [0; 4];
+    match input.read_exact(&mut length) {
+        Ok(()) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(false),
+        Err(error) => return Err(format!("The request could not be read: {}", error)),
+    }
+
+    let mut request = vec![0; u32::from_le_bytes(length) as usize];
+    input
+        .read_exact(&mut request)
+        .map_err(|e| format!("The request could not be read: {}", e))?;
+
+    let request = DynamicMessage::decode(
+        message_type(pool, "conformance.ConformanceRequest")?,
+        &request,
+    )
+    .map_err(|e| format!("The request could not be decoded: {}", e))?;
+    let (result, value) = run_test(pool, &request);
+
+    let mut response = DynamicMessage::new(message_type(pool, "conformance.ConformanceResponse")?);
+    response
+        .set_field_by_name(result, value)
+        .map_err(|e| e.to_string())?;
+    let response = response.encode_to_vec();
+
+    output
+        .write_all(&(response.len() as u32).to_le_bytes())
+        .and_then(|()| output.write_all(&response))
+        .and_then(|()| output.flush())
+        .map_err(|e| format!("The response could not be written: {}", e))?;
+    Ok(true)
+}
+
+// Returns the name of the field of the `result` oneof to set in the response, and its value.
+fn run_test(pool: &DescriptorPool, request: &DynamicMessage) -> (&'static str, FieldValue) {
+    let field = |name| request.get_field_by_name(name).map(|v| v.into_owned());
+    let string = |name| {
+        field(name)
+            .and_then(|v| v.as_str().map(str::to_string))
+            .unwrap_or_default()
+    };
+    let enum_number = |name| {
+        field(name)
+            .and_then(|v| v.as_enum_number())
+            .unwrap_or_default()
+    };
+
+    let message_type_name = string("message_type");
+    if message_type_name == "conformance.FailureSet" {
+        return ("protobuf_payload", FieldValue::Bytes(Vec::new()));
+    }
+
+    let descriptor = match pool.get_message_by_name(&message_type_name) {
+        Some(descriptor)
+            if message_type_name.ends_with(".TestAllTypesProto3")
+                || message_type_name.ends_with(".TestAllTypesProto2") =>
+        {
+            descriptor
+        }
+        _ => {
+            return (
+                "skipped",
+                FieldValue::from(format!(
+                    "The message type {} is not supported.",
+                    message_type_name
+                )),
+            )
+        }
+    };
+
+    let payload = [
+        "protobuf_payload",
+        "json_payload",
+        "jspb_payload",
+        "text_payload",
+    ]
+    .into_iter()
+    .find(|name| request.has_field(&request.descriptor().get_field_by_name(name).unwrap()));
+    let parsed = match (payload, field(payload.unwrap_or_default())) {
+        (Some("protobuf_payload"), Some(FieldValue::Bytes(bytes))) => {
+            DynamicMessage::decode(descriptor, &bytes).map_err(|e| e.to_string())
+        }
+        (Some("json_payload"), Some(FieldValue::String(_)))
+            if enum_number("test_category") == JSON_IGNORE_UNKNOWN_PARSING_TEST =>
+        {
+            return (
+                "skipped",
+                FieldValue::from("Unknown JSON fields can not be ignored."),
+            );
+        }
+        (Some("json_payload"), Some(FieldValue::String(text))) => {
+            DynamicMessage::parse_json(descriptor, &text).map_err(|e| e.to_string())
+        }
+        (Some("text_payload"), Some(FieldValue::String(text))) => {
+            DynamicMessage::parse_text(descriptor, &text).map_err(|e| e.to_string())
+        }
+        _ => {
+            return (
+                "skipped",
+                FieldValue::from("The payload format is not supported."),
+            )
+        }
+    };
+
+    let message = match parsed {
+        Ok(message) => message,
+        Err(error) => return ("parse_error", FieldValue::from(error)),
+    };
+
+    match enum_number("requested_output_format") {
+        PROTOBUF => (
+            "protobuf_payload",
+            FieldValue::Bytes(message.encode_to_vec()),
+        ),
+        JSON => match message.to_json() {
+            Ok(json) => ("json_payload", FieldValue::from(json.to_string())),
+            Err(error) => ("serialize_error", FieldValue::from(error.to_string())),
+        },
+        TEXT_FORMAT => ("text_payload", FieldValue::from(message.to_text())),
+        _ => (
+            "skipped",
+            FieldValue::from("The output format is not supported."),
+        ),
+    }
+}
+
+fn message_type(pool: &DescriptorPool, name: &str) -> Result<MessageDescriptor, String> {
+    pool.get_message_by_name(name)
+        .ok_or_else(|| format!("The message {} is not known.", name))
+}
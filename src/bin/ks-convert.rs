@@ -0,0 +1,125 @@
+//! Converts a message between the binary, proto3 JSON and text formats, reading stdin and writing
+//! stdout.
+//!
+//! Usage: `ks-convert (--descriptor_set_in=FILE | [-IPATH | --proto_path=PATH]... PROTO_FILES)
+//! --message=NAME [--from=binary|json|text] [--to=binary|json|text]`. The message type is looked
+//! up in the descriptor set or in the compiled `.proto` files, whose imports are found as
+//! `ks-protoc` finds them. Messages are converted from binary to text unless told otherwise.
+
+use ks_protobuf_v3::{DescriptorPool, DynamicMessage, ProtoCompiler};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("ks-convert: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut descriptor_set = None;
+    let mut include_paths = Vec::new();
+    let mut files = Vec::new();
+    let mut message_name = None;
+    let mut from = "binary".to_string();
+    let mut to = "text".to_string();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-I" || arg == "--proto_path" {
+            match args.next() {
+                Some(path) => include_paths.push(PathBuf::from(path)),
+                None => return Err(format!("The option {} needs a path.", arg)),
+            }
+        } else if let Some(path) = arg.strip_prefix("--descriptor_set_in=") {
+            descriptor_set = Some(path.to_string());
+        } else if let Some(name) = arg.strip_prefix("--message=") {
+            message_name = Some(name.to_string());
+        } else if let Some(format) = arg.strip_prefix("--from=") {
+            from = format.to_string();
+        } else if let Some(format) = arg.strip_prefix("--to=") {
+            to = format.to_string();
+        } else if let Some(path) = arg.strip_prefix("--proto_path=") {
+            include_paths.push(PathBuf::from(path));
+        } else if let Some(path) = arg.strip_prefix("-I") {
+            include_paths.push(PathBuf::from(path));
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option {}.", arg));
+        } else {
+            files.push(arg);
+        }
+    }
+
+    let message_name = message_name.ok_or("The --message option is required.")?;
+    let pool = match descriptor_set {
+        Some(path) => {
+            let bytes = std::fs::read(&path)
+                .map_err(|e| format!("The file {} could not be read: {}", path, e))?;
+            DescriptorPool::decode(&bytes).map_err(|e| e.to_string())?
+        }
+        None if files.is_empty() => {
+            return Err("Either --descriptor_set_in or .proto files must be given.".to_string())
+        }
+        None => compile(include_paths, &files)?,
+    };
+    let descriptor = pool
+        .get_message_by_name(&message_name)
+        .ok_or_else(|| format!("The message {} is not known.", message_name))?;
+
+    let mut input = Vec::new();
+    let _ = std::io::stdin()
+        .read_to_end(&mut input)
+        .map_err(|e| format!("The input could not be read: {}", e))?;
+
+    let message = match from.as_str() {
+        "binary" => DynamicMessage::decode(descriptor, &input).map_err(|e| e.to_string())?,
+        "json" => {
+            DynamicMessage::parse_json(descriptor, &utf8(input)?).map_err(|e| e.to_string())?
+        }
+        "text" => {
+            DynamicMessage::parse_text(descriptor, &utf8(input)?).map_err(|e| e.to_string())?
+        }
+        _ => return Err(format!("The format {} is not known.", from)),
+    };
+
+    let output = match to.as_str() {
+        "binary" => message.encode_to_vec(),
+        "json" => {
+            let json = message.to_json().map_err(|e| e.to_string())?;
+            let mut text = serde_json::to_string_pretty(&json).map_err(|e| e.to_string())?;
+            text.push('\n');
+            text.into_bytes()
+        }
+        "text" => message.to_text().into_bytes(),
+        _ => return Err(format!("The format {} is not known.", to)),
+    };
+
+    std::io::stdout()
+        .write_all(&output)
+        .map_err(|e| format!("The output could not be written: {}", e))
+}
+
+fn compile(include_paths: Vec<PathBuf>, files: &[String]) -> Result<DescriptorPool, String> {
+    let mut compiler = ProtoCompiler::new();
+    compiler.include_imports(true);
+    if include_paths.is_empty() {
+        compiler.add_include_path(".");
+    }
+    for include_path in include_paths {
+        compiler.add_include_path(include_path);
+    }
+
+    let names: Vec<String> = files.iter().map(|f| compiler.import_name(f)).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let set = compiler.compile(&names).map_err(|e| e.to_string())?;
+    DescriptorPool::from_file_descriptor_set(set).map_err(|e| e.to_string())
+}
+
+fn utf8(input: Vec<u8>) -> Result<String, String> {
+    String::from_utf8(input).map_err(|_| "The input is not UTF-8.".to_string())
+}
@@ -0,0 +1,116 @@
+//! Prints the fields of an encoded message without its schema, as `protoc --decode_raw` does.
+//!
+//! Usage: `ks-decode-raw [--format=raw|hex|base64] [--offsets] [FILE]`. The message is read from
+//! the file, or from stdin when no file is given. Hex and base64 input may be split over several
+//! lines, and base64 may use either the standard or the URL safe alphabet.
+
+use ks_protobuf_v3::RawDecoder;
+use std::io::Read;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(text) => {
+            print!("{}", text);
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            eprintln!("ks-decode-raw: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<String, String> {
+    let mut decoder = RawDecoder::new();
+    let mut format = "raw".to_string();
+    let mut file = None;
+
+    for arg in args {
+        if arg == "--offsets" {
+            decoder.show_offsets(true);
+        } else if let Some(value) = arg.strip_prefix("--format=") {
+            format = value.to_string();
+        } else if arg.starts_with('-') {
+            return Err(format!("Unknown option {}.", arg));
+        } else if file.is_some() {
+            return Err("Only one input file can be given.".to_string());
+        } else {
+            file = Some(arg);
+        }
+    }
+
+    let input = match &file {
+        Some(file) => std::fs::read(file)
+            .map_err(|e| format!("The file {} could not be read: {}", file, e))?,
+        None => {
+            let mut input = Vec::new();
+            let _ = std::io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|e| format!("The input could not be read: {}", e))?;
+            input
+        }
+    };
+
+    let bytes = match format.as_str() {
+        "raw" => input,
+        "hex" => decode_hex(&input)?,
+        "base64" => decode_base64(&input)?,
+        _ => return Err(format!("The format {} is not known.", format)),
+    };
+
+    decoder
+        .decode(&bytes)
+        .map_err(|e| format!("The input is not a valid message: {}", e))
+}
+
+fn decode_hex(input: &[u8]) -> Result<Vec<u8>, String> {
+    let digits = input
+        .iter()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|&c| match c {
+            b'0'..=b'9' => Ok(c - b'0'),
+            b'a'..=b'f' => Ok(c - b'a' + 10),
+            b'A'..=b'F' => Ok(c - b'A' + 10),
+            _ => Err(format!("The character {} is not a hex digit.", c as char)),
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+
+    if digits.len() % 2 != 0 {
+        return Err("The hex input has an odd number of digits.".to_string());
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect())
+}
+
+fn decode_base64(input: &[u8]) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut bits: u32 = 0;
+    let mut count = 0;
+
+    for &c in input
+        .iter()
+        .filter(|c| !c.is_ascii_whitespace() && **c != b'=')
+    {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(format!("The character {} is not valid base64.", c as char)),
+        };
+
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+
+    Ok(bytes)
+}
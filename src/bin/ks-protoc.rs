@@ -0,0 +1,69 @@
+//! Compiles `.proto` files into a `FileDescriptorSet`, as `protoc --descriptor_set_out` does.
+//!
+//! Usage: `ks-protoc [-IPATH | --proto_path=PATH]... [--include_imports] [--include_source_info]
+//! --descriptor_set_out=FILE PROTO_FILES`. Imports are looked up in the include paths, which
+//! default to the current directory, and then among the bundled `google/protobuf/*.proto` files.
+
+use ks_protobuf_v3::{Message, ProtoCompiler};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("ks-protoc: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: Vec<String>) -> Result<(), String> {
+    let mut compiler = ProtoCompiler::new();
+    let mut include_paths = Vec::new();
+    let mut output = None;
+    let mut files = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--include_imports" => compiler.include_imports(true),
+            "--include_source_info" => compiler.include_source_info(true),
+            "-I" | "--proto_path" => match args.next() {
+                Some(path) => include_paths.push(PathBuf::from(path)),
+                None => return Err(format!("The option {} needs a path.", arg)),
+            },
+            _ => {
+                if let Some(path) = arg.strip_prefix("--descriptor_set_out=") {
+                    output = Some(path.to_string());
+                } else if let Some(path) = arg.strip_prefix("--proto_path=") {
+                    include_paths.push(PathBuf::from(path));
+                } else if let Some(path) = arg.strip_prefix("-I") {
+                    include_paths.push(PathBuf::from(path));
+                } else if arg.starts_with('-') {
+                    return Err(format!("Unknown option {}.", arg));
+                } else {
+                    files.push(arg);
+                }
+            }
+        }
+    }
+
+    let output = output.ok_or("The --descriptor_set_out option is required.")?;
+    if files.is_empty() {
+        return Err("No input files were given.".to_string());
+    }
+    if include_paths.is_empty() {
+        include_paths.push(PathBuf::from("."));
+    }
+
+    for include_path in include_paths {
+        compiler.add_include_path(include_path);
+    }
+
+    let names: Vec<String> = files.iter().map(|f| compiler.import_name(f)).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).collect();
+    let set = compiler.compile(&names).map_err(|e| e.to_string())?;
+    std::fs::write(&output, set.encode_to_vec())
+        .map_err(|e| format!("The file {} could not be written: {}", output, e))
+}
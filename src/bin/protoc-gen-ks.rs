@@ -0,0 +1,36 @@
+//! A protoc plugin that generates Rust code for this crate.
+//!
+//! The protocol compiler runs the plugin with a `CodeGeneratorRequest` on stdin and reads the
+//! `CodeGeneratorResponse` from stdout, e.g. `protoc --ks_out=src/protos --ks_opt=layout=single`.
+//! A captured request can be fed in directly with `protoc-gen-ks < request.bin`.
+
+use ks_protobuf_v3::{run_plugin, CodeGeneratorRequest, Message};
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut input = Vec::new();
+    if let Err(error) = std::io::stdin().read_to_end(&mut input) {
+        eprintln!("protoc-gen-ks: The request could not be read: {}", error);
+        return ExitCode::FAILURE;
+    }
+
+    let request = match CodeGeneratorRequest::decode(&input) {
+        Ok(request) => request,
+        Err(error) => {
+            eprintln!("protoc-gen-ks: The request could not be decoded: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let response = run_plugin(&request).encode_to_vec();
+    if let Err(error) = std::io::stdout().write_all(&response) {
+        eprintln!(
+            "protoc-gen-ks: The response could not be written: {}",
+            error
+        );
+        return ExitCode::FAILURE;
+    }
+
+    ExitCode::SUCCESS
+}
@@ -16,6 +16,11 @@ impl Buffer {
     pub fn put_u8(&mut self, value: u8) {
         self.data.push(value);
     }
+
+    /// Appends a slice of bytes to the end of the buffer. The buffer will grow if necessary.
+    pub fn put_slice(&mut self, values: &[u8]) {
+        self.data.extend_from_slice(values);
+    }
 }
 
 #[cfg(test)]
@@ -28,7 +33,7 @@ mod tests {
         let buffer = Buffer::default();
 
         // Assert
-        assert_eq!(buffer.to_vec(), vec![]);
+        assert_eq!(buffer.to_vec(), Vec::<u8>::new());
     }
 
     #[test]
@@ -43,4 +48,17 @@ mod tests {
         // Assert
         assert_eq!(buffer.to_vec(), vec![10, 5]);
     }
+
+    #[test]
+    fn test_addition_of_slice_to_buffer() {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        buffer.put_u8(10);
+        buffer.put_slice(&[5, 6, 7]);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), vec![10, 5, 6, 7]);
+    }
 }
@@ -0,0 +1,2209 @@
+use crate::proto_parser::unescape_bytes;
+use crate::{
+    CodeGeneratorRequest, CodeGeneratorResponse, CodegenError, DescriptorPool, EnumDescriptor,
+    EnumValueDescriptor, Feature, FieldDescriptor, FieldType, FileDescriptor, FileDescriptorProto,
+    FileDescriptorSet, GeneratedFile, Message, MessageDescriptor, OneofDescriptor, Syntax,
+};
+use std::collections::{HashMap, HashSet};
+
+/// How the generated code is split into files.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum ModuleLayout {
+    /// One file for each package, named after the package, e.g. `foo.bar.rs`. Each file should be
+    /// included in a module tree that mirrors the package, as types in other packages are
+    /// referred to with relative paths.
+    #[default]
+    Package,
+
+    /// A single file named `protos.rs` holding a module for each package.
+    Single,
+}
+
+/// The Rust type used for map fields.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub enum MapType {
+    /// Map fields are held in a `HashMap`.
+    #[default]
+    HashMap,
+
+    /// Map fields are held in a `BTreeMap`, so they iterate in key order.
+    BTreeMap,
+}
+
+/// The options of the code generator.
+///
+/// Options are passed to the `protoc-gen-ks` plugin as a comma separated list, e.g.
+/// `--ks_opt=layout=single,map_type=btree_map,extern_path=.acme.common=::acme_common`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CodeGeneratorOptions {
+    /// How the generated code is split into files. Set with `layout=package` or `layout=single`.
+    pub module_layout: ModuleLayout,
+
+    /// The Rust type used for map fields. Set with `map_type=hash_map` or `map_type=btree_map`.
+    pub map_type: MapType,
+
+    /// Maps fully qualified Protocol Buffer names, or the packages and messages containing them,
+    /// to existing Rust paths so that code is not generated for them. Set with
+    /// `extern_path=.proto.name=::rust::path`. The well-known types this crate implements are
+    /// mapped by default.
+    pub extern_paths: Vec<(String, String)>,
+}
+
+impl Default for CodeGeneratorOptions {
+    fn default() -> Self {
+        Self {
+            module_layout: ModuleLayout::default(),
+            map_type: MapType::default(),
+            extern_paths: ["Struct", "Value", "ListValue", "NullValue"]
+                .iter()
+                .map(|name| {
+                    (
+                        format!(".google.protobuf.{}", name),
+                        format!("::ks_protobuf_v3::{}", name),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CodeGeneratorOptions {
+    /// Creates the options from the comma separated parameter given to a plugin.
+    pub fn from_parameter(parameter: &str) -> Result<Self, CodegenError> {
+        let mut options = Self::default();
+
+        for option in parameter
+            .split(',')
+            .map(str::trim)
+            .filter(|o| !o.is_empty())
+        {
+            match option.split_once('=') {
+                Some(("layout", "package")) => options.module_layout = ModuleLayout::Package,
+                Some(("layout", "single")) => options.module_layout = ModuleLayout::Single,
+                Some(("map_type", "hash_map")) => options.map_type = MapType::HashMap,
+                Some(("map_type", "btree_map")) => options.map_type = MapType::BTreeMap,
+                Some(("extern_path", path)) => match path.split_once('=') {
+                    Some((proto, rust)) if proto.starts_with('.') => options
+                        .extern_paths
+                        .push((proto.to_string(), rust.to_string())),
+                    _ => return Err(CodegenError::InvalidParameter(option.to_string())),
+                },
+                _ => return Err(CodegenError::InvalidParameter(option.to_string())),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Generates Rust code for `.proto` files, using the traits of this crate to encode and decode
+/// the messages.
+///
+/// Messages become structs implementing `Message`, enums become Rust enums implementing
+/// `Enumeration` and oneof groups become enums implementing `Oneof`. Nested types and oneof
+/// groups are placed in a module named after the message that contains them.
+///
+/// The module of each package embeds the descriptors of its files, and of the files they import,
+/// as `FILE_DESCRIPTOR_SET`, and `descriptor_pool()` builds them into a pool the first time it is
+/// called. Messages implement `ReflectMessage` with descriptors from that pool. Each package has its
+/// own pool, so descriptors of the same type from different packages are compared by full name.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{
+///     CodeGenerator, CodeGeneratorOptions, DescriptorPool, DescriptorProto, FileDescriptorProto,
+/// };
+///
+/// let mut pool = DescriptorPool::new();
+/// pool.add_file_descriptor_proto(FileDescriptorProto {
+///     name: Some("empty.proto".to_string()),
+///     package: Some("acme".to_string()),
+///     syntax: Some("proto3".to_string()),
+///     message_type: vec![DescriptorProto {
+///         name: Some("Empty".to_string()),
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// })
+/// .unwrap();
+///
+/// let generator = CodeGenerator::new(CodeGeneratorOptions::default());
+/// let files = generator.generate(&pool, &["empty.proto".to_string()]).unwrap();
+///
+/// assert_eq!(files[0].name(), "acme.rs");
+/// assert!(files[0].content().contains("pub struct Empty {"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CodeGenerator {
+    options: CodeGeneratorOptions,
+}
+
+impl CodeGenerator {
+    /// Creates a generator with the given options.
+    pub fn new(options: CodeGeneratorOptions) -> Self {
+        Self { options }
+    }
+
+    /// Generates the code for the named files, which must be held by the pool.
+    pub fn generate(
+        &self,
+        pool: &DescriptorPool,
+        files: &[String],
+    ) -> Result<Vec<GeneratedFile>, CodegenError> {
+        let mut packages: Vec<(String, Vec<FileDescriptor>)> = Vec::new();
+        for name in files {
+            let file = pool
+                .get_file_by_name(name)
+                .ok_or_else(|| CodegenError::UnknownFile(name.clone()))?;
+            let package = file.package_name().to_string();
+
+            match packages.iter_mut().find(|(p, _)| *p == package) {
+                Some((_, files)) => files.push(file),
+                None => packages.push((package, vec![file])),
+            }
+        }
+
+        let mut modules = ModuleTree::default();
+        for (package, files) in &packages {
+            let module = package_module(package);
+            let mut writer = CodeWriter::default();
+            write_descriptor_pool(&mut writer, pool, files);
+            for file in files {
+                Generator::new(&self.options, file, &mut writer).write_file()?;
+            }
+
+            match self.options.module_layout {
+                ModuleLayout::Package => modules.children.push((
+                    package.clone(),
+                    ModuleTree {
+                        code: writer.code,
+                        children: Vec::new(),
+                    },
+                )),
+                ModuleLayout::Single => modules.insert(&module, writer.code),
+            }
+        }
+
+        Ok(match self.options.module_layout {
+            ModuleLayout::Package => modules
+                .children
+                .into_iter()
+                .map(|(package, module)| GeneratedFile {
+                    name: Some(match package.is_empty() {
+                        true => "_.rs".to_string(),
+                        false => format!("{}.rs", package),
+                    }),
+                    content: Some(format!("{}{}{}", HEADER, IMPORTS, module.code)),
+                    ..Default::default()
+                })
+                .collect(),
+            ModuleLayout::Single => vec![GeneratedFile {
+                name: Some("protos.rs".to_string()),
+                content: Some(format!("{}{}", HEADER, modules.render(0))),
+                ..Default::default()
+            }],
+        })
+    }
+}
+
+/// Handles a request from the protocol compiler, generating code for the requested files. Any
+/// error is reported in the response rather than returned, as the plugin protocol requires.
+pub fn run_plugin(request: &CodeGeneratorRequest) -> CodeGeneratorResponse {
+    let result = CodeGeneratorOptions::from_parameter(request.parameter()).and_then(|options| {
+        let pool = DescriptorPool::from_file_descriptor_set(FileDescriptorSet {
+            file: request.proto_file.clone(),
+        })?;
+        CodeGenerator::new(options).generate(&pool, &request.file_to_generate)
+    });
+
+    let supported_features = Some(Feature::Proto3Optional as u64);
+    match result {
+        Ok(file) => CodeGeneratorResponse {
+            supported_features,
+            file,
+            ..Default::default()
+        },
+        Err(error) => CodeGeneratorResponse {
+            error: Some(error.to_string()),
+            supported_features,
+            ..Default::default()
+        },
+    }
+}
+
+const HEADER: &str = "// This file is @generated by protoc-gen-ks. Do not edit.\n\n";
+
+// The encoding traits are imported anonymously so that their names can not clash with the
+// generated types.
+const IMPORTS: &str = "#[allow(unused_imports)]
+use ::ks_protobuf_v3::{
+    Fixed32 as _, Fixed32Field as _, Fixed64 as _, Fixed64Field as _, Group as _, GroupField as _,
+    LengthDelimited as _, LengthDelimitedField as _, MapField as _, Message as _, Oneof as _,
+    RepeatedField as _, RepeatedGroupField as _, SignExtendedVarint as _,
+    SignExtendedVarintField as _, Varint as _, VarintField as _,
+};
+";
+
+// Embeds the descriptors of the files and the files they import, so that the messages generated
+// from them can return their descriptors. The comments in the source code info are left out.
+fn write_descriptor_pool(writer: &mut CodeWriter, pool: &DescriptorPool, files: &[FileDescriptor]) {
+    let mut needed = HashSet::new();
+    let mut pending = files.to_vec();
+    while let Some(file) = pending.pop() {
+        if needed.insert(file.name().to_string()) {
+            pending.extend(file.dependencies());
+        }
+    }
+
+    let set = FileDescriptorSet {
+        file: pool
+            .file_descriptor_set()
+            .file
+            .into_iter()
+            .filter(|f| needed.contains(f.name()))
+            .map(|f| FileDescriptorProto {
+                source_code_info: None,
+                ..f
+            })
+            .collect(),
+    };
+
+    writer.line("/// The encoded `FileDescriptorSet` of the files this module was generated from and the files they import.");
+    writer.line(&format!(
+        "pub const FILE_DESCRIPTOR_SET: &[u8] = b\"{}\";",
+        set.encode_to_vec().escape_ascii()
+    ));
+    writer.line("");
+    writer.line("/// Returns the pool holding the descriptors of this module, which its messages return through `ReflectMessage`.");
+    writer.open("pub fn descriptor_pool() -> &'static ::ks_protobuf_v3::DescriptorPool {");
+    writer.line("static POOL: ::std::sync::OnceLock<::ks_protobuf_v3::DescriptorPool> = ::std::sync::OnceLock::new();");
+    writer.line("POOL.get_or_init(|| ::ks_protobuf_v3::DescriptorPool::decode(FILE_DESCRIPTOR_SET).expect(\"the embedded descriptors are valid\"))");
+    writer.close("}");
+    writer.line("");
+}
+
+#[derive(Debug, Default)]
+struct ModuleTree {
+    code: String,
+    children: Vec<(String, ModuleTree)>,
+}
+
+impl ModuleTree {
+    fn insert(&mut self, path: &[String], code: String) {
+        match path.split_first() {
+            None => self.code.push_str(&code),
+            Some((first, rest)) => {
+                let position = match self.children.iter().position(|(n, _)| n == first) {
+                    Some(position) => position,
+                    None => {
+                        self.children.push((first.clone(), ModuleTree::default()));
+                        self.children.len() - 1
+                    }
+                };
+                self.children[position].1.insert(rest, code);
+            }
+        }
+    }
+
+    fn render(&self, depth: usize) -> String {
+        let mut code = indent(&self.code, depth);
+
+        for (name, child) in &self.children {
+            let padding = "    ".repeat(depth);
+            code.push_str(&format!("{}pub mod {} {{\n", padding, name));
+            code.push_str(&indent(IMPORTS, depth + 1));
+            code.push_str(&child.render(depth + 1));
+            code.push_str(&format!("{}}}\n", padding));
+        }
+
+        code
+    }
+}
+
+fn indent(code: &str, depth: usize) -> String {
+    code.lines()
+        .map(|line| match line.is_empty() {
+            true => "\n".to_string(),
+            false => format!("{}{}\n", "    ".repeat(depth), line),
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct CodeWriter {
+    code: String,
+    depth: usize,
+}
+
+impl CodeWriter {
+    fn line(&mut self, line: &str) {
+        if !line.is_empty() {
+            self.code.push_str(&"    ".repeat(self.depth));
+            self.code.push_str(line);
+        }
+        self.code.push('\n');
+    }
+
+    fn open(&mut self, line: &str) {
+        self.line(line);
+        self.depth += 1;
+    }
+
+    fn close(&mut self, line: &str) {
+        self.depth -= 1;
+        self.line(line);
+    }
+
+    fn comments(&mut self, comments: Option<&String>) {
+        for line in comments.into_iter().flat_map(|c| c.trim_end().lines()) {
+            match line.is_empty() {
+                true => self.line("///"),
+                false => self.line(&format!("///{}", line)),
+            }
+        }
+    }
+}
+
+// How a value of a field is written on the wire, which selects the encoding trait.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Encoding {
+    Varint,
+    SignExtendedVarint,
+    Fixed32,
+    Fixed64,
+    LengthDelimited,
+    Group,
+}
+
+impl Encoding {
+    fn of(field_type: FieldType) -> Encoding {
+        match field_type {
+            FieldType::Int32 | FieldType::Int64 => Encoding::SignExtendedVarint,
+            FieldType::Double | FieldType::Fixed64 | FieldType::Sfixed64 => Encoding::Fixed64,
+            FieldType::Float | FieldType::Fixed32 | FieldType::Sfixed32 => Encoding::Fixed32,
+            FieldType::String | FieldType::Bytes | FieldType::Message => Encoding::LengthDelimited,
+            FieldType::Group => Encoding::Group,
+            _ => Encoding::Varint,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Varint => "varint",
+            Encoding::SignExtendedVarint => "sign_extended_varint",
+            Encoding::Fixed32 => "fixed32",
+            Encoding::Fixed64 => "fixed64",
+            Encoding::LengthDelimited => "length_delimited",
+            Encoding::Group => "group",
+        }
+    }
+
+    fn trait_name(self) -> &'static str {
+        match self {
+            Encoding::Varint => "Varint",
+            Encoding::SignExtendedVarint => "SignExtendedVarint",
+            Encoding::Fixed32 => "Fixed32",
+            Encoding::Fixed64 => "Fixed64",
+            Encoding::LengthDelimited => "LengthDelimited",
+            Encoding::Group => "Group",
+        }
+    }
+
+    fn wire_type(self) -> &'static str {
+        match self {
+            Encoding::Varint | Encoding::SignExtendedVarint => "Varint",
+            Encoding::Fixed32 => "Fixed32",
+            Encoding::Fixed64 => "Fixed64",
+            Encoding::LengthDelimited => "LengthDelimited",
+            Encoding::Group => "StartGroup",
+        }
+    }
+
+    // Groups have no codec, as their end tag needs the field number, so they are encoded by the
+    // group traits instead.
+    fn codec(self) -> String {
+        format!("::ks_protobuf_v3::{}Codec", self.trait_name())
+    }
+}
+
+struct Generator<'a> {
+    options: &'a CodeGeneratorOptions,
+    file: &'a FileDescriptor,
+    writer: &'a mut CodeWriter,
+    comments: HashMap<Vec<i32>, String>,
+}
+
+impl<'a> Generator<'a> {
+    fn new(
+        options: &'a CodeGeneratorOptions,
+        file: &'a FileDescriptor,
+        writer: &'a mut CodeWriter,
+    ) -> Self {
+        let comments = file
+            .file_descriptor_proto()
+            .source_code_info
+            .iter()
+            .flat_map(|s| s.location.iter())
+            .filter_map(|l| Some((l.path.clone(), l.leading_comments.clone()?)))
+            .collect();
+
+        Self {
+            options,
+            file,
+            writer,
+            comments,
+        }
+    }
+
+    fn write_file(&mut self) -> Result<(), CodegenError> {
+        for (index, message) in self.file.messages().enumerate() {
+            self.write_message(&message, vec![4, index as i32])?;
+        }
+        for (index, enumeration) in self.file.enums().enumerate() {
+            self.write_enum(&enumeration, vec![5, index as i32]);
+        }
+        let module = package_module(self.file.package_name());
+        let extensions: Vec<_> = self.file.extensions().collect();
+        self.write_extensions(&extensions, &module, vec![7])
+    }
+
+    fn write_message(
+        &mut self,
+        message: &MessageDescriptor,
+        path: Vec<i32>,
+    ) -> Result<(), CodegenError> {
+        if message.is_map_entry() || self.extern_path(message.full_name()).is_some() {
+            return Ok(());
+        }
+
+        let name = type_name(message.name());
+        let module = message_module(message);
+        let oneofs: Vec<_> = message.oneofs().filter(|o| !o.is_synthetic()).collect();
+
+        let fields: Vec<_> = message.fields().filter(|f| !is_oneof_member(f)).collect();
+        let extension_ranges: Vec<_> = message.extension_ranges().collect();
+        if !extension_ranges.is_empty() && message.get_field_by_name("extensions").is_some() {
+            return Err(CodegenError::Unsupported(format!(
+                "the field {}.extensions, which clashes with the extensions of the message",
+                message.full_name()
+            )));
+        }
+        let keeps_unknown_fields = keeps_unknown_fields(message);
+        if keeps_unknown_fields && message.get_field_by_name("unknown_fields").is_some() {
+            return Err(CodegenError::Unsupported(format!(
+                "the field {}.unknown_fields, which clashes with the unknown fields of the message",
+                message.full_name()
+            )));
+        }
+
+        self.writer.comments(self.comments.get(&path));
+        self.writer
+            .line("#[derive(Debug, Default, Clone, PartialEq)]");
+        self.writer.open(&format!("pub struct {} {{", name));
+        for (index, field) in message.fields().enumerate() {
+            if is_oneof_member(&field) {
+                continue;
+            }
+            let mut field_path = path.clone();
+            field_path.extend([2, index as i32]);
+
+            self.writer.comments(self.comments.get(&field_path));
+            let field_type = self.field_type(&field, &module);
+            self.writer.line(&format!(
+                "pub {}: {},",
+                field_name(field.name()),
+                field_type
+            ));
+        }
+        for (index, oneof) in message.oneofs().enumerate() {
+            if oneof.is_synthetic() {
+                continue;
+            }
+            let mut oneof_path = path.clone();
+            oneof_path.extend([8, index as i32]);
+
+            self.writer.comments(self.comments.get(&oneof_path));
+            self.writer.line(&format!(
+                "pub {}: Option<{}::{}>,",
+                field_name(oneof.name()),
+                module_name(message.name()),
+                type_name(oneof.name())
+            ));
+        }
+        if !extension_ranges.is_empty() {
+            self.writer
+                .line("pub extensions: ::ks_protobuf_v3::ExtensionSet,");
+        }
+        if keeps_unknown_fields {
+            self.writer
+                .line("pub unknown_fields: ::ks_protobuf_v3::UnknownFields,");
+        }
+        self.writer.close("}");
+        self.writer.line("");
+
+        self.write_accessors(message, &fields, &module);
+
+        self.writer
+            .open(&format!("impl ::ks_protobuf_v3::Message for {} {{", name));
+        self.writer
+            .open("fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {");
+        self.writer.line("let mut size = 0;");
+        let mut by_number: Vec<_> = message.fields().collect();
+        by_number.sort_by_key(|f| f.number());
+        // Fields are written in field number order, so each extension range is written among
+        // them and each run of oneof members at the position of its numbers.
+        let mut ranges = extension_ranges.clone();
+        ranges.sort_by_key(|r| r.start);
+        let mut ranges = ranges.into_iter().peekable();
+        let mut position = 0;
+        while position < by_number.len() {
+            let field = &by_number[position];
+            if let Some(range) = ranges.next_if(|r| r.start < field.number()) {
+                self.writer.line(&format!(
+                    "size += self.extensions.encode_range({}..{}, buffer);",
+                    range.start, range.end
+                ));
+                continue;
+            }
+
+            let oneof = match field.containing_oneof().filter(|o| !o.is_synthetic()) {
+                Some(oneof) => oneof,
+                None => {
+                    let encode = self.encode_field(field);
+                    self.writer.line(&format!("size += {};", encode));
+                    position += 1;
+                    continue;
+                }
+            };
+
+            let next = ranges.peek().map_or(u32::MAX, |r| r.start);
+            let run: Vec<_> = by_number[position..]
+                .iter()
+                .take_while(|f| f.number() < next && f.containing_oneof().as_ref() == Some(&oneof))
+                .collect();
+            position += run.len();
+            let pattern = match run.len() == oneof.fields().count() {
+                true => "oneof".to_string(),
+                false => {
+                    let variants: Vec<String> = run
+                        .iter()
+                        .map(|f| {
+                            format!(
+                                "{}::{}::{}(_)",
+                                module_name(message.name()),
+                                type_name(oneof.name()),
+                                type_name(f.name())
+                            )
+                        })
+                        .collect();
+                    match variants.len() {
+                        1 => format!("oneof @ {}", variants[0]),
+                        _ => format!("oneof @ ({})", variants.join(" | ")),
+                    }
+                }
+            };
+            self.writer.line(&format!(
+                "if let Some({}) = &self.{} {{ size += oneof.encode(buffer); }}",
+                pattern,
+                field_name(oneof.name())
+            ));
+        }
+        for range in ranges {
+            self.writer.line(&format!(
+                "size += self.extensions.encode_range({}..{}, buffer);",
+                range.start, range.end
+            ));
+        }
+        if keeps_unknown_fields {
+            self.writer
+                .line("size += self.unknown_fields.encode(buffer);");
+        }
+        self.writer.line("size");
+        self.writer.close("}");
+        self.writer.line("");
+        self.writer.open("fn decode_field(&mut self, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {");
+        self.writer
+            .open("match (tag.field_number(), tag.wire_type()) {");
+        for field in &fields {
+            let decode = self.decode_field(field);
+            self.writer.line(&decode);
+        }
+        for oneof in &oneofs {
+            let numbers: Vec<String> = oneof.fields().map(|f| f.number().to_string()).collect();
+            self.writer.line(&format!(
+                "({}, _) => {}::{}::decode_field(&mut self.{}, tag, buffer),",
+                numbers.join(" | "),
+                module_name(message.name()),
+                type_name(oneof.name()),
+                field_name(oneof.name())
+            ));
+        }
+        if !extension_ranges.is_empty() {
+            let patterns: Vec<String> = extension_ranges
+                .iter()
+                .map(|r| format!("{}..={}", r.start, r.end - 1))
+                .collect();
+            self.writer.line(&format!(
+                "({}, _) => self.extensions.decode_field(tag, buffer).map(Some),",
+                patterns.join(" | ")
+            ));
+        }
+        self.writer.line("_ => Ok(None),");
+        self.writer.close("}");
+        self.writer.close("}");
+        if has_required_fields(message) {
+            self.writer.line("");
+            self.write_required_check(message);
+        }
+        let singular: Vec<String> = by_number
+            .iter()
+            .filter(|f| !f.is_repeated())
+            .map(|f| f.number().to_string())
+            .collect();
+        if !singular.is_empty() {
+            self.writer.line("");
+            self.writer.line(&format!(
+                "fn is_singular_field(&self, field_number: u32) -> bool {{ matches!(field_number, {}) }}",
+                singular.join(" | ")
+            ));
+        }
+        if keeps_unknown_fields {
+            self.writer.line("");
+            self.writer.line("fn unknown_fields_mut(&mut self) -> Option<&mut ::ks_protobuf_v3::UnknownFields> { Some(&mut self.unknown_fields) }");
+        }
+        if !fields.is_empty()
+            || !oneofs.is_empty()
+            || !extension_ranges.is_empty()
+            || keeps_unknown_fields
+        {
+            self.writer.line("");
+            self.write_merge(
+                &fields,
+                &oneofs,
+                !extension_ranges.is_empty(),
+                keeps_unknown_fields,
+            );
+        }
+        self.writer.close("}");
+        self.writer.line("");
+        self.write_reflect(message, &module);
+
+        let nested_messages: Vec<_> = message
+            .child_messages()
+            .enumerate()
+            .filter(|(_, m)| !m.is_map_entry())
+            .collect();
+        let nested_enums: Vec<_> = message.child_enums().enumerate().collect();
+        let nested_extensions: Vec<_> = message.child_extensions().collect();
+        if nested_messages.is_empty()
+            && nested_enums.is_empty()
+            && oneofs.is_empty()
+            && nested_extensions.is_empty()
+        {
+            return Ok(());
+        }
+
+        self.writer.comments(Some(&format!(
+            " Nested types and oneof groups of `{}`.",
+            name
+        )));
+        self.writer
+            .open(&format!("pub mod {} {{", module_name(message.name())));
+        for line in IMPORTS.lines() {
+            self.writer.line(line);
+        }
+        self.writer.line("");
+        for (index, nested) in nested_messages {
+            let mut nested_path = path.clone();
+            nested_path.extend([3, index as i32]);
+            self.write_message(&nested, nested_path)?;
+        }
+        for (index, nested) in nested_enums {
+            let mut nested_path = path.clone();
+            nested_path.extend([4, index as i32]);
+            self.write_enum(&nested, nested_path);
+        }
+        for oneof in &oneofs {
+            self.write_oneof(message, oneof.fields().collect(), oneof.name());
+        }
+        let mut nested_module = module.clone();
+        nested_module.push(module_name(message.name()));
+        let mut extensions_path = path.clone();
+        extensions_path.push(6);
+        self.write_extensions(&nested_extensions, &nested_module, extensions_path)?;
+        self.writer.close("}");
+        self.writer.line("");
+        Ok(())
+    }
+
+    // Writes methods that return the value of each singular proto2 field, or its default when
+    // the field is not set.
+    fn write_accessors(
+        &mut self,
+        message: &MessageDescriptor,
+        fields: &[FieldDescriptor],
+        module: &[String],
+    ) {
+        let fields: Vec<_> = fields
+            .iter()
+            .filter(|f| f.has_presence() && !f.is_repeated())
+            .filter(|f| !matches!(f.field_type(), FieldType::Message | FieldType::Group))
+            .collect();
+        if fields.is_empty() || message.parent_file().syntax() == Syntax::Proto3 {
+            return;
+        }
+
+        self.writer
+            .open(&format!("impl {} {{", type_name(message.name())));
+        for (index, field) in fields.iter().enumerate() {
+            let name = field_name(field.name());
+            let (return_type, value) = self.accessor(field, module);
+
+            if index > 0 {
+                self.writer.line("");
+            }
+            self.writer.line(&format!(
+                "/// Returns the value of the `{}` field, or its default when it is not set.",
+                field.name()
+            ));
+            self.writer
+                .open(&format!("pub fn {}(&self) -> {} {{", name, return_type));
+            self.writer.line(&format!("self.{}{}", name, value));
+            self.writer.close("}");
+        }
+        self.writer.close("}");
+        self.writer.line("");
+    }
+
+    // The return type of the accessor of a field and the expression that follows the field in its
+    // body.
+    fn accessor(&self, field: &FieldDescriptor, module: &[String]) -> (String, String) {
+        let default = field.default_value();
+        let number = |suffix: &str| {
+            let value = default.unwrap_or("0");
+            (
+                suffix.to_string(),
+                format!(".unwrap_or({}_{})", value, suffix),
+            )
+        };
+        let float = |suffix: &str| {
+            let value = match default.unwrap_or("0") {
+                "inf" => format!("{}::INFINITY", suffix),
+                "-inf" => format!("{}::NEG_INFINITY", suffix),
+                "nan" => format!("{}::NAN", suffix),
+                value => format!("{:?}_{}", value.parse::<f64>().unwrap_or_default(), suffix),
+            };
+            (suffix.to_string(), format!(".unwrap_or({})", value))
+        };
+
+        match field.field_type() {
+            FieldType::Double => float("f64"),
+            FieldType::Float => float("f32"),
+            FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => number("i64"),
+            FieldType::Uint64 | FieldType::Fixed64 => number("u64"),
+            FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => number("i32"),
+            FieldType::Uint32 | FieldType::Fixed32 => number("u32"),
+            FieldType::Bool => (
+                "bool".to_string(),
+                format!(".unwrap_or({})", default.unwrap_or("false")),
+            ),
+            FieldType::String => (
+                "&str".to_string(),
+                format!(".as_deref().unwrap_or({:?})", default.unwrap_or_default()),
+            ),
+            FieldType::Bytes => {
+                let bytes = default.and_then(unescape_bytes).unwrap_or_default();
+                let bytes: Vec<String> = bytes.iter().map(u8::to_string).collect();
+                (
+                    "&[u8]".to_string(),
+                    format!(".as_deref().unwrap_or(&[{}])", bytes.join(", ")),
+                )
+            }
+            FieldType::Enum => {
+                let enumeration = field.enum_type().expect("enum fields have an enum type");
+                let path = self.type_path(
+                    enumeration.full_name(),
+                    &enum_module(&enumeration),
+                    enumeration.name(),
+                    module,
+                );
+                let variants = enum_variants(&enumeration);
+                let variant = default
+                    .and_then(|d| variants.iter().find(|(_, _, v)| v.name() == d))
+                    .or_else(|| variants.first())
+                    .map_or_else(String::new, |(_, variant, _)| variant.clone());
+                (
+                    path.clone(),
+                    format!(".and_then(|v| v.known()).unwrap_or({}::{})", path, variant),
+                )
+            }
+            FieldType::Message | FieldType::Group => unreachable!("messages have no accessor"),
+        }
+    }
+
+    // Writes the check that fails decoding when a required field of the message, or of a message
+    // held by one of its fields, is not set.
+    fn write_required_check(&mut self, message: &MessageDescriptor) {
+        self.writer
+            .open("fn check_required_fields(&self) -> Result<(), ::ks_protobuf_v3::DecodeError> {");
+        for field in message.fields() {
+            let name = field_name(field.name());
+            if field.is_required() {
+                self.writer.line(&format!(
+                    "if self.{}.is_none() {{ return Err(::ks_protobuf_v3::DecodeError::MissingRequiredField(\"{}\".to_string())); }}",
+                    name,
+                    field.full_name()
+                ));
+            }
+
+            let reaches_required = field
+                .message_type()
+                .map(|m| match field.is_map() {
+                    true => m.get_field(2).and_then(|v| v.message_type()),
+                    false => Some(m),
+                })
+                .is_some_and(|m| m.as_ref().is_some_and(has_required_fields));
+            if !reaches_required {
+                continue;
+            }
+
+            let check = "v.check_required_fields()?;";
+            self.writer.line(&match field.containing_oneof() {
+                Some(oneof) if !oneof.is_synthetic() => format!(
+                    "if let Some({}::{}::{}(v)) = &self.{} {{ {} }}",
+                    module_name(message.name()),
+                    type_name(oneof.name()),
+                    type_name(field.name()),
+                    field_name(oneof.name()),
+                    check
+                ),
+                _ if field.is_map() => format!("for v in self.{}.values() {{ {} }}", name, check),
+                _ if field.is_repeated() => format!("for v in &self.{} {{ {} }}", name, check),
+                _ => format!("if let Some(v) = &self.{} {{ {} }}", name, check),
+            });
+        }
+        self.writer.line("Ok(())");
+        self.writer.close("}");
+    }
+
+    // Writes a constant for each extension, which reads and writes it in the extensions of the
+    // messages it extends.
+    fn write_extensions(
+        &mut self,
+        extensions: &[FieldDescriptor],
+        module: &[String],
+        path: Vec<i32>,
+    ) -> Result<(), CodegenError> {
+        for (index, extension) in extensions.iter().enumerate() {
+            if extension.field_type() == FieldType::Group {
+                return Err(CodegenError::Unsupported(format!(
+                    "the group extension {}",
+                    extension.full_name()
+                )));
+            }
+
+            let mut extension_path = path.clone();
+            extension_path.push(index as i32);
+            let value_type = self.value_type(extension, module);
+            let (value_type, constructor) = match extension.is_repeated() {
+                true if extension.is_packed() => (format!("Vec<{}>", value_type), "packed"),
+                true => (format!("Vec<{}>", value_type), "new"),
+                false => (value_type, "new"),
+            };
+
+            self.writer.comments(self.comments.get(&extension_path));
+            self.writer.line(&format!(
+                "pub const {}: ::ks_protobuf_v3::Extension<{}, {}> = ::ks_protobuf_v3::Extension::{}::<{}>();",
+                to_snake_case(extension.name()).to_uppercase(),
+                value_type,
+                Encoding::of(extension.field_type()).codec(),
+                constructor,
+                extension.number()
+            ));
+            self.writer.line("");
+        }
+        Ok(())
+    }
+
+    // Reflects the message through the descriptor embedded in the module of its package.
+    fn write_reflect(&mut self, message: &MessageDescriptor, module: &[String]) {
+        let package = package_module(message.parent_file().package_name());
+        let pool = format!(
+            "{}descriptor_pool()",
+            "super::".repeat(module.len() - package.len())
+        );
+
+        self.writer.open(&format!(
+            "impl ::ks_protobuf_v3::ReflectMessage for {} {{",
+            type_name(message.name())
+        ));
+        self.writer
+            .open("fn descriptor(&self) -> ::ks_protobuf_v3::MessageDescriptor {");
+        self.writer.line(&format!(
+            "{}.get_message_by_name(\"{}\").expect(\"the pool holds the message\")",
+            pool,
+            message.full_name()
+        ));
+        self.writer.close("}");
+        self.writer.line("");
+        self.writer.open("fn transcode_to_dynamic(&self) -> Result<::ks_protobuf_v3::DynamicMessage, ::ks_protobuf_v3::DecodeError> {");
+        self.writer.line("::ks_protobuf_v3::DynamicMessage::transcode_from(::ks_protobuf_v3::ReflectMessage::descriptor(self), self)");
+        self.writer.close("}");
+        self.writer.line("");
+        self.writer.open("fn transcode_from_dynamic(&mut self, message: &::ks_protobuf_v3::DynamicMessage) -> Result<(), ::ks_protobuf_v3::DecodeError> {");
+        self.writer.line("*self = message.transcode_to()?;");
+        self.writer.line("Ok(())");
+        self.writer.close("}");
+        self.writer.close("}");
+        self.writer.line("");
+    }
+
+    // Merges field by field rather than through the encoding of the other message.
+    fn write_merge(
+        &mut self,
+        fields: &[FieldDescriptor],
+        oneofs: &[OneofDescriptor],
+        has_extensions: bool,
+        keeps_unknown_fields: bool,
+    ) {
+        self.writer.open(
+            "fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {",
+        );
+        for field in fields {
+            let name = field_name(field.name());
+            self.writer.line(&match field.field_type() {
+                _ if field.is_repeated() => format!("self.{0}.extend(other.{0});", name),
+                FieldType::Message | FieldType::Group => format!(
+                    "::ks_protobuf_v3::merge_message(&mut self.{0}, other.{0})?;",
+                    name
+                ),
+                _ => format!(
+                    "if ::ks_protobuf_v3::Presence::is_present(&other.{0}) {{ self.{0} = other.{0}; }}",
+                    name
+                ),
+            });
+        }
+        for oneof in oneofs {
+            self.writer.line(&format!(
+                "::ks_protobuf_v3::merge_oneof(&mut self.{0}, other.{0})?;",
+                field_name(oneof.name())
+            ));
+        }
+        if has_extensions {
+            self.writer.line("self.extensions.merge(other.extensions);");
+        }
+        if keeps_unknown_fields {
+            self.writer
+                .line("self.unknown_fields.merge(other.unknown_fields);");
+        }
+        self.writer.line("Ok(())");
+        self.writer.close("}");
+    }
+
+    fn write_oneof(
+        &mut self,
+        message: &MessageDescriptor,
+        fields: Vec<FieldDescriptor>,
+        name: &str,
+    ) {
+        let mut module = message_module(message);
+        module.push(module_name(message.name()));
+        let name = type_name(name);
+
+        self.writer.line("#[derive(Debug, Clone, PartialEq)]");
+        self.writer.open(&format!("pub enum {} {{", name));
+        for field in &fields {
+            let value_type = self.value_type(field, &module);
+            self.writer
+                .line(&format!("{}({}),", type_name(field.name()), value_type));
+        }
+        self.writer.close("}");
+        self.writer.line("");
+
+        self.writer.line("#[allow(unreachable_patterns)]");
+        self.writer
+            .open(&format!("impl ::ks_protobuf_v3::Oneof for {} {{", name));
+        self.writer
+            .open("fn encode(&self, buffer: &mut ::ks_protobuf_v3::Buffer) -> usize {");
+        self.writer.open("match self {");
+        for field in &fields {
+            self.writer.line(&format!(
+                "{}::{}(v) => v.to_{}_field_always::<{}>(buffer),",
+                name,
+                type_name(field.name()),
+                Encoding::of(field.field_type()).name(),
+                field.number()
+            ));
+        }
+        self.writer.close("}");
+        self.writer.close("}");
+        self.writer.line("");
+        self.writer.open("fn decode_field(oneof: &mut Option<Self>, tag: &::ks_protobuf_v3::Tag, buffer: &[u8]) -> Result<Option<usize>, ::ks_protobuf_v3::DecodeError> {");
+        self.writer
+            .open("match (tag.field_number(), tag.wire_type()) {");
+        for field in &fields {
+            let encoding = Encoding::of(field.field_type());
+            let variant = format!("{}::{}", name, type_name(field.name()));
+            let decode = match field.field_type() {
+                FieldType::Message => format!(
+                    "::ks_protobuf_v3::merge_oneof_message(oneof, buffer, |v| match v {{ {}(v) => Some(v), _ => None }}, {}).map(Some)",
+                    variant, variant
+                ),
+                FieldType::Group => format!(
+                    "::ks_protobuf_v3::merge_oneof_group(oneof, {}, buffer, |v| match v {{ {}(v) => Some(v), _ => None }}, {}).map(Some)",
+                    field.number(),
+                    variant,
+                    variant
+                ),
+                FieldType::Enum if is_closed_enum(field) => format!(
+                    "::ks_protobuf_v3::decode_closed_enum(buffer, |v| *oneof = Some({}(v)))",
+                    variant
+                ),
+                _ => format!(
+                    "::ks_protobuf_v3::decode_oneof_member(oneof, buffer, <{} as ::ks_protobuf_v3::{}>::from_{}, {}).map(Some)",
+                    self.value_type(field, &module),
+                    encoding.trait_name(),
+                    encoding.name(),
+                    variant
+                ),
+            };
+            self.writer.line(&format!(
+                "({}, ::ks_protobuf_v3::WireType::{}) => {},",
+                field.number(),
+                encoding.wire_type(),
+                decode
+            ));
+        }
+        self.writer.line("_ => Ok(None),");
+        self.writer.close("}");
+        self.writer.close("}");
+        let messages: Vec<_> = fields
+            .iter()
+            .filter(|f| matches!(f.field_type(), FieldType::Message | FieldType::Group))
+            .collect();
+        if !messages.is_empty() {
+            self.writer.line("");
+            self.writer.open(
+                "fn merge(&mut self, other: Self) -> Result<(), ::ks_protobuf_v3::DecodeError> {",
+            );
+            self.writer.open("match (self, other) {");
+            for field in messages {
+                let variant = format!("{}::{}", name, type_name(field.name()));
+                self.writer.line(&format!(
+                    "({0}(v), {0}(o)) => ::ks_protobuf_v3::Message::merge(v, o),",
+                    variant
+                ));
+            }
+            self.writer
+                .line("(this, other) => { *this = other; Ok(()) }");
+            self.writer.close("}");
+            self.writer.close("}");
+        }
+        self.writer.close("}");
+        self.writer.line("");
+    }
+
+    fn write_enum(&mut self, enumeration: &EnumDescriptor, path: Vec<i32>) {
+        if self.extern_path(enumeration.full_name()).is_some() {
+            return;
+        }
+
+        let name = type_name(enumeration.name());
+
+        self.writer.comments(self.comments.get(&path));
+        self.writer
+            .line("#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]");
+        self.writer.open(&format!("pub enum {} {{", name));
+        let mut variants = Vec::new();
+        for (index, variant, value) in enum_variants(enumeration) {
+            let mut value_path = path.clone();
+            value_path.extend([2, index as i32]);
+
+            self.writer.comments(self.comments.get(&value_path));
+            self.writer
+                .line(&format!("{} = {},", variant, value.number()));
+            variants.push((variant, value.name().to_string()));
+        }
+        self.writer.close("}");
+        self.writer.line("");
+
+        self.writer.open(&format!(
+            "impl ::ks_protobuf_v3::Enumeration for {} {{",
+            name
+        ));
+        let all: Vec<String> = variants
+            .iter()
+            .map(|(v, _)| format!("{}::{}", name, v))
+            .collect();
+        self.writer.line(&format!(
+            "const VALUES: &'static [Self] = &[{}];",
+            all.join(", ")
+        ));
+        self.writer.line("");
+        self.writer.open("fn number(self) -> i32 {");
+        self.writer.line("self as i32");
+        self.writer.close("}");
+        self.writer.line("");
+        self.writer.open("fn name(self) -> &'static str {");
+        self.writer.open("match self {");
+        for (variant, proto_name) in &variants {
+            self.writer
+                .line(&format!("{}::{} => \"{}\",", name, variant, proto_name));
+        }
+        self.writer.close("}");
+        self.writer.close("}");
+        self.writer.close("}");
+        self.writer.line("");
+    }
+
+    // The Rust type of a field of a message struct.
+    fn field_type(&self, field: &FieldDescriptor, module: &[String]) -> String {
+        if field.is_map() {
+            let entry = field.message_type().expect("map fields have an entry type");
+            let key = entry.get_field(1).expect("map entries have a key");
+            let value = entry.get_field(2).expect("map entries have a value");
+            let map = match self.options.map_type {
+                MapType::HashMap => "::std::collections::HashMap",
+                MapType::BTreeMap => "::std::collections::BTreeMap",
+            };
+            return format!(
+                "{}<{}, {}>",
+                map,
+                self.value_type(&key, module),
+                self.value_type(&value, module)
+            );
+        }
+
+        let value_type = self.value_type(field, module);
+        if field.is_repeated() {
+            format!("Vec<{}>", value_type)
+        } else if field.has_presence() {
+            format!("Option<{}>", value_type)
+        } else {
+            value_type
+        }
+    }
+
+    // The Rust type of a single value of a field.
+    fn value_type(&self, field: &FieldDescriptor, module: &[String]) -> String {
+        match field.field_type() {
+            FieldType::Double => "f64".to_string(),
+            FieldType::Float => "f32".to_string(),
+            FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => "i64".to_string(),
+            FieldType::Uint64 | FieldType::Fixed64 => "u64".to_string(),
+            FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => "i32".to_string(),
+            FieldType::Uint32 | FieldType::Fixed32 => "u32".to_string(),
+            FieldType::Bool => "bool".to_string(),
+            FieldType::String => "String".to_string(),
+            FieldType::Bytes => "Vec<u8>".to_string(),
+            FieldType::Enum => {
+                let enumeration = field.enum_type().expect("enum fields have an enum type");
+                format!(
+                    "::ks_protobuf_v3::OpenEnum<{}>",
+                    self.type_path(
+                        enumeration.full_name(),
+                        &enum_module(&enumeration),
+                        enumeration.name(),
+                        module
+                    )
+                )
+            }
+            FieldType::Message | FieldType::Group => {
+                let message = field
+                    .message_type()
+                    .expect("message fields have a message type");
+                let path = self.type_path(
+                    message.full_name(),
+                    &message_module(&message),
+                    message.name(),
+                    module,
+                );
+                // Extensions are held outside of the message they extend, so they are never
+                // boxed.
+                let recursive = !field.is_extension()
+                    && !field.is_repeated()
+                    && reaches(&message, field.containing_message());
+                match recursive {
+                    true => format!("Box<{}>", path),
+                    false => path,
+                }
+            }
+        }
+    }
+
+    fn encode_field(&self, field: &FieldDescriptor) -> String {
+        let name = field_name(field.name());
+
+        if field.is_map() {
+            let (key, value) = map_codecs(field);
+            return format!(
+                "self.{}.to_map_field::<{}, {}, {}>(buffer)",
+                name,
+                key,
+                value,
+                field.number()
+            );
+        }
+
+        let encoding = Encoding::of(field.field_type());
+        if field.is_repeated() && encoding == Encoding::Group {
+            return format!(
+                "self.{}.to_group_fields::<{}>(buffer)",
+                name,
+                field.number()
+            );
+        }
+        if field.is_repeated() {
+            let packing = match field.is_packed() {
+                true => "packed",
+                false => "unpacked",
+            };
+            return format!(
+                "self.{}.to_{}_field::<{}, {}>(buffer)",
+                name,
+                packing,
+                encoding.codec(),
+                field.number()
+            );
+        }
+
+        // Implicit presence fields are skipped when they hold their default value. Fields with
+        // explicit presence are held in an `Option`, so they are written whenever they are set.
+        format!(
+            "self.{}.to_{}_field::<{}>(buffer)",
+            name,
+            encoding.name(),
+            field.number()
+        )
+    }
+
+    fn decode_field(&self, field: &FieldDescriptor) -> String {
+        let name = field_name(field.name());
+
+        if field.is_map() {
+            let (key, value) = map_codecs(field);
+            return format!(
+                "({}, ::ks_protobuf_v3::WireType::LengthDelimited) => self.{}.from_map_entry::<{}, {}>(buffer).map(Some),",
+                field.number(),
+                name,
+                key,
+                value
+            );
+        }
+
+        let encoding = Encoding::of(field.field_type());
+        match field.field_type() {
+            FieldType::Group => {
+                let decode = match field.is_repeated() {
+                    true => "from_group_field",
+                    false => "from_group",
+                };
+                return format!(
+                    "({}, ::ks_protobuf_v3::WireType::StartGroup) => self.{}.{}({}, buffer).map(Some),",
+                    field.number(),
+                    name,
+                    decode,
+                    field.number()
+                );
+            }
+            // Values that are not in a closed enum are kept with the unknown fields.
+            FieldType::Enum if is_closed_enum(field) && field.is_repeated() => {
+                return format!(
+                    "({}, _) => ::ks_protobuf_v3::decode_closed_enums(&mut self.{}, &mut self.unknown_fields, tag, buffer),",
+                    field.number(),
+                    name
+                );
+            }
+            FieldType::Enum if is_closed_enum(field) => {
+                let set = match field.has_presence() {
+                    true => "Some(v)",
+                    false => "v",
+                };
+                return format!(
+                    "({}, ::ks_protobuf_v3::WireType::Varint) => ::ks_protobuf_v3::decode_closed_enum(buffer, |v| self.{} = {}),",
+                    field.number(),
+                    name,
+                    set
+                );
+            }
+            _ => {}
+        }
+        if field.is_repeated() {
+            return format!(
+                "({}, wire_type) => self.{}.from_repeated_field::<{}>(wire_type, buffer),",
+                field.number(),
+                name,
+                encoding.codec()
+            );
+        }
+
+        format!(
+            "({}, ::ks_protobuf_v3::WireType::{}) => self.{}.from_{}(buffer).map(Some),",
+            field.number(),
+            encoding.wire_type(),
+            name,
+            encoding.name()
+        )
+    }
+
+    // The path of a type relative to the module the reference is made from.
+    fn type_path(
+        &self,
+        full_name: &str,
+        type_module: &[String],
+        name: &str,
+        from: &[String],
+    ) -> String {
+        if let Some(path) = self.extern_path(full_name) {
+            return path;
+        }
+
+        let common = from
+            .iter()
+            .zip(type_module)
+            .take_while(|(a, b)| a == b)
+            .count();
+        let mut path: Vec<String> = vec!["super".to_string(); from.len() - common];
+        path.extend(type_module[common..].iter().cloned());
+        path.push(type_name(name));
+        path.join("::")
+    }
+
+    fn extern_path(&self, full_name: &str) -> Option<String> {
+        let name = format!(".{}", full_name);
+
+        self.options
+            .extern_paths
+            .iter()
+            .filter_map(|(proto, rust)| match name.strip_prefix(proto.as_str()) {
+                Some("") => Some(rust.clone()),
+                Some(rest) if rest.starts_with('.') => {
+                    let mut parts: Vec<&str> = rest[1..].split('.').collect();
+                    let last = type_name(parts.pop().unwrap_or_default());
+                    let mut path = vec![rust.clone()];
+                    path.extend(parts.into_iter().map(module_name));
+                    path.push(last);
+                    Some(path.join("::"))
+                }
+                _ => None,
+            })
+            .next()
+    }
+}
+
+fn is_oneof_member(field: &FieldDescriptor) -> bool {
+    field.containing_oneof().is_some_and(|o| !o.is_synthetic())
+}
+
+// Messages keep the fields they do not know, except in proto3 files which can not hold closed
+// enums and are generated as they were before unknown fields were kept.
+fn keeps_unknown_fields(message: &MessageDescriptor) -> bool {
+    message.parent_file().syntax() != Syntax::Proto3
+}
+
+fn is_closed_enum(field: &FieldDescriptor) -> bool {
+    field.enum_type().is_some_and(|e| e.is_closed())
+}
+
+// The values of an enum with the names of their variants, along with their index in the enum.
+// Aliases share a number with an earlier value, which a Rust enum can not represent, so they are
+// left out. The name of the enum is stripped from the start of the names when every value has it.
+fn enum_variants(enumeration: &EnumDescriptor) -> Vec<(usize, String, EnumValueDescriptor)> {
+    let prefix = format!("{}_", enumeration.name().to_uppercase());
+
+    let mut numbers = HashSet::new();
+    let values: Vec<_> = enumeration
+        .values()
+        .enumerate()
+        .filter(|(_, v)| numbers.insert(v.number()))
+        .collect();
+    let stripped: Vec<String> = values
+        .iter()
+        .map(|(_, v)| v.name().strip_prefix(&prefix).unwrap_or(v.name()))
+        .filter(|n| n.starts_with(|c: char| c.is_ascii_alphabetic()))
+        .map(|n| type_name(&n.to_lowercase()))
+        .collect();
+    let strip = stripped.len() == values.len()
+        && stripped.iter().collect::<HashSet<_>>().len() == stripped.len();
+
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(position, (index, value))| {
+            let variant = match strip {
+                true => stripped[position].clone(),
+                false => type_name(&value.name().to_lowercase()),
+            };
+            (index, variant, value)
+        })
+        .collect()
+}
+
+// Returns true when the message, or a message held by its fields, has a required field.
+fn has_required_fields(message: &MessageDescriptor) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = vec![message.clone()];
+
+    while let Some(current) = pending.pop() {
+        if !visited.insert(current.full_name().to_string()) {
+            continue;
+        }
+        if current.fields().any(|f| f.is_required()) {
+            return true;
+        }
+        pending.extend(current.fields().filter_map(|f| f.message_type()));
+    }
+
+    false
+}
+
+fn map_codecs(field: &FieldDescriptor) -> (String, String) {
+    let entry = field.message_type().expect("map fields have an entry type");
+    let codec = |number| {
+        let field = entry
+            .get_field(number)
+            .expect("map entries have a key and a value");
+        Encoding::of(field.field_type()).codec()
+    };
+    (codec(1), codec(2))
+}
+
+// Returns true when a value of the message can contain a value of the target directly, through
+// singular message fields, so that holding it inline would give the target an infinite size.
+fn reaches(message: &MessageDescriptor, target: &MessageDescriptor) -> bool {
+    let mut visited = HashSet::new();
+    let mut pending = vec![message.clone()];
+
+    while let Some(current) = pending.pop() {
+        if current == *target {
+            return true;
+        }
+        if !visited.insert(current.full_name().to_string()) {
+            continue;
+        }
+
+        pending.extend(
+            current
+                .fields()
+                .filter(|f| !f.is_repeated())
+                .filter_map(|f| f.message_type()),
+        );
+    }
+
+    false
+}
+
+fn package_module(package: &str) -> Vec<String> {
+    package
+        .split('.')
+        .filter(|p| !p.is_empty())
+        .map(module_name)
+        .collect()
+}
+
+// The module holding a message: the module of its package followed by a module for each message
+// it is nested in.
+fn message_module(message: &MessageDescriptor) -> Vec<String> {
+    match message.parent_message() {
+        Some(parent) => {
+            let mut module = message_module(&parent);
+            module.push(module_name(parent.name()));
+            module
+        }
+        None => package_module(message.parent_file().package_name()),
+    }
+}
+
+fn enum_module(enumeration: &EnumDescriptor) -> Vec<String> {
+    match enumeration.parent_message() {
+        Some(parent) => {
+            let mut module = message_module(&parent);
+            module.push(module_name(parent.name()));
+            module
+        }
+        None => package_module(enumeration.parent_file().package_name()),
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+fn escape(name: String) -> String {
+    match name.as_str() {
+        "self" | "super" | "crate" | "Self" => format!("{}_", name),
+        _ if KEYWORDS.contains(&name.as_str()) => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+fn field_name(name: &str) -> String {
+    escape(to_snake_case(name))
+}
+
+fn module_name(name: &str) -> String {
+    escape(to_snake_case(name))
+}
+
+fn type_name(name: &str) -> String {
+    escape(to_upper_camel_case(name))
+}
+
+pub(crate) fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for (index, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && index > 0 {
+            let previous = chars[index - 1];
+            let next_is_lower = chars.get(index + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if previous.is_ascii_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_ascii_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c.to_ascii_lowercase());
+    }
+
+    snake
+}
+
+pub(crate) fn to_upper_camel_case(name: &str) -> String {
+    name.split('_')
+        .filter(|p| !p.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DescriptorProto, EncodeMode, EnumDescriptorProto, EnumValueDescriptorProto,
+        FieldDescriptorProto, FieldLabel, FileDescriptorProto, Message, MessageOptions,
+        OneofDescriptorProto, ProtoCompiler,
+    };
+    use rstest::*;
+
+    fn field(
+        name: &str,
+        number: i32,
+        field_type: FieldType,
+        type_name: &str,
+    ) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(FieldLabel::Optional.into()),
+            r#type: Some(field_type.into()),
+            type_name: (!type_name.is_empty()).then(|| type_name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn repeated(field: FieldDescriptorProto) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            label: Some(FieldLabel::Repeated.into()),
+            ..field
+        }
+    }
+
+    fn in_oneof(field: FieldDescriptorProto, index: i32) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            oneof_index: Some(index),
+            ..field
+        }
+    }
+
+    fn message(name: &str, fields: Vec<FieldDescriptorProto>) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.to_string()),
+            field: fields,
+            ..Default::default()
+        }
+    }
+
+    // A file using every kind of field the generator supports.
+    fn person_file() -> FileDescriptorProto {
+        let mut entry = message(
+            "ScoresEntry",
+            vec![
+                field("key", 1, FieldType::String, ""),
+                field("value", 2, FieldType::Int32, ""),
+            ],
+        );
+        entry.options = Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        });
+
+        let mut person = message(
+            "Person",
+            vec![
+                field("name", 1, FieldType::String, ""),
+                field("id", 2, FieldType::Int32, ""),
+                repeated(field("emails", 3, FieldType::String, "")),
+                repeated(field("scores", 4, FieldType::Message, "ScoresEntry")),
+                in_oneof(field("phone", 5, FieldType::String, ""), 0),
+                in_oneof(field("address", 6, FieldType::Message, "Address"), 0),
+                FieldDescriptorProto {
+                    proto3_optional: Some(true),
+                    ..in_oneof(field("active", 7, FieldType::Bool, ""), 1)
+                },
+                field("kind", 8, FieldType::Enum, "Kind"),
+                repeated(field("lucky_numbers", 9, FieldType::Sint32, "")),
+                field("manager", 10, FieldType::Message, "Person"),
+            ],
+        );
+        person.nested_type = vec![
+            message("Address", vec![field("street", 1, FieldType::String, "")]),
+            entry,
+        ];
+        person.enum_type = vec![EnumDescriptorProto {
+            name: Some("Kind".to_string()),
+            value: ["KIND_UNSPECIFIED", "KIND_STAFF", "KIND_CONTRACTOR"]
+                .iter()
+                .enumerate()
+                .map(|(number, name)| EnumValueDescriptorProto {
+                    name: Some(name.to_string()),
+                    number: Some(number as i32),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }];
+        person.oneof_decl = ["contact", "_active"]
+            .iter()
+            .map(|name| OneofDescriptorProto {
+                name: Some(name.to_string()),
+                ..Default::default()
+            })
+            .collect();
+
+        FileDescriptorProto {
+            name: Some("person.proto".to_string()),
+            package: Some("acme.people".to_string()),
+            message_type: vec![person],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn generate(options: CodeGeneratorOptions) -> Vec<GeneratedFile> {
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(person_file()).unwrap();
+
+        CodeGenerator::new(options)
+            .generate(&pool, &["person.proto".to_string()])
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case("", ModuleLayout::Package, MapType::HashMap)]
+    #[case("layout=single", ModuleLayout::Single, MapType::HashMap)]
+    #[case(
+        "map_type=btree_map, layout=package",
+        ModuleLayout::Package,
+        MapType::BTreeMap
+    )]
+    fn test_options_are_parsed(
+        #[case] parameter: &str,
+        #[case] expected_layout: ModuleLayout,
+        #[case] expected_map_type: MapType,
+    ) {
+        // Arrange
+        // Act
+        let options = CodeGeneratorOptions::from_parameter(parameter).unwrap();
+
+        // Assert
+        assert_eq!(options.module_layout, expected_layout);
+        assert_eq!(options.map_type, expected_map_type);
+    }
+
+    #[rstest]
+    #[case("layout=nested")]
+    #[case("unknown")]
+    #[case("extern_path=acme=::acme")]
+    fn test_invalid_options_are_rejected(#[case] parameter: &str) {
+        // Arrange
+        // Act
+        let result = CodeGeneratorOptions::from_parameter(parameter);
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(CodegenError::InvalidParameter(parameter.to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case("pub name: String,")]
+    #[case("pub id: i32,")]
+    #[case("pub emails: Vec<String>,")]
+    #[case("pub scores: ::std::collections::HashMap<String, i32>,")]
+    #[case("pub active: Option<bool>,")]
+    #[case("pub kind: ::ks_protobuf_v3::OpenEnum<person::Kind>,")]
+    #[case("pub manager: Option<Box<Person>>,")]
+    #[case("pub contact: Option<person::Contact>,")]
+    #[case(
+        "size += self.lucky_numbers.to_packed_field::<::ks_protobuf_v3::VarintCodec, 9>(buffer);"
+    )]
+    #[case("size += self.scores.to_map_field::<::ks_protobuf_v3::LengthDelimitedCodec, ::ks_protobuf_v3::SignExtendedVarintCodec, 4>(buffer);")]
+    #[case("(5 | 6, _) => person::Contact::decode_field(&mut self.contact, tag, buffer),")]
+    #[case("Address(Address),")]
+    #[case("Staff = 1,")]
+    #[case("Kind::Contractor => \"KIND_CONTRACTOR\",")]
+    #[case("if ::ks_protobuf_v3::Presence::is_present(&other.name) { self.name = other.name; }")]
+    #[case("self.emails.extend(other.emails);")]
+    #[case("self.scores.extend(other.scores);")]
+    #[case("::ks_protobuf_v3::merge_message(&mut self.manager, other.manager)?;")]
+    #[case("::ks_protobuf_v3::merge_oneof(&mut self.contact, other.contact)?;")]
+    #[case("(Contact::Address(v), Contact::Address(o)) => ::ks_protobuf_v3::Message::merge(v, o),")]
+    #[case("(this, other) => { *this = other; Ok(()) }")]
+    #[case("impl ::ks_protobuf_v3::ReflectMessage for Person {")]
+    #[case("descriptor_pool().get_message_by_name(\"acme.people.Person\").expect(\"the pool holds the message\")")]
+    #[case("super::descriptor_pool().get_message_by_name(\"acme.people.Person.Address\").expect(\"the pool holds the message\")")]
+    fn test_generated_code(#[case] expected_line: &str) {
+        // Arrange
+        // Act
+        let files = generate(CodeGeneratorOptions::default());
+
+        // Assert
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name(), "acme.people.rs");
+        assert!(
+            files[0]
+                .content()
+                .lines()
+                .any(|l| l.trim() == expected_line),
+            "{}",
+            files[0].content()
+        );
+    }
+
+    const LEGACY: &str = r#"syntax = "proto2";
+package legacy;
+enum Level { NONE = 0; LOW = 1; HIGH = 2; }
+message Entry {
+  required int32 id = 1;
+  optional string name = 2 [default = "none"];
+  optional Level level = 3 [default = HIGH];
+  repeated Level levels = 4;
+  repeated group Item = 5 { required int32 n = 6; }
+  extensions 100 to 199;
+  oneof choice { int32 first = 7; int32 last = 300; }
+}
+extend Entry { optional int32 priority = 100; }
+"#;
+
+    #[rstest]
+    #[case("pub id: Option<i32>,")]
+    #[case("pub extensions: ::ks_protobuf_v3::ExtensionSet,")]
+    #[case("self.name.as_deref().unwrap_or(\"none\")")]
+    #[case("self.level.and_then(|v| v.known()).unwrap_or(Level::High)")]
+    #[case("(3, ::ks_protobuf_v3::WireType::Varint) => ::ks_protobuf_v3::decode_closed_enum(buffer, |v| self.level = Some(v)),")]
+    #[case("(4, _) => ::ks_protobuf_v3::decode_closed_enums(&mut self.levels, &mut self.unknown_fields, tag, buffer),")]
+    #[case("pub unknown_fields: ::ks_protobuf_v3::UnknownFields,")]
+    #[case("size += self.unknown_fields.encode(buffer);")]
+    #[case("fn unknown_fields_mut(&mut self) -> Option<&mut ::ks_protobuf_v3::UnknownFields> { Some(&mut self.unknown_fields) }")]
+    #[case("self.unknown_fields.merge(other.unknown_fields);")]
+    #[case("size += self.item.to_group_fields::<5>(buffer);")]
+    #[case("(5, ::ks_protobuf_v3::WireType::StartGroup) => self.item.from_group_field(5, buffer).map(Some),")]
+    #[case("(100..=199, _) => self.extensions.decode_field(tag, buffer).map(Some),")]
+    #[case("if self.id.is_none() { return Err(::ks_protobuf_v3::DecodeError::MissingRequiredField(\"legacy.Entry.id\".to_string())); }")]
+    #[case("for v in &self.item { v.check_required_fields()?; }")]
+    #[case("pub const PRIORITY: ::ks_protobuf_v3::Extension<i32, ::ks_protobuf_v3::SignExtendedVarintCodec> = ::ks_protobuf_v3::Extension::new::<100>();")]
+    #[case("if let Some(oneof @ entry::Choice::First(_)) = &self.choice { size += oneof.encode(buffer); }")]
+    #[case("size += self.extensions.encode_range(100..200, buffer);")]
+    #[case("if let Some(oneof @ entry::Choice::Last(_)) = &self.choice { size += oneof.encode(buffer); }")]
+    #[case("fn is_singular_field(&self, field_number: u32) -> bool { matches!(field_number, 1 | 2 | 3 | 7 | 300) }")]
+    #[case("if ::ks_protobuf_v3::Presence::is_present(&other.id) { self.id = other.id; }")]
+    #[case("self.item.extend(other.item);")]
+    #[case("self.extensions.merge(other.extensions);")]
+    fn test_proto2_generated_code(#[case] expected_line: &str) {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("legacy.proto", LEGACY);
+        let set = compiler.compile(&["legacy.proto"]).unwrap();
+        let pool = DescriptorPool::from_file_descriptor_set(set).unwrap();
+
+        // Act
+        let files = CodeGenerator::default()
+            .generate(&pool, &["legacy.proto".to_string()])
+            .unwrap();
+
+        // Assert
+        assert!(
+            files[0]
+                .content()
+                .lines()
+                .any(|l| l.trim() == expected_line),
+            "{}",
+            files[0].content()
+        );
+    }
+
+    #[test]
+    fn test_single_layout_nests_packages() {
+        // Arrange
+        let options = CodeGeneratorOptions {
+            module_layout: ModuleLayout::Single,
+            map_type: MapType::BTreeMap,
+            ..Default::default()
+        };
+
+        // Act
+        let files = generate(options);
+
+        // Assert
+        let content = files[0].content();
+        assert_eq!(files[0].name(), "protos.rs");
+        assert!(content.contains("pub mod acme {\n"));
+        assert!(content.contains("\n    pub mod people {\n"));
+        assert!(content.contains("pub scores: ::std::collections::BTreeMap<String, i32>,"));
+    }
+
+    #[test]
+    fn test_extern_types_are_not_generated() {
+        // Arrange
+        let options = CodeGeneratorOptions::from_parameter(
+            "extern_path=.acme.people.Person.Kind=::acme::Kind",
+        )
+        .unwrap();
+
+        // Act
+        let files = generate(options);
+
+        // Assert
+        let content = files[0].content();
+        assert!(content.contains("pub kind: ::ks_protobuf_v3::OpenEnum<::acme::Kind>,"));
+        assert!(!content.contains("pub enum Kind"));
+    }
+
+    #[test]
+    fn test_descriptors_are_embedded() {
+        // Arrange
+        let files = generate(CodeGeneratorOptions::default());
+        let literal = files[0]
+            .content()
+            .lines()
+            .find_map(|l| l.strip_prefix("pub const FILE_DESCRIPTOR_SET: &[u8] = b\""))
+            .and_then(|l| l.strip_suffix("\";"))
+            .unwrap();
+
+        // Act
+        let pool = DescriptorPool::decode(&unescape_bytes(literal).unwrap()).unwrap();
+
+        // Assert
+        let person = pool.get_message_by_name("acme.people.Person").unwrap();
+        assert_eq!(person.get_field(10).unwrap().name(), "manager");
+        assert_eq!(pool.file_descriptor_set().file[0].source_code_info, None);
+    }
+
+    // Code generated from golden/closed_enums.proto and golden/golden.proto, checked in so that it
+    // is compiled with the tests.
+    #[allow(clippy::all, unused_qualifications)]
+    mod closed_enums {
+        include!("../golden/closed_enums.rs");
+    }
+
+    #[allow(clippy::all, unused_qualifications)]
+    mod golden {
+        include!("../golden/golden.rs");
+    }
+
+    #[rstest]
+    #[case(
+        "closed_enums.proto",
+        include_str!("../golden/closed_enums.proto"),
+        include_str!("../golden/closed_enums.rs")
+    )]
+    #[case(
+        "golden.proto",
+        include_str!("../golden/golden.proto"),
+        include_str!("../golden/golden.rs")
+    )]
+    fn test_golden_code_is_up_to_date(
+        #[case] file_name: &str,
+        #[case] source: &str,
+        #[case] expected_content: &str,
+    ) {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.include_source_info(true);
+        compiler.add_source(file_name, source);
+        let set = compiler.compile(&[file_name]).unwrap();
+        let pool = DescriptorPool::from_file_descriptor_set(set).unwrap();
+
+        // Act
+        let files = CodeGenerator::default()
+            .generate(&pool, &[file_name.to_string()])
+            .unwrap();
+
+        // Assert
+        assert_eq!(files[0].content(), expected_content);
+    }
+
+    fn round_trip<M: Message>(bytes: &[u8]) -> Vec<u8> {
+        let message = M::decode(bytes).unwrap();
+        EncodeMode::Deterministic.scope(|| message.encode_to_vec())
+    }
+
+    // The unknown payload is left out, as proto3 generated code does not keep unknown fields.
+    #[rstest]
+    #[case(round_trip::<golden::Scalars>, include_bytes!("../golden/scalars.bin"))]
+    #[case(round_trip::<golden::Scalars>, include_bytes!("../golden/negatives.bin"))]
+    #[case(round_trip::<golden::Packed>, include_bytes!("../golden/packed.bin"))]
+    #[case(round_trip::<golden::Unpacked>, include_bytes!("../golden/unpacked.bin"))]
+    #[case(round_trip::<golden::Maps>, include_bytes!("../golden/maps.bin"))]
+    #[case(round_trip::<golden::Nested>, include_bytes!("../golden/nested.bin"))]
+    fn test_golden_payloads_round_trip_through_generated_code(
+        #[case] round_trip: fn(&[u8]) -> Vec<u8>,
+        #[case] bytes: &[u8],
+    ) {
+        // Act
+        let encoded = round_trip(bytes);
+
+        // Assert
+        assert_eq!(encoded, bytes);
+    }
+
+    // The float value of -0.0 is not written by the reference implementation, as it compares equal
+    // to the default value.
+    fn negatives() -> golden::Scalars {
+        golden::Scalars {
+            int32_value: -1,
+            int64_value: -1,
+            sint32_value: -1,
+            sint64_value: -1,
+            sfixed32_value: -1,
+            sfixed64_value: -1,
+            double_value: -1e300,
+            enum_value: golden::Colour::Negative.into(),
+            ..Default::default()
+        }
+    }
+
+    #[rstest]
+    #[case(
+        include_bytes!("../golden/scalars.bin"),
+        golden::Scalars {
+            int32_value: i32::MAX,
+            int64_value: i64::MAX,
+            uint32_value: u32::MAX,
+            uint64_value: u64::MAX,
+            sint32_value: i32::MIN,
+            sint64_value: i64::MIN,
+            fixed32_value: u32::MAX,
+            fixed64_value: u64::MAX,
+            sfixed32_value: i32::MIN,
+            sfixed64_value: i64::MIN,
+            float_value: 3.5,
+            double_value: -1.25e-300,
+            bool_value: true,
+            string_value: "héllo €😀".to_string(),
+            bytes_value: vec![0, 1, 254, 255],
+            enum_value: golden::Colour::Green.into(),
+        }
+    )]
+    #[case(include_bytes!("../golden/negatives.bin"), negatives())]
+    fn test_golden_scalars_decode_through_generated_code(
+        #[case] bytes: &[u8],
+        #[case] expected: golden::Scalars,
+    ) {
+        // Act
+        let scalars = golden::Scalars::decode(bytes).unwrap();
+
+        // Assert
+        assert_eq!(scalars, expected);
+    }
+
+    #[test]
+    fn test_golden_repeated_fields_decode_through_generated_code() {
+        // Act
+        let packed = golden::Packed::decode(include_bytes!("../golden/packed.bin")).unwrap();
+        let unpacked = golden::Unpacked::decode(include_bytes!("../golden/unpacked.bin")).unwrap();
+
+        // Assert
+        assert_eq!(packed.int32_values, vec![0, 1, -1, i32::MAX, i32::MIN]);
+        assert_eq!(packed.int64_values, vec![0, 300, -300, i64::MAX, i64::MIN]);
+        assert_eq!(packed.uint64_values, vec![0, 16383, 16384, u64::MAX]);
+        assert_eq!(packed.sint64_values, vec![0, -1, 1, i64::MIN, i64::MAX]);
+        assert_eq!(packed.float_values, vec![0.0, -1.5, 1e30]);
+        assert_eq!(packed.bool_values, vec![true, false, true]);
+        assert_eq!(
+            packed.enum_values,
+            vec![
+                golden::Colour::Unspecified.into(),
+                golden::Colour::Red.into(),
+                golden::Colour::Negative.into(),
+                golden::Colour::Green.into(),
+            ]
+        );
+        assert_eq!(unpacked.sint32_values, vec![0, -1, 1, i32::MIN, i32::MAX]);
+        assert_eq!(unpacked.sfixed64_values, vec![0, -1, i64::MAX]);
+        assert_eq!(unpacked.string_values, vec!["", "a", "é"]);
+        assert_eq!(unpacked.bytes_values, vec![vec![], vec![0, 255]]);
+    }
+
+    #[test]
+    fn test_golden_maps_decode_through_generated_code() {
+        // Act
+        let maps = golden::Maps::decode(include_bytes!("../golden/maps.bin")).unwrap();
+
+        // Assert
+        assert_eq!(
+            maps.string_to_int32,
+            HashMap::from([
+                ("".to_string(), 0),
+                ("a".to_string(), -1),
+                ("b".to_string(), i32::MAX)
+            ])
+        );
+        assert_eq!(
+            maps.int32_to_string,
+            HashMap::from([
+                (-5, "minus five".to_string()),
+                (0, "".to_string()),
+                (7, "seven".to_string()),
+            ])
+        );
+        assert_eq!(maps.int64_to_message[&i64::MIN].name, "min");
+        assert_eq!(
+            maps.int64_to_message[&1].child.as_ref().unwrap().name,
+            "child"
+        );
+        assert_eq!(
+            maps.uint32_to_enum,
+            HashMap::from([
+                (0, golden::Colour::Red.into()),
+                (u32::MAX, golden::Colour::Negative.into()),
+            ])
+        );
+        assert_eq!(maps.sint64_to_double, HashMap::from([(-1, 0.5), (1, -0.5)]));
+        assert_eq!(
+            maps.bool_to_bytes,
+            HashMap::from([(false, vec![]), (true, vec![255])])
+        );
+        assert_eq!(
+            maps.fixed32_to_fixed64,
+            HashMap::from([(1, u64::MAX), (u32::MAX, 0)])
+        );
+    }
+
+    #[test]
+    fn test_golden_nested_decodes_through_generated_code() {
+        // Arrange
+        let named = |name: &str| golden::Nested {
+            name: name.to_string(),
+            ..Default::default()
+        };
+        let expected = golden::Nested {
+            child: Some(Box::new(golden::Nested {
+                child: Some(Box::new(golden::Nested {
+                    child: Some(Box::new(named("great grandchild"))),
+                    ..Default::default()
+                })),
+                choice: Some(golden::nested::Choice::Number(5)),
+                ..named("child")
+            })),
+            children: vec![
+                golden::Nested {
+                    choice: Some(golden::nested::Choice::Text("text".to_string())),
+                    ..named("first")
+                },
+                golden::Nested::default(),
+                golden::Nested {
+                    choice: Some(golden::nested::Choice::Other(Box::new(named("other")))),
+                    ..named("third")
+                },
+            ],
+            scalars: Some(negatives()),
+            ..named("root")
+        };
+
+        // Act
+        let nested = golden::Nested::decode(include_bytes!("../golden/nested.bin")).unwrap();
+
+        // Assert
+        assert_eq!(nested, expected);
+    }
+
+    #[test]
+    fn test_golden_unknown_fields_are_skipped_by_generated_code() {
+        // Act
+        let partial =
+            golden::PartialScalars::decode(include_bytes!("../golden/unknown.bin")).unwrap();
+
+        // Assert
+        assert_eq!(
+            partial,
+            golden::PartialScalars {
+                int32_value: i32::MAX,
+                sint64_value: i64::MIN,
+                string_value: "héllo €😀".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_closed_enum_values_survive_round_trip() {
+        // Arrange
+        let buffer = [8, 7, 16, 7, 16, 1, 26, 2, 1, 9, 32, 8];
+
+        // Act
+        let entry = closed_enums::Entry::decode(&buffer).unwrap();
+        let encoded = entry.encode_to_vec();
+
+        // Assert
+        assert_eq!(entry.level, None);
+        assert_eq!(entry.level(), closed_enums::Level::None);
+        assert_eq!(entry.levels, vec![closed_enums::Level::Low.into()]);
+        assert_eq!(entry.packed_levels, vec![closed_enums::Level::Low.into()]);
+        assert_eq!(entry.choice, None);
+        assert_eq!(
+            entry.unknown_fields.as_bytes(),
+            &[8, 7, 16, 7, 24, 9, 32, 8]
+        );
+        assert_eq!(encoded, vec![16, 1, 26, 1, 1, 8, 7, 16, 7, 24, 9, 32, 8]);
+        assert_eq!(closed_enums::Entry::decode(&encoded).unwrap(), entry);
+    }
+
+    #[rstest]
+    #[case("", None)]
+    #[case("layout=flat", Some("The parameter layout=flat is not recognised."))]
+    fn test_plugin_round_trip(#[case] parameter: &str, #[case] expected_error: Option<&str>) {
+        // Arrange
+        let request = CodeGeneratorRequest {
+            file_to_generate: vec!["person.proto".to_string()],
+            parameter: Some(parameter.to_string()),
+            proto_file: vec![person_file()],
+            ..Default::default()
+        };
+        let request = CodeGeneratorRequest::decode(&request.encode_to_vec()).unwrap();
+
+        // Act
+        let response = run_plugin(&request);
+
+        // Assert
+        let response = CodeGeneratorResponse::decode(&response.encode_to_vec()).unwrap();
+        assert_eq!(response.error.as_deref(), expected_error);
+        assert_eq!(response.file.len(), usize::from(expected_error.is_none()));
+        assert_eq!(
+            response.supported_features,
+            Some(Feature::Proto3Optional as u64)
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::scoped::scoped_set;
+use std::cell::Cell;
+
+thread_local! {
+    static CURRENT: Cell<DecodeMode> = const { Cell::new(DecodeMode::Lenient) };
+}
+
+/// Decides how input that is well formed but not in its canonical form is decoded.
+///
+/// The mode is held per thread. It applies while a message is decoded with `decode_with_mode`, or
+/// within [`DecodeMode::scope`], on the thread which called it, so that the field decoders nested
+/// within the message do not need to be passed it. It does not propagate to other threads: a
+/// decoder which hands work to another thread must read [`DecodeMode::current`] first and enter
+/// the mode there with [`DecodeMode::scope`], or that thread decodes leniently.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{DecodeError, DecodeMode, Message, Tag, Varint, VarintField};
+///
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Flag {
+///     on: bool,
+/// }
+///
+/// impl Message for Flag {
+///     fn encode(&self, buffer: &mut ks_protobuf_v3::Buffer) -> usize {
+///         self.on.to_varint_field::<1>(buffer)
+///     }
+///
+///     fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+///         match tag.field_number() {
+///             1 => self.on.from_varint(buffer).map(Some),
+///             _ => Ok(None),
+///         }
+///     }
+/// }
+///
+/// assert_eq!(Flag::decode(&[8, 2]).unwrap(), Flag { on: true });
+/// assert!(Flag::decode_with_mode(&[8, 2], DecodeMode::Strict).is_err());
+/// ```
+///
+/// Decoding on another thread:
+/// ```
+/// use ks_protobuf_v3::DecodeMode;
+///
+/// DecodeMode::Strict.scope(|| {
+///     let mode = DecodeMode::current();
+///     std::thread::scope(|s| {
+///         let other = s.spawn(DecodeMode::current).join().unwrap();
+///         let entered = s.spawn(|| mode.scope(DecodeMode::current)).join().unwrap();
+///
+///         assert_eq!(other, DecodeMode::Lenient);
+///         assert_eq!(entered, DecodeMode::Strict);
+///     });
+/// });
+/// ```
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum DecodeMode {
+    /// Accepts what the reference implementation accepts. Any non-zero bool is true, varints too
+    /// large for 32 bit fields are truncated and a singular field set more than once keeps the
+    /// last value.
+    #[default]
+    Lenient,
+
+    /// Rejects varints written with more bytes than needed, bools other than 0 and 1, values too
+    /// large for their field, singular fields set more than once and enum values without a
+    /// variant. Intended for parsing input which must have a single encoding.
+    Strict,
+}
+
+impl DecodeMode {
+    /// Returns the mode used by decoding on the current thread.
+    pub fn current() -> Self {
+        CURRENT.with(Cell::get)
+    }
+
+    /// Returns true when decoding on the current thread is strict.
+    pub fn is_strict() -> bool {
+        Self::current() == DecodeMode::Strict
+    }
+
+    /// Runs the function with the mode used by decoding on the current thread, restoring the
+    /// previous mode afterwards.
+    pub fn scope<T>(self, f: impl FnOnce() -> T) -> T {
+        scoped_set(&CURRENT, self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_restores_mode() {
+        // Act
+        let inner = DecodeMode::Strict.scope(|| {
+            let nested = DecodeMode::Lenient.scope(DecodeMode::current);
+            (nested, DecodeMode::current())
+        });
+
+        // Assert
+        assert_eq!(inner, (DecodeMode::Lenient, DecodeMode::Strict));
+        assert_eq!(DecodeMode::current(), DecodeMode::Lenient);
+    }
+}
@@ -0,0 +1,2653 @@
+use crate::{
+    skip_tagged_field, Buffer, DecodeError, Enumeration, Fixed64, Fixed64Field, LengthDelimited,
+    LengthDelimitedCodec, LengthDelimitedField, Message, OpenEnum, RepeatedField,
+    SignExtendedVarint, SignExtendedVarintCodec, SignExtendedVarintField, Tag, Varint, VarintField,
+    WireType,
+};
+
+/// Represents `google.protobuf.FileDescriptorSet`, the set of `.proto` files the protocol compiler
+/// writes out when it is run with `--descriptor_set_out`.
+///
+/// The types in this module mirror `google/protobuf/descriptor.proto` and are written by hand
+/// using the same traits as any other message, so that the crate can read descriptors without
+/// depending on generated code.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{FileDescriptorProto, FileDescriptorSet, Message};
+///
+/// let set = FileDescriptorSet {
+///     file: vec![FileDescriptorProto {
+///         name: Some("greeting.proto".to_string()),
+///         package: Some("greeting".to_string()),
+///         ..Default::default()
+///     }],
+/// };
+///
+/// let bytes = set.encode_to_vec();
+/// let decoded = FileDescriptorSet::decode(&bytes).unwrap();
+///
+/// assert_eq!(decoded.file[0].package(), "greeting");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileDescriptorSet {
+    /// The files in the set.
+    pub file: Vec<FileDescriptorProto>,
+}
+
+impl Message for FileDescriptorSet {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        self.file
+            .to_unpacked_field::<LengthDelimitedCodec, 1>(buffer)
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, wire_type) => self
+                .file
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes a complete `.proto` file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileDescriptorProto {
+    /// The file name, relative to the root of the source tree.
+    pub name: Option<String>,
+
+    /// The package of the file, e.g. `foo.bar`.
+    pub package: Option<String>,
+
+    /// The names of the files imported by this file.
+    pub dependency: Vec<String>,
+
+    /// All top-level message definitions in this file.
+    pub message_type: Vec<DescriptorProto>,
+
+    /// All top-level enum definitions in this file.
+    pub enum_type: Vec<EnumDescriptorProto>,
+
+    /// All service definitions in this file.
+    pub service: Vec<ServiceDescriptorProto>,
+
+    /// All top-level extension definitions in this file.
+    pub extension: Vec<FieldDescriptorProto>,
+
+    /// The options of the file.
+    pub options: Option<FileOptions>,
+
+    /// The locations of the definitions in the original source file.
+    pub source_code_info: Option<SourceCodeInfo>,
+
+    /// Indexes of the public imported files in the dependency list.
+    pub public_dependency: Vec<i32>,
+
+    /// Indexes of the weak imported files in the dependency list.
+    pub weak_dependency: Vec<i32>,
+
+    /// The syntax of the file, either `proto2`, `proto3` or `editions`.
+    pub syntax: Option<String>,
+
+    /// The edition of the file when its syntax is `editions`.
+    pub edition: Option<OpenEnum<Edition>>,
+}
+
+impl FileDescriptorProto {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `package` field, or an empty string when it is not set.
+    pub fn package(&self) -> &str {
+        self.package.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `syntax` field, or an empty string when it is not set.
+    pub fn syntax(&self) -> &str {
+        self.syntax.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `edition` field, or `None` when it is not set or is not a known
+    /// value.
+    pub fn edition(&self) -> Option<Edition> {
+        self.edition.and_then(|v| v.known())
+    }
+}
+
+impl Message for FileDescriptorProto {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self.package.to_length_delimited_field::<2>(buffer);
+        size += self
+            .dependency
+            .to_unpacked_field::<LengthDelimitedCodec, 3>(buffer);
+        size += self
+            .message_type
+            .to_unpacked_field::<LengthDelimitedCodec, 4>(buffer);
+        size += self
+            .enum_type
+            .to_unpacked_field::<LengthDelimitedCodec, 5>(buffer);
+        size += self
+            .service
+            .to_unpacked_field::<LengthDelimitedCodec, 6>(buffer);
+        size += self
+            .extension
+            .to_unpacked_field::<LengthDelimitedCodec, 7>(buffer);
+        size += self.options.to_length_delimited_field::<8>(buffer);
+        size += self.source_code_info.to_length_delimited_field::<9>(buffer);
+        size += self
+            .public_dependency
+            .to_unpacked_field::<SignExtendedVarintCodec, 10>(buffer);
+        size += self
+            .weak_dependency
+            .to_unpacked_field::<SignExtendedVarintCodec, 11>(buffer);
+        size += self.syntax.to_length_delimited_field::<12>(buffer);
+        size += self.edition.to_varint_field::<14>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, WireType::LengthDelimited) => self.package.from_length_delimited(buffer).map(Some),
+            (3, wire_type) => self
+                .dependency
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (4, wire_type) => self
+                .message_type
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (5, wire_type) => self
+                .enum_type
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (6, wire_type) => self
+                .service
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (7, wire_type) => self
+                .extension
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (8, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            (9, WireType::LengthDelimited) => self
+                .source_code_info
+                .from_length_delimited(buffer)
+                .map(Some),
+            (10, wire_type) => self
+                .public_dependency
+                .from_repeated_field::<SignExtendedVarintCodec>(wire_type, buffer),
+            (11, wire_type) => self
+                .weak_dependency
+                .from_repeated_field::<SignExtendedVarintCodec>(wire_type, buffer),
+            (12, WireType::LengthDelimited) => self.syntax.from_length_delimited(buffer).map(Some),
+            (14, WireType::Varint) => self.edition.from_varint(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes a message type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct DescriptorProto {
+    /// The name of the message type.
+    pub name: Option<String>,
+
+    /// The fields of the message type.
+    pub field: Vec<FieldDescriptorProto>,
+
+    /// The message types nested within the message type.
+    pub nested_type: Vec<DescriptorProto>,
+
+    /// The enum types nested within the message type.
+    pub enum_type: Vec<EnumDescriptorProto>,
+
+    /// The ranges of field numbers reserved for extensions.
+    pub extension_range: Vec<ExtensionRange>,
+
+    /// The extensions declared within the message type.
+    pub extension: Vec<FieldDescriptorProto>,
+
+    /// The options of the message type.
+    pub options: Option<MessageOptions>,
+
+    /// The oneof groups of the message type.
+    pub oneof_decl: Vec<OneofDescriptorProto>,
+
+    /// Ranges of field numbers that may not be used by fields.
+    pub reserved_range: Vec<ReservedRange>,
+
+    /// Field names that may not be used by fields.
+    pub reserved_name: Vec<String>,
+}
+
+impl DescriptorProto {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for DescriptorProto {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self
+            .field
+            .to_unpacked_field::<LengthDelimitedCodec, 2>(buffer);
+        size += self
+            .nested_type
+            .to_unpacked_field::<LengthDelimitedCodec, 3>(buffer);
+        size += self
+            .enum_type
+            .to_unpacked_field::<LengthDelimitedCodec, 4>(buffer);
+        size += self
+            .extension_range
+            .to_unpacked_field::<LengthDelimitedCodec, 5>(buffer);
+        size += self
+            .extension
+            .to_unpacked_field::<LengthDelimitedCodec, 6>(buffer);
+        size += self.options.to_length_delimited_field::<7>(buffer);
+        size += self
+            .oneof_decl
+            .to_unpacked_field::<LengthDelimitedCodec, 8>(buffer);
+        size += self
+            .reserved_range
+            .to_unpacked_field::<LengthDelimitedCodec, 9>(buffer);
+        size += self
+            .reserved_name
+            .to_unpacked_field::<LengthDelimitedCodec, 10>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, wire_type) => self
+                .field
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (3, wire_type) => self
+                .nested_type
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (4, wire_type) => self
+                .enum_type
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (5, wire_type) => self
+                .extension_range
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (6, wire_type) => self
+                .extension
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (7, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            (8, wire_type) => self
+                .oneof_decl
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (9, wire_type) => self
+                .reserved_range
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (10, wire_type) => self
+                .reserved_name
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A range of field numbers in a message type reserved for extensions.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExtensionRange {
+    /// The first field number of the range, inclusive.
+    pub start: Option<i32>,
+
+    /// The last field number of the range, exclusive.
+    pub end: Option<i32>,
+
+    /// The options of the range.
+    pub options: Option<ExtensionRangeOptions>,
+}
+
+impl ExtensionRange {
+    /// Returns the value of the `start` field, or zero when it is not set.
+    pub fn start(&self) -> i32 {
+        self.start.unwrap_or_default()
+    }
+
+    /// Returns the value of the `end` field, or zero when it is not set.
+    pub fn end(&self) -> i32 {
+        self.end.unwrap_or_default()
+    }
+}
+
+impl Message for ExtensionRange {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.start.to_sign_extended_varint_field::<1>(buffer);
+        size += self.end.to_sign_extended_varint_field::<2>(buffer);
+        size += self.options.to_length_delimited_field::<3>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => self.start.from_sign_extended_varint(buffer).map(Some),
+            (2, WireType::Varint) => self.end.from_sign_extended_varint(buffer).map(Some),
+            (3, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A range of reserved field numbers in a message type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReservedRange {
+    /// The first field number of the range, inclusive.
+    pub start: Option<i32>,
+
+    /// The last field number of the range, exclusive.
+    pub end: Option<i32>,
+}
+
+impl ReservedRange {
+    /// Returns the value of the `start` field, or zero when it is not set.
+    pub fn start(&self) -> i32 {
+        self.start.unwrap_or_default()
+    }
+
+    /// Returns the value of the `end` field, or zero when it is not set.
+    pub fn end(&self) -> i32 {
+        self.end.unwrap_or_default()
+    }
+}
+
+impl Message for ReservedRange {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.start.to_sign_extended_varint_field::<1>(buffer);
+        size += self.end.to_sign_extended_varint_field::<2>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => self.start.from_sign_extended_varint(buffer).map(Some),
+            (2, WireType::Varint) => self.end.from_sign_extended_varint(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of an extension range.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExtensionRangeOptions {
+    /// The features set on the extension range, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl Message for ExtensionRangeOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.features.to_length_delimited_field::<50>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (50, WireType::LengthDelimited) => {
+                self.features.from_length_delimited(buffer).map(Some)
+            }
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes a field within a message.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FieldDescriptorProto {
+    /// The name of the field.
+    pub name: Option<String>,
+
+    /// For extensions, the name of the message type being extended.
+    pub extendee: Option<String>,
+
+    /// The field number.
+    pub number: Option<i32>,
+
+    /// Whether the field is optional, required or repeated.
+    pub label: Option<OpenEnum<FieldLabel>>,
+
+    /// The type of the field. Not set when only the type name is known.
+    pub r#type: Option<OpenEnum<FieldType>>,
+
+    /// For message and enum types, the name of the type. Names starting with `.` are fully qualified.
+    pub type_name: Option<String>,
+
+    /// The default value of the field, written as it is in the `.proto` file.
+    pub default_value: Option<String>,
+
+    /// The options of the field.
+    pub options: Option<FieldOptions>,
+
+    /// The index of the oneof group containing the field within `oneof_decl`.
+    pub oneof_index: Option<i32>,
+
+    /// The JSON name of the field.
+    pub json_name: Option<String>,
+
+    /// True when a proto3 field was declared `optional`.
+    pub proto3_optional: Option<bool>,
+}
+
+impl FieldDescriptorProto {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `extendee` field, or an empty string when it is not set.
+    pub fn extendee(&self) -> &str {
+        self.extendee.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `number` field, or zero when it is not set.
+    pub fn number(&self) -> i32 {
+        self.number.unwrap_or_default()
+    }
+
+    /// Returns the value of the `label` field, or `None` when it is not set or is not a known value.
+    pub fn label(&self) -> Option<FieldLabel> {
+        self.label.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `type` field, or `None` when it is not set or is not a known value.
+    pub fn r#type(&self) -> Option<FieldType> {
+        self.r#type.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `type_name` field, or an empty string when it is not set.
+    pub fn type_name(&self) -> &str {
+        self.type_name.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `default_value` field, or an empty string when it is not set.
+    pub fn default_value(&self) -> &str {
+        self.default_value.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `oneof_index` field, or zero when it is not set.
+    pub fn oneof_index(&self) -> i32 {
+        self.oneof_index.unwrap_or_default()
+    }
+
+    /// Returns the value of the `json_name` field, or an empty string when it is not set.
+    pub fn json_name(&self) -> &str {
+        self.json_name.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `proto3_optional` field, or `false` when it is not set.
+    pub fn proto3_optional(&self) -> bool {
+        self.proto3_optional.unwrap_or_default()
+    }
+}
+
+impl Message for FieldDescriptorProto {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self.extendee.to_length_delimited_field::<2>(buffer);
+        size += self.number.to_sign_extended_varint_field::<3>(buffer);
+        size += self.label.to_varint_field::<4>(buffer);
+        size += self.r#type.to_varint_field::<5>(buffer);
+        size += self.type_name.to_length_delimited_field::<6>(buffer);
+        size += self.default_value.to_length_delimited_field::<7>(buffer);
+        size += self.options.to_length_delimited_field::<8>(buffer);
+        size += self.oneof_index.to_sign_extended_varint_field::<9>(buffer);
+        size += self.json_name.to_length_delimited_field::<10>(buffer);
+        size += self.proto3_optional.to_varint_field::<17>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, WireType::LengthDelimited) => self.extendee.from_length_delimited(buffer).map(Some),
+            (3, WireType::Varint) => self.number.from_sign_extended_varint(buffer).map(Some),
+            (4, WireType::Varint) => self.label.from_varint(buffer).map(Some),
+            (5, WireType::Varint) => self.r#type.from_varint(buffer).map(Some),
+            (6, WireType::LengthDelimited) => {
+                self.type_name.from_length_delimited(buffer).map(Some)
+            }
+            (7, WireType::LengthDelimited) => {
+                self.default_value.from_length_delimited(buffer).map(Some)
+            }
+            (8, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            (9, WireType::Varint) => self.oneof_index.from_sign_extended_varint(buffer).map(Some),
+            (10, WireType::LengthDelimited) => {
+                self.json_name.from_length_delimited(buffer).map(Some)
+            }
+            (17, WireType::Varint) => self.proto3_optional.from_varint(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes a oneof group.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OneofDescriptorProto {
+    /// The name of the group.
+    pub name: Option<String>,
+
+    /// The options of the group.
+    pub options: Option<OneofOptions>,
+}
+
+impl OneofDescriptorProto {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for OneofDescriptorProto {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self.options.to_length_delimited_field::<2>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes an enum type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnumDescriptorProto {
+    /// The name of the enum type.
+    pub name: Option<String>,
+
+    /// The values of the enum type.
+    pub value: Vec<EnumValueDescriptorProto>,
+
+    /// The options of the enum type.
+    pub options: Option<EnumOptions>,
+
+    /// Ranges of numbers that may not be used by values.
+    pub reserved_range: Vec<EnumReservedRange>,
+
+    /// Names that may not be used by values.
+    pub reserved_name: Vec<String>,
+}
+
+impl EnumDescriptorProto {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for EnumDescriptorProto {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self
+            .value
+            .to_unpacked_field::<LengthDelimitedCodec, 2>(buffer);
+        size += self.options.to_length_delimited_field::<3>(buffer);
+        size += self
+            .reserved_range
+            .to_unpacked_field::<LengthDelimitedCodec, 4>(buffer);
+        size += self
+            .reserved_name
+            .to_unpacked_field::<LengthDelimitedCodec, 5>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, wire_type) => self
+                .value
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (3, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            (4, wire_type) => self
+                .reserved_range
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (5, wire_type) => self
+                .reserved_name
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A range of reserved numbers in an enum type. Unlike message ranges, the end is inclusive.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnumReservedRange {
+    /// The first number of the range, inclusive.
+    pub start: Option<i32>,
+
+    /// The last number of the range, inclusive.
+    pub end: Option<i32>,
+}
+
+impl EnumReservedRange {
+    /// Returns the value of the `start` field, or zero when it is not set.
+    pub fn start(&self) -> i32 {
+        self.start.unwrap_or_default()
+    }
+
+    /// Returns the value of the `end` field, or zero when it is not set.
+    pub fn end(&self) -> i32 {
+        self.end.unwrap_or_default()
+    }
+}
+
+impl Message for EnumReservedRange {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.start.to_sign_extended_varint_field::<1>(buffer);
+        size += self.end.to_sign_extended_varint_field::<2>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => self.start.from_sign_extended_varint(buffer).map(Some),
+            (2, WireType::Varint) => self.end.from_sign_extended_varint(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes a value within an enum type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnumValueDescriptorProto {
+    /// The name of the value.
+    pub name: Option<String>,
+
+    /// The number of the value.
+    pub number: Option<i32>,
+
+    /// The options of the value.
+    pub options: Option<EnumValueOptions>,
+}
+
+impl EnumValueDescriptorProto {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `number` field, or zero when it is not set.
+    pub fn number(&self) -> i32 {
+        self.number.unwrap_or_default()
+    }
+}
+
+impl Message for EnumValueDescriptorProto {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self.number.to_sign_extended_varint_field::<2>(buffer);
+        size += self.options.to_length_delimited_field::<3>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, WireType::Varint) => self.number.from_sign_extended_varint(buffer).map(Some),
+            (3, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes a service.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ServiceDescriptorProto {
+    /// The name of the service.
+    pub name: Option<String>,
+
+    /// The methods of the service.
+    pub method: Vec<MethodDescriptorProto>,
+
+    /// The options of the service.
+    pub options: Option<ServiceOptions>,
+}
+
+impl ServiceDescriptorProto {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for ServiceDescriptorProto {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self
+            .method
+            .to_unpacked_field::<LengthDelimitedCodec, 2>(buffer);
+        size += self.options.to_length_delimited_field::<3>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, wire_type) => self
+                .method
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (3, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes a method of a service.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MethodDescriptorProto {
+    /// The name of the method.
+    pub name: Option<String>,
+
+    /// The fully qualified name of the request message type.
+    pub input_type: Option<String>,
+
+    /// The fully qualified name of the response message type.
+    pub output_type: Option<String>,
+
+    /// The options of the method.
+    pub options: Option<MethodOptions>,
+
+    /// True when the client streams multiple requests.
+    pub client_streaming: Option<bool>,
+
+    /// True when the server streams multiple responses.
+    pub server_streaming: Option<bool>,
+}
+
+impl MethodDescriptorProto {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `input_type` field, or an empty string when it is not set.
+    pub fn input_type(&self) -> &str {
+        self.input_type.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `output_type` field, or an empty string when it is not set.
+    pub fn output_type(&self) -> &str {
+        self.output_type.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `client_streaming` field, or `false` when it is not set.
+    pub fn client_streaming(&self) -> bool {
+        self.client_streaming.unwrap_or_default()
+    }
+
+    /// Returns the value of the `server_streaming` field, or `false` when it is not set.
+    pub fn server_streaming(&self) -> bool {
+        self.server_streaming.unwrap_or_default()
+    }
+}
+
+impl Message for MethodDescriptorProto {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self.input_type.to_length_delimited_field::<2>(buffer);
+        size += self.output_type.to_length_delimited_field::<3>(buffer);
+        size += self.options.to_length_delimited_field::<4>(buffer);
+        size += self.client_streaming.to_varint_field::<5>(buffer);
+        size += self.server_streaming.to_varint_field::<6>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, WireType::LengthDelimited) => {
+                self.input_type.from_length_delimited(buffer).map(Some)
+            }
+            (3, WireType::LengthDelimited) => {
+                self.output_type.from_length_delimited(buffer).map(Some)
+            }
+            (4, WireType::LengthDelimited) => self.options.from_length_delimited(buffer).map(Some),
+            (5, WireType::Varint) => self.client_streaming.from_varint(buffer).map(Some),
+            (6, WireType::Varint) => self.server_streaming.from_varint(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of a file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FileOptions {
+    /// The package of the generated Java classes.
+    pub java_package: Option<String>,
+
+    /// The name of the generated Java outer class.
+    pub java_outer_classname: Option<String>,
+
+    /// What the generated code should be optimised for.
+    pub optimize_for: Option<OpenEnum<OptimizeMode>>,
+
+    /// True to generate a separate Java file for each top-level type.
+    pub java_multiple_files: Option<bool>,
+
+    /// The import path of the generated Go package.
+    pub go_package: Option<String>,
+
+    /// True to generate abstract C++ service classes.
+    pub cc_generic_services: Option<bool>,
+
+    /// True to generate abstract Java service classes.
+    pub java_generic_services: Option<bool>,
+
+    /// True to generate abstract Python service classes.
+    pub py_generic_services: Option<bool>,
+
+    /// Deprecated and ignored.
+    pub java_generate_equals_and_hash: Option<bool>,
+
+    /// True when everything in the file is deprecated.
+    pub deprecated: Option<bool>,
+
+    /// True to check strings are valid UTF-8 in Java.
+    pub java_string_check_utf8: Option<bool>,
+
+    /// True to enable arena allocation in C++.
+    pub cc_enable_arenas: Option<bool>,
+
+    /// The prefix of generated Objective-C classes.
+    pub objc_class_prefix: Option<String>,
+
+    /// The namespace of the generated C# classes.
+    pub csharp_namespace: Option<String>,
+
+    /// The prefix of generated Swift types.
+    pub swift_prefix: Option<String>,
+
+    /// The prefix of generated PHP classes.
+    pub php_class_prefix: Option<String>,
+
+    /// The namespace of the generated PHP classes.
+    pub php_namespace: Option<String>,
+
+    /// The namespace of the generated PHP metadata classes.
+    pub php_metadata_namespace: Option<String>,
+
+    /// The package of the generated Ruby classes.
+    pub ruby_package: Option<String>,
+
+    /// The features set on the file, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl FileOptions {
+    /// Returns the value of the `java_package` field, or an empty string when it is not set.
+    pub fn java_package(&self) -> &str {
+        self.java_package.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `java_outer_classname` field, or an empty string when it is not set.
+    pub fn java_outer_classname(&self) -> &str {
+        self.java_outer_classname.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `optimize_for` field, or `None` when it is not set or is not a known value.
+    pub fn optimize_for(&self) -> Option<OptimizeMode> {
+        self.optimize_for.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `java_multiple_files` field, or `false` when it is not set.
+    pub fn java_multiple_files(&self) -> bool {
+        self.java_multiple_files.unwrap_or_default()
+    }
+
+    /// Returns the value of the `go_package` field, or an empty string when it is not set.
+    pub fn go_package(&self) -> &str {
+        self.go_package.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `cc_generic_services` field, or `false` when it is not set.
+    pub fn cc_generic_services(&self) -> bool {
+        self.cc_generic_services.unwrap_or_default()
+    }
+
+    /// Returns the value of the `java_generic_services` field, or `false` when it is not set.
+    pub fn java_generic_services(&self) -> bool {
+        self.java_generic_services.unwrap_or_default()
+    }
+
+    /// Returns the value of the `py_generic_services` field, or `false` when it is not set.
+    pub fn py_generic_services(&self) -> bool {
+        self.py_generic_services.unwrap_or_default()
+    }
+
+    /// Returns the value of the `java_generate_equals_and_hash` field, or `false` when it is not set.
+    pub fn java_generate_equals_and_hash(&self) -> bool {
+        self.java_generate_equals_and_hash.unwrap_or_default()
+    }
+
+    /// Returns the value of the `deprecated` field, or `false` when it is not set.
+    pub fn deprecated(&self) -> bool {
+        self.deprecated.unwrap_or_default()
+    }
+
+    /// Returns the value of the `java_string_check_utf8` field, or `false` when it is not set.
+    pub fn java_string_check_utf8(&self) -> bool {
+        self.java_string_check_utf8.unwrap_or_default()
+    }
+
+    /// Returns the value of the `cc_enable_arenas` field, or `false` when it is not set.
+    pub fn cc_enable_arenas(&self) -> bool {
+        self.cc_enable_arenas.unwrap_or_default()
+    }
+
+    /// Returns the value of the `objc_class_prefix` field, or an empty string when it is not set.
+    pub fn objc_class_prefix(&self) -> &str {
+        self.objc_class_prefix.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `csharp_namespace` field, or an empty string when it is not set.
+    pub fn csharp_namespace(&self) -> &str {
+        self.csharp_namespace.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `swift_prefix` field, or an empty string when it is not set.
+    pub fn swift_prefix(&self) -> &str {
+        self.swift_prefix.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `php_class_prefix` field, or an empty string when it is not set.
+    pub fn php_class_prefix(&self) -> &str {
+        self.php_class_prefix.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `php_namespace` field, or an empty string when it is not set.
+    pub fn php_namespace(&self) -> &str {
+        self.php_namespace.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `php_metadata_namespace` field, or an empty string when it is not set.
+    pub fn php_metadata_namespace(&self) -> &str {
+        self.php_metadata_namespace.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `ruby_package` field, or an empty string when it is not set.
+    pub fn ruby_package(&self) -> &str {
+        self.ruby_package.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for FileOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.java_package.to_length_delimited_field::<1>(buffer);
+        size += self
+            .java_outer_classname
+            .to_length_delimited_field::<8>(buffer);
+        size += self.optimize_for.to_varint_field::<9>(buffer);
+        size += self.java_multiple_files.to_varint_field::<10>(buffer);
+        size += self.go_package.to_length_delimited_field::<11>(buffer);
+        size += self.cc_generic_services.to_varint_field::<16>(buffer);
+        size += self.java_generic_services.to_varint_field::<17>(buffer);
+        size += self.py_generic_services.to_varint_field::<18>(buffer);
+        size += self
+            .java_generate_equals_and_hash
+            .to_varint_field::<20>(buffer);
+        size += self.deprecated.to_varint_field::<23>(buffer);
+        size += self.java_string_check_utf8.to_varint_field::<27>(buffer);
+        size += self.cc_enable_arenas.to_varint_field::<31>(buffer);
+        size += self
+            .objc_class_prefix
+            .to_length_delimited_field::<36>(buffer);
+        size += self
+            .csharp_namespace
+            .to_length_delimited_field::<37>(buffer);
+        size += self.swift_prefix.to_length_delimited_field::<39>(buffer);
+        size += self
+            .php_class_prefix
+            .to_length_delimited_field::<40>(buffer);
+        size += self.php_namespace.to_length_delimited_field::<41>(buffer);
+        size += self
+            .php_metadata_namespace
+            .to_length_delimited_field::<44>(buffer);
+        size += self.ruby_package.to_length_delimited_field::<45>(buffer);
+        size += self.features.to_length_delimited_field::<50>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => {
+                self.java_package.from_length_delimited(buffer).map(Some)
+            }
+            (8, WireType::LengthDelimited) => self
+                .java_outer_classname
+                .from_length_delimited(buffer)
+                .map(Some),
+            (9, WireType::Varint) => self.optimize_for.from_varint(buffer).map(Some),
+            (10, WireType::Varint) => self.java_multiple_files.from_varint(buffer).map(Some),
+            (11, WireType::LengthDelimited) => {
+                self.go_package.from_length_delimited(buffer).map(Some)
+            }
+            (16, WireType::Varint) => self.cc_generic_services.from_varint(buffer).map(Some),
+            (17, WireType::Varint) => self.java_generic_services.from_varint(buffer).map(Some),
+            (18, WireType::Varint) => self.py_generic_services.from_varint(buffer).map(Some),
+            (20, WireType::Varint) => self
+                .java_generate_equals_and_hash
+                .from_varint(buffer)
+                .map(Some),
+            (23, WireType::Varint) => self.deprecated.from_varint(buffer).map(Some),
+            (27, WireType::Varint) => self.java_string_check_utf8.from_varint(buffer).map(Some),
+            (31, WireType::Varint) => self.cc_enable_arenas.from_varint(buffer).map(Some),
+            (36, WireType::LengthDelimited) => self
+                .objc_class_prefix
+                .from_length_delimited(buffer)
+                .map(Some),
+            (37, WireType::LengthDelimited) => self
+                .csharp_namespace
+                .from_length_delimited(buffer)
+                .map(Some),
+            (39, WireType::LengthDelimited) => {
+                self.swift_prefix.from_length_delimited(buffer).map(Some)
+            }
+            (40, WireType::LengthDelimited) => self
+                .php_class_prefix
+                .from_length_delimited(buffer)
+                .map(Some),
+            (41, WireType::LengthDelimited) => {
+                self.php_namespace.from_length_delimited(buffer).map(Some)
+            }
+            (44, WireType::LengthDelimited) => self
+                .php_metadata_namespace
+                .from_length_delimited(buffer)
+                .map(Some),
+            (45, WireType::LengthDelimited) => {
+                self.ruby_package.from_length_delimited(buffer).map(Some)
+            }
+            (50, WireType::LengthDelimited) => {
+                self.features.from_length_delimited(buffer).map(Some)
+            }
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of a message type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MessageOptions {
+    /// True to use the legacy MessageSet wire format.
+    pub message_set_wire_format: Option<bool>,
+
+    /// True to disable the standard descriptor accessor.
+    pub no_standard_descriptor_accessor: Option<bool>,
+
+    /// True when the message type is deprecated.
+    pub deprecated: Option<bool>,
+
+    /// True when the message type is the entry of a map field.
+    pub map_entry: Option<bool>,
+
+    /// True to allow legacy JSON field name conflicts.
+    pub deprecated_legacy_json_field_conflicts: Option<bool>,
+
+    /// The features set on the message type, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl MessageOptions {
+    /// Returns the value of the `message_set_wire_format` field, or `false` when it is not set.
+    pub fn message_set_wire_format(&self) -> bool {
+        self.message_set_wire_format.unwrap_or_default()
+    }
+
+    /// Returns the value of the `no_standard_descriptor_accessor` field, or `false` when it is not set.
+    pub fn no_standard_descriptor_accessor(&self) -> bool {
+        self.no_standard_descriptor_accessor.unwrap_or_default()
+    }
+
+    /// Returns the value of the `deprecated` field, or `false` when it is not set.
+    pub fn deprecated(&self) -> bool {
+        self.deprecated.unwrap_or_default()
+    }
+
+    /// Returns the value of the `map_entry` field, or `false` when it is not set.
+    pub fn map_entry(&self) -> bool {
+        self.map_entry.unwrap_or_default()
+    }
+
+    /// Returns the value of the `deprecated_legacy_json_field_conflicts` field, or `false` when it is not set.
+    pub fn deprecated_legacy_json_field_conflicts(&self) -> bool {
+        self.deprecated_legacy_json_field_conflicts
+            .unwrap_or_default()
+    }
+}
+
+impl Message for MessageOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.message_set_wire_format.to_varint_field::<1>(buffer);
+        size += self
+            .no_standard_descriptor_accessor
+            .to_varint_field::<2>(buffer);
+        size += self.deprecated.to_varint_field::<3>(buffer);
+        size += self.map_entry.to_varint_field::<7>(buffer);
+        size += self
+            .deprecated_legacy_json_field_conflicts
+            .to_varint_field::<11>(buffer);
+        size += self.features.to_length_delimited_field::<12>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => self.message_set_wire_format.from_varint(buffer).map(Some),
+            (2, WireType::Varint) => self
+                .no_standard_descriptor_accessor
+                .from_varint(buffer)
+                .map(Some),
+            (3, WireType::Varint) => self.deprecated.from_varint(buffer).map(Some),
+            (7, WireType::Varint) => self.map_entry.from_varint(buffer).map(Some),
+            (11, WireType::Varint) => self
+                .deprecated_legacy_json_field_conflicts
+                .from_varint(buffer)
+                .map(Some),
+            (12, WireType::LengthDelimited) => {
+                self.features.from_length_delimited(buffer).map(Some)
+            }
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of a field.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FieldOptions {
+    /// The C++ representation of a string field.
+    pub ctype: Option<OpenEnum<CType>>,
+
+    /// True when a repeated scalar field uses the packed encoding.
+    pub packed: Option<bool>,
+
+    /// True when the field is deprecated.
+    pub deprecated: Option<bool>,
+
+    /// True to parse the message field lazily.
+    pub lazy: Option<bool>,
+
+    /// The JavaScript type of a 64 bit integer field.
+    pub jstype: Option<OpenEnum<JsType>>,
+
+    /// True when the field refers to a weak import.
+    pub weak: Option<bool>,
+
+    /// True to parse the message field lazily without verification.
+    pub unverified_lazy: Option<bool>,
+
+    /// True to redact the field when printed for debugging.
+    pub debug_redact: Option<bool>,
+
+    /// The features set on the field, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl FieldOptions {
+    /// Returns the value of the `ctype` field, or `None` when it is not set or is not a known value.
+    pub fn ctype(&self) -> Option<CType> {
+        self.ctype.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `packed` field, or `false` when it is not set.
+    pub fn packed(&self) -> bool {
+        self.packed.unwrap_or_default()
+    }
+
+    /// Returns the value of the `deprecated` field, or `false` when it is not set.
+    pub fn deprecated(&self) -> bool {
+        self.deprecated.unwrap_or_default()
+    }
+
+    /// Returns the value of the `lazy` field, or `false` when it is not set.
+    pub fn lazy(&self) -> bool {
+        self.lazy.unwrap_or_default()
+    }
+
+    /// Returns the value of the `jstype` field, or `None` when it is not set or is not a known value.
+    pub fn jstype(&self) -> Option<JsType> {
+        self.jstype.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `weak` field, or `false` when it is not set.
+    pub fn weak(&self) -> bool {
+        self.weak.unwrap_or_default()
+    }
+
+    /// Returns the value of the `unverified_lazy` field, or `false` when it is not set.
+    pub fn unverified_lazy(&self) -> bool {
+        self.unverified_lazy.unwrap_or_default()
+    }
+
+    /// Returns the value of the `debug_redact` field, or `false` when it is not set.
+    pub fn debug_redact(&self) -> bool {
+        self.debug_redact.unwrap_or_default()
+    }
+}
+
+impl Message for FieldOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.ctype.to_varint_field::<1>(buffer);
+        size += self.packed.to_varint_field::<2>(buffer);
+        size += self.deprecated.to_varint_field::<3>(buffer);
+        size += self.lazy.to_varint_field::<5>(buffer);
+        size += self.jstype.to_varint_field::<6>(buffer);
+        size += self.weak.to_varint_field::<10>(buffer);
+        size += self.unverified_lazy.to_varint_field::<15>(buffer);
+        size += self.debug_redact.to_varint_field::<16>(buffer);
+        size += self.features.to_length_delimited_field::<21>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => self.ctype.from_varint(buffer).map(Some),
+            (2, WireType::Varint) => self.packed.from_varint(buffer).map(Some),
+            (3, WireType::Varint) => self.deprecated.from_varint(buffer).map(Some),
+            (5, WireType::Varint) => self.lazy.from_varint(buffer).map(Some),
+            (6, WireType::Varint) => self.jstype.from_varint(buffer).map(Some),
+            (10, WireType::Varint) => self.weak.from_varint(buffer).map(Some),
+            (15, WireType::Varint) => self.unverified_lazy.from_varint(buffer).map(Some),
+            (16, WireType::Varint) => self.debug_redact.from_varint(buffer).map(Some),
+            (21, WireType::LengthDelimited) => {
+                self.features.from_length_delimited(buffer).map(Some)
+            }
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of a oneof group.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OneofOptions {
+    /// The features set on the oneof group, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl Message for OneofOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.features.to_length_delimited_field::<1>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.features.from_length_delimited(buffer).map(Some),
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of an enum type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnumOptions {
+    /// True to allow several values to share a number.
+    pub allow_alias: Option<bool>,
+
+    /// True when the enum type is deprecated.
+    pub deprecated: Option<bool>,
+
+    /// The features set on the enum type, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl EnumOptions {
+    /// Returns the value of the `allow_alias` field, or `false` when it is not set.
+    pub fn allow_alias(&self) -> bool {
+        self.allow_alias.unwrap_or_default()
+    }
+
+    /// Returns the value of the `deprecated` field, or `false` when it is not set.
+    pub fn deprecated(&self) -> bool {
+        self.deprecated.unwrap_or_default()
+    }
+}
+
+impl Message for EnumOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.allow_alias.to_varint_field::<2>(buffer);
+        size += self.deprecated.to_varint_field::<3>(buffer);
+        size += self.features.to_length_delimited_field::<7>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (2, WireType::Varint) => self.allow_alias.from_varint(buffer).map(Some),
+            (3, WireType::Varint) => self.deprecated.from_varint(buffer).map(Some),
+            (7, WireType::LengthDelimited) => self.features.from_length_delimited(buffer).map(Some),
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of an enum value.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EnumValueOptions {
+    /// True when the value is deprecated.
+    pub deprecated: Option<bool>,
+
+    /// The features set on the enum value, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl EnumValueOptions {
+    /// Returns the value of the `deprecated` field, or `false` when it is not set.
+    pub fn deprecated(&self) -> bool {
+        self.deprecated.unwrap_or_default()
+    }
+}
+
+impl Message for EnumValueOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.deprecated.to_varint_field::<1>(buffer);
+        size += self.features.to_length_delimited_field::<2>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => self.deprecated.from_varint(buffer).map(Some),
+            (2, WireType::LengthDelimited) => self.features.from_length_delimited(buffer).map(Some),
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of a service.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ServiceOptions {
+    /// True when the service is deprecated.
+    pub deprecated: Option<bool>,
+
+    /// The features set on the service, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl ServiceOptions {
+    /// Returns the value of the `deprecated` field, or `false` when it is not set.
+    pub fn deprecated(&self) -> bool {
+        self.deprecated.unwrap_or_default()
+    }
+}
+
+impl Message for ServiceOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.deprecated.to_varint_field::<33>(buffer);
+        size += self.features.to_length_delimited_field::<34>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (33, WireType::Varint) => self.deprecated.from_varint(buffer).map(Some),
+            (34, WireType::LengthDelimited) => {
+                self.features.from_length_delimited(buffer).map(Some)
+            }
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The options of a method.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MethodOptions {
+    /// True when the method is deprecated.
+    pub deprecated: Option<bool>,
+
+    /// Whether the method has side effects.
+    pub idempotency_level: Option<OpenEnum<IdempotencyLevel>>,
+
+    /// The features set on the method, which override those it inherits.
+    pub features: Option<FeatureSet>,
+
+    /// Options the parser did not recognise.
+    pub uninterpreted_option: Vec<UninterpretedOption>,
+
+    /// The extension fields set on the options, such as custom options, as they are encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl MethodOptions {
+    /// Returns the value of the `deprecated` field, or `false` when it is not set.
+    pub fn deprecated(&self) -> bool {
+        self.deprecated.unwrap_or_default()
+    }
+
+    /// Returns the value of the `idempotency_level` field, or `None` when it is not set or is not a known value.
+    pub fn idempotency_level(&self) -> Option<IdempotencyLevel> {
+        self.idempotency_level.and_then(|v| v.known())
+    }
+}
+
+impl Message for MethodOptions {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.deprecated.to_varint_field::<33>(buffer);
+        size += self.idempotency_level.to_varint_field::<34>(buffer);
+        size += self.features.to_length_delimited_field::<35>(buffer);
+        size += self
+            .uninterpreted_option
+            .to_unpacked_field::<LengthDelimitedCodec, 999>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (33, WireType::Varint) => self.deprecated.from_varint(buffer).map(Some),
+            (34, WireType::Varint) => self.idempotency_level.from_varint(buffer).map(Some),
+            (35, WireType::LengthDelimited) => {
+                self.features.from_length_delimited(buffer).map(Some)
+            }
+            (999, wire_type) => self
+                .uninterpreted_option
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The features of a definition in an editions file, which control how its fields are encoded and
+/// checked. Each feature that is not set is inherited from the enclosing definition, and from the
+/// defaults of the edition at the top.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FeatureSet {
+    /// Whether singular fields track if they are set.
+    pub field_presence: Option<OpenEnum<FieldPresence>>,
+
+    /// Whether enums are open or closed.
+    pub enum_type: Option<OpenEnum<EnumType>>,
+
+    /// Whether repeated scalar fields are packed.
+    pub repeated_field_encoding: Option<OpenEnum<RepeatedFieldEncoding>>,
+
+    /// Whether string fields are checked to be UTF-8.
+    pub utf8_validation: Option<OpenEnum<Utf8Validation>>,
+
+    /// Whether message fields are length prefixed or delimited by group tags.
+    pub message_encoding: Option<OpenEnum<MessageEncoding>>,
+
+    /// Whether the JSON mapping must be well defined.
+    pub json_format: Option<OpenEnum<JsonFormat>>,
+
+    /// The extension fields set on the features, such as language specific features, as they are
+    /// encoded.
+    pub extensions: Vec<u8>,
+}
+
+impl FeatureSet {
+    /// Returns the value of the `field_presence` field, or `None` when it is not set or is not a
+    /// known value.
+    pub fn field_presence(&self) -> Option<FieldPresence> {
+        self.field_presence.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `enum_type` field, or `None` when it is not set or is not a known
+    /// value.
+    pub fn enum_type(&self) -> Option<EnumType> {
+        self.enum_type.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `repeated_field_encoding` field, or `None` when it is not set or is
+    /// not a known value.
+    pub fn repeated_field_encoding(&self) -> Option<RepeatedFieldEncoding> {
+        self.repeated_field_encoding.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `utf8_validation` field, or `None` when it is not set or is not a
+    /// known value.
+    pub fn utf8_validation(&self) -> Option<Utf8Validation> {
+        self.utf8_validation.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `message_encoding` field, or `None` when it is not set or is not a
+    /// known value.
+    pub fn message_encoding(&self) -> Option<MessageEncoding> {
+        self.message_encoding.and_then(|v| v.known())
+    }
+
+    /// Returns the value of the `json_format` field, or `None` when it is not set or is not a known
+    /// value.
+    pub fn json_format(&self) -> Option<JsonFormat> {
+        self.json_format.and_then(|v| v.known())
+    }
+}
+
+impl Message for FeatureSet {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.field_presence.to_varint_field::<1>(buffer);
+        size += self.enum_type.to_varint_field::<2>(buffer);
+        size += self.repeated_field_encoding.to_varint_field::<3>(buffer);
+        size += self.utf8_validation.to_varint_field::<4>(buffer);
+        size += self.message_encoding.to_varint_field::<5>(buffer);
+        size += self.json_format.to_varint_field::<6>(buffer);
+        size += encode_extensions(&self.extensions, buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => self.field_presence.from_varint(buffer).map(Some),
+            (2, WireType::Varint) => self.enum_type.from_varint(buffer).map(Some),
+            (3, WireType::Varint) => self.repeated_field_encoding.from_varint(buffer).map(Some),
+            (4, WireType::Varint) => self.utf8_validation.from_varint(buffer).map(Some),
+            (5, WireType::Varint) => self.message_encoding.from_varint(buffer).map(Some),
+            (6, WireType::Varint) => self.json_format.from_varint(buffer).map(Some),
+            (number, _) if number >= 1000 => decode_extension(&mut self.extensions, tag, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// An option the parser did not recognise, held as it was written so that it can be interpreted later.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UninterpretedOption {
+    /// The parts of the dotted option name.
+    pub name: Vec<NamePart>,
+
+    /// The value when it was written as an identifier.
+    pub identifier_value: Option<String>,
+
+    /// The value when it was written as a positive integer.
+    pub positive_int_value: Option<u64>,
+
+    /// The value when it was written as a negative integer.
+    pub negative_int_value: Option<i64>,
+
+    /// The value when it was written as a floating point number.
+    pub double_value: Option<f64>,
+
+    /// The value when it was written as a string.
+    pub string_value: Option<Vec<u8>>,
+
+    /// The value when it was written as an aggregate in text format.
+    pub aggregate_value: Option<String>,
+}
+
+impl UninterpretedOption {
+    /// Returns the value of the `identifier_value` field, or an empty string when it is not set.
+    pub fn identifier_value(&self) -> &str {
+        self.identifier_value.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `positive_int_value` field, or zero when it is not set.
+    pub fn positive_int_value(&self) -> u64 {
+        self.positive_int_value.unwrap_or_default()
+    }
+
+    /// Returns the value of the `negative_int_value` field, or zero when it is not set.
+    pub fn negative_int_value(&self) -> i64 {
+        self.negative_int_value.unwrap_or_default()
+    }
+
+    /// Returns the value of the `double_value` field, or zero when it is not set.
+    pub fn double_value(&self) -> f64 {
+        self.double_value.unwrap_or_default()
+    }
+
+    /// Returns the value of the `string_value` field, or an empty slice when it is not set.
+    pub fn string_value(&self) -> &[u8] {
+        self.string_value.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `aggregate_value` field, or an empty string when it is not set.
+    pub fn aggregate_value(&self) -> &str {
+        self.aggregate_value.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for UninterpretedOption {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self
+            .name
+            .to_unpacked_field::<LengthDelimitedCodec, 2>(buffer);
+        size += self.identifier_value.to_length_delimited_field::<3>(buffer);
+        size += self.positive_int_value.to_varint_field::<4>(buffer);
+        size += self
+            .negative_int_value
+            .to_sign_extended_varint_field::<5>(buffer);
+        size += self.double_value.to_fixed64_field::<6>(buffer);
+        size += self.string_value.to_length_delimited_field::<7>(buffer);
+        size += self.aggregate_value.to_length_delimited_field::<8>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (2, wire_type) => self
+                .name
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (3, WireType::LengthDelimited) => self
+                .identifier_value
+                .from_length_delimited(buffer)
+                .map(Some),
+            (4, WireType::Varint) => self.positive_int_value.from_varint(buffer).map(Some),
+            (5, WireType::Varint) => self
+                .negative_int_value
+                .from_sign_extended_varint(buffer)
+                .map(Some),
+            (6, WireType::Fixed64) => self.double_value.from_fixed64(buffer).map(Some),
+            (7, WireType::LengthDelimited) => {
+                self.string_value.from_length_delimited(buffer).map(Some)
+            }
+            (8, WireType::LengthDelimited) => {
+                self.aggregate_value.from_length_delimited(buffer).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A part of the dotted name of an uninterpreted option.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NamePart {
+    /// The name of the part.
+    pub name_part: Option<String>,
+
+    /// True when the part was written in parentheses and names an extension.
+    pub is_extension: Option<bool>,
+}
+
+impl NamePart {
+    /// Returns the value of the `name_part` field, or an empty string when it is not set.
+    pub fn name_part(&self) -> &str {
+        self.name_part.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `is_extension` field, or `false` when it is not set.
+    pub fn is_extension(&self) -> bool {
+        self.is_extension.unwrap_or_default()
+    }
+}
+
+impl Message for NamePart {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name_part.to_length_delimited_field::<1>(buffer);
+        size += self.is_extension.to_varint_field::<2>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => {
+                self.name_part.from_length_delimited(buffer).map(Some)
+            }
+            (2, WireType::Varint) => self.is_extension.from_varint(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Describes where the definitions of a file appear in the original source file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SourceCodeInfo {
+    /// The locations of the definitions.
+    pub location: Vec<SourceLocation>,
+}
+
+impl Message for SourceCodeInfo {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        self.location
+            .to_unpacked_field::<LengthDelimitedCodec, 1>(buffer)
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, wire_type) => self
+                .location
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The location of a definition within a source file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SourceLocation {
+    /// The path to the definition, as field numbers and indexes from the `FileDescriptorProto`.
+    pub path: Vec<i32>,
+
+    /// The start line, start column, end line (when different) and end column, all zero based.
+    pub span: Vec<i32>,
+
+    /// The comment immediately before the definition.
+    pub leading_comments: Option<String>,
+
+    /// The comment immediately after the definition.
+    pub trailing_comments: Option<String>,
+
+    /// Comments before the definition separated from it by a blank line.
+    pub leading_detached_comments: Vec<String>,
+}
+
+impl SourceLocation {
+    /// Returns the value of the `leading_comments` field, or an empty string when it is not set.
+    pub fn leading_comments(&self) -> &str {
+        self.leading_comments.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `trailing_comments` field, or an empty string when it is not set.
+    pub fn trailing_comments(&self) -> &str {
+        self.trailing_comments.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for SourceLocation {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self
+            .path
+            .to_packed_field::<SignExtendedVarintCodec, 1>(buffer);
+        size += self
+            .span
+            .to_packed_field::<SignExtendedVarintCodec, 2>(buffer);
+        size += self.leading_comments.to_length_delimited_field::<3>(buffer);
+        size += self
+            .trailing_comments
+            .to_length_delimited_field::<4>(buffer);
+        size += self
+            .leading_detached_comments
+            .to_unpacked_field::<LengthDelimitedCodec, 6>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, wire_type) => self
+                .path
+                .from_repeated_field::<SignExtendedVarintCodec>(wire_type, buffer),
+            (2, wire_type) => self
+                .span
+                .from_repeated_field::<SignExtendedVarintCodec>(wire_type, buffer),
+            (3, WireType::LengthDelimited) => self
+                .leading_comments
+                .from_length_delimited(buffer)
+                .map(Some),
+            (4, WireType::LengthDelimited) => self
+                .trailing_comments
+                .from_length_delimited(buffer)
+                .map(Some),
+            (6, wire_type) => self
+                .leading_detached_comments
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The type of a field.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FieldType {
+    /// A `double` field.
+    Double = 1,
+
+    /// A `float` field.
+    Float = 2,
+
+    /// An `int64` field.
+    Int64 = 3,
+
+    /// A `uint64` field.
+    Uint64 = 4,
+
+    /// An `int32` field.
+    Int32 = 5,
+
+    /// A `fixed64` field.
+    Fixed64 = 6,
+
+    /// A `fixed32` field.
+    Fixed32 = 7,
+
+    /// A `bool` field.
+    Bool = 8,
+
+    /// A `string` field.
+    String = 9,
+
+    /// A proto2 group field.
+    Group = 10,
+
+    /// A message field.
+    Message = 11,
+
+    /// A `bytes` field.
+    Bytes = 12,
+
+    /// A `uint32` field.
+    Uint32 = 13,
+
+    /// An enum field.
+    Enum = 14,
+
+    /// An `sfixed32` field.
+    Sfixed32 = 15,
+
+    /// An `sfixed64` field.
+    Sfixed64 = 16,
+
+    /// An `sint32` field.
+    Sint32 = 17,
+
+    /// An `sint64` field.
+    Sint64 = 18,
+}
+
+impl Enumeration for FieldType {
+    const VALUES: &'static [Self] = &[
+        FieldType::Double,
+        FieldType::Float,
+        FieldType::Int64,
+        FieldType::Uint64,
+        FieldType::Int32,
+        FieldType::Fixed64,
+        FieldType::Fixed32,
+        FieldType::Bool,
+        FieldType::String,
+        FieldType::Group,
+        FieldType::Message,
+        FieldType::Bytes,
+        FieldType::Uint32,
+        FieldType::Enum,
+        FieldType::Sfixed32,
+        FieldType::Sfixed64,
+        FieldType::Sint32,
+        FieldType::Sint64,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            FieldType::Double => "TYPE_DOUBLE",
+            FieldType::Float => "TYPE_FLOAT",
+            FieldType::Int64 => "TYPE_INT64",
+            FieldType::Uint64 => "TYPE_UINT64",
+            FieldType::Int32 => "TYPE_INT32",
+            FieldType::Fixed64 => "TYPE_FIXED64",
+            FieldType::Fixed32 => "TYPE_FIXED32",
+            FieldType::Bool => "TYPE_BOOL",
+            FieldType::String => "TYPE_STRING",
+            FieldType::Group => "TYPE_GROUP",
+            FieldType::Message => "TYPE_MESSAGE",
+            FieldType::Bytes => "TYPE_BYTES",
+            FieldType::Uint32 => "TYPE_UINT32",
+            FieldType::Enum => "TYPE_ENUM",
+            FieldType::Sfixed32 => "TYPE_SFIXED32",
+            FieldType::Sfixed64 => "TYPE_SFIXED64",
+            FieldType::Sint32 => "TYPE_SINT32",
+            FieldType::Sint64 => "TYPE_SINT64",
+        }
+    }
+}
+
+/// The cardinality of a field.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FieldLabel {
+    /// The field holds at most one value.
+    Optional = 1,
+
+    /// The field must hold a value. Only valid in proto2.
+    Required = 2,
+
+    /// The field holds any number of values.
+    Repeated = 3,
+}
+
+impl Enumeration for FieldLabel {
+    const VALUES: &'static [Self] = &[
+        FieldLabel::Optional,
+        FieldLabel::Required,
+        FieldLabel::Repeated,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            FieldLabel::Optional => "LABEL_OPTIONAL",
+            FieldLabel::Required => "LABEL_REQUIRED",
+            FieldLabel::Repeated => "LABEL_REPEATED",
+        }
+    }
+}
+
+/// What generated code should be optimised for.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum OptimizeMode {
+    /// Generate complete code for parsing and serialization.
+    Speed = 1,
+
+    /// Use reflection to reduce the size of the generated code.
+    CodeSize = 2,
+
+    /// Generate code that depends on the lite runtime.
+    LiteRuntime = 3,
+}
+
+impl Enumeration for OptimizeMode {
+    const VALUES: &'static [Self] = &[
+        OptimizeMode::Speed,
+        OptimizeMode::CodeSize,
+        OptimizeMode::LiteRuntime,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            OptimizeMode::Speed => "SPEED",
+            OptimizeMode::CodeSize => "CODE_SIZE",
+            OptimizeMode::LiteRuntime => "LITE_RUNTIME",
+        }
+    }
+}
+
+/// The C++ representation of a string field.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum CType {
+    /// A standard string.
+    String = 0,
+
+    /// A cord.
+    Cord = 1,
+
+    /// A string piece.
+    StringPiece = 2,
+}
+
+impl Enumeration for CType {
+    const VALUES: &'static [Self] = &[CType::String, CType::Cord, CType::StringPiece];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            CType::String => "STRING",
+            CType::Cord => "CORD",
+            CType::StringPiece => "STRING_PIECE",
+        }
+    }
+}
+
+/// The JavaScript representation of a 64 bit integer field.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum JsType {
+    /// The default representation.
+    JsNormal = 0,
+
+    /// A string.
+    JsString = 1,
+
+    /// A number.
+    JsNumber = 2,
+}
+
+impl Enumeration for JsType {
+    const VALUES: &'static [Self] = &[JsType::JsNormal, JsType::JsString, JsType::JsNumber];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            JsType::JsNormal => "JS_NORMAL",
+            JsType::JsString => "JS_STRING",
+            JsType::JsNumber => "JS_NUMBER",
+        }
+    }
+}
+
+/// Whether a method has side effects.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum IdempotencyLevel {
+    /// Nothing is known about the side effects.
+    IdempotencyUnknown = 0,
+
+    /// The method has no side effects.
+    NoSideEffects = 1,
+
+    /// The method may have side effects but is idempotent.
+    Idempotent = 2,
+}
+
+impl Enumeration for IdempotencyLevel {
+    const VALUES: &'static [Self] = &[
+        IdempotencyLevel::IdempotencyUnknown,
+        IdempotencyLevel::NoSideEffects,
+        IdempotencyLevel::Idempotent,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            IdempotencyLevel::IdempotencyUnknown => "IDEMPOTENCY_UNKNOWN",
+            IdempotencyLevel::NoSideEffects => "NO_SIDE_EFFECTS",
+            IdempotencyLevel::Idempotent => "IDEMPOTENT",
+        }
+    }
+}
+
+/// An edition of the Protocol Buffers language, which sets the default features of a file.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Edition {
+    /// The edition is not known.
+    Unknown = 0,
+
+    /// The defaults of features that predate editions.
+    Legacy = 900,
+
+    /// The behaviour of files written with `syntax = "proto2";`.
+    Proto2 = 998,
+
+    /// The behaviour of files written with `syntax = "proto3";`.
+    Proto3 = 999,
+
+    /// Edition 2023.
+    Edition2023 = 1000,
+
+    /// Edition 2024.
+    Edition2024 = 1001,
+
+    /// A placeholder for the latest edition.
+    Max = 2147483647,
+}
+
+impl Enumeration for Edition {
+    const VALUES: &'static [Self] = &[
+        Edition::Unknown,
+        Edition::Legacy,
+        Edition::Proto2,
+        Edition::Proto3,
+        Edition::Edition2023,
+        Edition::Edition2024,
+        Edition::Max,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Edition::Unknown => "EDITION_UNKNOWN",
+            Edition::Legacy => "EDITION_LEGACY",
+            Edition::Proto2 => "EDITION_PROTO2",
+            Edition::Proto3 => "EDITION_PROTO3",
+            Edition::Edition2023 => "EDITION_2023",
+            Edition::Edition2024 => "EDITION_2024",
+            Edition::Max => "EDITION_MAX",
+        }
+    }
+}
+
+/// Whether a singular field tracks if it is set.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum FieldPresence {
+    /// The feature is not set.
+    FieldPresenceUnknown = 0,
+
+    /// The field tracks if it is set, as proto2 fields do.
+    Explicit = 1,
+
+    /// The field is unset when it holds its default value, as proto3 fields are.
+    Implicit = 2,
+
+    /// The field must be set, as proto2 `required` fields must.
+    LegacyRequired = 3,
+}
+
+impl Enumeration for FieldPresence {
+    const VALUES: &'static [Self] = &[
+        FieldPresence::FieldPresenceUnknown,
+        FieldPresence::Explicit,
+        FieldPresence::Implicit,
+        FieldPresence::LegacyRequired,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            FieldPresence::FieldPresenceUnknown => "FIELD_PRESENCE_UNKNOWN",
+            FieldPresence::Explicit => "EXPLICIT",
+            FieldPresence::Implicit => "IMPLICIT",
+            FieldPresence::LegacyRequired => "LEGACY_REQUIRED",
+        }
+    }
+}
+
+/// Whether an enum holds values it does not declare.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum EnumType {
+    /// The feature is not set.
+    EnumTypeUnknown = 0,
+
+    /// The enum holds any number, as proto3 enums do.
+    Open = 1,
+
+    /// Numbers the enum does not declare are kept as unknown fields, as in proto2.
+    Closed = 2,
+}
+
+impl Enumeration for EnumType {
+    const VALUES: &'static [Self] = &[EnumType::EnumTypeUnknown, EnumType::Open, EnumType::Closed];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            EnumType::EnumTypeUnknown => "ENUM_TYPE_UNKNOWN",
+            EnumType::Open => "OPEN",
+            EnumType::Closed => "CLOSED",
+        }
+    }
+}
+
+/// Whether a repeated scalar field is packed.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RepeatedFieldEncoding {
+    /// The feature is not set.
+    RepeatedFieldEncodingUnknown = 0,
+
+    /// The elements are written as a single length delimited field.
+    Packed = 1,
+
+    /// Each element is written as a field of its own.
+    Expanded = 2,
+}
+
+impl Enumeration for RepeatedFieldEncoding {
+    const VALUES: &'static [Self] = &[
+        RepeatedFieldEncoding::RepeatedFieldEncodingUnknown,
+        RepeatedFieldEncoding::Packed,
+        RepeatedFieldEncoding::Expanded,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            RepeatedFieldEncoding::RepeatedFieldEncodingUnknown => {
+                "REPEATED_FIELD_ENCODING_UNKNOWN"
+            }
+            RepeatedFieldEncoding::Packed => "PACKED",
+            RepeatedFieldEncoding::Expanded => "EXPANDED",
+        }
+    }
+}
+
+/// Whether a string field is checked to be UTF-8.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Utf8Validation {
+    /// The feature is not set.
+    Utf8ValidationUnknown = 0,
+
+    /// Strings that are not UTF-8 are rejected.
+    Verify = 2,
+
+    /// Strings are not checked.
+    None = 3,
+}
+
+impl Enumeration for Utf8Validation {
+    const VALUES: &'static [Self] = &[
+        Utf8Validation::Utf8ValidationUnknown,
+        Utf8Validation::Verify,
+        Utf8Validation::None,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Utf8Validation::Utf8ValidationUnknown => "UTF8_VALIDATION_UNKNOWN",
+            Utf8Validation::Verify => "VERIFY",
+            Utf8Validation::None => "NONE",
+        }
+    }
+}
+
+/// How a message field is written.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum MessageEncoding {
+    /// The feature is not set.
+    MessageEncodingUnknown = 0,
+
+    /// The message is preceded by its length.
+    LengthPrefixed = 1,
+
+    /// The message is written between start and end group tags.
+    Delimited = 2,
+}
+
+impl Enumeration for MessageEncoding {
+    const VALUES: &'static [Self] = &[
+        MessageEncoding::MessageEncodingUnknown,
+        MessageEncoding::LengthPrefixed,
+        MessageEncoding::Delimited,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            MessageEncoding::MessageEncodingUnknown => "MESSAGE_ENCODING_UNKNOWN",
+            MessageEncoding::LengthPrefixed => "LENGTH_PREFIXED",
+            MessageEncoding::Delimited => "DELIMITED",
+        }
+    }
+}
+
+/// Whether a definition must have a well defined JSON mapping.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum JsonFormat {
+    /// The feature is not set.
+    JsonFormatUnknown = 0,
+
+    /// The JSON mapping must be well defined.
+    Allow = 1,
+
+    /// Conflicts in the JSON mapping are allowed.
+    LegacyBestEffort = 2,
+}
+
+impl Enumeration for JsonFormat {
+    const VALUES: &'static [Self] = &[
+        JsonFormat::JsonFormatUnknown,
+        JsonFormat::Allow,
+        JsonFormat::LegacyBestEffort,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            JsonFormat::JsonFormatUnknown => "JSON_FORMAT_UNKNOWN",
+            JsonFormat::Allow => "ALLOW",
+            JsonFormat::LegacyBestEffort => "LEGACY_BEST_EFFORT",
+        }
+    }
+}
+
+// Writes the extension fields of an options message, which are kept as they were read.
+fn encode_extensions(extensions: &[u8], buffer: &mut Buffer) -> usize {
+    buffer.put_slice(extensions);
+    extensions.len()
+}
+
+// Keeps an extension field of an options message, tag included, so that custom options survive
+// being decoded and encoded again.
+fn decode_extension(
+    extensions: &mut Vec<u8>,
+    tag: &Tag,
+    buffer: &[u8],
+) -> Result<Option<usize>, DecodeError> {
+    let size = skip_tagged_field(tag, buffer)?;
+    let mut encoded = Buffer::default();
+    let _ = tag.to_varint(&mut encoded);
+
+    extensions.extend_from_slice(&encoded.to_vec());
+    extensions.extend_from_slice(&buffer[..size]);
+    Ok(Some(size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    fn file_descriptor_set_bytes() -> Vec<u8> {
+        vec![
+            10, 26, 10, 7, 97, 46, 112, 114, 111, 116, 111, 34, 15, 10, 1, 77, 18, 10, 10, 2, 105,
+            100, 24, 1, 32, 1, 40, 5,
+        ]
+    }
+
+    #[test]
+    fn test_file_descriptor_set_decoding() {
+        // Arrange
+        let bytes = file_descriptor_set_bytes();
+
+        // Act
+        let set = FileDescriptorSet::decode(&bytes).unwrap();
+
+        // Assert
+        let file = &set.file[0];
+        let field = &file.message_type[0].field[0];
+        assert_eq!(file.name(), "a.proto");
+        assert_eq!(file.package(), "");
+        assert_eq!(file.message_type[0].name(), "M");
+        assert_eq!(field.name(), "id");
+        assert_eq!(field.number(), 1);
+        assert_eq!(field.label(), Some(FieldLabel::Optional));
+        assert_eq!(field.r#type(), Some(FieldType::Int32));
+        assert_eq!(field.type_name, None);
+    }
+
+    #[test]
+    fn test_file_descriptor_set_encoding() {
+        // Arrange
+        let set = FileDescriptorSet {
+            file: vec![FileDescriptorProto {
+                name: Some("a.proto".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("M".to_string()),
+                    field: vec![FieldDescriptorProto {
+                        name: Some("id".to_string()),
+                        number: Some(1),
+                        label: Some(FieldLabel::Optional.into()),
+                        r#type: Some(FieldType::Int32.into()),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        };
+
+        // Act
+        let bytes = set.encode_to_vec();
+
+        // Assert
+        assert_eq!(bytes, file_descriptor_set_bytes());
+    }
+
+    #[test]
+    fn test_default_values_are_written_when_set() {
+        // Arrange
+        let field = FieldDescriptorProto {
+            number: Some(0),
+            proto3_optional: Some(false),
+            ..Default::default()
+        };
+
+        // Act
+        let bytes = field.encode_to_vec();
+
+        // Assert
+        assert_eq!(bytes, vec![24, 0, 136, 1, 0]);
+    }
+
+    #[test]
+    fn test_negative_enum_value_number() {
+        // Arrange
+        let bytes = vec![16, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1];
+
+        // Act
+        let value = EnumValueDescriptorProto::decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(value.number(), -1);
+        assert_eq!(value.encode_to_vec(), bytes);
+    }
+
+    #[rstest]
+    #[case(vec![10, 3, 4, 0, 1])]
+    #[case(vec![8, 4, 8, 0, 8, 1])]
+    fn test_source_location_path_decoding(#[case] bytes: Vec<u8>) {
+        // Act
+        let location = SourceLocation::decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(location.path, vec![4, 0, 1]);
+        assert_eq!(location.encode_to_vec(), vec![10, 3, 4, 0, 1]);
+    }
+
+    #[test]
+    fn test_unknown_fields_and_enum_values() {
+        // Arrange
+        let bytes = vec![40, 99, 112, 232, 7, 10, 1, 120];
+
+        // Act
+        let field = FieldDescriptorProto::decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(field.name(), "x");
+        assert_eq!(field.r#type(), None);
+        assert_eq!(field.r#type.map(|v| v.number()), Some(99));
+    }
+
+    #[rstest]
+    #[case("TYPE_SINT64", Some(FieldType::Sint64))]
+    #[case("TYPE_GROUP", Some(FieldType::Group))]
+    #[case("SINT64", None)]
+    fn test_field_type_names(#[case] name: &str, #[case] expected_type: Option<FieldType>) {
+        // Act
+        let field_type = FieldType::from_name(name);
+
+        // Assert
+        assert_eq!(field_type, expected_type);
+    }
+
+    #[test]
+    fn test_options_extensions_are_kept() {
+        // Arrange
+        let bytes = vec![24, 1, 128, 181, 24, 1, 138, 181, 24, 2, 8, 3];
+
+        // Act
+        let options = FieldOptions::decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(options.deprecated, Some(true));
+        assert_eq!(
+            options.extensions,
+            vec![128, 181, 24, 1, 138, 181, 24, 2, 8, 3]
+        );
+        assert_eq!(options.encode_to_vec(), bytes);
+    }
+}
@@ -0,0 +1,2386 @@
+use crate::{
+    DescriptorError, DescriptorProto, DynamicMessage, Edition, EnumDescriptorProto, EnumType,
+    Enumeration, FeatureSet, FieldDescriptorProto, FieldLabel, FieldPresence, FieldType,
+    FieldValue, FileDescriptorProto, FileDescriptorSet, Message, MessageEncoding,
+    RepeatedFieldEncoding, ServiceDescriptorProto, Tag, Utf8Validation,
+};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The syntax a `.proto` file was written in.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Syntax {
+    /// The file was written with `syntax = "proto2";` or does not declare a syntax.
+    Proto2,
+
+    /// The file was written with `syntax = "proto3";`.
+    Proto3,
+
+    /// The file was written with `edition = "...";`, and its behaviour is set by features.
+    Editions,
+}
+
+/// The features of a definition, resolved from the defaults of the edition of its file and the
+/// features set on the definition and those that enclose it.
+///
+/// Files written with `syntax = "proto2";` and `syntax = "proto3";` have the features of the
+/// matching edition, so the features describe the behaviour of every file.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Edition, EnumType, FeatureSet, RepeatedFieldEncoding, ResolvedFeatures};
+///
+/// let defaults = ResolvedFeatures::for_edition(Edition::Edition2023).unwrap();
+/// let features = defaults.merge(Some(&FeatureSet {
+///     repeated_field_encoding: Some(RepeatedFieldEncoding::Expanded.into()),
+///     ..Default::default()
+/// }));
+///
+/// assert_eq!(features.enum_type, EnumType::Open);
+/// assert_eq!(features.repeated_field_encoding, RepeatedFieldEncoding::Expanded);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ResolvedFeatures {
+    /// Whether singular fields track if they are set.
+    pub field_presence: FieldPresence,
+
+    /// Whether enums are open or closed.
+    pub enum_type: EnumType,
+
+    /// Whether repeated scalar fields are packed.
+    pub repeated_field_encoding: RepeatedFieldEncoding,
+
+    /// Whether string fields are checked to be UTF-8.
+    pub utf8_validation: Utf8Validation,
+
+    /// Whether message fields are length prefixed or delimited by group tags.
+    pub message_encoding: MessageEncoding,
+}
+
+impl ResolvedFeatures {
+    /// Returns the default features of an edition, or `None` when the edition is not supported.
+    pub fn for_edition(edition: Edition) -> Option<Self> {
+        match edition {
+            Edition::Legacy | Edition::Proto2 => Some(Self {
+                field_presence: FieldPresence::Explicit,
+                enum_type: EnumType::Closed,
+                repeated_field_encoding: RepeatedFieldEncoding::Expanded,
+                utf8_validation: Utf8Validation::None,
+                message_encoding: MessageEncoding::LengthPrefixed,
+            }),
+            Edition::Proto3 => Some(Self {
+                field_presence: FieldPresence::Implicit,
+                enum_type: EnumType::Open,
+                repeated_field_encoding: RepeatedFieldEncoding::Packed,
+                utf8_validation: Utf8Validation::Verify,
+                message_encoding: MessageEncoding::LengthPrefixed,
+            }),
+            Edition::Edition2023 => Some(Self {
+                field_presence: FieldPresence::Explicit,
+                enum_type: EnumType::Open,
+                repeated_field_encoding: RepeatedFieldEncoding::Packed,
+                utf8_validation: Utf8Validation::Verify,
+                message_encoding: MessageEncoding::LengthPrefixed,
+            }),
+            Edition::Unknown | Edition::Edition2024 | Edition::Max => None,
+        }
+    }
+
+    /// Returns the features with those set in the feature set replacing them. Features that are
+    /// not set, or are set to an unknown value, are inherited.
+    pub fn merge(&self, features: Option<&FeatureSet>) -> Self {
+        let features = match features {
+            Some(features) => features,
+            None => return *self,
+        };
+
+        Self {
+            field_presence: known(features.field_presence()).unwrap_or(self.field_presence),
+            enum_type: known(features.enum_type()).unwrap_or(self.enum_type),
+            repeated_field_encoding: known(features.repeated_field_encoding())
+                .unwrap_or(self.repeated_field_encoding),
+            utf8_validation: known(features.utf8_validation()).unwrap_or(self.utf8_validation),
+            message_encoding: known(features.message_encoding()).unwrap_or(self.message_encoding),
+        }
+    }
+}
+
+// Leaves out the zero value of a feature enum, which stands for the feature not being set.
+fn known<E: Enumeration>(value: Option<E>) -> Option<E> {
+    value.filter(|v| v.number() != 0)
+}
+
+/// Holds a set of files and indexes the messages, enums, services and fields they define by their
+/// fully qualified names.
+///
+/// When a file is added every type reference within it is resolved using the Protocol Buffer
+/// scoping rules, so relative names are looked up from the innermost scope outwards and only the
+/// file itself, its imports and the files they publicly import are visible. A file is only added
+/// when every reference resolves, so the pool never holds dangling references.
+///
+/// The pool is cheap to clone and the descriptors it hands out keep it alive.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{
+///     DescriptorPool, DescriptorProto, FieldDescriptorProto, FieldType, FileDescriptorProto,
+/// };
+///
+/// let file = FileDescriptorProto {
+///     name: Some("greeting.proto".to_string()),
+///     package: Some("greeting".to_string()),
+///     syntax: Some("proto3".to_string()),
+///     message_type: vec![DescriptorProto {
+///         name: Some("Hello".to_string()),
+///         field: vec![FieldDescriptorProto {
+///             name: Some("user_name".to_string()),
+///             number: Some(1),
+///             r#type: Some(FieldType::String.into()),
+///             ..Default::default()
+///         }],
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// };
+///
+/// let mut pool = DescriptorPool::new();
+/// pool.add_file_descriptor_proto(file).unwrap();
+///
+/// let message = pool.get_message_by_name("greeting.Hello").unwrap();
+/// let field = message.get_field_by_name("user_name").unwrap();
+///
+/// assert_eq!(field.json_name(), "userName");
+/// ```
+#[derive(Clone, Default)]
+pub struct DescriptorPool {
+    inner: Arc<PoolInner>,
+}
+
+#[derive(Debug, Clone, Default)]
+struct PoolInner {
+    files: Vec<FileEntry>,
+    messages: Vec<MessageEntry>,
+    enums: Vec<EnumEntry>,
+    services: Vec<ServiceEntry>,
+    extensions: Vec<ExtensionEntry>,
+    names: HashMap<String, NameEntry>,
+    file_names: HashMap<String, usize>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Symbol {
+    Package,
+    Message(usize),
+    Enum(usize),
+    Service,
+    Extension(usize),
+    Member,
+}
+
+#[derive(Debug, Clone)]
+struct NameEntry {
+    symbol: Symbol,
+    file: usize,
+}
+
+#[derive(Debug, Clone)]
+struct FileEntry {
+    proto: FileDescriptorProto,
+    syntax: Syntax,
+    edition: Edition,
+    features: ResolvedFeatures,
+    dependencies: Vec<usize>,
+    messages: Vec<usize>,
+    enums: Vec<usize>,
+    services: Vec<usize>,
+    extensions: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct MessageEntry {
+    full_name: String,
+    file: usize,
+    parent: Option<usize>,
+    proto: DescriptorProto,
+    features: ResolvedFeatures,
+    fields: Vec<FieldEntry>,
+    oneofs: Vec<OneofEntry>,
+    messages: Vec<usize>,
+    enums: Vec<usize>,
+    // The extensions declared within the message.
+    child_extensions: Vec<usize>,
+    // The extensions that extend the message, indexed by their numbers.
+    extensions: Vec<usize>,
+    extension_numbers: HashMap<u32, usize>,
+    field_numbers: HashMap<u32, usize>,
+    field_names: HashMap<String, usize>,
+    field_json_names: HashMap<String, usize>,
+}
+
+#[derive(Debug, Clone)]
+struct FieldEntry {
+    full_name: String,
+    json_name: String,
+    label: FieldLabel,
+    field_type: FieldType,
+    type_reference: Option<Symbol>,
+    oneof: Option<usize>,
+    proto: FieldDescriptorProto,
+    features: ResolvedFeatures,
+}
+
+#[derive(Debug, Clone)]
+struct ExtensionEntry {
+    scope: Option<usize>,
+    extendee: usize,
+    field: FieldEntry,
+}
+
+#[derive(Debug, Clone)]
+struct OneofEntry {
+    full_name: String,
+    fields: Vec<usize>,
+    features: ResolvedFeatures,
+}
+
+#[derive(Debug, Clone)]
+struct EnumEntry {
+    full_name: String,
+    file: usize,
+    parent: Option<usize>,
+    proto: EnumDescriptorProto,
+    features: ResolvedFeatures,
+    value_full_names: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct ServiceEntry {
+    full_name: String,
+    file: usize,
+    proto: ServiceDescriptorProto,
+    methods: Vec<MethodEntry>,
+}
+
+#[derive(Debug, Clone)]
+struct MethodEntry {
+    full_name: String,
+    input: usize,
+    output: usize,
+}
+
+// A type reference that can only be resolved once every name in the file has been registered.
+enum Reference {
+    Field {
+        message: usize,
+        field: usize,
+    },
+    Extension {
+        scope: String,
+        parent: Option<usize>,
+        proto: Box<FieldDescriptorProto>,
+        // The features of the scope the extension is declared in.
+        features: ResolvedFeatures,
+    },
+    Method {
+        service: usize,
+        method: usize,
+    },
+}
+
+impl DescriptorPool {
+    /// Creates an empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a pool from the binary encoding of a `FileDescriptorSet`, such as the output of
+    /// `protoc --descriptor_set_out`.
+    pub fn decode(buffer: &[u8]) -> Result<Self, DescriptorError> {
+        Self::from_file_descriptor_set(FileDescriptorSet::decode(buffer)?)
+    }
+
+    /// Creates a pool holding every file in the set.
+    pub fn from_file_descriptor_set(set: FileDescriptorSet) -> Result<Self, DescriptorError> {
+        let mut pool = Self::new();
+        pool.add_file_descriptor_set(set)?;
+        Ok(pool)
+    }
+
+    /// Adds every file in the set to the pool. The files may be in any order, as each file is
+    /// added once the files it imports have been added. Nothing is added when an error is
+    /// returned.
+    pub fn add_file_descriptor_set(
+        &mut self,
+        set: FileDescriptorSet,
+    ) -> Result<(), DescriptorError> {
+        let mut inner = PoolInner::clone(&self.inner);
+        let mut pending = set.file;
+
+        while !pending.is_empty() {
+            let ready = pending
+                .iter()
+                .position(|f| {
+                    f.dependency
+                        .iter()
+                        .all(|d| inner.file_names.contains_key(d))
+                })
+                .unwrap_or(0);
+
+            inner.add_file(pending.remove(ready))?;
+        }
+
+        self.inner = Arc::new(inner);
+        Ok(())
+    }
+
+    /// Adds a single file to the pool. Every file it imports must already have been added.
+    /// Adding a file that is already in the pool with the same contents has no effect.
+    pub fn add_file_descriptor_proto(
+        &mut self,
+        file: FileDescriptorProto,
+    ) -> Result<(), DescriptorError> {
+        let mut inner = PoolInner::clone(&self.inner);
+        inner.add_file(file)?;
+
+        self.inner = Arc::new(inner);
+        Ok(())
+    }
+
+    /// Returns every file in the pool in the order they were added.
+    pub fn files(&self) -> impl ExactSizeIterator<Item = FileDescriptor> + '_ {
+        (0..self.inner.files.len()).map(|index| FileDescriptor {
+            pool: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns a `FileDescriptorSet` holding every file in the pool, with each file following
+    /// the files it imports.
+    pub fn file_descriptor_set(&self) -> FileDescriptorSet {
+        FileDescriptorSet {
+            file: self.inner.files.iter().map(|f| f.proto.clone()).collect(),
+        }
+    }
+
+    /// Returns every message in the pool, including nested messages.
+    pub fn all_messages(&self) -> impl ExactSizeIterator<Item = MessageDescriptor> + '_ {
+        (0..self.inner.messages.len()).map(|index| MessageDescriptor {
+            pool: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns every enum in the pool, including nested enums.
+    pub fn all_enums(&self) -> impl ExactSizeIterator<Item = EnumDescriptor> + '_ {
+        (0..self.inner.enums.len()).map(|index| EnumDescriptor {
+            pool: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns every service in the pool.
+    pub fn services(&self) -> impl ExactSizeIterator<Item = ServiceDescriptor> + '_ {
+        (0..self.inner.services.len()).map(|index| ServiceDescriptor {
+            pool: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns every extension in the pool, including extensions declared within messages.
+    pub fn all_extensions(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
+        (0..self.inner.extensions.len()).map(|index| self.extension(index))
+    }
+
+    /// Returns the file with the given name.
+    pub fn get_file_by_name(&self, name: &str) -> Option<FileDescriptor> {
+        self.inner
+            .file_names
+            .get(name)
+            .map(|&index| FileDescriptor {
+                pool: self.clone(),
+                index,
+            })
+    }
+
+    /// Returns the message with the given fully qualified name. A leading `.` is ignored.
+    pub fn get_message_by_name(&self, name: &str) -> Option<MessageDescriptor> {
+        match self.inner.symbol(name)? {
+            Symbol::Message(index) => Some(MessageDescriptor {
+                pool: self.clone(),
+                index,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the enum with the given fully qualified name. A leading `.` is ignored.
+    pub fn get_enum_by_name(&self, name: &str) -> Option<EnumDescriptor> {
+        match self.inner.symbol(name)? {
+            Symbol::Enum(index) => Some(EnumDescriptor {
+                pool: self.clone(),
+                index,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the service with the given fully qualified name. A leading `.` is ignored.
+    pub fn get_service_by_name(&self, name: &str) -> Option<ServiceDescriptor> {
+        let name = name.strip_prefix('.').unwrap_or(name);
+        self.inner
+            .services
+            .iter()
+            .position(|s| s.full_name == name)
+            .map(|index| ServiceDescriptor {
+                pool: self.clone(),
+                index,
+            })
+    }
+
+    /// Returns the extension with the given fully qualified name. A leading `.` is ignored.
+    pub fn get_extension_by_name(&self, name: &str) -> Option<FieldDescriptor> {
+        match self.inner.symbol(name)? {
+            Symbol::Extension(index) => Some(self.extension(index)),
+            _ => None,
+        }
+    }
+
+    fn extension(&self, index: usize) -> FieldDescriptor {
+        FieldDescriptor {
+            message: MessageDescriptor {
+                pool: self.clone(),
+                index: self.inner.extensions[index].extendee,
+            },
+            index,
+            is_extension: true,
+        }
+    }
+
+    // Resolves the name of an extension used within the scope of a file, as the name of a custom
+    // option would be.
+    pub(crate) fn resolve_extension_name(
+        &self,
+        file: &str,
+        scope: &str,
+        name: &str,
+    ) -> Option<FieldDescriptor> {
+        let inner = &self.inner;
+        let file = *inner.file_names.get(file)?;
+
+        match inner.resolve(&inner.visible_files(file), scope, name)? {
+            Symbol::Extension(index) => Some(self.extension(index)),
+            _ => None,
+        }
+    }
+
+    // Resolves a type name used within the scope of a file, as a field type would be, returning
+    // the fully qualified name and whether it names a message or an enum.
+    pub(crate) fn resolve_type_name(
+        &self,
+        file: &str,
+        scope: &str,
+        type_name: &str,
+    ) -> Option<(String, FieldType)> {
+        let inner = &self.inner;
+        let file = *inner.file_names.get(file)?;
+
+        match inner.resolve(&inner.visible_files(file), scope, type_name)? {
+            Symbol::Message(index) => Some((
+                format!(".{}", inner.messages[index].full_name),
+                FieldType::Message,
+            )),
+            Symbol::Enum(index) => Some((
+                format!(".{}", inner.enums[index].full_name),
+                FieldType::Enum,
+            )),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for DescriptorPool {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for DescriptorPool {}
+
+impl fmt::Debug for DescriptorPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DescriptorPool")
+            .field(
+                "files",
+                &self
+                    .inner
+                    .files
+                    .iter()
+                    .map(|f| f.proto.name())
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl PoolInner {
+    fn symbol(&self, name: &str) -> Option<Symbol> {
+        let name = name.strip_prefix('.').unwrap_or(name);
+        self.names.get(name).map(|n| n.symbol)
+    }
+
+    fn add_file(&mut self, proto: FileDescriptorProto) -> Result<(), DescriptorError> {
+        if let Some(&index) = self.file_names.get(proto.name()) {
+            return match self.files[index].proto == proto {
+                true => Ok(()),
+                false => Err(DescriptorError::DuplicateFile(proto.name().to_string())),
+            };
+        }
+
+        let mut dependencies = Vec::new();
+        for dependency in &proto.dependency {
+            match self.file_names.get(dependency) {
+                Some(&index) => dependencies.push(index),
+                None => {
+                    return Err(DescriptorError::MissingDependency {
+                        file: proto.name().to_string(),
+                        dependency: dependency.clone(),
+                    })
+                }
+            }
+        }
+
+        let file = self.files.len();
+        let _ = self.file_names.insert(proto.name().to_string(), file);
+
+        let package = proto.package().to_string();
+        for (position, _) in package.match_indices('.') {
+            self.add_name(&package[..position], Symbol::Package, file)?;
+        }
+        if !package.is_empty() {
+            self.add_name(&package, Symbol::Package, file)?;
+        }
+
+        let (syntax, edition) = match proto.syntax() {
+            "proto3" => (Syntax::Proto3, Edition::Proto3),
+            "editions" => (
+                Syntax::Editions,
+                proto.edition().unwrap_or(Edition::Unknown),
+            ),
+            _ => (Syntax::Proto2, Edition::Proto2),
+        };
+        let features = match ResolvedFeatures::for_edition(edition) {
+            Some(defaults) => {
+                defaults.merge(proto.options.as_ref().and_then(|o| o.features.as_ref()))
+            }
+            None => {
+                return Err(DescriptorError::UnsupportedEdition {
+                    file: proto.name().to_string(),
+                    edition: proto.edition.map_or(0, |e| e.number()),
+                })
+            }
+        };
+
+        let mut references = Vec::new();
+        let mut messages = Vec::new();
+        for message in &proto.message_type {
+            messages.push(self.add_message(
+                message,
+                file,
+                &package,
+                None,
+                features,
+                &mut references,
+            )?);
+        }
+
+        let mut enums = Vec::new();
+        for enumeration in &proto.enum_type {
+            enums.push(self.add_enum(enumeration, file, &package, None, features)?);
+        }
+
+        for extension in &proto.extension {
+            self.add_name(&join(&package, extension.name()), Symbol::Member, file)?;
+            references.push(Reference::Extension {
+                scope: package.clone(),
+                parent: None,
+                proto: Box::new(extension.clone()),
+                features,
+            });
+        }
+
+        let mut services = Vec::new();
+        for service in &proto.service {
+            services.push(self.add_service(service, file, &package, &mut references)?);
+        }
+
+        self.files.push(FileEntry {
+            proto,
+            syntax,
+            edition,
+            features,
+            dependencies,
+            messages,
+            enums,
+            services,
+            extensions: Vec::new(),
+        });
+
+        let visible = self.visible_files(file);
+        for reference in references {
+            self.resolve_reference(reference, &visible, &package)?;
+        }
+
+        Ok(())
+    }
+
+    fn add_name(&mut self, name: &str, symbol: Symbol, file: usize) -> Result<(), DescriptorError> {
+        match self.names.get(name) {
+            Some(n) if n.symbol == Symbol::Package && symbol == Symbol::Package => Ok(()),
+            Some(_) => Err(DescriptorError::DuplicateName(name.to_string())),
+            None => {
+                let _ = self
+                    .names
+                    .insert(name.to_string(), NameEntry { symbol, file });
+                Ok(())
+            }
+        }
+    }
+
+    fn add_message(
+        &mut self,
+        proto: &DescriptorProto,
+        file: usize,
+        scope: &str,
+        parent: Option<usize>,
+        inherited: ResolvedFeatures,
+        references: &mut Vec<Reference>,
+    ) -> Result<usize, DescriptorError> {
+        let full_name = join(scope, proto.name());
+        let index = self.messages.len();
+        self.add_name(&full_name, Symbol::Message(index), file)?;
+
+        let features = inherited.merge(proto.options.as_ref().and_then(|o| o.features.as_ref()));
+        let mut entry = MessageEntry {
+            full_name: full_name.clone(),
+            file,
+            parent,
+            proto: proto.clone(),
+            features,
+            fields: Vec::new(),
+            oneofs: Vec::new(),
+            messages: Vec::new(),
+            enums: Vec::new(),
+            child_extensions: Vec::new(),
+            extensions: Vec::new(),
+            extension_numbers: HashMap::new(),
+            field_numbers: HashMap::new(),
+            field_names: HashMap::new(),
+            field_json_names: HashMap::new(),
+        };
+
+        for oneof in &proto.oneof_decl {
+            let oneof_name = join(&full_name, oneof.name());
+            self.add_name(&oneof_name, Symbol::Member, file)?;
+            entry.oneofs.push(OneofEntry {
+                full_name: oneof_name,
+                fields: Vec::new(),
+                features: features.merge(oneof.options.as_ref().and_then(|o| o.features.as_ref())),
+            });
+        }
+
+        for (position, field) in proto.field.iter().enumerate() {
+            let field_name = join(&full_name, field.name());
+            self.add_name(&field_name, Symbol::Member, file)?;
+
+            let number = field.number() as u32;
+            Tag::validate_field_number(number).map_err(|error| {
+                DescriptorError::InvalidFieldNumber {
+                    field: field_name.clone(),
+                    error,
+                }
+            })?;
+            if entry.field_numbers.insert(number, position).is_some() {
+                return Err(DescriptorError::DuplicateFieldNumber {
+                    message: full_name,
+                    number: field.number(),
+                });
+            }
+
+            let oneof = field.oneof_index.map(|i| i as usize);
+            let mut field_features = features;
+            if let Some(o) = oneof.and_then(|i| entry.oneofs.get_mut(i)) {
+                o.fields.push(position);
+                field_features = o.features;
+            }
+
+            let json_name = match &field.json_name {
+                Some(json_name) => json_name.clone(),
+                None => to_json_name(field.name()),
+            };
+            let _ = entry.field_names.insert(field.name().to_string(), position);
+            let _ = entry.field_json_names.insert(json_name.clone(), position);
+
+            entry.fields.push(FieldEntry {
+                full_name: field_name,
+                json_name,
+                label: field.label().unwrap_or(FieldLabel::Optional),
+                field_type: field.r#type().unwrap_or(FieldType::Message),
+                type_reference: None,
+                oneof,
+                proto: field.clone(),
+                features: field_features
+                    .merge(field.options.as_ref().and_then(|o| o.features.as_ref())),
+            });
+            references.push(Reference::Field {
+                message: index,
+                field: position,
+            });
+        }
+
+        // The entry is pushed before the nested types so that message indexes follow the order
+        // of the definitions.
+        self.messages.push(entry);
+
+        let mut messages = Vec::new();
+        for message in &proto.nested_type {
+            messages.push(self.add_message(
+                message,
+                file,
+                &full_name,
+                Some(index),
+                features,
+                references,
+            )?);
+        }
+
+        let mut enums = Vec::new();
+        for enumeration in &proto.enum_type {
+            enums.push(self.add_enum(enumeration, file, &full_name, Some(index), features)?);
+        }
+
+        for extension in &proto.extension {
+            self.add_name(&join(&full_name, extension.name()), Symbol::Member, file)?;
+            references.push(Reference::Extension {
+                scope: full_name.clone(),
+                parent: Some(index),
+                proto: Box::new(extension.clone()),
+                features,
+            });
+        }
+
+        self.messages[index].messages = messages;
+        self.messages[index].enums = enums;
+        Ok(index)
+    }
+
+    fn add_enum(
+        &mut self,
+        proto: &EnumDescriptorProto,
+        file: usize,
+        scope: &str,
+        parent: Option<usize>,
+        inherited: ResolvedFeatures,
+    ) -> Result<usize, DescriptorError> {
+        let full_name = join(scope, proto.name());
+        let index = self.enums.len();
+        self.add_name(&full_name, Symbol::Enum(index), file)?;
+
+        // Enum values are siblings of their enum rather than children, as in C++.
+        let mut value_full_names = Vec::new();
+        for value in &proto.value {
+            let value_name = join(scope, value.name());
+            self.add_name(&value_name, Symbol::Member, file)?;
+            value_full_names.push(value_name);
+        }
+
+        self.enums.push(EnumEntry {
+            full_name,
+            file,
+            parent,
+            proto: proto.clone(),
+            features: inherited.merge(proto.options.as_ref().and_then(|o| o.features.as_ref())),
+            value_full_names,
+        });
+        Ok(index)
+    }
+
+    fn add_service(
+        &mut self,
+        proto: &ServiceDescriptorProto,
+        file: usize,
+        scope: &str,
+        references: &mut Vec<Reference>,
+    ) -> Result<usize, DescriptorError> {
+        let full_name = join(scope, proto.name());
+        let index = self.services.len();
+        self.add_name(&full_name, Symbol::Service, file)?;
+
+        let mut methods = Vec::new();
+        for (position, method) in proto.method.iter().enumerate() {
+            let method_name = join(&full_name, method.name());
+            self.add_name(&method_name, Symbol::Member, file)?;
+
+            methods.push(MethodEntry {
+                full_name: method_name,
+                input: 0,
+                output: 0,
+            });
+            references.push(Reference::Method {
+                service: index,
+                method: position,
+            });
+        }
+
+        self.services.push(ServiceEntry {
+            full_name,
+            file,
+            proto: proto.clone(),
+            methods,
+        });
+        Ok(index)
+    }
+
+    // Returns the files whose definitions can be referenced from the file: the file itself, the
+    // files it imports and any files those files publicly import.
+    fn visible_files(&self, file: usize) -> HashSet<usize> {
+        let mut visible = HashSet::from([file]);
+        let mut pending = self.files[file].dependencies.clone();
+
+        while let Some(dependency) = pending.pop() {
+            if visible.insert(dependency) {
+                let entry = &self.files[dependency];
+                pending.extend(
+                    entry
+                        .proto
+                        .public_dependency
+                        .iter()
+                        .filter_map(|&i| entry.dependencies.get(i as usize)),
+                );
+            }
+        }
+
+        visible
+    }
+
+    fn resolve_reference(
+        &mut self,
+        reference: Reference,
+        visible: &HashSet<usize>,
+        package: &str,
+    ) -> Result<(), DescriptorError> {
+        match reference {
+            Reference::Field { message, field } => {
+                let scope = self.messages[message].full_name.clone();
+                let entry = &self.messages[message].fields[field];
+                let resolved =
+                    self.resolve_field_type(&entry.proto, &entry.full_name, visible, &scope)?;
+
+                if let Some((field_type, symbol)) = resolved {
+                    let field_type = self.encoded_type(field_type, symbol, &entry.features);
+                    let entry = &mut self.messages[message].fields[field];
+                    entry.field_type = field_type;
+                    entry.type_reference = Some(symbol);
+                }
+                Ok(())
+            }
+            Reference::Extension {
+                scope,
+                parent,
+                proto,
+                features,
+            } => {
+                let full_name = join(&scope, proto.name());
+                let resolved = self.resolve_field_type(&proto, &full_name, visible, &scope)?;
+
+                let extendee = match self.resolve(visible, &scope, proto.extendee()) {
+                    Some(Symbol::Message(extendee)) => extendee,
+                    _ => {
+                        return Err(DescriptorError::UnresolvedTypeName {
+                            name: full_name,
+                            type_name: proto.extendee().to_string(),
+                        })
+                    }
+                };
+
+                let index = self.extensions.len();
+                let number = proto.number() as u32;
+                Tag::validate_field_number(number).map_err(|error| {
+                    DescriptorError::InvalidFieldNumber {
+                        field: full_name.clone(),
+                        error,
+                    }
+                })?;
+                let message = &mut self.messages[extendee];
+                let in_range = message
+                    .proto
+                    .extension_range
+                    .iter()
+                    .any(|r| r.start() <= proto.number() && proto.number() < r.end());
+                if !in_range {
+                    return Err(DescriptorError::ExtensionNumberOutOfRange {
+                        extension: full_name,
+                        message: message.full_name.clone(),
+                        number: proto.number(),
+                    });
+                }
+                if message.field_numbers.contains_key(&number)
+                    || message.extension_numbers.insert(number, index).is_some()
+                {
+                    return Err(DescriptorError::DuplicateFieldNumber {
+                        message: message.full_name.clone(),
+                        number: proto.number(),
+                    });
+                }
+                message.extensions.push(index);
+
+                let file = self.files.len() - 1;
+                match parent {
+                    Some(parent) => self.messages[parent].child_extensions.push(index),
+                    None => self.files[file].extensions.push(index),
+                }
+                if let Some(name) = self.names.get_mut(&full_name) {
+                    name.symbol = Symbol::Extension(index);
+                }
+
+                let features =
+                    features.merge(proto.options.as_ref().and_then(|o| o.features.as_ref()));
+                let (field_type, type_reference) = match resolved {
+                    Some((field_type, symbol)) => (
+                        self.encoded_type(field_type, symbol, &features),
+                        Some(symbol),
+                    ),
+                    None => (proto.r#type().unwrap_or(FieldType::Message), None),
+                };
+                self.extensions.push(ExtensionEntry {
+                    scope: parent,
+                    extendee,
+                    field: FieldEntry {
+                        json_name: match &proto.json_name {
+                            Some(json_name) => json_name.clone(),
+                            None => to_json_name(proto.name()),
+                        },
+                        full_name,
+                        label: proto.label().unwrap_or(FieldLabel::Optional),
+                        field_type,
+                        type_reference,
+                        oneof: None,
+                        proto: *proto,
+                        features,
+                    },
+                });
+                Ok(())
+            }
+            Reference::Method { service, method } => {
+                let proto = &self.services[service].proto.method[method];
+                let full_name = &self.services[service].methods[method].full_name;
+
+                let mut types = [0; 2];
+                for (index, type_name) in [proto.input_type(), proto.output_type()]
+                    .into_iter()
+                    .enumerate()
+                {
+                    types[index] = match self.resolve(visible, package, type_name) {
+                        Some(Symbol::Message(m)) => m,
+                        _ => {
+                            return Err(DescriptorError::UnresolvedTypeName {
+                                name: full_name.clone(),
+                                type_name: type_name.to_string(),
+                            })
+                        }
+                    };
+                }
+
+                let entry = &mut self.services[service].methods[method];
+                entry.input = types[0];
+                entry.output = types[1];
+                Ok(())
+            }
+        }
+    }
+
+    // Resolves the type name of a field, returning `None` for scalar fields.
+    fn resolve_field_type(
+        &self,
+        proto: &FieldDescriptorProto,
+        full_name: &str,
+        visible: &HashSet<usize>,
+        scope: &str,
+    ) -> Result<Option<(FieldType, Symbol)>, DescriptorError> {
+        let field_type = proto.r#type();
+        if !matches!(
+            field_type,
+            None | Some(FieldType::Message) | Some(FieldType::Group) | Some(FieldType::Enum)
+        ) {
+            return Ok(None);
+        }
+
+        if proto.type_name().is_empty() {
+            return Err(DescriptorError::MissingFieldType(full_name.to_string()));
+        }
+
+        match (field_type, self.resolve(visible, scope, proto.type_name())) {
+            (Some(FieldType::Group), Some(symbol @ Symbol::Message(_))) => {
+                Ok(Some((FieldType::Group, symbol)))
+            }
+            (None | Some(FieldType::Message), Some(symbol @ Symbol::Message(_))) => {
+                Ok(Some((FieldType::Message, symbol)))
+            }
+            (None | Some(FieldType::Enum), Some(symbol @ Symbol::Enum(_))) => {
+                Ok(Some((FieldType::Enum, symbol)))
+            }
+            _ => Err(DescriptorError::UnresolvedTypeName {
+                name: full_name.to_string(),
+                type_name: proto.type_name().to_string(),
+            }),
+        }
+    }
+
+    // Returns the type a resolved field is encoded as. Message fields with the delimited message
+    // encoding are written as groups, except for map fields, whose entries are always length
+    // prefixed.
+    fn encoded_type(
+        &self,
+        field_type: FieldType,
+        symbol: Symbol,
+        features: &ResolvedFeatures,
+    ) -> FieldType {
+        match (field_type, symbol) {
+            (FieldType::Message, Symbol::Message(index))
+                if features.message_encoding == MessageEncoding::Delimited
+                    && !self.messages[index]
+                        .proto
+                        .options
+                        .as_ref()
+                        .is_some_and(|o| o.map_entry()) =>
+            {
+                FieldType::Group
+            }
+            _ => field_type,
+        }
+    }
+
+    // Resolves a type name using the Protocol Buffer scoping rules. Fully qualified names start
+    // with a `.`, otherwise the first part of the name is looked up in each enclosing scope,
+    // innermost first, and the rest of the name is looked up within the first match that can
+    // contain other definitions.
+    fn resolve(&self, visible: &HashSet<usize>, scope: &str, type_name: &str) -> Option<Symbol> {
+        if let Some(name) = type_name.strip_prefix('.') {
+            return self.lookup(visible, name);
+        }
+
+        let first = type_name.split('.').next().unwrap_or_default();
+        let mut scope = scope;
+
+        loop {
+            if let Some(symbol) = self.lookup(visible, &join(scope, first)) {
+                if first.len() == type_name.len() {
+                    return Some(symbol);
+                }
+                if matches!(symbol, Symbol::Package | Symbol::Message(_)) {
+                    return self.lookup(visible, &join(scope, type_name));
+                }
+            }
+
+            if scope.is_empty() {
+                return None;
+            }
+            scope = scope.rfind('.').map_or("", |i| &scope[..i]);
+        }
+    }
+
+    fn lookup(&self, visible: &HashSet<usize>, name: &str) -> Option<Symbol> {
+        let entry = self.names.get(name)?;
+
+        match entry.symbol == Symbol::Package || visible.contains(&entry.file) {
+            true => Some(entry.symbol),
+            false => None,
+        }
+    }
+}
+
+fn join(scope: &str, name: &str) -> String {
+    match scope.is_empty() {
+        true => name.to_string(),
+        false => format!("{}.{}", scope, name),
+    }
+}
+
+/// Converts a field name to the name used by the JSON mapping, removing underscores and
+/// capitalising the letter that follows each of them.
+pub(crate) fn to_json_name(name: &str) -> String {
+    let mut json_name = String::with_capacity(name.len());
+    let mut capitalise_next = false;
+
+    for c in name.chars() {
+        if c == '_' {
+            capitalise_next = true;
+        } else if capitalise_next {
+            json_name.push(c.to_ascii_uppercase());
+            capitalise_next = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+
+    json_name
+}
+
+/// Describes a file held by a `DescriptorPool`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FileDescriptor {
+    pool: DescriptorPool,
+    index: usize,
+}
+
+impl FileDescriptor {
+    fn entry(&self) -> &FileEntry {
+        &self.pool.inner.files[self.index]
+    }
+
+    /// Returns the pool holding the file.
+    pub fn parent_pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Returns the name of the file, relative to the root of the source tree.
+    pub fn name(&self) -> &str {
+        self.entry().proto.name()
+    }
+
+    /// Returns the package of the file, which is empty when the file does not declare one.
+    pub fn package_name(&self) -> &str {
+        self.entry().proto.package()
+    }
+
+    /// Returns the syntax the file was written in.
+    pub fn syntax(&self) -> Syntax {
+        self.entry().syntax
+    }
+
+    /// Returns the edition of the file, which is `Edition::Proto2` or `Edition::Proto3` for files
+    /// that declare a syntax rather than an edition.
+    pub fn edition(&self) -> Edition {
+        self.entry().edition
+    }
+
+    /// Returns the features of the file, resolved from the defaults of its edition.
+    pub fn features(&self) -> ResolvedFeatures {
+        self.entry().features
+    }
+
+    /// Returns the files imported by the file.
+    pub fn dependencies(&self) -> impl ExactSizeIterator<Item = FileDescriptor> + '_ {
+        self.entry()
+            .dependencies
+            .iter()
+            .map(|&index| FileDescriptor {
+                pool: self.pool.clone(),
+                index,
+            })
+    }
+
+    /// Returns the top-level messages of the file.
+    pub fn messages(&self) -> impl ExactSizeIterator<Item = MessageDescriptor> + '_ {
+        self.entry()
+            .messages
+            .iter()
+            .map(|&index| MessageDescriptor {
+                pool: self.pool.clone(),
+                index,
+            })
+    }
+
+    /// Returns the top-level enums of the file.
+    pub fn enums(&self) -> impl ExactSizeIterator<Item = EnumDescriptor> + '_ {
+        self.entry().enums.iter().map(|&index| EnumDescriptor {
+            pool: self.pool.clone(),
+            index,
+        })
+    }
+
+    /// Returns the services of the file.
+    pub fn services(&self) -> impl ExactSizeIterator<Item = ServiceDescriptor> + '_ {
+        self.entry()
+            .services
+            .iter()
+            .map(|&index| ServiceDescriptor {
+                pool: self.pool.clone(),
+                index,
+            })
+    }
+
+    /// Returns the extensions declared at the top level of the file.
+    pub fn extensions(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
+        self.entry()
+            .extensions
+            .iter()
+            .map(|&index| self.pool.extension(index))
+    }
+
+    /// Returns the value of a custom option set on the file, given the extension of
+    /// `google.protobuf.FileOptions` that defines it.
+    pub fn get_option(&self, extension: &FieldDescriptor) -> Option<FieldValue> {
+        option_value(
+            self.entry().proto.options.as_ref(),
+            extension,
+            "FileOptions",
+        )
+    }
+
+    /// Returns the descriptor the file was created from.
+    pub fn file_descriptor_proto(&self) -> &FileDescriptorProto {
+        &self.entry().proto
+    }
+}
+
+/// Describes a message held by a `DescriptorPool`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MessageDescriptor {
+    pool: DescriptorPool,
+    index: usize,
+}
+
+impl MessageDescriptor {
+    fn entry(&self) -> &MessageEntry {
+        &self.pool.inner.messages[self.index]
+    }
+
+    /// Returns the pool holding the message.
+    pub fn parent_pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Returns the file the message is defined in.
+    pub fn parent_file(&self) -> FileDescriptor {
+        FileDescriptor {
+            pool: self.pool.clone(),
+            index: self.entry().file,
+        }
+    }
+
+    /// Returns the message the message is nested in, or `None` for a top-level message.
+    pub fn parent_message(&self) -> Option<MessageDescriptor> {
+        self.entry().parent.map(|index| MessageDescriptor {
+            pool: self.pool.clone(),
+            index,
+        })
+    }
+
+    /// Returns the name of the message.
+    pub fn name(&self) -> &str {
+        self.entry().proto.name()
+    }
+
+    /// Returns the fully qualified name of the message, e.g. `foo.Bar.Baz`.
+    pub fn full_name(&self) -> &str {
+        &self.entry().full_name
+    }
+
+    /// Returns the fields of the message in the order they were declared.
+    pub fn fields(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
+        (0..self.entry().fields.len()).map(|index| FieldDescriptor {
+            message: self.clone(),
+            index,
+            is_extension: false,
+        })
+    }
+
+    /// Returns the field with the given number.
+    pub fn get_field(&self, number: u32) -> Option<FieldDescriptor> {
+        self.entry()
+            .field_numbers
+            .get(&number)
+            .map(|&index| FieldDescriptor {
+                message: self.clone(),
+                index,
+                is_extension: false,
+            })
+    }
+
+    /// Returns the field with the given name.
+    pub fn get_field_by_name(&self, name: &str) -> Option<FieldDescriptor> {
+        self.entry()
+            .field_names
+            .get(name)
+            .map(|&index| FieldDescriptor {
+                message: self.clone(),
+                index,
+                is_extension: false,
+            })
+    }
+
+    /// Returns the field with the given JSON name.
+    pub fn get_field_by_json_name(&self, json_name: &str) -> Option<FieldDescriptor> {
+        self.entry()
+            .field_json_names
+            .get(json_name)
+            .map(|&index| FieldDescriptor {
+                message: self.clone(),
+                index,
+                is_extension: false,
+            })
+    }
+
+    /// Returns the oneof groups of the message, including the synthetic groups created for
+    /// proto3 `optional` fields.
+    pub fn oneofs(&self) -> impl ExactSizeIterator<Item = OneofDescriptor> + '_ {
+        (0..self.entry().oneofs.len()).map(|index| OneofDescriptor {
+            message: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns the messages nested in the message.
+    pub fn child_messages(&self) -> impl ExactSizeIterator<Item = MessageDescriptor> + '_ {
+        self.entry()
+            .messages
+            .iter()
+            .map(|&index| MessageDescriptor {
+                pool: self.pool.clone(),
+                index,
+            })
+    }
+
+    /// Returns the enums nested in the message.
+    pub fn child_enums(&self) -> impl ExactSizeIterator<Item = EnumDescriptor> + '_ {
+        self.entry().enums.iter().map(|&index| EnumDescriptor {
+            pool: self.pool.clone(),
+            index,
+        })
+    }
+
+    /// Returns the extensions declared within the message.
+    pub fn child_extensions(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
+        self.entry()
+            .child_extensions
+            .iter()
+            .map(|&index| self.pool.extension(index))
+    }
+
+    /// Returns the extensions in the pool that extend the message.
+    pub fn extensions(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
+        self.entry()
+            .extensions
+            .iter()
+            .map(|&index| self.pool.extension(index))
+    }
+
+    /// Returns the extension of the message with the given number.
+    pub fn get_extension(&self, number: u32) -> Option<FieldDescriptor> {
+        self.entry()
+            .extension_numbers
+            .get(&number)
+            .map(|&index| self.pool.extension(index))
+    }
+
+    /// Returns the ranges of field numbers reserved for extensions, with exclusive ends.
+    pub fn extension_ranges(&self) -> impl ExactSizeIterator<Item = Range<u32>> + '_ {
+        self.entry()
+            .proto
+            .extension_range
+            .iter()
+            .map(|r| r.start() as u32..r.end() as u32)
+    }
+
+    /// Returns the value of a custom option set on the message, given the extension of
+    /// `google.protobuf.MessageOptions` that defines it.
+    pub fn get_option(&self, extension: &FieldDescriptor) -> Option<FieldValue> {
+        option_value(
+            self.entry().proto.options.as_ref(),
+            extension,
+            "MessageOptions",
+        )
+    }
+
+    /// Returns the features of the message, resolved from those of its file and the messages it
+    /// is nested in.
+    pub fn features(&self) -> ResolvedFeatures {
+        self.entry().features
+    }
+
+    /// Returns true when the message is the entry type generated for a map field.
+    pub fn is_map_entry(&self) -> bool {
+        self.entry()
+            .proto
+            .options
+            .as_ref()
+            .is_some_and(|o| o.map_entry())
+    }
+
+    /// Returns the descriptor the message was created from.
+    pub fn descriptor_proto(&self) -> &DescriptorProto {
+        &self.entry().proto
+    }
+}
+
+/// Describes a field of a message held by a `DescriptorPool`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FieldDescriptor {
+    message: MessageDescriptor,
+    index: usize,
+    // True when the index is that of an extension in the pool rather than a field of the message.
+    is_extension: bool,
+}
+
+impl FieldDescriptor {
+    fn entry(&self) -> &FieldEntry {
+        match self.is_extension {
+            true => &self.extension_entry().field,
+            false => &self.message.entry().fields[self.index],
+        }
+    }
+
+    fn extension_entry(&self) -> &ExtensionEntry {
+        &self.message.pool.inner.extensions[self.index]
+    }
+
+    /// Returns the message the field belongs to, which for an extension is the message it
+    /// extends.
+    pub fn containing_message(&self) -> &MessageDescriptor {
+        &self.message
+    }
+
+    /// Returns true when the field is an extension declared with `extend`.
+    pub fn is_extension(&self) -> bool {
+        self.is_extension
+    }
+
+    /// Returns the message an extension is declared within, or `None` for an extension declared
+    /// at the top level of a file or a field that is not an extension.
+    pub fn extension_scope(&self) -> Option<MessageDescriptor> {
+        match self.is_extension {
+            true => self.extension_entry().scope.map(|index| MessageDescriptor {
+                pool: self.message.pool.clone(),
+                index,
+            }),
+            false => None,
+        }
+    }
+
+    /// Returns the name of the field.
+    pub fn name(&self) -> &str {
+        self.entry().proto.name()
+    }
+
+    /// Returns the fully qualified name of the field, e.g. `foo.Bar.baz`.
+    pub fn full_name(&self) -> &str {
+        &self.entry().full_name
+    }
+
+    /// Returns the name of the field in the JSON mapping.
+    pub fn json_name(&self) -> &str {
+        &self.entry().json_name
+    }
+
+    /// Returns the field number.
+    pub fn number(&self) -> u32 {
+        self.entry().proto.number() as u32
+    }
+
+    /// Returns the cardinality of the field.
+    pub fn label(&self) -> FieldLabel {
+        self.entry().label
+    }
+
+    /// Returns the type of the field. Message and enum fields declared without a type are given
+    /// the kind of the type they refer to, and message fields with the delimited message
+    /// encoding are groups.
+    pub fn field_type(&self) -> FieldType {
+        self.entry().field_type
+    }
+
+    /// Returns the features of the field, resolved from those of its message or extension scope
+    /// and its oneof group.
+    pub fn features(&self) -> ResolvedFeatures {
+        self.entry().features
+    }
+
+    /// Returns true when the field is a proto2 `required` field, or has the legacy required field
+    /// presence, which must be set for its message to be decoded.
+    pub fn is_required(&self) -> bool {
+        self.label() == FieldLabel::Required
+            || self.features().field_presence == FieldPresence::LegacyRequired
+    }
+
+    /// Returns true when the field is repeated, including map fields.
+    pub fn is_repeated(&self) -> bool {
+        self.label() == FieldLabel::Repeated
+    }
+
+    /// Returns true when the field is a map field.
+    pub fn is_map(&self) -> bool {
+        self.is_repeated() && self.message_type().is_some_and(|m| m.is_map_entry())
+    }
+
+    /// Returns true when the field is written with the packed encoding. Repeated scalar fields
+    /// are packed by the repeated field encoding feature, which packs them in proto3 and
+    /// editions but not in proto2, unless the `packed` option says otherwise.
+    pub fn is_packed(&self) -> bool {
+        if !self.is_repeated() || !is_packable(self.field_type()) {
+            return false;
+        }
+
+        match self.entry().proto.options.as_ref().and_then(|o| o.packed) {
+            Some(packed) => packed,
+            None => self.features().repeated_field_encoding == RepeatedFieldEncoding::Packed,
+        }
+    }
+
+    /// Returns true when the field tracks whether it has been set, rather than treating the
+    /// default value as unset. Only singular scalar fields with implicit field presence, as in
+    /// proto3, do not.
+    pub fn has_presence(&self) -> bool {
+        if self.is_repeated() {
+            return false;
+        }
+
+        matches!(self.field_type(), FieldType::Message | FieldType::Group)
+            || self.is_extension
+            || self.entry().oneof.is_some()
+            || self.features().field_presence != FieldPresence::Implicit
+    }
+
+    /// Returns true when the values of a string field must be UTF-8. Without the check, as in
+    /// proto2, a value that is not UTF-8 is kept as an unknown field when it is decoded.
+    pub fn validates_utf8(&self) -> bool {
+        self.field_type() == FieldType::String
+            && self.features().utf8_validation == Utf8Validation::Verify
+    }
+
+    /// Returns the message type of a message or group field.
+    pub fn message_type(&self) -> Option<MessageDescriptor> {
+        match self.entry().type_reference? {
+            Symbol::Message(index) => Some(MessageDescriptor {
+                pool: self.message.pool.clone(),
+                index,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the enum type of an enum field.
+    pub fn enum_type(&self) -> Option<EnumDescriptor> {
+        match self.entry().type_reference? {
+            Symbol::Enum(index) => Some(EnumDescriptor {
+                pool: self.message.pool.clone(),
+                index,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Returns the oneof group the field belongs to.
+    pub fn containing_oneof(&self) -> Option<OneofDescriptor> {
+        self.entry()
+            .oneof
+            .filter(|&index| index < self.message.entry().oneofs.len())
+            .map(|index| OneofDescriptor {
+                message: self.message.clone(),
+                index,
+            })
+    }
+
+    /// Returns the default value of the field as written in the `.proto` file.
+    pub fn default_value(&self) -> Option<&str> {
+        self.entry().proto.default_value.as_deref()
+    }
+
+    /// Returns the value of a custom option set on the field, given the extension of
+    /// `google.protobuf.FieldOptions` that defines it.
+    pub fn get_option(&self, extension: &FieldDescriptor) -> Option<FieldValue> {
+        option_value(
+            self.entry().proto.options.as_ref(),
+            extension,
+            "FieldOptions",
+        )
+    }
+
+    /// Returns the descriptor the field was created from.
+    pub fn field_descriptor_proto(&self) -> &FieldDescriptorProto {
+        &self.entry().proto
+    }
+}
+
+// Reads a custom option from the extension fields of an options message, returning `None` when
+// the option is not set or the extension does not extend the options message.
+fn option_value<O: Message>(
+    options: Option<&O>,
+    extension: &FieldDescriptor,
+    options_name: &str,
+) -> Option<FieldValue> {
+    let extendee = extension.containing_message();
+    if !extension.is_extension()
+        || extendee.full_name().strip_prefix("google.protobuf.") != Some(options_name)
+    {
+        return None;
+    }
+
+    let bytes = options.map(Message::encode_to_vec).unwrap_or_default();
+    let message = DynamicMessage::decode(extendee.clone(), &bytes).ok()?;
+    message
+        .has_field(extension)
+        .then(|| message.get_field(extension).into_owned())
+}
+
+fn is_packable(field_type: FieldType) -> bool {
+    !matches!(
+        field_type,
+        FieldType::String | FieldType::Bytes | FieldType::Message | FieldType::Group
+    )
+}
+
+/// Describes a oneof group of a message held by a `DescriptorPool`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OneofDescriptor {
+    message: MessageDescriptor,
+    index: usize,
+}
+
+impl OneofDescriptor {
+    fn entry(&self) -> &OneofEntry {
+        &self.message.entry().oneofs[self.index]
+    }
+
+    /// Returns the message the group belongs to.
+    pub fn containing_message(&self) -> &MessageDescriptor {
+        &self.message
+    }
+
+    /// Returns the name of the group.
+    pub fn name(&self) -> &str {
+        self.message.entry().proto.oneof_decl[self.index].name()
+    }
+
+    /// Returns the fully qualified name of the group.
+    pub fn full_name(&self) -> &str {
+        &self.entry().full_name
+    }
+
+    /// Returns the fields of the group.
+    pub fn fields(&self) -> impl ExactSizeIterator<Item = FieldDescriptor> + '_ {
+        self.entry().fields.iter().map(|&index| FieldDescriptor {
+            message: self.message.clone(),
+            index,
+            is_extension: false,
+        })
+    }
+
+    /// Returns the features of the group, resolved from those of its message.
+    pub fn features(&self) -> ResolvedFeatures {
+        self.entry().features
+    }
+
+    /// Returns the value of a custom option set on the group, given the extension of
+    /// `google.protobuf.OneofOptions` that defines it.
+    pub fn get_option(&self, extension: &FieldDescriptor) -> Option<FieldValue> {
+        let proto = &self.message.entry().proto.oneof_decl[self.index];
+        option_value(proto.options.as_ref(), extension, "OneofOptions")
+    }
+
+    /// Returns true when the group was created by the compiler for a proto3 `optional` field.
+    pub fn is_synthetic(&self) -> bool {
+        let mut fields = self.fields();
+        fields.len() == 1
+            && fields
+                .next()
+                .is_some_and(|f| f.field_descriptor_proto().proto3_optional())
+    }
+}
+
+/// Describes an enum held by a `DescriptorPool`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EnumDescriptor {
+    pool: DescriptorPool,
+    index: usize,
+}
+
+impl EnumDescriptor {
+    fn entry(&self) -> &EnumEntry {
+        &self.pool.inner.enums[self.index]
+    }
+
+    /// Returns the pool holding the enum.
+    pub fn parent_pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Returns the file the enum is defined in.
+    pub fn parent_file(&self) -> FileDescriptor {
+        FileDescriptor {
+            pool: self.pool.clone(),
+            index: self.entry().file,
+        }
+    }
+
+    /// Returns the message the enum is nested in, or `None` for a top-level enum.
+    pub fn parent_message(&self) -> Option<MessageDescriptor> {
+        self.entry().parent.map(|index| MessageDescriptor {
+            pool: self.pool.clone(),
+            index,
+        })
+    }
+
+    /// Returns the name of the enum.
+    pub fn name(&self) -> &str {
+        self.entry().proto.name()
+    }
+
+    /// Returns the fully qualified name of the enum.
+    pub fn full_name(&self) -> &str {
+        &self.entry().full_name
+    }
+
+    /// Returns the values of the enum in the order they were declared.
+    pub fn values(&self) -> impl ExactSizeIterator<Item = EnumValueDescriptor> + '_ {
+        (0..self.entry().proto.value.len()).map(|index| EnumValueDescriptor {
+            parent: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns the first value with the given number.
+    pub fn get_value(&self, number: i32) -> Option<EnumValueDescriptor> {
+        self.values().find(|v| v.number() == number)
+    }
+
+    /// Returns the value with the given name.
+    pub fn get_value_by_name(&self, name: &str) -> Option<EnumValueDescriptor> {
+        self.values().find(|v| v.name() == name)
+    }
+
+    /// Returns the features of the enum, resolved from those of its file and the messages it is
+    /// nested in.
+    pub fn features(&self) -> ResolvedFeatures {
+        self.entry().features
+    }
+
+    /// Returns true when the enum is closed, as proto2 enums are. A closed enum field only holds
+    /// the values the enum declares, and other values read from the wire are kept as unknown
+    /// fields. Open enums, as in proto3, hold any number.
+    pub fn is_closed(&self) -> bool {
+        self.features().enum_type == EnumType::Closed
+    }
+
+    /// Returns the default value of the enum, which is its first value.
+    pub fn default_value(&self) -> Option<EnumValueDescriptor> {
+        self.values().next()
+    }
+
+    /// Returns the value of a custom option set on the enum, given the extension of
+    /// `google.protobuf.EnumOptions` that defines it.
+    pub fn get_option(&self, extension: &FieldDescriptor) -> Option<FieldValue> {
+        option_value(
+            self.entry().proto.options.as_ref(),
+            extension,
+            "EnumOptions",
+        )
+    }
+
+    /// Returns the descriptor the enum was created from.
+    pub fn enum_descriptor_proto(&self) -> &EnumDescriptorProto {
+        &self.entry().proto
+    }
+}
+
+/// Describes a value of an enum held by a `DescriptorPool`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EnumValueDescriptor {
+    parent: EnumDescriptor,
+    index: usize,
+}
+
+impl EnumValueDescriptor {
+    /// Returns the enum the value belongs to.
+    pub fn parent_enum(&self) -> &EnumDescriptor {
+        &self.parent
+    }
+
+    /// Returns the name of the value.
+    pub fn name(&self) -> &str {
+        self.parent.entry().proto.value[self.index].name()
+    }
+
+    /// Returns the fully qualified name of the value. Values are scoped alongside their enum, so
+    /// the value `BAR` of the enum `foo.Foo` is named `foo.BAR`.
+    pub fn full_name(&self) -> &str {
+        &self.parent.entry().value_full_names[self.index]
+    }
+
+    /// Returns the number of the value.
+    pub fn number(&self) -> i32 {
+        self.parent.entry().proto.value[self.index].number()
+    }
+
+    /// Returns the value of a custom option set on the value, given the extension of
+    /// `google.protobuf.EnumValueOptions` that defines it.
+    pub fn get_option(&self, extension: &FieldDescriptor) -> Option<FieldValue> {
+        let proto = &self.parent.entry().proto.value[self.index];
+        option_value(proto.options.as_ref(), extension, "EnumValueOptions")
+    }
+}
+
+/// Describes a service held by a `DescriptorPool`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ServiceDescriptor {
+    pool: DescriptorPool,
+    index: usize,
+}
+
+impl ServiceDescriptor {
+    fn entry(&self) -> &ServiceEntry {
+        &self.pool.inner.services[self.index]
+    }
+
+    /// Returns the file the service is defined in.
+    pub fn parent_file(&self) -> FileDescriptor {
+        FileDescriptor {
+            pool: self.pool.clone(),
+            index: self.entry().file,
+        }
+    }
+
+    /// Returns the name of the service.
+    pub fn name(&self) -> &str {
+        self.entry().proto.name()
+    }
+
+    /// Returns the fully qualified name of the service.
+    pub fn full_name(&self) -> &str {
+        &self.entry().full_name
+    }
+
+    /// Returns the methods of the service.
+    pub fn methods(&self) -> impl ExactSizeIterator<Item = MethodDescriptor> + '_ {
+        (0..self.entry().methods.len()).map(|index| MethodDescriptor {
+            service: self.clone(),
+            index,
+        })
+    }
+
+    /// Returns the value of a custom option set on the service, given the extension of
+    /// `google.protobuf.ServiceOptions` that defines it.
+    pub fn get_option(&self, extension: &FieldDescriptor) -> Option<FieldValue> {
+        option_value(
+            self.entry().proto.options.as_ref(),
+            extension,
+            "ServiceOptions",
+        )
+    }
+}
+
+/// Describes a method of a service held by a `DescriptorPool`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MethodDescriptor {
+    service: ServiceDescriptor,
+    index: usize,
+}
+
+impl MethodDescriptor {
+    fn entry(&self) -> &MethodEntry {
+        &self.service.entry().methods[self.index]
+    }
+
+    /// Returns the service the method belongs to.
+    pub fn parent_service(&self) -> &ServiceDescriptor {
+        &self.service
+    }
+
+    /// Returns the name of the method.
+    pub fn name(&self) -> &str {
+        self.service.entry().proto.method[self.index].name()
+    }
+
+    /// Returns the fully qualified name of the method.
+    pub fn full_name(&self) -> &str {
+        &self.entry().full_name
+    }
+
+    /// Returns the request message type.
+    pub fn input(&self) -> MessageDescriptor {
+        MessageDescriptor {
+            pool: self.service.pool.clone(),
+            index: self.entry().input,
+        }
+    }
+
+    /// Returns the response message type.
+    pub fn output(&self) -> MessageDescriptor {
+        MessageDescriptor {
+            pool: self.service.pool.clone(),
+            index: self.entry().output,
+        }
+    }
+
+    /// Returns true when the client streams multiple requests.
+    pub fn is_client_streaming(&self) -> bool {
+        self.service.entry().proto.method[self.index].client_streaming()
+    }
+
+    /// Returns true when the server streams multiple responses.
+    pub fn is_server_streaming(&self) -> bool {
+        self.service.entry().proto.method[self.index].server_streaming()
+    }
+
+    /// Returns the value of a custom option set on the method, given the extension of
+    /// `google.protobuf.MethodOptions` that defines it.
+    pub fn get_option(&self, extension: &FieldDescriptor) -> Option<FieldValue> {
+        let proto = &self.service.entry().proto.method[self.index];
+        option_value(proto.options.as_ref(), extension, "MethodOptions")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        EncodeError, EnumValueDescriptorProto, FieldOptions, FileOptions, MessageOptions,
+        OneofDescriptorProto,
+    };
+    use rstest::*;
+
+    fn field(
+        name: &str,
+        number: i32,
+        field_type: Option<FieldType>,
+        type_name: &str,
+    ) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(FieldLabel::Optional.into()),
+            r#type: field_type.map(Into::into),
+            type_name: (!type_name.is_empty()).then(|| type_name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn message(name: &str, fields: Vec<FieldDescriptorProto>) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.to_string()),
+            field: fields,
+            ..Default::default()
+        }
+    }
+
+    fn file(
+        name: &str,
+        package: &str,
+        dependency: &[&str],
+        messages: Vec<DescriptorProto>,
+    ) -> FileDescriptorProto {
+        FileDescriptorProto {
+            name: Some(name.to_string()),
+            package: Some(package.to_string()),
+            dependency: dependency.iter().map(|d| d.to_string()).collect(),
+            message_type: messages,
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn editions(mut proto: FileDescriptorProto, edition: Edition) -> FileDescriptorProto {
+        proto.syntax = Some("editions".to_string());
+        proto.edition = Some(edition.into());
+        proto
+    }
+
+    #[rstest]
+    #[case("Inner", "a.b.Outer.Inner")]
+    #[case("Outer.Inner", "a.b.Outer.Inner")]
+    #[case("b.Outer", "a.b.Outer")]
+    #[case(".a.b.Outer.Inner", "a.b.Outer.Inner")]
+    #[case("Top", "Top")]
+    fn test_relative_type_names_are_resolved(#[case] type_name: &str, #[case] expected_name: &str) {
+        // Arrange
+        let mut outer = message("Outer", vec![field("value", 1, None, type_name)]);
+        outer.nested_type.push(message("Inner", vec![]));
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(file("top.proto", "", &[], vec![message("Top", vec![])]))
+            .unwrap();
+
+        // Act
+        pool.add_file_descriptor_proto(file("a.proto", "a.b", &["top.proto"], vec![outer]))
+            .unwrap();
+
+        // Assert
+        let value = pool
+            .get_message_by_name("a.b.Outer")
+            .unwrap()
+            .get_field(1)
+            .unwrap();
+        assert_eq!(value.field_type(), FieldType::Message);
+        assert_eq!(value.message_type().unwrap().full_name(), expected_name);
+    }
+
+    #[test]
+    fn test_file_descriptor_set_is_added_in_dependency_order() {
+        // Arrange
+        let set = FileDescriptorSet {
+            file: vec![
+                file(
+                    "main.proto",
+                    "main",
+                    &["dep.proto"],
+                    vec![message("Main", vec![field("dep", 1, None, "dep.Dep")])],
+                ),
+                file("dep.proto", "dep", &[], vec![message("Dep", vec![])]),
+            ],
+        };
+
+        // Act
+        let pool = DescriptorPool::decode(&set.encode_to_vec()).unwrap();
+
+        // Assert
+        let names: Vec<String> = pool.files().map(|f| f.name().to_string()).collect();
+        assert_eq!(names, vec!["dep.proto", "main.proto"]);
+        let dep = pool
+            .get_message_by_name(".main.Main")
+            .unwrap()
+            .get_field_by_name("dep")
+            .unwrap();
+        assert_eq!(dep.message_type(), pool.get_message_by_name("dep.Dep"));
+    }
+
+    #[test]
+    fn test_public_imports_are_visible() {
+        // Arrange
+        let mut public = file("public.proto", "p", &["c.proto"], vec![]);
+        public.public_dependency.push(0);
+        let set = FileDescriptorSet {
+            file: vec![
+                file("c.proto", "p", &[], vec![message("C", vec![])]),
+                public,
+                file(
+                    "a.proto",
+                    "p",
+                    &["public.proto"],
+                    vec![message("A", vec![field("c", 1, None, "C")])],
+                ),
+            ],
+        };
+
+        // Act
+        let result = DescriptorPool::from_file_descriptor_set(set);
+
+        // Assert
+        assert!(result.is_ok());
+    }
+
+    #[rstest]
+    #[case(file("a.proto", "", &["missing.proto"], vec![]), DescriptorError::MissingDependency { file: "a.proto".to_string(), dependency: "missing.proto".to_string() })]
+    #[case(file("a.proto", "", &[], vec![message("A", vec![field("b", 1, None, "B")])]), DescriptorError::UnresolvedTypeName { name: "A.b".to_string(), type_name: "B".to_string() })]
+    #[case(file("a.proto", "", &["private.proto"], vec![message("A", vec![field("c", 1, None, "C")])]), DescriptorError::UnresolvedTypeName { name: "A.c".to_string(), type_name: "C".to_string() })]
+    #[case(file("a.proto", "", &[], vec![message("A", vec![field("a", 1, None, "A.a")])]), DescriptorError::UnresolvedTypeName { name: "A.a".to_string(), type_name: "A.a".to_string() })]
+    #[case(file("a.proto", "", &[], vec![message("A", vec![field("a", 1, None, "")])]), DescriptorError::MissingFieldType("A.a".to_string()))]
+    #[case(file("a.proto", "", &[], vec![message("Private", vec![])]), DescriptorError::DuplicateName("Private".to_string()))]
+    #[case(file("a.proto", "", &[], vec![message("A", vec![field("x", 1, Some(FieldType::Bool), ""), field("y", 1, Some(FieldType::Bool), "")])]), DescriptorError::DuplicateFieldNumber { message: "A".to_string(), number: 1 })]
+    #[case(file("a.proto", "", &[], vec![message("A", vec![field("x", 0, Some(FieldType::Bool), "")])]), DescriptorError::InvalidFieldNumber { field: "A.x".to_string(), error: EncodeError::FieldNumberOutOfRange(0) })]
+    #[case(file("a.proto", "", &[], vec![message("A", vec![field("x", 19500, Some(FieldType::Bool), "")])]), DescriptorError::InvalidFieldNumber { field: "A.x".to_string(), error: EncodeError::ReservedFieldNumber(19500) })]
+    #[case(file("a.proto", "", &[], vec![message("A", vec![field("x", 1, Some(FieldType::Bool), ""), field("x", 2, Some(FieldType::Bool), "")])]), DescriptorError::DuplicateName("A.x".to_string()))]
+    #[case(editions(file("a.proto", "", &[], vec![]), Edition::Edition2024), DescriptorError::UnsupportedEdition { file: "a.proto".to_string(), edition: 1001 })]
+    fn test_invalid_files_are_rejected(
+        #[case] invalid: FileDescriptorProto,
+        #[case] expected_error: DescriptorError,
+    ) {
+        // Arrange
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(file("c.proto", "", &[], vec![message("C", vec![])]))
+            .unwrap();
+        pool.add_file_descriptor_proto(file(
+            "private.proto",
+            "",
+            &["c.proto"],
+            vec![message("Private", vec![])],
+        ))
+        .unwrap();
+
+        // Act
+        let result = pool.add_file_descriptor_proto(invalid);
+
+        // Assert
+        assert_eq!(result, Err(expected_error));
+        assert!(pool.get_message_by_name("A").is_none());
+    }
+
+    #[test]
+    fn test_duplicate_files() {
+        // Arrange
+        let mut pool = DescriptorPool::new();
+        let original = file("a.proto", "", &[], vec![message("A", vec![])]);
+        pool.add_file_descriptor_proto(original.clone()).unwrap();
+
+        // Act
+        let same = pool.add_file_descriptor_proto(original);
+        let different = pool.add_file_descriptor_proto(file("a.proto", "", &[], vec![]));
+
+        // Assert
+        assert_eq!(same, Ok(()));
+        assert_eq!(
+            different,
+            Err(DescriptorError::DuplicateFile("a.proto".to_string()))
+        );
+        assert_eq!(pool.files().len(), 1);
+    }
+
+    #[test]
+    fn test_field_descriptors() {
+        // Arrange
+        let mut entry = message(
+            "TagsEntry",
+            vec![
+                field("key", 1, Some(FieldType::String), ""),
+                field("value", 2, Some(FieldType::Int32), ""),
+            ],
+        );
+        entry.options = Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        });
+        let mut numbers = field("numbers", 2, Some(FieldType::Int32), "");
+        numbers.label = Some(FieldLabel::Repeated.into());
+        let mut tags = field("tags", 3, None, "TagsEntry");
+        tags.label = Some(FieldLabel::Repeated.into());
+        let mut nickname = field("nick_name", 4, Some(FieldType::String), "");
+        nickname.json_name = Some("nick".to_string());
+        nickname.oneof_index = Some(0);
+        nickname.proto3_optional = Some(true);
+        let mut person = message(
+            "Person",
+            vec![
+                field("user_name", 1, Some(FieldType::String), ""),
+                numbers,
+                tags,
+                nickname,
+                field("status", 5, None, "Status"),
+            ],
+        );
+        person.nested_type.push(entry);
+        person.oneof_decl.push(OneofDescriptorProto {
+            name: Some("_nick_name".to_string()),
+            ..Default::default()
+        });
+        let mut proto = file("person.proto", "people", &[], vec![person]);
+        proto.enum_type.push(EnumDescriptorProto {
+            name: Some("Status".to_string()),
+            value: vec![EnumValueDescriptorProto {
+                name: Some("STATUS_UNKNOWN".to_string()),
+                number: Some(0),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let mut pool = DescriptorPool::new();
+
+        // Act
+        pool.add_file_descriptor_proto(proto).unwrap();
+
+        // Assert
+        let person = pool.get_message_by_name("people.Person").unwrap();
+        let user_name = person.get_field_by_json_name("userName").unwrap();
+        assert_eq!(
+            (
+                user_name.number(),
+                user_name.label(),
+                user_name.has_presence()
+            ),
+            (1, FieldLabel::Optional, false)
+        );
+        let numbers = person.get_field(2).unwrap();
+        assert!(numbers.is_repeated() && numbers.is_packed() && !numbers.is_map());
+        let tags = person.get_field_by_name("tags").unwrap();
+        assert!(tags.is_map() && !tags.is_packed());
+        let nickname = person.get_field_by_json_name("nick").unwrap();
+        assert!(nickname.has_presence());
+        assert!(nickname.containing_oneof().unwrap().is_synthetic());
+        let status = person.get_field(5).unwrap();
+        assert_eq!(status.field_type(), FieldType::Enum);
+        assert_eq!(
+            status
+                .enum_type()
+                .unwrap()
+                .default_value()
+                .unwrap()
+                .full_name(),
+            "people.STATUS_UNKNOWN"
+        );
+        assert_eq!(
+            person.child_messages().next().unwrap().parent_message(),
+            Some(person.clone())
+        );
+    }
+
+    #[test]
+    fn test_features_are_resolved() {
+        // Arrange
+        let features = |features: FeatureSet| Some(features);
+        let mut child = field("child", 1, None, "Node");
+        child.options = Some(FieldOptions {
+            features: features(FeatureSet {
+                message_encoding: Some(MessageEncoding::Delimited.into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let mut values = field("values", 2, Some(FieldType::Int32), "");
+        values.label = Some(FieldLabel::Repeated.into());
+        let mut count = field("count", 3, Some(FieldType::Int32), "");
+        count.options = Some(FieldOptions {
+            features: features(FeatureSet {
+                field_presence: Some(FieldPresence::Implicit.into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let mut node = message("Node", vec![child, values, count]);
+        node.options = Some(MessageOptions {
+            features: features(FeatureSet {
+                repeated_field_encoding: Some(RepeatedFieldEncoding::Expanded.into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        let mut proto = editions(
+            file("tree.proto", "tree", &[], vec![node]),
+            Edition::Edition2023,
+        );
+        proto.options = Some(FileOptions {
+            features: features(FeatureSet {
+                enum_type: Some(EnumType::Closed.into()),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        proto.enum_type.push(EnumDescriptorProto {
+            name: Some("Kind".to_string()),
+            value: vec![EnumValueDescriptorProto {
+                name: Some("KIND_LEAF".to_string()),
+                number: Some(1),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let mut pool = DescriptorPool::new();
+
+        // Act
+        pool.add_file_descriptor_proto(proto).unwrap();
+
+        // Assert
+        let file = pool.files().next().unwrap();
+        assert_eq!(
+            (file.syntax(), file.edition()),
+            (Syntax::Editions, Edition::Edition2023)
+        );
+        let node = pool.get_message_by_name("tree.Node").unwrap();
+        assert_eq!(
+            node.features(),
+            ResolvedFeatures {
+                field_presence: FieldPresence::Explicit,
+                enum_type: EnumType::Closed,
+                repeated_field_encoding: RepeatedFieldEncoding::Expanded,
+                utf8_validation: Utf8Validation::Verify,
+                message_encoding: MessageEncoding::LengthPrefixed,
+            }
+        );
+        let child = node.get_field_by_name("child").unwrap();
+        assert_eq!(child.field_type(), FieldType::Group);
+        assert!(child.has_presence());
+        assert!(!node.get_field_by_name("values").unwrap().is_packed());
+        assert!(!node.get_field_by_name("count").unwrap().has_presence());
+        assert!(pool.get_enum_by_name("tree.Kind").unwrap().is_closed());
+    }
+
+    #[test]
+    fn test_services() {
+        // Arrange
+        let mut proto = file(
+            "a.proto",
+            "a",
+            &[],
+            vec![message("Request", vec![]), message("Response", vec![])],
+        );
+        proto.service.push(ServiceDescriptorProto {
+            name: Some("Api".to_string()),
+            method: vec![crate::MethodDescriptorProto {
+                name: Some("Call".to_string()),
+                input_type: Some(".a.Request".to_string()),
+                output_type: Some("Response".to_string()),
+                server_streaming: Some(true),
+                ..Default::default()
+            }],
+            ..Default::default()
+        });
+        let mut pool = DescriptorPool::new();
+
+        // Act
+        pool.add_file_descriptor_proto(proto).unwrap();
+
+        // Assert
+        let method = pool
+            .get_service_by_name("a.Api")
+            .unwrap()
+            .methods()
+            .next()
+            .unwrap();
+        assert_eq!(method.full_name(), "a.Api.Call");
+        assert_eq!(method.input().full_name(), "a.Request");
+        assert_eq!(method.output().full_name(), "a.Response");
+        assert!(method.is_server_streaming() && !method.is_client_streaming());
+    }
+
+    #[rstest]
+    #[case("user_name", "userName")]
+    #[case("name", "name")]
+    #[case("a_b_c", "aBC")]
+    #[case("field__1", "field1")]
+    fn test_json_names(#[case] name: &str, #[case] expected_json_name: &str) {
+        // Act
+        let json_name = to_json_name(name);
+
+        // Assert
+        assert_eq!(json_name, expected_json_name);
+    }
+
+    #[test]
+    fn test_extensions_are_registered() {
+        // Arrange
+        let mut target = message("Target", vec![]);
+        target.extension_range.push(crate::ExtensionRange {
+            start: Some(100),
+            end: Some(200),
+            ..Default::default()
+        });
+        let mut scope = message("Scope", vec![]);
+        scope
+            .extension
+            .push(field("nested", 101, Some(FieldType::Int32), ""));
+        scope.extension[0].extendee = Some("Target".to_string());
+        let mut proto = file("a.proto", "a", &[], vec![target, scope]);
+        proto.syntax = Some("proto2".to_string());
+        proto
+            .extension
+            .push(field("top", 100, Some(FieldType::String), ""));
+        proto.extension[0].extendee = Some(".a.Target".to_string());
+        let mut pool = DescriptorPool::new();
+
+        // Act
+        pool.add_file_descriptor_proto(proto).unwrap();
+
+        // Assert
+        let target = pool.get_message_by_name("a.Target").unwrap();
+        let names: Vec<_> = target
+            .extensions()
+            .map(|e| e.full_name().to_string())
+            .collect();
+        assert_eq!(names, vec!["a.Scope.nested", "a.top"]);
+        let nested = pool.get_extension_by_name("a.Scope.nested").unwrap();
+        assert!(nested.is_extension());
+        assert!(nested.has_presence());
+        assert_eq!(nested.containing_message(), &target);
+        assert_eq!(nested.extension_scope().unwrap().full_name(), "a.Scope");
+        assert_eq!(target.get_extension(100).unwrap().full_name(), "a.top");
+        assert_eq!(target.get_field(100), None);
+        assert_eq!(pool.all_extensions().count(), 2);
+    }
+}
@@ -0,0 +1,1747 @@
+use crate::length_delimited_encoding::decode_length;
+use crate::message::{decode_nested, DecodedFields};
+use crate::proto_parser::unescape_bytes;
+use crate::unknown_fields::sort_fields;
+use crate::{
+    skip_tagged_field, Buffer, DecodeError, DecodeMode, EncodeMode, FieldDescriptor, FieldError,
+    FieldType, Fixed32, Fixed64, LengthDelimited, Message, MessageDescriptor, SignExtendedVarint,
+    Tag, Varint, WireType,
+};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// Holds the value of a field of a `DynamicMessage`.
+///
+/// Each field type has a single variant: the `sint`, `sfixed` and `int` types share the signed
+/// variants, the `uint` and `fixed` types share the unsigned variants and enum fields hold the
+/// number of the value, which may not be known to the enum.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldValue {
+    /// The value of a `bool` field.
+    Bool(bool),
+
+    /// The value of an `int32`, `sint32` or `sfixed32` field.
+    I32(i32),
+
+    /// The value of an `int64`, `sint64` or `sfixed64` field.
+    I64(i64),
+
+    /// The value of a `uint32` or `fixed32` field.
+    U32(u32),
+
+    /// The value of a `uint64` or `fixed64` field.
+    U64(u64),
+
+    /// The value of a `float` field.
+    F32(f32),
+
+    /// The value of a `double` field.
+    F64(f64),
+
+    /// The value of a `string` field.
+    String(String),
+
+    /// The value of a `bytes` field.
+    Bytes(Vec<u8>),
+
+    /// The number of the value of an enum field.
+    EnumNumber(i32),
+
+    /// The value of a message or group field.
+    Message(DynamicMessage),
+
+    /// The values of a repeated field.
+    List(Vec<FieldValue>),
+
+    /// The entries of a map field.
+    Map(BTreeMap<FieldMapKey, FieldValue>),
+}
+
+/// Holds the key of an entry of a map field held by a `DynamicMessage`.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, PartialOrd, Ord)]
+pub enum FieldMapKey {
+    /// A `bool` key.
+    Bool(bool),
+
+    /// An `int32`, `sint32` or `sfixed32` key.
+    I32(i32),
+
+    /// An `int64`, `sint64` or `sfixed64` key.
+    I64(i64),
+
+    /// A `uint32` or `fixed32` key.
+    U32(u32),
+
+    /// A `uint64` or `fixed64` key.
+    U64(u64),
+
+    /// A `string` key.
+    String(String),
+}
+
+impl FieldValue {
+    /// Returns the default value of the field. Repeated fields default to an empty list or map,
+    /// and proto2 fields use the default declared in the `.proto` file.
+    pub fn default_value(field: &FieldDescriptor) -> FieldValue {
+        if field.is_map() {
+            return FieldValue::Map(BTreeMap::new());
+        }
+        if field.is_repeated() {
+            return FieldValue::List(Vec::new());
+        }
+
+        let default = field.default_value();
+        match field.field_type() {
+            FieldType::Double => FieldValue::F64(parse_default(default)),
+            FieldType::Float => FieldValue::F32(parse_default(default)),
+            FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => {
+                FieldValue::I64(parse_default(default))
+            }
+            FieldType::Uint64 | FieldType::Fixed64 => FieldValue::U64(parse_default(default)),
+            FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => {
+                FieldValue::I32(parse_default(default))
+            }
+            FieldType::Uint32 | FieldType::Fixed32 => FieldValue::U32(parse_default(default)),
+            FieldType::Bool => FieldValue::Bool(parse_default(default)),
+            FieldType::String => FieldValue::String(default.unwrap_or_default().to_string()),
+            FieldType::Bytes => {
+                FieldValue::Bytes(default.and_then(unescape_bytes).unwrap_or_default())
+            }
+            FieldType::Enum => {
+                let enumeration = field.enum_type();
+                let value = match default {
+                    Some(name) => enumeration.and_then(|e| e.get_value_by_name(name)),
+                    None => enumeration.and_then(|e| e.default_value()),
+                };
+                FieldValue::EnumNumber(value.map_or(0, |v| v.number()))
+            }
+            FieldType::Message | FieldType::Group => {
+                FieldValue::Message(DynamicMessage::new(message_type(field)))
+            }
+        }
+    }
+
+    /// Returns true when the value can be held by the field.
+    pub fn is_valid_for_field(&self, field: &FieldDescriptor) -> bool {
+        match self {
+            FieldValue::Map(entries) if field.is_map() => {
+                let entry = message_type(field);
+                let (key, value) = match (entry.get_field(1), entry.get_field(2)) {
+                    (Some(key), Some(value)) => (key, value),
+                    _ => return false,
+                };
+
+                entries.iter().all(|(k, v)| {
+                    FieldValue::from(k.clone()).is_valid_for_type(&key)
+                        && v.is_valid_for_type(&value)
+                })
+            }
+            FieldValue::List(values) if field.is_repeated() && !field.is_map() => {
+                values.iter().all(|v| v.is_valid_for_type(field))
+            }
+            _ => !field.is_repeated() && self.is_valid_for_type(field),
+        }
+    }
+
+    // Checks a single value, ignoring the cardinality of the field.
+    fn is_valid_for_type(&self, field: &FieldDescriptor) -> bool {
+        match (field.field_type(), self) {
+            (FieldType::Double, FieldValue::F64(_))
+            | (FieldType::Float, FieldValue::F32(_))
+            | (FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64, FieldValue::I64(_))
+            | (FieldType::Uint64 | FieldType::Fixed64, FieldValue::U64(_))
+            | (FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32, FieldValue::I32(_))
+            | (FieldType::Uint32 | FieldType::Fixed32, FieldValue::U32(_))
+            | (FieldType::Bool, FieldValue::Bool(_))
+            | (FieldType::String, FieldValue::String(_))
+            | (FieldType::Bytes, FieldValue::Bytes(_))
+            | (FieldType::Enum, FieldValue::EnumNumber(_)) => true,
+            (FieldType::Message | FieldType::Group, FieldValue::Message(message)) => {
+                field.message_type().as_ref() == Some(message.descriptor())
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the value of a `bool` field.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            FieldValue::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an `int32`, `sint32` or `sfixed32` field.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            FieldValue::I32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an `int64`, `sint64` or `sfixed64` field.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            FieldValue::I64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `uint32` or `fixed32` field.
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            FieldValue::U32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `uint64` or `fixed64` field.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            FieldValue::U64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `float` field.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            FieldValue::F32(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `double` field.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            FieldValue::F64(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `string` field.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            FieldValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `bytes` field.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            FieldValue::Bytes(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of the value of an enum field.
+    pub fn as_enum_number(&self) -> Option<i32> {
+        match self {
+            FieldValue::EnumNumber(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a message or group field.
+    pub fn as_message(&self) -> Option<&DynamicMessage> {
+        match self {
+            FieldValue::Message(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the values of a repeated field.
+    pub fn as_list(&self) -> Option<&[FieldValue]> {
+        match self {
+            FieldValue::List(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries of a map field.
+    pub fn as_map(&self) -> Option<&BTreeMap<FieldMapKey, FieldValue>> {
+        match self {
+            FieldValue::Map(entries) => Some(entries),
+            _ => None,
+        }
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+impl From<i32> for FieldValue {
+    fn from(value: i32) -> Self {
+        FieldValue::I32(value)
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(value: i64) -> Self {
+        FieldValue::I64(value)
+    }
+}
+
+impl From<u32> for FieldValue {
+    fn from(value: u32) -> Self {
+        FieldValue::U32(value)
+    }
+}
+
+impl From<u64> for FieldValue {
+    fn from(value: u64) -> Self {
+        FieldValue::U64(value)
+    }
+}
+
+impl From<f32> for FieldValue {
+    fn from(value: f32) -> Self {
+        FieldValue::F32(value)
+    }
+}
+
+impl From<f64> for FieldValue {
+    fn from(value: f64) -> Self {
+        FieldValue::F64(value)
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::String(value)
+    }
+}
+
+impl From<&str> for FieldValue {
+    fn from(value: &str) -> Self {
+        FieldValue::String(value.to_string())
+    }
+}
+
+impl From<Vec<u8>> for FieldValue {
+    fn from(value: Vec<u8>) -> Self {
+        FieldValue::Bytes(value)
+    }
+}
+
+impl From<DynamicMessage> for FieldValue {
+    fn from(value: DynamicMessage) -> Self {
+        FieldValue::Message(value)
+    }
+}
+
+impl From<FieldMapKey> for FieldValue {
+    fn from(value: FieldMapKey) -> Self {
+        match value {
+            FieldMapKey::Bool(v) => FieldValue::Bool(v),
+            FieldMapKey::I32(v) => FieldValue::I32(v),
+            FieldMapKey::I64(v) => FieldValue::I64(v),
+            FieldMapKey::U32(v) => FieldValue::U32(v),
+            FieldMapKey::U64(v) => FieldValue::U64(v),
+            FieldMapKey::String(v) => FieldValue::String(v),
+        }
+    }
+}
+
+impl TryFrom<FieldValue> for FieldMapKey {
+    type Error = FieldValue;
+
+    fn try_from(value: FieldValue) -> Result<Self, Self::Error> {
+        match value {
+            FieldValue::Bool(v) => Ok(FieldMapKey::Bool(v)),
+            FieldValue::I32(v) => Ok(FieldMapKey::I32(v)),
+            FieldValue::I64(v) => Ok(FieldMapKey::I64(v)),
+            FieldValue::U32(v) => Ok(FieldMapKey::U32(v)),
+            FieldValue::U64(v) => Ok(FieldMapKey::U64(v)),
+            FieldValue::String(v) => Ok(FieldMapKey::String(v)),
+            _ => Err(value),
+        }
+    }
+}
+
+/// Holds a message whose type is only known at runtime.
+///
+/// The fields are held as `FieldValue`s and are encoded and decoded using the same wire format as
+/// a compiled message, guided by the `MessageDescriptor`. Fields that are not in the descriptor
+/// are kept as they were read and written back out after the known fields.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{
+///     DescriptorPool, DescriptorProto, DynamicMessage, FieldDescriptorProto, FieldType,
+///     FileDescriptorProto,
+/// };
+///
+/// let mut pool = DescriptorPool::new();
+/// pool.add_file_descriptor_proto(FileDescriptorProto {
+///     name: Some("counter.proto".to_string()),
+///     syntax: Some("proto3".to_string()),
+///     message_type: vec![DescriptorProto {
+///         name: Some("Counter".to_string()),
+///         field: vec![FieldDescriptorProto {
+///             name: Some("count".to_string()),
+///             number: Some(1),
+///             r#type: Some(FieldType::Uint32.into()),
+///             ..Default::default()
+///         }],
+///         ..Default::default()
+///     }],
+///     ..Default::default()
+/// })
+/// .unwrap();
+///
+/// let descriptor = pool.get_message_by_name("Counter").unwrap();
+/// let mut counter = DynamicMessage::new(descriptor.clone());
+/// counter.set_field_by_name("count", 150u32.into()).unwrap();
+///
+/// let bytes = counter.encode_to_vec();
+/// let decoded = DynamicMessage::decode(descriptor, &bytes).unwrap();
+///
+/// assert_eq!(bytes, vec![8, 150, 1]);
+/// assert_eq!(decoded.get_field_by_name("count").unwrap().as_u32(), Some(150));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicMessage {
+    descriptor: MessageDescriptor,
+    fields: BTreeMap<u32, FieldValue>,
+    unknown_fields: Vec<u8>,
+}
+
+impl DynamicMessage {
+    /// Creates a message with every field unset.
+    pub fn new(descriptor: MessageDescriptor) -> Self {
+        Self {
+            descriptor,
+            fields: BTreeMap::new(),
+            unknown_fields: Vec::new(),
+        }
+    }
+
+    /// Decodes a message of the given type from the whole of the buffer. Decoding fails when a
+    /// required field of the message, or of a message within it, is not set.
+    pub fn decode(descriptor: MessageDescriptor, buffer: &[u8]) -> Result<Self, DecodeError> {
+        let mut message = Self::new(descriptor);
+        let _ = message.merge_fields(buffer, None)?;
+        message.check_required_fields()?;
+        Ok(message)
+    }
+
+    /// Decodes a message of the type described from the whole of the buffer, using the mode to
+    /// decide whether input which is not in its canonical form is accepted. The mode applies to
+    /// the current thread only, see [`DecodeMode`].
+    pub fn decode_with_mode(
+        descriptor: MessageDescriptor,
+        buffer: &[u8],
+        mode: DecodeMode,
+    ) -> Result<Self, DecodeError> {
+        mode.scope(|| Self::decode(descriptor, buffer))
+    }
+
+    /// Merges the fields in the buffer into the message, as though the buffer had followed the
+    /// message's own encoding. Merging fails when a required field is not set.
+    pub fn merge_from_bytes(&mut self, buffer: &[u8]) -> Result<(), DecodeError> {
+        let _ = self.merge_fields(buffer, None)?;
+        self.check_required_fields()
+    }
+
+    /// Merges the other message into this one. Singular fields which are set in the other message
+    /// replace those in this one, except for messages which are merged, lists are appended, map
+    /// entries replace those with the same key and unknown fields are appended.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the other message is not of the same type.
+    pub fn merge(&mut self, mut other: DynamicMessage) {
+        assert!(
+            self.descriptor == other.descriptor,
+            "merged messages must be of the same type"
+        );
+
+        let set: Vec<FieldDescriptor> = other.fields().map(|(field, _)| field).collect();
+        for field in set {
+            let value = other
+                .fields
+                .remove(&field.number())
+                .expect("set fields have a value");
+            match (self.get_field_mut(&field), value) {
+                (FieldValue::List(values), FieldValue::List(others)) => values.extend(others),
+                (FieldValue::Map(entries), FieldValue::Map(others)) => entries.extend(others),
+                (FieldValue::Message(message), FieldValue::Message(other)) => message.merge(other),
+                (current, value) => *current = value,
+            }
+        }
+        self.unknown_fields.extend(other.unknown_fields);
+    }
+
+    /// Returns an error naming the first required field that is not set, searching the message
+    /// and then the messages within it.
+    pub fn check_required_fields(&self) -> Result<(), DecodeError> {
+        if let Some(field) = self
+            .descriptor
+            .fields()
+            .find(|f| f.is_required() && !self.fields.contains_key(&f.number()))
+        {
+            return Err(DecodeError::MissingRequiredField(
+                field.full_name().to_string(),
+            ));
+        }
+
+        for value in self.fields.values() {
+            match value {
+                FieldValue::Message(message) => message.check_required_fields()?,
+                FieldValue::List(values) => values
+                    .iter()
+                    .filter_map(FieldValue::as_message)
+                    .try_for_each(DynamicMessage::check_required_fields)?,
+                FieldValue::Map(entries) => entries
+                    .values()
+                    .filter_map(FieldValue::as_message)
+                    .try_for_each(DynamicMessage::check_required_fields)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a message of the given type holding the fields of a compiled message, by encoding
+    /// it and decoding the result.
+    pub fn transcode_from<M: Message>(
+        descriptor: MessageDescriptor,
+        message: &M,
+    ) -> Result<Self, DecodeError> {
+        Self::decode(descriptor, &message.encode_to_vec())
+    }
+
+    /// Creates a compiled message holding the fields of the message, by encoding it and decoding
+    /// the result.
+    pub fn transcode_to<M: Message>(&self) -> Result<M, DecodeError> {
+        M::decode(&self.encode_to_vec())
+    }
+
+    /// Returns the type of the message.
+    pub fn descriptor(&self) -> &MessageDescriptor {
+        &self.descriptor
+    }
+
+    /// Returns the fields that are set, in field number order.
+    pub fn fields(&self) -> impl Iterator<Item = (FieldDescriptor, &FieldValue)> + '_ {
+        self.fields.iter().filter_map(|(&number, value)| {
+            let field = self
+                .descriptor
+                .get_field(number)
+                .or_else(|| self.descriptor.get_extension(number))?;
+            self.has_field(&field).then_some((field, value))
+        })
+    }
+
+    /// Returns the fields that were decoded but are not in the descriptor, as they were read.
+    pub fn unknown_fields(&self) -> &[u8] {
+        &self.unknown_fields
+    }
+
+    /// Returns true when the field is set. Fields without presence are only set when they do not
+    /// hold their default value, and repeated fields are only set when they are not empty.
+    pub fn has_field(&self, field: &FieldDescriptor) -> bool {
+        match self.fields.get(&field.number()) {
+            None => false,
+            Some(FieldValue::List(values)) => !values.is_empty(),
+            Some(FieldValue::Map(entries)) => !entries.is_empty(),
+            Some(value) => field.has_presence() || *value != FieldValue::default_value(field),
+        }
+    }
+
+    /// Returns the value of the field, or its default value when it is not set.
+    pub fn get_field(&self, field: &FieldDescriptor) -> Cow<'_, FieldValue> {
+        match self.fields.get(&field.number()) {
+            Some(value) => Cow::Borrowed(value),
+            None => Cow::Owned(FieldValue::default_value(field)),
+        }
+    }
+
+    /// Returns the value of the field with the given name, or `None` when there is no such
+    /// field.
+    pub fn get_field_by_name(&self, name: &str) -> Option<Cow<'_, FieldValue>> {
+        let field = self.descriptor.get_field_by_name(name)?;
+        Some(self.get_field(&field))
+    }
+
+    /// Returns the value of the field with the given number, or `None` when there is no such
+    /// field.
+    pub fn get_field_by_number(&self, number: u32) -> Option<Cow<'_, FieldValue>> {
+        let field = self.descriptor.get_field(number)?;
+        Some(self.get_field(&field))
+    }
+
+    /// Returns a mutable reference to the value of the field, setting it to its default value
+    /// when it is not set. Any other field of the same oneof group is cleared.
+    pub fn get_field_mut(&mut self, field: &FieldDescriptor) -> &mut FieldValue {
+        self.clear_oneof_siblings(field);
+        self.fields
+            .entry(field.number())
+            .or_insert_with(|| FieldValue::default_value(field))
+    }
+
+    /// Sets the value of the field, clearing any other field of the same oneof group.
+    pub fn set_field(
+        &mut self,
+        field: &FieldDescriptor,
+        value: FieldValue,
+    ) -> Result<(), FieldError> {
+        if field.containing_message() != &self.descriptor {
+            return Err(FieldError::NotFound(field.full_name().to_string()));
+        }
+        if !value.is_valid_for_field(field) {
+            return Err(FieldError::InvalidType(field.full_name().to_string()));
+        }
+
+        *self.get_field_mut(field) = value;
+        Ok(())
+    }
+
+    /// Sets the value of the field with the given name.
+    pub fn set_field_by_name(&mut self, name: &str, value: FieldValue) -> Result<(), FieldError> {
+        match self.descriptor.get_field_by_name(name) {
+            Some(field) => self.set_field(&field, value),
+            None => Err(FieldError::NotFound(name.to_string())),
+        }
+    }
+
+    /// Sets the value of the field with the given number.
+    pub fn set_field_by_number(
+        &mut self,
+        number: u32,
+        value: FieldValue,
+    ) -> Result<(), FieldError> {
+        match self.descriptor.get_field(number) {
+            Some(field) => self.set_field(&field, value),
+            None => Err(FieldError::NotFound(number.to_string())),
+        }
+    }
+
+    /// Clears the field, returning the value it held.
+    pub fn clear_field(&mut self, field: &FieldDescriptor) -> Option<FieldValue> {
+        self.fields.remove(&field.number())
+    }
+
+    /// Clears the field with the given name, returning the value it held.
+    pub fn clear_field_by_name(&mut self, name: &str) -> Option<FieldValue> {
+        let field = self.descriptor.get_field_by_name(name)?;
+        self.clear_field(&field)
+    }
+
+    /// Clears the field with the given number, returning the value it held.
+    pub fn clear_field_by_number(&mut self, number: u32) -> Option<FieldValue> {
+        self.fields.remove(&number)
+    }
+
+    fn clear_oneof_siblings(&mut self, field: &FieldDescriptor) {
+        if let Some(oneof) = field.containing_oneof() {
+            for sibling in oneof.fields().filter(|f| f.number() != field.number()) {
+                let _ = self.fields.remove(&sibling.number());
+            }
+        }
+    }
+
+    /// This function writes out every field that is set, in field number order, followed by any
+    /// unknown fields.
+    pub fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        for (field, value) in self.fields() {
+            size += match value {
+                FieldValue::List(values) if field.is_packed() => {
+                    let mut elements = Buffer::default();
+                    for value in values {
+                        let _ = encode_value(field.field_type(), value, &mut elements);
+                    }
+
+                    Tag::new_unchecked(field.number(), WireType::LengthDelimited).to_varint(buffer)
+                        + elements.to_vec().to_length_delimited(buffer)
+                }
+                FieldValue::List(values) => values
+                    .iter()
+                    .map(|v| encode_tagged(field.number(), field.field_type(), v, buffer))
+                    .sum(),
+                FieldValue::Map(entries) => {
+                    let entry = message_type(&field);
+                    let key_type = entry
+                        .get_field(1)
+                        .map_or(FieldType::String, |f| f.field_type());
+                    let value_type = entry
+                        .get_field(2)
+                        .map_or(FieldType::String, |f| f.field_type());
+
+                    entries
+                        .iter()
+                        .map(|(k, v)| {
+                            let mut entry = Buffer::default();
+                            let _ = encode_tagged(1, key_type, &k.clone().into(), &mut entry);
+                            let _ = encode_tagged(2, value_type, v, &mut entry);
+
+                            Tag::new_unchecked(field.number(), WireType::LengthDelimited)
+                                .to_varint(buffer)
+                                + entry.to_vec().to_length_delimited(buffer)
+                        })
+                        .sum()
+                }
+                _ => encode_tagged(field.number(), field.field_type(), value, buffer),
+            };
+        }
+
+        match EncodeMode::is_deterministic() {
+            true => buffer.put_slice(&sort_fields(&self.unknown_fields)),
+            false => buffer.put_slice(&self.unknown_fields),
+        }
+        size + self.unknown_fields.len()
+    }
+
+    /// This function encodes the message into a new Vec<u8>.
+    pub fn encode_to_vec(&self) -> Vec<u8> {
+        let mut buffer = Buffer::default();
+        let _ = self.encode(&mut buffer);
+        buffer.to_vec()
+    }
+
+    /// This function encodes the message into a new Vec<u8>, using the mode to decide whether the
+    /// encoding is deterministic. The mode applies to the current thread only, see [`EncodeMode`].
+    pub fn encode_to_vec_with_mode(&self, mode: EncodeMode) -> Vec<u8> {
+        mode.scope(|| self.encode_to_vec())
+    }
+
+    // Decodes fields into the message until the end of the buffer or, for a group, until the end
+    // group tag with the group's field number.
+    fn merge_fields(&mut self, buffer: &[u8], group: Option<u32>) -> Result<usize, DecodeError> {
+        let mut position = 0;
+        let mut decoded = DecodedFields::new();
+
+        while position < buffer.len() {
+            let start = position;
+            let mut tag = Tag::new_unchecked(0, WireType::Varint);
+            position += tag.from_varint(&buffer[position..])?;
+
+            if tag.wire_type() == WireType::EndGroup {
+                return match group == Some(tag.field_number()) {
+                    true => Ok(position),
+                    false => Err(DecodeError::UnableToDecode),
+                };
+            }
+
+            let value = &buffer[position..];
+            position += match self.merge_field(&tag, value)? {
+                Some(size) => {
+                    decoded.check_number(tag.field_number(), |n| self.is_singular_field(n))?;
+                    size
+                }
+                None => {
+                    let size = skip_tagged_field(&tag, value)?;
+                    self.unknown_fields
+                        .extend_from_slice(&buffer[start..position + size]);
+                    size
+                }
+            };
+        }
+
+        match group {
+            Some(_) => Err(DecodeError::BufferOverrun),
+            None => Ok(position),
+        }
+    }
+
+    fn merge_length_delimited(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        let (length, size) = decode_length(buffer)?;
+        let end = size + length;
+        if buffer.len() < end {
+            return Err(DecodeError::BufferOverrun);
+        }
+
+        let _ = self.merge_fields(&buffer[size..end], None)?;
+        Ok(end)
+    }
+
+    fn is_singular_field(&self, number: u32) -> bool {
+        self.descriptor
+            .get_field(number)
+            .or_else(|| self.descriptor.get_extension(number))
+            .is_some_and(|f| !f.is_repeated())
+    }
+
+    // Decodes a single field, returning `None` when the field is not in the descriptor or was
+    // written with a wire type that can not hold it.
+    fn merge_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        let number = tag.field_number();
+        let field = match self.descriptor.get_field(number) {
+            Some(field) => field,
+            None => match self.descriptor.get_extension(number) {
+                Some(extension) => extension,
+                None => return Ok(None),
+            },
+        };
+        let field_type = field.field_type();
+
+        if field.is_map() {
+            if tag.wire_type() != WireType::LengthDelimited {
+                return Ok(None);
+            }
+
+            let entry_type = message_type(&field);
+            let mut entry = DynamicMessage::new(entry_type.clone());
+            let size = entry.merge_length_delimited(buffer)?;
+            // An entry whose value is unknown to a closed enum, or whose key or value is a string
+            // that is not UTF-8, is kept whole as an unknown field.
+            if contains_field(&entry.unknown_fields, 1) || contains_field(&entry.unknown_fields, 2)
+            {
+                return Ok(None);
+            }
+
+            let mut take = |number| {
+                let field = entry_type.get_field(number)?;
+                let value = entry.fields.remove(&number);
+                Some(value.unwrap_or_else(|| FieldValue::default_value(&field)))
+            };
+            let (key, value) = match (take(1).map(FieldMapKey::try_from), take(2)) {
+                (Some(Ok(key)), Some(value)) => (key, value),
+                _ => return Err(DecodeError::UnableToDecode),
+            };
+
+            if let FieldValue::Map(entries) = self.get_field_mut(&field) {
+                let _ = entries.insert(key, value);
+            }
+            return Ok(Some(size));
+        }
+
+        // A string that is not UTF-8 can not be held as a value, so unless the field checks its
+        // values it is kept as an unknown field rather than rejected.
+        if field_type == FieldType::String
+            && !field.validates_utf8()
+            && tag.wire_type() == WireType::LengthDelimited
+        {
+            let mut value: Vec<u8> = Vec::new();
+            let _ = value.from_length_delimited(buffer)?;
+            if std::str::from_utf8(&value).is_err() {
+                return Ok(None);
+            }
+        }
+
+        if field.is_repeated() {
+            let mut values = Vec::new();
+            let size = if tag.wire_type() == wire_type(field_type) {
+                let (value, size) = decode_value(&field, buffer)?;
+                if is_unknown_enum_value(&field, &value) {
+                    return Ok(None);
+                }
+                values.push(value);
+                size
+            } else if tag.wire_type() == WireType::LengthDelimited && is_scalar(field_type) {
+                let (length, size) = decode_length(buffer)?;
+                let end = size + length;
+                if buffer.len() < end {
+                    return Err(DecodeError::BufferOverrun);
+                }
+
+                let mut position = size;
+                while position < end {
+                    let (value, size) = decode_value(&field, &buffer[position..end])?;
+                    // Packed values unknown to a closed enum are kept as unpacked unknown fields.
+                    if is_unknown_enum_value(&field, &value) {
+                        let mut unknown = Buffer::default();
+                        let _ = encode_tagged(number, field_type, &value, &mut unknown);
+                        self.unknown_fields.extend(unknown.to_vec());
+                    } else {
+                        values.push(value);
+                    }
+                    position += size;
+                }
+                end
+            } else {
+                return Ok(None);
+            };
+
+            if let FieldValue::List(list) = self.get_field_mut(&field) {
+                list.extend(values);
+            }
+            return Ok(Some(size));
+        }
+
+        if tag.wire_type() != wire_type(field_type) {
+            return Ok(None);
+        }
+
+        // A message that is already set has the new fields merged into it.
+        if let Some(FieldValue::Message(message)) = self.fields.get_mut(&field.number()) {
+            return decode_nested(|| match field_type {
+                FieldType::Group => message.merge_fields(buffer, Some(field.number())),
+                _ => message.merge_length_delimited(buffer),
+            })
+            .map(Some);
+        }
+
+        let (value, size) = decode_value(&field, buffer)?;
+        if is_unknown_enum_value(&field, &value) {
+            return Ok(None);
+        }
+        *self.get_field_mut(&field) = value;
+        Ok(Some(size))
+    }
+}
+
+// Returns true when the fields, which have already been decoded once, include the field number.
+fn contains_field(buffer: &[u8], number: u32) -> bool {
+    let mut position = 0;
+    while position < buffer.len() {
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
+        position += tag.from_varint(&buffer[position..]).unwrap_or(buffer.len());
+        if tag.field_number() == number {
+            return true;
+        }
+        position += skip_tagged_field(&tag, &buffer[position..]).unwrap_or(buffer.len());
+    }
+    false
+}
+
+// Closed enum fields only hold the values of the enum, so other numbers are unknown fields.
+fn is_unknown_enum_value(field: &FieldDescriptor, value: &FieldValue) -> bool {
+    match (field.enum_type(), value) {
+        (Some(enumeration), FieldValue::EnumNumber(number)) => {
+            enumeration.is_closed() && enumeration.get_value(*number).is_none()
+        }
+        _ => false,
+    }
+}
+
+fn message_type(field: &FieldDescriptor) -> MessageDescriptor {
+    field
+        .message_type()
+        .expect("message fields are resolved when they are added to the pool")
+}
+
+fn parse_default<T: std::str::FromStr + Default>(default: Option<&str>) -> T {
+    default.and_then(|d| d.parse().ok()).unwrap_or_default()
+}
+
+fn is_scalar(field_type: FieldType) -> bool {
+    !matches!(
+        field_type,
+        FieldType::String | FieldType::Bytes | FieldType::Message | FieldType::Group
+    )
+}
+
+fn wire_type(field_type: FieldType) -> WireType {
+    match field_type {
+        FieldType::Double | FieldType::Fixed64 | FieldType::Sfixed64 => WireType::Fixed64,
+        FieldType::Float | FieldType::Fixed32 | FieldType::Sfixed32 => WireType::Fixed32,
+        FieldType::String | FieldType::Bytes | FieldType::Message => WireType::LengthDelimited,
+        FieldType::Group => WireType::StartGroup,
+        _ => WireType::Varint,
+    }
+}
+
+// Writes a single value preceded by its tag. Groups are written between a start and an end group
+// tag rather than with a length.
+fn encode_tagged(
+    field_number: u32,
+    field_type: FieldType,
+    value: &FieldValue,
+    buffer: &mut Buffer,
+) -> usize {
+    let size = Tag::new_unchecked(field_number, wire_type(field_type)).to_varint(buffer);
+    let size = size + encode_value(field_type, value, buffer);
+
+    match field_type {
+        FieldType::Group => {
+            size + Tag::new_unchecked(field_number, WireType::EndGroup).to_varint(buffer)
+        }
+        _ => size,
+    }
+}
+
+fn encode_value(field_type: FieldType, value: &FieldValue, buffer: &mut Buffer) -> usize {
+    match (field_type, value) {
+        (FieldType::Double, FieldValue::F64(v)) => v.to_fixed64(buffer),
+        (FieldType::Float, FieldValue::F32(v)) => v.to_fixed32(buffer),
+        (FieldType::Int64, FieldValue::I64(v)) => v.to_sign_extended_varint(buffer),
+        (FieldType::Uint64, FieldValue::U64(v)) => v.to_varint(buffer),
+        (FieldType::Int32, FieldValue::I32(v)) => v.to_sign_extended_varint(buffer),
+        (FieldType::Fixed64, FieldValue::U64(v)) => v.to_fixed64(buffer),
+        (FieldType::Fixed32, FieldValue::U32(v)) => v.to_fixed32(buffer),
+        (FieldType::Bool, FieldValue::Bool(v)) => v.to_varint(buffer),
+        (FieldType::String, FieldValue::String(v)) => v.to_length_delimited(buffer),
+        (FieldType::Group, FieldValue::Message(v)) => v.encode(buffer),
+        (FieldType::Message, FieldValue::Message(v)) => {
+            v.encode_to_vec().to_length_delimited(buffer)
+        }
+        (FieldType::Bytes, FieldValue::Bytes(v)) => v.to_length_delimited(buffer),
+        (FieldType::Uint32, FieldValue::U32(v)) => v.to_varint(buffer),
+        (FieldType::Enum, FieldValue::EnumNumber(v)) => v.to_sign_extended_varint(buffer),
+        (FieldType::Sfixed32, FieldValue::I32(v)) => v.to_fixed32(buffer),
+        (FieldType::Sfixed64, FieldValue::I64(v)) => v.to_fixed64(buffer),
+        (FieldType::Sint32, FieldValue::I32(v)) => v.to_varint(buffer),
+        (FieldType::Sint64, FieldValue::I64(v)) => v.to_varint(buffer),
+        // Values are checked against the field when they are set, so this can not happen.
+        _ => 0,
+    }
+}
+
+// Decodes a single value of the field.
+fn decode_value(
+    field: &FieldDescriptor,
+    buffer: &[u8],
+) -> Result<(FieldValue, usize), DecodeError> {
+    match field.field_type() {
+        FieldType::Double => decode_with(buffer, f64::from_fixed64, FieldValue::F64),
+        FieldType::Float => decode_with(buffer, f32::from_fixed32, FieldValue::F32),
+        FieldType::Int64 => decode_with(buffer, i64::from_sign_extended_varint, FieldValue::I64),
+        FieldType::Uint64 => decode_with(buffer, u64::from_varint, FieldValue::U64),
+        FieldType::Int32 => decode_with(buffer, i32::from_sign_extended_varint, FieldValue::I32),
+        FieldType::Fixed64 => decode_with(buffer, u64::from_fixed64, FieldValue::U64),
+        FieldType::Fixed32 => decode_with(buffer, u32::from_fixed32, FieldValue::U32),
+        FieldType::Bool => decode_with(buffer, bool::from_varint, FieldValue::Bool),
+        FieldType::String => decode_with(buffer, String::from_length_delimited, FieldValue::String),
+        FieldType::Group => {
+            let mut message = DynamicMessage::new(message_type(field));
+            let size = decode_nested(|| message.merge_fields(buffer, Some(field.number())))?;
+            Ok((FieldValue::Message(message), size))
+        }
+        FieldType::Message => {
+            let mut message = DynamicMessage::new(message_type(field));
+            let size = decode_nested(|| message.merge_length_delimited(buffer))?;
+            Ok((FieldValue::Message(message), size))
+        }
+        FieldType::Bytes => {
+            decode_with(buffer, Vec::<u8>::from_length_delimited, FieldValue::Bytes)
+        }
+        FieldType::Uint32 => decode_with(buffer, u32::from_varint, FieldValue::U32),
+        FieldType::Enum => {
+            let (value, size) = decode_with(
+                buffer,
+                i32::from_sign_extended_varint,
+                FieldValue::EnumNumber,
+            )?;
+            // Strict decoding only accepts the values of the enum, whether it is open or closed.
+            match (&value, field.enum_type()) {
+                (FieldValue::EnumNumber(number), Some(enumeration))
+                    if DecodeMode::is_strict() && enumeration.get_value(*number).is_none() =>
+                {
+                    Err(DecodeError::UnknownEnumValue(*number))
+                }
+                _ => Ok((value, size)),
+            }
+        }
+        FieldType::Sfixed32 => decode_with(buffer, i32::from_fixed32, FieldValue::I32),
+        FieldType::Sfixed64 => decode_with(buffer, i64::from_fixed64, FieldValue::I64),
+        FieldType::Sint32 => decode_with(buffer, i32::from_varint, FieldValue::I32),
+        FieldType::Sint64 => decode_with(buffer, i64::from_varint, FieldValue::I64),
+    }
+}
+
+fn decode_with<T: Default>(
+    buffer: &[u8],
+    decode: impl FnOnce(&mut T, &[u8]) -> Result<usize, DecodeError>,
+    wrap: impl FnOnce(T) -> FieldValue,
+) -> Result<(FieldValue, usize), DecodeError> {
+    let mut value = T::default();
+    let size = decode(&mut value, buffer)?;
+    Ok((wrap(value), size))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DescriptorPool, DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto,
+        FieldDescriptorProto, FieldLabel, FileDescriptorProto, MessageOptions,
+        OneofDescriptorProto, ProtoCompiler, RECURSION_LIMIT,
+    };
+    use rstest::*;
+
+    fn field(
+        name: &str,
+        number: i32,
+        field_type: FieldType,
+        type_name: &str,
+    ) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(FieldLabel::Optional.into()),
+            r#type: Some(field_type.into()),
+            type_name: (!type_name.is_empty()).then(|| type_name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn repeated(mut field: FieldDescriptorProto) -> FieldDescriptorProto {
+        field.label = Some(FieldLabel::Repeated.into());
+        field
+    }
+
+    fn member(mut field: FieldDescriptorProto) -> FieldDescriptorProto {
+        field.oneof_index = Some(0);
+        field
+    }
+
+    // message Item { string name = 1; }
+    // enum Colour { COLOUR_UNSPECIFIED = 0; RED = 1; }
+    // message Record {
+    //   int32 id = 1; sint64 delta = 2; double score = 3; bytes data = 4; Colour colour = 5;
+    //   Item item = 6; repeated int32 numbers = 7; repeated string tags = 8;
+    //   map<string, int32> counts = 9; oneof choice { string text = 10; Item other = 11; }
+    // }
+    fn record() -> MessageDescriptor {
+        let counts_entry = DescriptorProto {
+            name: Some("CountsEntry".to_string()),
+            field: vec![
+                field("key", 1, FieldType::String, ""),
+                field("value", 2, FieldType::Int32, ""),
+            ],
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let record = DescriptorProto {
+            name: Some("Record".to_string()),
+            field: vec![
+                field("id", 1, FieldType::Int32, ""),
+                field("delta", 2, FieldType::Sint64, ""),
+                field("score", 3, FieldType::Double, ""),
+                field("data", 4, FieldType::Bytes, ""),
+                field("colour", 5, FieldType::Enum, "Colour"),
+                field("item", 6, FieldType::Message, "Item"),
+                repeated(field("numbers", 7, FieldType::Int32, "")),
+                repeated(field("tags", 8, FieldType::String, "")),
+                repeated(field("counts", 9, FieldType::Message, "CountsEntry")),
+                member(field("text", 10, FieldType::String, "")),
+                member(field("other", 11, FieldType::Message, "Item")),
+            ],
+            nested_type: vec![counts_entry],
+            oneof_decl: vec![OneofDescriptorProto {
+                name: Some("choice".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let item = DescriptorProto {
+            name: Some("Item".to_string()),
+            field: vec![field("name", 1, FieldType::String, "")],
+            ..Default::default()
+        };
+        let colour = EnumDescriptorProto {
+            name: Some("Colour".to_string()),
+            value: ["COLOUR_UNSPECIFIED", "RED"]
+                .iter()
+                .enumerate()
+                .map(|(number, name)| EnumValueDescriptorProto {
+                    name: Some(name.to_string()),
+                    number: Some(number as i32),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_proto(FileDescriptorProto {
+            name: Some("record.proto".to_string()),
+            package: Some("test".to_string()),
+            syntax: Some("proto3".to_string()),
+            message_type: vec![item, record],
+            enum_type: vec![colour],
+            ..Default::default()
+        })
+        .unwrap();
+        pool.get_message_by_name("test.Record").unwrap()
+    }
+
+    fn record_bytes() -> Vec<u8> {
+        vec![
+            8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1, 50, 3, 10, 1, 97, 58, 2, 1, 2, 66,
+            1, 120, 74, 5, 10, 1, 107, 16, 3,
+        ]
+    }
+
+    fn item(descriptor: &MessageDescriptor, name: &str) -> FieldValue {
+        let mut item =
+            DynamicMessage::new(descriptor.get_field(6).unwrap().message_type().unwrap());
+        item.set_field_by_name("name", name.into()).unwrap();
+        FieldValue::Message(item)
+    }
+
+    #[test]
+    fn test_dynamic_message_decoding() {
+        // Arrange
+        let descriptor = record();
+
+        // Act
+        let message = DynamicMessage::decode(descriptor, &record_bytes()).unwrap();
+
+        // Assert
+        let get = |name| message.get_field_by_name(name).unwrap().into_owned();
+        assert_eq!(get("id"), FieldValue::I32(-1));
+        assert_eq!(
+            get("item")
+                .as_message()
+                .and_then(|m| m.get_field_by_number(1))
+                .unwrap()
+                .as_str(),
+            Some("a")
+        );
+        assert_eq!(get("numbers"), FieldValue::List(vec![1.into(), 2.into()]));
+        assert_eq!(get("tags"), FieldValue::List(vec!["x".into()]));
+        assert_eq!(
+            get("counts")
+                .as_map()
+                .unwrap()
+                .get(&FieldMapKey::String("k".to_string())),
+            Some(&FieldValue::I32(3))
+        );
+        assert_eq!(get("score"), FieldValue::F64(0.0));
+        assert!(!message.has_field(&message.descriptor().get_field(3).unwrap()));
+    }
+
+    #[test]
+    fn test_dynamic_message_encoding() {
+        // Arrange
+        let descriptor = record();
+        let mut message = DynamicMessage::new(descriptor.clone());
+        let mut counts = BTreeMap::new();
+        let _ = counts.insert(FieldMapKey::String("k".to_string()), FieldValue::I32(3));
+
+        // Act
+        message
+            .set_field_by_name("counts", FieldValue::Map(counts))
+            .unwrap();
+        message
+            .set_field_by_name("tags", FieldValue::List(vec!["x".into()]))
+            .unwrap();
+        message
+            .set_field_by_name("numbers", FieldValue::List(vec![1.into(), 2.into()]))
+            .unwrap();
+        message
+            .set_field_by_name("item", item(&descriptor, "a"))
+            .unwrap();
+        message.set_field_by_name("id", (-1).into()).unwrap();
+        message.set_field_by_name("score", 0.0.into()).unwrap();
+
+        // Assert
+        assert_eq!(message.encode_to_vec(), record_bytes());
+    }
+
+    #[rstest]
+    #[case("delta", FieldValue::I64(-2), vec![16, 3])]
+    #[case("score", FieldValue::F64(0.5), vec![25, 0, 0, 0, 0, 0, 0, 224, 63])]
+    #[case("data", FieldValue::Bytes(vec![1, 2]), vec![34, 2, 1, 2])]
+    #[case("colour", FieldValue::EnumNumber(1), vec![40, 1])]
+    #[case("colour", FieldValue::EnumNumber(7), vec![40, 7])]
+    #[case("text", FieldValue::String(String::new()), vec![82, 0])]
+    fn test_dynamic_field_round_trip(
+        #[case] name: &str,
+        #[case] value: FieldValue,
+        #[case] expected_bytes: Vec<u8>,
+    ) {
+        // Arrange
+        let mut message = DynamicMessage::new(record());
+
+        // Act
+        message.set_field_by_name(name, value.clone()).unwrap();
+        let bytes = message.encode_to_vec();
+        let decoded = DynamicMessage::decode(record(), &bytes).unwrap();
+
+        // Assert
+        assert_eq!(bytes, expected_bytes);
+        assert_eq!(decoded.get_field_by_name(name).unwrap().into_owned(), value);
+    }
+
+    #[test]
+    fn test_unpacked_repeated_fields_are_accepted() {
+        // Arrange
+        let bytes = vec![56, 1, 56, 2];
+
+        // Act
+        let message = DynamicMessage::decode(record(), &bytes).unwrap();
+
+        // Assert
+        assert_eq!(
+            message.get_field_by_number(7).unwrap().as_list(),
+            Some(&[1.into(), 2.into()][..])
+        );
+        assert_eq!(message.encode_to_vec(), vec![58, 2, 1, 2]);
+    }
+
+    #[test]
+    fn test_repeated_messages_are_merged() {
+        // Arrange
+        let bytes = vec![50, 3, 10, 1, 97, 50, 0];
+
+        // Act
+        let message = DynamicMessage::decode(record(), &bytes).unwrap();
+
+        // Assert
+        assert_eq!(
+            message.get_field_by_name("item").unwrap().into_owned(),
+            item(message.descriptor(), "a")
+        );
+    }
+
+    #[test]
+    fn test_merge_matches_concatenated_decoding() {
+        // Arrange
+        let descriptor = record();
+        let second = vec![
+            8, 5, 50, 0, 58, 1, 3, 74, 5, 10, 1, 107, 16, 4, 74, 5, 10, 1, 106, 16, 1, 82, 1, 116,
+            160, 6, 1,
+        ];
+        let mut merged = DynamicMessage::decode(descriptor.clone(), &record_bytes()).unwrap();
+        let mut merged_from_bytes = merged.clone();
+        let other = DynamicMessage::decode(descriptor.clone(), &second).unwrap();
+
+        // Act
+        merged.merge(other);
+        merged_from_bytes.merge_from_bytes(&second).unwrap();
+
+        // Assert
+        let concatenated =
+            DynamicMessage::decode(descriptor.clone(), &[record_bytes(), second].concat()).unwrap();
+        assert_eq!(merged, concatenated);
+        assert_eq!(merged_from_bytes, concatenated);
+        let get = |name| merged.get_field_by_name(name).unwrap().into_owned();
+        assert_eq!(get("id"), FieldValue::I32(5));
+        assert_eq!(get("item"), item(&descriptor, "a"));
+        assert_eq!(
+            get("numbers"),
+            FieldValue::List(vec![1.into(), 2.into(), 3.into()])
+        );
+        assert_eq!(get("counts").as_map().unwrap().len(), 2);
+        assert_eq!(get("text"), FieldValue::from("t"));
+        assert_eq!(merged.unknown_fields(), &[160, 6, 1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "merged messages must be of the same type")]
+    fn test_merge_rejects_other_types() {
+        // Arrange
+        let descriptor = record();
+        let mut message = DynamicMessage::new(descriptor.clone());
+        let other = DynamicMessage::new(descriptor.get_field(6).unwrap().message_type().unwrap());
+
+        // Act
+        message.merge(other);
+    }
+
+    #[test]
+    fn test_oneof_members_replace_each_other() {
+        // Arrange
+        let descriptor = record();
+        let mut message = DynamicMessage::new(descriptor.clone());
+        message.set_field_by_name("text", "hello".into()).unwrap();
+
+        // Act
+        message
+            .set_field_by_name("other", item(&descriptor, "b"))
+            .unwrap();
+
+        // Assert
+        let set: Vec<String> = message
+            .fields()
+            .map(|(f, _)| f.name().to_string())
+            .collect();
+        assert_eq!(set, vec!["other"]);
+    }
+
+    #[rstest]
+    #[case("id", FieldValue::U32(1), FieldError::InvalidType("test.Record.id".to_string()))]
+    #[case("numbers", FieldValue::I32(1), FieldError::InvalidType("test.Record.numbers".to_string()))]
+    #[case("tags", FieldValue::List(vec![FieldValue::I32(1)]), FieldError::InvalidType("test.Record.tags".to_string()))]
+    #[case("missing", FieldValue::I32(1), FieldError::NotFound("missing".to_string()))]
+    fn test_invalid_field_values_are_rejected(
+        #[case] name: &str,
+        #[case] value: FieldValue,
+        #[case] expected_error: FieldError,
+    ) {
+        // Arrange
+        let mut message = DynamicMessage::new(record());
+
+        // Act
+        let result = message.set_field_by_name(name, value);
+
+        // Assert
+        assert_eq!(result, Err(expected_error));
+    }
+
+    #[test]
+    fn test_unknown_fields_are_preserved() {
+        // Arrange
+        let bytes = vec![8, 1, 160, 6, 5, 165, 6, 1, 0, 0, 0];
+
+        // Act
+        let message = DynamicMessage::decode(record(), &bytes).unwrap();
+
+        // Assert
+        assert_eq!(message.unknown_fields(), &[160, 6, 5, 165, 6, 1, 0, 0, 0]);
+        assert_eq!(message.encode_to_vec(), bytes);
+    }
+
+    #[rstest]
+    #[case(EncodeMode::Standard, vec![8, 1, 168, 6, 7, 160, 6, 5, 168, 6, 8])]
+    #[case(EncodeMode::Deterministic, vec![8, 1, 160, 6, 5, 168, 6, 7, 168, 6, 8])]
+    fn test_unknown_fields_placement(#[case] mode: EncodeMode, #[case] expected_bytes: Vec<u8>) {
+        // Arrange
+        let bytes = vec![168, 6, 7, 8, 1, 160, 6, 5, 168, 6, 8];
+        let message = DynamicMessage::decode(record(), &bytes).unwrap();
+
+        // Act
+        let encoded = message.encode_to_vec_with_mode(mode);
+
+        // Assert
+        assert_eq!(encoded, expected_bytes);
+    }
+
+    #[test]
+    fn test_unknown_groups_are_preserved() {
+        // Arrange
+        let bytes = vec![8, 2, 163, 6, 8, 1, 171, 6, 172, 6, 164, 6];
+
+        // Act
+        let message = DynamicMessage::decode(record(), &bytes).unwrap();
+
+        // Assert
+        assert_eq!(message.unknown_fields(), &bytes[2..]);
+        assert_eq!(message.encode_to_vec(), bytes);
+    }
+
+    #[rstest]
+    #[case(vec![163, 6, 8, 1])]
+    #[case(vec![163, 6, 172, 6])]
+    #[case(vec![164, 6])]
+    fn test_unmatched_groups_are_rejected(#[case] bytes: Vec<u8>) {
+        // Act
+        let result = DynamicMessage::decode(record(), &bytes);
+
+        // Assert
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_clear_field() {
+        // Arrange
+        let mut message = DynamicMessage::decode(record(), &record_bytes()).unwrap();
+
+        // Act
+        let cleared = message.clear_field_by_name("id");
+
+        // Assert
+        assert_eq!(cleared, Some(FieldValue::I32(-1)));
+        assert_eq!(
+            message.get_field_by_name("id").unwrap().into_owned(),
+            FieldValue::I32(0)
+        );
+        assert_eq!(message.encode_to_vec(), record_bytes()[11..].to_vec());
+    }
+
+    const LEGACY: &str = r#"syntax = "proto2";
+package legacy;
+enum Level { NONE = 0; LOW = 1; HIGH = 2; }
+message Entry {
+  required int32 id = 1;
+  optional string name = 2 [default = "none"];
+  optional Level level = 3 [default = HIGH];
+  repeated Level levels = 4 [packed = true];
+  optional group Detail = 5 { required string note = 6; }
+  map<int32, Level> history = 7;
+  extensions 100 to 199;
+}
+extend Entry { optional int32 priority = 100; }
+"#;
+
+    fn legacy() -> MessageDescriptor {
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("legacy.proto", LEGACY);
+        let set = compiler.compile(&["legacy.proto"]).unwrap();
+        let pool = DescriptorPool::from_file_descriptor_set(set).unwrap();
+        pool.get_message_by_name("legacy.Entry").unwrap()
+    }
+
+    #[test]
+    fn test_proto2_defaults_are_used_when_unset() {
+        // Act
+        let message = DynamicMessage::new(legacy());
+
+        // Assert
+        assert_eq!(
+            message.get_field_by_name("name").unwrap().as_str(),
+            Some("none")
+        );
+        assert_eq!(
+            message.get_field_by_name("level").unwrap().as_enum_number(),
+            Some(2)
+        );
+        assert!(!message.has_field(&legacy().get_field_by_name("level").unwrap()));
+    }
+
+    #[rstest]
+    #[case(vec![], "legacy.Entry.id")]
+    #[case(vec![8, 1, 43, 44], "legacy.Entry.Detail.note")]
+    fn test_missing_required_fields_are_rejected(
+        #[case] bytes: Vec<u8>,
+        #[case] expected_field: &str,
+    ) {
+        // Act
+        let error = DynamicMessage::decode(legacy(), &bytes).unwrap_err();
+
+        // Assert
+        assert_eq!(
+            error,
+            DecodeError::MissingRequiredField(expected_field.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case(vec![8, 1, 24, 9], vec![24, 9], 1)]
+    #[case(vec![8, 1, 34, 2, 1, 9], vec![32, 9], 2)]
+    #[case(vec![8, 1, 58, 4, 8, 3, 16, 9], vec![58, 4, 8, 3, 16, 9], 1)]
+    fn test_unknown_closed_enum_values_are_unknown_fields(
+        #[case] bytes: Vec<u8>,
+        #[case] expected_unknown_fields: Vec<u8>,
+        #[case] expected_field_count: usize,
+    ) {
+        // Act
+        let message = DynamicMessage::decode(legacy(), &bytes).unwrap();
+
+        // Assert
+        assert_eq!(message.unknown_fields(), expected_unknown_fields);
+        assert_eq!(message.fields().count(), expected_field_count);
+    }
+
+    #[test]
+    fn test_proto2_groups_and_extensions_round_trip() {
+        // Arrange
+        let bytes = vec![8, 1, 43, 50, 1, 120, 44, 160, 6, 7];
+
+        // Act
+        let message = DynamicMessage::decode(legacy(), &bytes).unwrap();
+
+        // Assert
+        let detail = message.get_field_by_name("detail").unwrap();
+        let note = detail.as_message().unwrap().get_field_by_name("note");
+        assert_eq!(note.unwrap().as_str(), Some("x"));
+        let priority = message.descriptor().get_extension(100).unwrap();
+        assert_eq!(message.get_field(&priority).as_i32(), Some(7));
+        assert_eq!(message.encode_to_vec(), bytes);
+    }
+
+    // Compiles the schema of the golden payloads, which were serialized by the reference
+    // implementation.
+    const MODERN: &str = r#"edition = "2023";
+package modern;
+option features.utf8_validation = NONE;
+enum Level { option features.enum_type = CLOSED; LOW = 1; HIGH = 2; }
+message Entry {
+  int32 id = 1 [features.field_presence = LEGACY_REQUIRED];
+  int32 count = 2 [features.field_presence = IMPLICIT];
+  int32 limit = 3;
+  repeated int32 values = 4;
+  repeated int32 codes = 5 [features.repeated_field_encoding = EXPANDED];
+  Entry child = 6 [features.message_encoding = DELIMITED];
+  Level level = 7;
+  string note = 8;
+}
+"#;
+
+    fn modern() -> MessageDescriptor {
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("modern.proto", MODERN);
+        let set = compiler.compile(&["modern.proto"]).unwrap();
+        let pool = DescriptorPool::from_file_descriptor_set(set).unwrap();
+        pool.get_message_by_name("modern.Entry").unwrap()
+    }
+
+    #[test]
+    fn test_editions_features_are_honoured() {
+        // Arrange
+        let descriptor = modern();
+        let mut child = DynamicMessage::new(descriptor.clone());
+        child.set_field_by_name("id", FieldValue::I32(2)).unwrap();
+        let mut message = DynamicMessage::new(descriptor.clone());
+        let fields = [
+            ("id", FieldValue::I32(1)),
+            ("count", FieldValue::I32(0)),
+            ("limit", FieldValue::I32(0)),
+            (
+                "values",
+                FieldValue::List(vec![FieldValue::I32(1), FieldValue::I32(2)]),
+            ),
+            (
+                "codes",
+                FieldValue::List(vec![FieldValue::I32(1), FieldValue::I32(2)]),
+            ),
+            ("child", FieldValue::Message(child)),
+        ];
+        for (name, value) in fields {
+            message.set_field_by_name(name, value).unwrap();
+        }
+
+        // Act
+        let bytes = message.encode_to_vec();
+        let decoded = DynamicMessage::decode(descriptor, &bytes).unwrap();
+
+        // Assert
+        assert_eq!(
+            bytes,
+            vec![8, 1, 24, 0, 34, 2, 1, 2, 40, 1, 40, 2, 51, 8, 2, 52]
+        );
+        let _ = message.clear_field_by_name("count");
+        assert_eq!(decoded, message);
+    }
+
+    #[rstest]
+    #[case(vec![8, 1, 56, 9], vec![56, 9])]
+    #[case(vec![8, 1, 66, 1, 255], vec![66, 1, 255])]
+    fn test_editions_unknown_values_are_unknown_fields(
+        #[case] bytes: Vec<u8>,
+        #[case] expected_unknown_fields: Vec<u8>,
+    ) {
+        // Act
+        let message = DynamicMessage::decode(modern(), &bytes).unwrap();
+
+        // Assert
+        assert_eq!(message.unknown_fields(), expected_unknown_fields);
+        assert_eq!(message.fields().count(), 1);
+    }
+
+    #[rstest]
+    #[case(vec![8, 1, 8, 2], Err(DecodeError::DuplicateField(1)))]
+    #[case(vec![8, 1, 56, 9], Err(DecodeError::UnknownEnumValue(9)))]
+    #[case(vec![8, 1, 34, 1, 1, 34, 1, 2], Ok(()))]
+    fn test_strict_decoding(
+        #[case] bytes: Vec<u8>,
+        #[case] expected_result: Result<(), DecodeError>,
+    ) {
+        // Act
+        let strict = DynamicMessage::decode_with_mode(modern(), &bytes, DecodeMode::Strict);
+        let lenient = DynamicMessage::decode_with_mode(modern(), &bytes, DecodeMode::Lenient);
+
+        // Assert
+        assert_eq!(strict.map(|_| ()), expected_result);
+        assert!(lenient.is_ok());
+    }
+
+    #[test]
+    fn test_editions_legacy_required_fields_are_checked() {
+        // Act
+        let error = DynamicMessage::decode(modern(), &[16, 1]).unwrap_err();
+
+        // Assert
+        assert_eq!(
+            error,
+            DecodeError::MissingRequiredField("modern.Entry.id".to_string())
+        );
+    }
+
+    fn golden(name: &str) -> MessageDescriptor {
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("golden.proto", include_str!("../golden/golden.proto"));
+        let set = compiler.compile(&["golden.proto"]).unwrap();
+        let pool = DescriptorPool::from_file_descriptor_set(set).unwrap();
+        pool.get_message_by_name(&format!("golden.{}", name))
+            .unwrap()
+    }
+
+    #[rstest]
+    #[case("Scalars", include_bytes!("../golden/scalars.bin"))]
+    #[case("Scalars", include_bytes!("../golden/negatives.bin"))]
+    #[case("Packed", include_bytes!("../golden/packed.bin"))]
+    #[case("Unpacked", include_bytes!("../golden/unpacked.bin"))]
+    #[case("Maps", include_bytes!("../golden/maps.bin"))]
+    #[case("Nested", include_bytes!("../golden/nested.bin"))]
+    #[case("PartialScalars", include_bytes!("../golden/unknown.bin"))]
+    fn test_golden_payloads_round_trip(#[case] message_name: &str, #[case] bytes: &[u8]) {
+        // Act
+        let message = DynamicMessage::decode(golden(message_name), bytes).unwrap();
+
+        // Assert
+        assert_eq!(message.encode_to_vec(), bytes);
+    }
+
+    #[test]
+    fn test_golden_scalars_are_decoded() {
+        // Act
+        let message =
+            DynamicMessage::decode(golden("Scalars"), include_bytes!("../golden/scalars.bin"))
+                .unwrap();
+
+        // Assert
+        let field = |name| message.get_field_by_name(name).unwrap().into_owned();
+        assert_eq!(field("int64_value"), FieldValue::I64(i64::MAX));
+        assert_eq!(field("uint64_value"), FieldValue::U64(u64::MAX));
+        assert_eq!(field("sint32_value"), FieldValue::I32(i32::MIN));
+        assert_eq!(field("sint64_value"), FieldValue::I64(i64::MIN));
+        assert_eq!(field("sfixed64_value"), FieldValue::I64(i64::MIN));
+        assert_eq!(field("double_value"), FieldValue::F64(-1.25e-300));
+        assert_eq!(field("string_value"), FieldValue::from("héllo €😀"));
+        assert_eq!(
+            field("bytes_value"),
+            FieldValue::Bytes(vec![0, 1, 254, 255])
+        );
+        assert_eq!(field("enum_value"), FieldValue::EnumNumber(2));
+    }
+
+    #[test]
+    fn test_golden_unknown_fields_are_kept() {
+        // Arrange
+        let bytes = include_bytes!("../golden/unknown.bin");
+
+        // Act
+        let message = DynamicMessage::decode(golden("PartialScalars"), bytes).unwrap();
+
+        // Assert
+        assert_eq!(message.fields().count(), 3);
+        assert_eq!(message.unknown_fields(), &bytes[33..]);
+    }
+
+    #[rstest]
+    #[case(RECURSION_LIMIT, false, true)]
+    #[case(RECURSION_LIMIT, true, true)]
+    #[case(RECURSION_LIMIT + 1, false, false)]
+    #[case(RECURSION_LIMIT + 1, true, false)]
+    #[case(100_000, false, false)]
+    fn test_recursion_limit(#[case] depth: usize, #[case] via_map: bool, #[case] is_ok: bool) {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source(
+            "chain.proto",
+            "syntax = \"proto3\"; message M { M child = 1; map<int32, M> children = 2; }",
+        );
+        let set = compiler.compile(&["chain.proto"]).unwrap();
+        let pool = DescriptorPool::from_file_descriptor_set(set).unwrap();
+        let descriptor = pool.get_message_by_name("M").unwrap();
+        // Built from the inside out in reverse, as encoding such a chain would recurse. A map
+        // entry, which is not counted as a level, is written with the same tag as the field.
+        let tags: &[u8] = match via_map {
+            true => &[18, 18],
+            false => &[10],
+        };
+        let mut reversed = Vec::new();
+        for _ in 0..depth {
+            for tag in tags {
+                let mut buffer = Buffer::default();
+                let _ = (reversed.len() as u64).to_varint(&mut buffer);
+                reversed.extend(buffer.to_vec().into_iter().rev());
+                reversed.push(*tag);
+            }
+        }
+        let bytes: Vec<u8> = reversed.into_iter().rev().collect();
+
+        // Act
+        let result = DynamicMessage::decode(descriptor, &bytes);
+
+        // Assert
+        match is_ok {
+            true => assert!(result.is_ok()),
+            false => assert_eq!(result, Err(DecodeError::RecursionLimitExceeded)),
+        }
+    }
+}
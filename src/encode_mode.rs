@@ -0,0 +1,83 @@
+use crate::scoped::scoped_set;
+use std::cell::Cell;
+
+thread_local! {
+    static CURRENT: Cell<EncodeMode> = const { Cell::new(EncodeMode::Standard) };
+}
+
+/// Decides whether messages are encoded to the same bytes every time.
+///
+/// The mode is held per thread. It applies while a message is encoded with
+/// `encode_to_vec_with_mode`, or within [`EncodeMode::scope`], on the thread which called it, so
+/// that the field encoders nested within the message do not need to be passed it. It does not
+/// propagate to other threads: an encoder which hands work to another thread must read
+/// [`EncodeMode::current`] first and enter the mode there with [`EncodeMode::scope`], or that
+/// thread writes the standard encoding.
+///
+/// Fields are always written in field number order, with extensions among them and the member of
+/// a oneof at the position of its own number. Deterministic encoding also writes the entries of a
+/// map in the order of their keys, with strings compared by their bytes, and writes unknown fields
+/// after the known fields in field number order, keeping the order of those with the same number.
+/// This output is stable across versions of the crate, so it can be hashed or signed. It is not
+/// canonical: other implementations may order map entries and unknown fields differently.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Buffer, EncodeMode, LengthDelimitedCodec, MapField, VarintCodec};
+/// use std::collections::HashMap;
+///
+/// let scores: HashMap<String, u32> = [("b", 2), ("a", 1)]
+///     .into_iter()
+///     .map(|(k, v)| (k.to_string(), v))
+///     .collect();
+///
+/// let mut buffer = Buffer::default();
+/// let _ = EncodeMode::Deterministic
+///     .scope(|| scores.to_map_field::<LengthDelimitedCodec, VarintCodec, 1>(&mut buffer));
+///
+/// assert_eq!(buffer.to_vec(), vec![10, 5, 10, 1, 97, 16, 1, 10, 5, 10, 1, 98, 16, 2]);
+/// ```
+///
+/// Encoding on another thread:
+/// ```
+/// use ks_protobuf_v3::EncodeMode;
+///
+/// EncodeMode::Deterministic.scope(|| {
+///     let mode = EncodeMode::current();
+///     std::thread::scope(|s| {
+///         let other = s.spawn(EncodeMode::current).join().unwrap();
+///         let entered = s.spawn(|| mode.scope(EncodeMode::current)).join().unwrap();
+///
+///         assert_eq!(other, EncodeMode::Standard);
+///         assert_eq!(entered, EncodeMode::Deterministic);
+///     });
+/// });
+/// ```
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash)]
+pub enum EncodeMode {
+    /// Writes map entries in the order in which the map iterates and unknown fields in the order
+    /// in which they were read.
+    #[default]
+    Standard,
+
+    /// Writes map entries in key order and unknown fields in field number order.
+    Deterministic,
+}
+
+impl EncodeMode {
+    /// Returns the mode used by encoding on the current thread.
+    pub fn current() -> Self {
+        CURRENT.with(Cell::get)
+    }
+
+    /// Returns true when encoding on the current thread is deterministic.
+    pub fn is_deterministic() -> bool {
+        Self::current() == EncodeMode::Deterministic
+    }
+
+    /// Runs the function with the mode used by encoding on the current thread, restoring the
+    /// previous mode afterwards.
+    pub fn scope<T>(self, f: impl FnOnce() -> T) -> T {
+        scoped_set(&CURRENT, self, f)
+    }
+}
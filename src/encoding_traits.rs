@@ -1,7 +1,18 @@
 use crate::{Buffer, DecodeError, Tag, WireType};
 
+/// This trait determines whether the value of a field is written out.
+///
+/// Proto3 fields without explicit presence are not written out when they hold their default
+/// value. Fields with explicit presence, such as `optional` fields held in an `Option`, are written
+/// out whenever they are set, even when they are set to the default value.
+pub trait Presence {
+    /// This function returns true when the value must be written out.
+    fn is_present(&self) -> bool;
+}
+
 /// This trait can be applied to types to enable them to encode and decode value to and from
 /// Protocol Buffers with the Varint wire type.
+#[allow(clippy::wrong_self_convention)]
 pub trait Varint {
     /// This function takes a buffer and converts the attached type to a Protocol Buffer encoded
     /// with the Varint wire type.
@@ -16,10 +27,25 @@ pub trait Varint {
 ///
 /// These fields include the field number, the type of wire type encoding and the value of the
 /// attached type encoded.
-pub trait VarintField: Varint {
-    /// This function writes out a varint field to the Protocol Buffer.
-    fn to_varint_field(&self, field_number: u32, buffer: &mut Buffer) -> usize {
-        let tag = Tag::new(field_number, WireType::Varint);
+///
+/// The field number is a constant, so an invalid field number is rejected when compiling, see
+/// [`Tag::new`].
+pub trait VarintField: Varint + Presence {
+    /// This function writes out a varint field to the Protocol Buffer. Nothing is written when
+    /// the value is not present, such as a proto3 scalar holding its default value.
+    fn to_varint_field<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        if !self.is_present() {
+            return 0;
+        }
+
+        self.to_varint_field_always::<FIELD_NUMBER>(buffer)
+    }
+
+    /// This function writes out a varint field to the Protocol Buffer regardless of whether the
+    /// value is present. It is used for values that are always written, such as the members of a
+    /// `oneof` group and the elements of a repeated field.
+    fn to_varint_field_always<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        let tag = Tag::new::<FIELD_NUMBER>(WireType::Varint);
 
         let size1 = tag.to_varint(buffer);
         let size2 = self.to_varint(buffer);
@@ -27,8 +53,57 @@ pub trait VarintField: Varint {
     }
 }
 
+/// This trait can be applied to signed integer types to enable them to encode and decode value to
+/// and from Protocol Buffers as a sign extended Varint, as used by the `int32` and `int64` types.
+///
+/// Unlike the ZigZag encoding used by the `Varint` trait for signed integers, negative values are
+/// sign extended to 64 bits and therefore always require 10 bytes.
+#[allow(clippy::wrong_self_convention)]
+pub trait SignExtendedVarint {
+    /// This function takes a buffer and converts the attached type to a Protocol Buffer encoded
+    /// as a sign extended Varint.
+    fn to_sign_extended_varint(&self, buffer: &mut Buffer) -> usize;
+
+    /// This function takes a buffer and extracts the value encoded as a sign extended Varint back
+    /// to the attached type.
+    fn from_sign_extended_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError>;
+}
+
+/// This trait can be applied to types to enable them to encode and decode fields.
+///
+/// These fields include the field number, the type of wire type encoding and the value of the
+/// attached type encoded.
+///
+/// The field number is a constant, so an invalid field number is rejected when compiling, see
+/// [`Tag::new`].
+pub trait SignExtendedVarintField: SignExtendedVarint + Presence {
+    /// This function writes out a sign extended varint field to the Protocol Buffer. Nothing is
+    /// written when the value is not present, such as a proto3 scalar holding its default value.
+    fn to_sign_extended_varint_field<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        if !self.is_present() {
+            return 0;
+        }
+
+        self.to_sign_extended_varint_field_always::<FIELD_NUMBER>(buffer)
+    }
+
+    /// This function writes out a sign extended varint field to the Protocol Buffer regardless of
+    /// whether the value is present.
+    fn to_sign_extended_varint_field_always<const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize {
+        let tag = Tag::new::<FIELD_NUMBER>(WireType::Varint);
+
+        let size1 = tag.to_varint(buffer);
+        let size2 = self.to_sign_extended_varint(buffer);
+        size1 + size2
+    }
+}
+
 /// This trait can be applied to types to enable them to encode and decode value to and from
 /// Protocol Buffers with the Fixed32 wire type.
+#[allow(clippy::wrong_self_convention)]
 pub trait Fixed32 {
     /// This function takes a buffer and converts the attached type to a Protocol Buffer encoded
     /// with the Fixed32 wire type.
@@ -43,10 +118,25 @@ pub trait Fixed32 {
 ///
 /// These fields include the field number, the type of wire type encoding and the value of the
 /// attached type encoded.
-pub trait Fixed32Field: Fixed32 {
-    /// This function writes out a fixed32 field to the Protocol Buffer.
-    fn to_fixed32_field(&self, field_number: u32, buffer: &mut Buffer) -> usize {
-        let tag = Tag::new(field_number, WireType::Fixed32);
+///
+/// The field number is a constant, so an invalid field number is rejected when compiling, see
+/// [`Tag::new`].
+pub trait Fixed32Field: Fixed32 + Presence {
+    /// This function writes out a fixed32 field to the Protocol Buffer. Nothing is written when
+    /// the value is not present, such as a proto3 scalar holding its default value.
+    fn to_fixed32_field<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        if !self.is_present() {
+            return 0;
+        }
+
+        self.to_fixed32_field_always::<FIELD_NUMBER>(buffer)
+    }
+
+    /// This function writes out a fixed32 field to the Protocol Buffer regardless of whether the
+    /// value is present. It is used for values that are always written, such as the members of a
+    /// `oneof` group and the elements of a repeated field.
+    fn to_fixed32_field_always<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        let tag = Tag::new::<FIELD_NUMBER>(WireType::Fixed32);
 
         let size1 = tag.to_varint(buffer);
         let size2 = self.to_fixed32(buffer);
@@ -56,6 +146,7 @@ pub trait Fixed32Field: Fixed32 {
 
 /// This trait can be applied to types to enable them to encode and decode value to and from
 /// Protocol Buffers with the Fixed32 wire type.
+#[allow(clippy::wrong_self_convention)]
 pub trait Fixed64 {
     /// This function takes a buffer and converts the attached type to a Protocol Buffer encoded
     /// with the Fixed64 wire type.
@@ -70,10 +161,25 @@ pub trait Fixed64 {
 ///
 /// These fields include the field number, the type of wire type encoding and the value of the
 /// attached type encoded.
-pub trait Fixed64Field: Fixed64 {
-    /// This function writes out a fixed32 field to the Protocol Buffer.
-    fn to_fixed64_field(&self, field_number: u32, buffer: &mut Buffer) -> usize {
-        let tag = Tag::new(field_number, WireType::Fixed64);
+///
+/// The field number is a constant, so an invalid field number is rejected when compiling, see
+/// [`Tag::new`].
+pub trait Fixed64Field: Fixed64 + Presence {
+    /// This function writes out a fixed64 field to the Protocol Buffer. Nothing is written when
+    /// the value is not present, such as a proto3 scalar holding its default value.
+    fn to_fixed64_field<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        if !self.is_present() {
+            return 0;
+        }
+
+        self.to_fixed64_field_always::<FIELD_NUMBER>(buffer)
+    }
+
+    /// This function writes out a fixed64 field to the Protocol Buffer regardless of whether the
+    /// value is present. It is used for values that are always written, such as the members of a
+    /// `oneof` group and the elements of a repeated field.
+    fn to_fixed64_field_always<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        let tag = Tag::new::<FIELD_NUMBER>(WireType::Fixed64);
 
         let size1 = tag.to_varint(buffer);
         let size2 = self.to_fixed64(buffer);
@@ -83,6 +189,7 @@ pub trait Fixed64Field: Fixed64 {
 
 /// This trait can be applied to types to enable them to encode and decode value to and from
 /// Protocol Buffers with the Length Delimited wire type.
+#[allow(clippy::wrong_self_convention)]
 pub trait LengthDelimited {
     /// This function takes a buffer and converts the attached type to a Protocol Buffer encoded
     /// with the Length Delimited wire type.
@@ -97,13 +204,166 @@ pub trait LengthDelimited {
 ///
 /// These fields include the field number, the type of wire type encoding and the value of the
 /// attached type encoded.
-pub trait LengthDelimitedField: LengthDelimited {
-    /// This function writes out a fixed32 field to the Protocol Buffer.
-    fn to_length_delimited_field(&self, field_number: u32, buffer: &mut Buffer) -> usize {
-        let tag = Tag::new(field_number, WireType::LengthDelimited);
+///
+/// The field number is a constant, so an invalid field number is rejected when compiling, see
+/// [`Tag::new`].
+pub trait LengthDelimitedField: LengthDelimited + Presence {
+    /// This function writes out a length delimited field to the Protocol Buffer. Nothing is
+    /// written when the value is not present, such as a proto3 scalar holding its default value.
+    fn to_length_delimited_field<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        if !self.is_present() {
+            return 0;
+        }
+
+        self.to_length_delimited_field_always::<FIELD_NUMBER>(buffer)
+    }
+
+    /// This function writes out a length delimited field to the Protocol Buffer regardless of
+    /// whether the value is present. It is used for values that are always written, such as the
+    /// members of a `oneof` group and the elements of a repeated field.
+    fn to_length_delimited_field_always<const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize {
+        let tag = Tag::new::<FIELD_NUMBER>(WireType::LengthDelimited);
 
         let size1 = tag.to_varint(buffer);
         let size2 = self.to_length_delimited(buffer);
         size1 + size2
     }
 }
+
+/// This trait can be applied to types to enable them to encode and decode value to and from
+/// Protocol Buffers as groups, which are delimited by a start and an end group tag rather than by
+/// a length.
+#[allow(clippy::wrong_self_convention)]
+pub trait Group {
+    /// This function writes out the contents of the group followed by the end group tag with the
+    /// given field number. The start group tag is not written.
+    fn to_group<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize;
+
+    /// This function extracts the contents of a group, starting immediately after the start group
+    /// tag. The end group tag is consumed and must have the given field number.
+    fn from_group(&mut self, field_number: u32, buffer: &[u8]) -> Result<usize, DecodeError>;
+}
+
+/// This trait can be applied to types to enable them to encode group fields.
+///
+/// These fields include the start group tag, the contents of the group and the end group tag.
+///
+/// The field number is a constant, so an invalid field number is rejected when compiling, see
+/// [`Tag::new`].
+pub trait GroupField: Group + Presence {
+    /// This function writes out a group field to the Protocol Buffer. Nothing is written when the
+    /// value is not present.
+    fn to_group_field<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        if !self.is_present() {
+            return 0;
+        }
+
+        self.to_group_field_always::<FIELD_NUMBER>(buffer)
+    }
+
+    /// This function writes out a group field to the Protocol Buffer regardless of whether the
+    /// value is present.
+    fn to_group_field_always<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        let tag = Tag::new::<FIELD_NUMBER>(WireType::StartGroup);
+
+        let size1 = tag.to_varint(buffer);
+        let size2 = self.to_group::<FIELD_NUMBER>(buffer);
+        size1 + size2
+    }
+}
+
+/// This trait selects the wire type used to encode and decode values of a type when the encoding
+/// can not be chosen by calling the encoding traits directly, such as for map keys and values.
+pub trait FieldCodec<T> {
+    /// The wire type the values are encoded with.
+    const WIRE_TYPE: WireType;
+
+    /// This function writes out the value to the Protocol Buffer without a tag.
+    fn encode(value: &T, buffer: &mut Buffer) -> usize;
+
+    /// This function extracts the value from the Protocol Buffer.
+    fn decode(value: &mut T, buffer: &[u8]) -> Result<usize, DecodeError>;
+}
+
+/// Encodes values with the Varint wire type.
+#[derive(Debug)]
+pub struct VarintCodec;
+
+impl<T: Varint> FieldCodec<T> for VarintCodec {
+    const WIRE_TYPE: WireType = WireType::Varint;
+
+    fn encode(value: &T, buffer: &mut Buffer) -> usize {
+        value.to_varint(buffer)
+    }
+
+    fn decode(value: &mut T, buffer: &[u8]) -> Result<usize, DecodeError> {
+        value.from_varint(buffer)
+    }
+}
+
+/// Encodes values as sign extended varints with the Varint wire type.
+#[derive(Debug)]
+pub struct SignExtendedVarintCodec;
+
+impl<T: SignExtendedVarint> FieldCodec<T> for SignExtendedVarintCodec {
+    const WIRE_TYPE: WireType = WireType::Varint;
+
+    fn encode(value: &T, buffer: &mut Buffer) -> usize {
+        value.to_sign_extended_varint(buffer)
+    }
+
+    fn decode(value: &mut T, buffer: &[u8]) -> Result<usize, DecodeError> {
+        value.from_sign_extended_varint(buffer)
+    }
+}
+
+/// Encodes values with the Fixed32 wire type.
+#[derive(Debug)]
+pub struct Fixed32Codec;
+
+impl<T: Fixed32> FieldCodec<T> for Fixed32Codec {
+    const WIRE_TYPE: WireType = WireType::Fixed32;
+
+    fn encode(value: &T, buffer: &mut Buffer) -> usize {
+        value.to_fixed32(buffer)
+    }
+
+    fn decode(value: &mut T, buffer: &[u8]) -> Result<usize, DecodeError> {
+        value.from_fixed32(buffer)
+    }
+}
+
+/// Encodes values with the Fixed64 wire type.
+#[derive(Debug)]
+pub struct Fixed64Codec;
+
+impl<T: Fixed64> FieldCodec<T> for Fixed64Codec {
+    const WIRE_TYPE: WireType = WireType::Fixed64;
+
+    fn encode(value: &T, buffer: &mut Buffer) -> usize {
+        value.to_fixed64(buffer)
+    }
+
+    fn decode(value: &mut T, buffer: &[u8]) -> Result<usize, DecodeError> {
+        value.from_fixed64(buffer)
+    }
+}
+
+/// Encodes values with the Length Delimited wire type.
+#[derive(Debug)]
+pub struct LengthDelimitedCodec;
+
+impl<T: LengthDelimited> FieldCodec<T> for LengthDelimitedCodec {
+    const WIRE_TYPE: WireType = WireType::LengthDelimited;
+
+    fn encode(value: &T, buffer: &mut Buffer) -> usize {
+        value.to_length_delimited(buffer)
+    }
+
+    fn decode(value: &mut T, buffer: &[u8]) -> Result<usize, DecodeError> {
+        value.from_length_delimited(buffer)
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{
+    encode_varint64, Buffer, DecodeError, DecodeMode, Presence, RepeatedField, SignExtendedVarint,
+    Tag, UnknownFields, Varint, VarintCodec, VarintField,
+};
+use std::marker::PhantomData;
+
+/// This trait can be applied to Rust enums that represent a Protocol Buffer enumeration.
+///
+/// It maps the variants to and from their numeric values and names so that they can be used by
+/// the binary, JSON and text formats.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::Enumeration;
+///
+/// #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// enum Colour {
+///     Unspecified = 0,
+///     Red = 1,
+/// }
+///
+/// impl Enumeration for Colour {
+///     const VALUES: &'static [Self] = &[Colour::Unspecified, Colour::Red];
+///
+///     fn number(self) -> i32 {
+///         self as i32
+///     }
+///
+///     fn name(self) -> &'static str {
+///         match self {
+///             Colour::Unspecified => "COLOUR_UNSPECIFIED",
+///             Colour::Red => "RED",
+///         }
+///     }
+/// }
+///
+/// assert_eq!(Colour::from_number(1), Some(Colour::Red));
+/// assert_eq!(Colour::from_name("RED"), Some(Colour::Red));
+/// ```
+pub trait Enumeration: Copy + 'static {
+    /// Every variant of the enumeration.
+    const VALUES: &'static [Self];
+
+    /// This function returns the numeric value of the variant.
+    fn number(self) -> i32;
+
+    /// This function returns the name of the variant as declared in the `.proto` file.
+    fn name(self) -> &'static str;
+
+    /// This function returns the variant with the given numeric value.
+    fn from_number(number: i32) -> Option<Self> {
+        Self::VALUES.iter().copied().find(|v| v.number() == number)
+    }
+
+    /// This function returns the variant with the given name.
+    fn from_name(name: &str) -> Option<Self> {
+        Self::VALUES.iter().copied().find(|v| v.name() == name)
+    }
+}
+
+/// Holds the value of an open enumeration field.
+///
+/// Proto3 enumerations are open, so a field can hold numeric values that do not have a variant.
+/// These values are preserved on decode so that they are written back out unchanged.
+///
+/// The value is encoded as a sign extended varint, so negative values always use 10 bytes.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Buffer, Enumeration, OpenEnum, Varint};
+///
+/// #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// enum Colour {
+///     Unspecified = 0,
+/// }
+///
+/// impl Enumeration for Colour {
+///     const VALUES: &'static [Self] = &[Colour::Unspecified];
+///
+///     fn number(self) -> i32 {
+///         self as i32
+///     }
+///
+///     fn name(self) -> &'static str {
+///         "COLOUR_UNSPECIFIED"
+///     }
+/// }
+///
+/// let mut value: OpenEnum<Colour> = OpenEnum::default();
+/// value.from_varint(&[7]).unwrap();
+///
+/// assert_eq!(value.known(), None);
+/// assert_eq!(value.number(), 7);
+/// ```
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct OpenEnum<E> {
+    number: i32,
+    enumeration: PhantomData<E>,
+}
+
+impl<E: Enumeration> OpenEnum<E> {
+    /// Creates a value from a numeric value, which may not have a variant.
+    pub fn from_number(number: i32) -> Self {
+        Self {
+            number,
+            enumeration: PhantomData,
+        }
+    }
+
+    /// Creates a value from the name of a variant.
+    pub fn from_name(name: &str) -> Option<Self> {
+        E::from_name(name).map(Self::from)
+    }
+
+    /// Returns the numeric value.
+    pub fn number(&self) -> i32 {
+        self.number
+    }
+
+    /// Returns the variant for the numeric value, or `None` when the value is unknown.
+    pub fn known(&self) -> Option<E> {
+        E::from_number(self.number)
+    }
+
+    /// Returns the name of the variant, or `None` when the value is unknown.
+    pub fn name(&self) -> Option<&'static str> {
+        self.known().map(E::name)
+    }
+}
+
+impl<E> Default for OpenEnum<E> {
+    fn default() -> Self {
+        Self {
+            number: 0,
+            enumeration: PhantomData,
+        }
+    }
+}
+
+impl<E: Enumeration> From<E> for OpenEnum<E> {
+    fn from(value: E) -> Self {
+        Self::from_number(value.number())
+    }
+}
+
+impl<E: Enumeration> Varint for OpenEnum<E> {
+    fn to_varint(&self, buffer: &mut Buffer) -> usize {
+        encode_varint64(self.number as i64 as u64, buffer)
+    }
+
+    // Strict decoding only accepts the values of the enumeration's variants.
+    fn from_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        let size = self.number.from_sign_extended_varint(buffer)?;
+        if DecodeMode::is_strict() && self.known().is_none() {
+            return Err(DecodeError::UnknownEnumValue(self.number));
+        }
+        Ok(size)
+    }
+}
+
+impl<E> Presence for OpenEnum<E> {
+    fn is_present(&self) -> bool {
+        self.number != 0
+    }
+}
+
+impl<E: Enumeration> VarintField for OpenEnum<E> {}
+
+/// Decodes a value of a closed enumeration field, passing it to the set function only when it is
+/// a variant of the enumeration.
+///
+/// Proto2 enumerations are closed, so numeric values without a variant are treated as unknown
+/// fields rather than being held by the field. `None` is returned for them, so that the message
+/// keeps the field with its unknown fields.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{decode_closed_enum, NullValue, OpenEnum};
+///
+/// let mut value: Option<OpenEnum<NullValue>> = None;
+///
+/// let size = decode_closed_enum(&[5], |v| value = Some(v)).unwrap();
+///
+/// assert_eq!(size, None);
+/// assert_eq!(value, None);
+/// ```
+pub fn decode_closed_enum<E: Enumeration>(
+    buffer: &[u8],
+    set: impl FnOnce(OpenEnum<E>),
+) -> Result<Option<usize>, DecodeError> {
+    let mut value = OpenEnum::default();
+    let size = value.from_varint(buffer)?;
+
+    if value.known().is_none() {
+        return Ok(None);
+    }
+    set(value);
+    Ok(Some(size))
+}
+
+/// Decodes either a single element or a packed run of elements of a repeated closed enumeration
+/// field, appending those that are variants of the enumeration. The others are kept with the
+/// unknown fields as unpacked elements of the field.
+pub fn decode_closed_enums<E: Enumeration>(
+    values: &mut Vec<OpenEnum<E>>,
+    unknown_fields: &mut UnknownFields,
+    tag: &Tag,
+    buffer: &[u8],
+) -> Result<Option<usize>, DecodeError> {
+    let mut decoded: Vec<OpenEnum<E>> = Vec::new();
+    let size = decoded.from_repeated_field::<VarintCodec>(tag.wire_type(), buffer)?;
+
+    for value in decoded {
+        match value.known() {
+            Some(_) => values.push(value),
+            None => unknown_fields.push_varint_with_tag(tag, value.number() as i64 as u64),
+        }
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WireType;
+    use rstest::*;
+
+    #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+    enum Status {
+        Unknown = 0,
+        Active = 1,
+        Retired = -1,
+    }
+
+    impl Enumeration for Status {
+        const VALUES: &'static [Self] = &[Status::Unknown, Status::Active, Status::Retired];
+
+        fn number(self) -> i32 {
+            self as i32
+        }
+
+        fn name(self) -> &'static str {
+            match self {
+                Status::Unknown => "STATUS_UNKNOWN",
+                Status::Active => "STATUS_ACTIVE",
+                Status::Retired => "STATUS_RETIRED",
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(OpenEnum::from(Status::Unknown), vec![0])]
+    #[case(OpenEnum::from(Status::Active), vec![1])]
+    #[case(OpenEnum::from(Status::Retired), vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1])]
+    #[case(OpenEnum::from_number(300), vec![172, 2])]
+    fn test_enum_encoding(#[case] value: OpenEnum<Status>, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_varint(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(vec![1], 1, Some(Status::Active))]
+    #[case(vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1], -1, Some(Status::Retired))]
+    #[case(vec![255, 255, 255, 255, 15], -1, Some(Status::Retired))]
+    #[case(vec![172, 2], 300, None)]
+    fn test_enum_decoding(
+        #[case] buffer: Vec<u8>,
+        #[case] expected_number: i32,
+        #[case] expected_variant: Option<Status>,
+    ) {
+        // Arrange
+        let mut value: OpenEnum<Status> = OpenEnum::default();
+
+        // Act
+        let size = value.from_varint(&buffer).unwrap();
+
+        // Assert
+        assert_eq!(value.number(), expected_number);
+        assert_eq!(value.known(), expected_variant);
+        assert_eq!(size, buffer.len());
+    }
+
+    #[test]
+    fn test_unknown_enum_value_round_trip() {
+        // Arrange
+        let mut value: OpenEnum<Status> = OpenEnum::default();
+        let mut buffer = Buffer::default();
+        let _ = value.from_varint(&[172, 2]).unwrap();
+
+        // Act
+        let _ = value.to_varint_field::<4>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), vec![32, 172, 2]);
+    }
+
+    #[rstest]
+    #[case(vec![1], Ok(1))]
+    #[case(vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1], Ok(-1))]
+    #[case(vec![172, 2], Err(DecodeError::UnknownEnumValue(300)))]
+    fn test_strict_enum_decoding(
+        #[case] buffer: Vec<u8>,
+        #[case] expected_result: Result<i32, DecodeError>,
+    ) {
+        // Arrange
+        let mut value: OpenEnum<Status> = OpenEnum::default();
+
+        // Act
+        let result = DecodeMode::Strict.scope(|| value.from_varint(&buffer));
+
+        // Assert
+        assert_eq!(result.map(|_| value.number()), expected_result);
+    }
+
+    #[rstest]
+    #[case(vec![1], Some(1), Some(OpenEnum::from(Status::Active)))]
+    #[case(vec![2], None, None)]
+    fn test_closed_enum_decoding(
+        #[case] buffer: Vec<u8>,
+        #[case] expected_size: Option<usize>,
+        #[case] expected_value: Option<OpenEnum<Status>>,
+    ) {
+        // Arrange
+        let mut value = None;
+
+        // Act
+        let size = decode_closed_enum(&buffer, |v| value = Some(v)).unwrap();
+
+        // Assert
+        assert_eq!(size, expected_size);
+        assert_eq!(value, expected_value);
+    }
+
+    #[test]
+    fn test_closed_enum_packed_decoding() {
+        // Arrange
+        let mut values = vec![OpenEnum::from(Status::Unknown)];
+        let mut unknown_fields = UnknownFields::default();
+        let tag = Tag::new::<4>(WireType::LengthDelimited);
+
+        // Act
+        let size =
+            decode_closed_enums(&mut values, &mut unknown_fields, &tag, &[3, 1, 7, 0]).unwrap();
+
+        // Assert
+        assert_eq!(size, Some(4));
+        assert_eq!(
+            values,
+            vec![
+                OpenEnum::from(Status::Unknown),
+                OpenEnum::from(Status::Active),
+                OpenEnum::from(Status::Unknown)
+            ]
+        );
+        assert_eq!(unknown_fields.as_bytes(), &[32, 7]);
+    }
+
+    #[rstest]
+    #[case("STATUS_ACTIVE", Some(Status::Active))]
+    #[case("STATUS_RETIRED", Some(Status::Retired))]
+    #[case("ACTIVE", None)]
+    fn test_enum_name_lookup(#[case] name: &str, #[case] expected_variant: Option<Status>) {
+        // Act
+        let value = OpenEnum::<Status>::from_name(name);
+
+        // Assert
+        assert_eq!(value.and_then(|v| v.known()), expected_variant);
+        assert_eq!(
+            value.and_then(|v| v.name()),
+            expected_variant.map(Status::name)
+        );
+    }
+}
@@ -17,4 +17,169 @@ pub enum DecodeError {
 
     #[error("The wire type was unrecognised during decoding.")]
     UnknownWireType,
+
+    #[error("The required field {0} is not set.")]
+    MissingRequiredField(String),
+
+    #[error("The field number 0 is not valid.")]
+    InvalidFieldNumber,
+
+    #[error("The varint was encoded with more bytes than needed.")]
+    NonCanonicalVarint,
+
+    #[error("The field {0} is set more than once.")]
+    DuplicateField(u32),
+
+    #[error("The enum value {0} is not known.")]
+    UnknownEnumValue(i32),
+
+    #[error(
+        "The messages are nested more than {} levels deep.",
+        crate::RECURSION_LIMIT
+    )]
+    RecursionLimitExceeded,
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum EncodeError {
+    #[error("The field number {0} is not between 1 and 536870911.")]
+    FieldNumberOutOfRange(u32),
+
+    #[error("The field number {0} is reserved for the Protocol Buffers implementation.")]
+    ReservedFieldNumber(u32),
+}
+
+#[cfg(feature = "json")]
+#[derive(Debug, Error, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum JsonError {
+    #[error("The number can not be represented in JSON.")]
+    NonFiniteNumber,
+
+    #[error("The value does not have a kind set.")]
+    ValueKindNotSet,
+
+    #[error("The JSON value was not of the expected type.")]
+    UnexpectedType,
+
+    #[error("The JSON could not be parsed: {0}")]
+    Syntax(String),
+
+    #[error("The message {message} does not have the field {field}.")]
+    UnknownField { message: String, field: String },
+
+    #[error("The field {0} is set more than once.")]
+    DuplicateField(String),
+
+    #[error("The value of {field} must be {expected}.")]
+    InvalidValue { field: String, expected: String },
+
+    #[error("The type {0} is not known.")]
+    UnknownType(String),
+
+    #[error("The message could not be decoded: {0}")]
+    Decode(#[from] DecodeError),
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum DescriptorError {
+    #[error("The descriptors could not be decoded: {0}")]
+    Decode(#[from] DecodeError),
+
+    #[error("The file {0} has already been added with different contents.")]
+    DuplicateFile(String),
+
+    #[error("The file {file} imports {dependency}, which has not been added.")]
+    MissingDependency { file: String, dependency: String },
+
+    #[error("The name {0} is defined more than once.")]
+    DuplicateName(String),
+
+    #[error("The field number {number} is used more than once in {message}.")]
+    DuplicateFieldNumber { message: String, number: i32 },
+
+    #[error("The type {type_name} used by {name} could not be resolved.")]
+    UnresolvedTypeName { name: String, type_name: String },
+
+    #[error("The field {0} does not have a type.")]
+    MissingFieldType(String),
+
+    #[error("The number {number} of the extension {extension} is not within an extension range of {message}.")]
+    ExtensionNumberOutOfRange {
+        extension: String,
+        message: String,
+        number: i32,
+    },
+
+    #[error("The edition {edition} of the file {file} is not supported.")]
+    UnsupportedEdition { file: String, edition: i32 },
+
+    #[error("The number of the field {field} is not valid: {error}")]
+    InvalidFieldNumber { field: String, error: EncodeError },
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum FieldError {
+    #[error("The message does not have the field {0}.")]
+    NotFound(String),
+
+    #[error("The value does not match the type of the field {0}.")]
+    InvalidType(String),
+
+    #[error("The message could not be rebuilt from its fields: {0}")]
+    Decode(#[from] DecodeError),
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum CodegenError {
+    #[error("The parameter {0} is not recognised.")]
+    InvalidParameter(String),
+
+    #[error(transparent)]
+    Descriptor(#[from] DescriptorError),
+
+    #[error("The file {0} was not found.")]
+    UnknownFile(String),
+
+    #[error("Code can not be generated for {0}.")]
+    Unsupported(String),
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum CompileError {
+    #[error("The file {0} could not be found.")]
+    FileNotFound(String),
+
+    #[error("The file {file} could not be read: {message}")]
+    Io { file: String, message: String },
+
+    #[error("The files import each other in a cycle: {0}.")]
+    ImportCycle(String),
+
+    #[error("The file {file} is invalid at line {line}, column {column}: {message}")]
+    Source {
+        file: String,
+        line: usize,
+        column: usize,
+        message: String,
+    },
+
+    #[error(transparent)]
+    Descriptor(#[from] DescriptorError),
+}
+
+#[derive(Debug, Error, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub enum TextFormatError {
+    #[error("The text is invalid at line {line}, column {column}: {message}")]
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
 }
@@ -0,0 +1,394 @@
+use crate::{
+    skip_tagged_field, Buffer, DecodeError, EncodeError, FieldCodec, LengthDelimited,
+    RepeatedField, Tag, Varint, WireType,
+};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// Identifies an extension of a proto2 message by its field number, along with the type of its
+/// value and the codec that encodes it. Generated code declares a constant for every extension.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Extension, VarintCodec};
+///
+/// const PRIORITY: Extension<u32, VarintCodec> = Extension::new::<100>();
+///
+/// assert_eq!(PRIORITY.number(), 100);
+/// ```
+pub struct Extension<T, C> {
+    number: u32,
+    packed: bool,
+    value: PhantomData<fn() -> (T, C)>,
+}
+
+impl<T, C> Extension<T, C> {
+    /// Creates an extension with the given field number. The field number is a constant, so an
+    /// invalid field number fails to compile. Use [`Extension::try_new`] for field numbers which
+    /// are only known at run time.
+    ///
+    /// ```compile_fail
+    /// # use ks_protobuf_v3::*;
+    /// const PRIORITY: Extension<u32, VarintCodec> = Extension::new::<19000>();
+    /// let _ = PRIORITY.number();
+    /// ```
+    pub const fn new<const FIELD_NUMBER: u32>() -> Self {
+        const {
+            assert!(
+                Tag::validate_field_number(FIELD_NUMBER).is_ok(),
+                "the field number can not be used by a field"
+            );
+        }
+        Self {
+            number: FIELD_NUMBER,
+            packed: false,
+            value: PhantomData,
+        }
+    }
+
+    /// Creates a repeated extension with the given field number whose elements are written as a
+    /// single packed field. Like [`Extension::new`], an invalid field number fails to compile.
+    pub const fn packed<const FIELD_NUMBER: u32>() -> Self {
+        const {
+            assert!(
+                Tag::validate_field_number(FIELD_NUMBER).is_ok(),
+                "the field number can not be used by a field"
+            );
+        }
+        Self {
+            number: FIELD_NUMBER,
+            packed: true,
+            value: PhantomData,
+        }
+    }
+
+    /// Creates an extension after checking that the field number can be used by a field.
+    ///
+    /// ```
+    /// # use ks_protobuf_v3::*;
+    /// assert!(Extension::<u32, VarintCodec>::try_new(100).is_ok());
+    /// assert_eq!(
+    ///     Extension::<u32, VarintCodec>::try_new(1 << 29).unwrap_err(),
+    ///     EncodeError::FieldNumberOutOfRange(1 << 29)
+    /// );
+    /// ```
+    pub fn try_new(number: u32) -> Result<Self, EncodeError> {
+        Tag::validate_field_number(number)?;
+        Ok(Self {
+            number,
+            packed: false,
+            value: PhantomData,
+        })
+    }
+
+    /// Returns the field number of the extension.
+    pub const fn number(&self) -> u32 {
+        self.number
+    }
+}
+
+impl<T, C> fmt::Debug for Extension<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Extension")
+            .field("number", &self.number)
+            .field("packed", &self.packed)
+            .finish()
+    }
+}
+
+/// Holds the extension fields of a message, as they were read from the wire, keyed by their field
+/// number.
+///
+/// The fields are only decoded when they are read through an `Extension`, so a message can hold
+/// extensions that are not known to the code that decoded it and write them out unchanged.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Buffer, Extension, ExtensionSet, VarintCodec};
+///
+/// const PRIORITY: Extension<u32, VarintCodec> = Extension::new::<100>();
+///
+/// let mut extensions = ExtensionSet::default();
+/// extensions.set(&PRIORITY, &7);
+///
+/// let mut buffer = Buffer::default();
+/// let size = extensions.encode(&mut buffer);
+///
+/// assert_eq!(buffer.to_vec(), vec![160, 6, 7]);
+/// assert_eq!(extensions.get(&PRIORITY), Ok(Some(7)));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ExtensionSet {
+    fields: BTreeMap<u32, Vec<u8>>,
+}
+
+impl ExtensionSet {
+    /// Returns true when the extension is set.
+    pub fn has<T, C>(&self, extension: &Extension<T, C>) -> bool {
+        self.fields.contains_key(&extension.number)
+    }
+
+    /// Decodes the value of a singular extension, or returns `None` when it is not set. When the
+    /// extension was read more than once, the last scalar wins and messages are merged.
+    pub fn get<T: Default, C: FieldCodec<T>>(
+        &self,
+        extension: &Extension<T, C>,
+    ) -> Result<Option<T>, DecodeError> {
+        let mut value = None;
+
+        self.for_each_field(extension.number, |wire_type, buffer| {
+            if wire_type != C::WIRE_TYPE {
+                return Ok(None);
+            }
+            C::decode(value.get_or_insert_with(T::default), buffer).map(Some)
+        })?;
+        Ok(value)
+    }
+
+    /// Decodes the elements of a repeated extension, which are accepted both packed and unpacked.
+    pub fn get_repeated<T: Default, C: FieldCodec<T>>(
+        &self,
+        extension: &Extension<Vec<T>, C>,
+    ) -> Result<Vec<T>, DecodeError> {
+        let mut values = Vec::new();
+
+        self.for_each_field(extension.number, |wire_type, buffer| {
+            values.from_repeated_field::<C>(wire_type, buffer)
+        })?;
+        Ok(values)
+    }
+
+    /// Sets the value of a singular extension, replacing any value it held.
+    pub fn set<T, C: FieldCodec<T>>(&mut self, extension: &Extension<T, C>, value: &T) {
+        let mut buffer = Buffer::default();
+        let _ = Tag::new_unchecked(extension.number, C::WIRE_TYPE).to_varint(&mut buffer);
+        let _ = C::encode(value, &mut buffer);
+
+        let _ = self.fields.insert(extension.number, buffer.to_vec());
+    }
+
+    /// Sets the elements of a repeated extension, replacing any elements it held.
+    pub fn set_repeated<T, C: FieldCodec<T>>(
+        &mut self,
+        extension: &Extension<Vec<T>, C>,
+        values: &[T],
+    ) {
+        let mut buffer = Buffer::default();
+        if extension.packed && !values.is_empty() {
+            let mut elements = Buffer::default();
+            for value in values {
+                let _ = C::encode(value, &mut elements);
+            }
+
+            let _ = Tag::new_unchecked(extension.number, WireType::LengthDelimited)
+                .to_varint(&mut buffer);
+            let _ = elements.to_vec().to_length_delimited(&mut buffer);
+        } else {
+            for value in values {
+                let _ = Tag::new_unchecked(extension.number, C::WIRE_TYPE).to_varint(&mut buffer);
+                let _ = C::encode(value, &mut buffer);
+            }
+        }
+
+        let _ = self.fields.insert(extension.number, buffer.to_vec());
+    }
+
+    /// Removes the extension, returning true when it was set.
+    pub fn clear<T, C>(&mut self, extension: &Extension<T, C>) -> bool {
+        self.fields.remove(&extension.number).is_some()
+    }
+
+    /// This function writes out every extension field, in field number order.
+    pub fn encode(&self, buffer: &mut Buffer) -> usize {
+        self.fields
+            .values()
+            .map(|field| {
+                buffer.put_slice(field);
+                field.len()
+            })
+            .sum()
+    }
+
+    /// This function writes out the extension fields whose numbers are in the range, in field
+    /// number order, so that they can be written among the fields of the message.
+    pub fn encode_range(&self, numbers: Range<u32>, buffer: &mut Buffer) -> usize {
+        self.fields
+            .range(numbers)
+            .map(|(_, field)| {
+                buffer.put_slice(field);
+                field.len()
+            })
+            .sum()
+    }
+
+    /// This function merges the other extensions into these, as though their fields had been
+    /// read after these. Reading a singular extension then takes the last scalar or merges the
+    /// messages, and repeated extensions are appended.
+    pub fn merge(&mut self, other: ExtensionSet) {
+        for (number, field) in other.fields {
+            self.fields.entry(number).or_default().extend(field);
+        }
+    }
+
+    /// This function keeps a single extension field without decoding it. The buffer starts
+    /// immediately after the tag and the number of bytes consumed by the value is returned.
+    pub fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<usize, DecodeError> {
+        let size = skip_tagged_field(tag, buffer)?;
+
+        let mut field = Buffer::default();
+        let _ = tag.to_varint(&mut field);
+        let stored = self.fields.entry(tag.field_number()).or_default();
+        stored.extend(field.to_vec());
+        stored.extend_from_slice(&buffer[..size]);
+        Ok(size)
+    }
+
+    // Passes the wire type and value of every field kept for the field number to the decode
+    // function, which returns `None` when it can not use the value.
+    fn for_each_field(
+        &self,
+        number: u32,
+        mut decode: impl FnMut(WireType, &[u8]) -> Result<Option<usize>, DecodeError>,
+    ) -> Result<(), DecodeError> {
+        let fields = match self.fields.get(&number) {
+            Some(fields) => fields.as_slice(),
+            None => return Ok(()),
+        };
+
+        let mut position = 0;
+        while position < fields.len() {
+            let mut tag = Tag::new_unchecked(0, WireType::Varint);
+            position += tag.from_varint(&fields[position..])?;
+
+            let value = &fields[position..];
+            position += match decode(tag.wire_type(), value)? {
+                Some(size) => size,
+                None => skip_tagged_field(&tag, value)?,
+            };
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LengthDelimitedCodec, ListValue, SignExtendedVarintCodec, Value, VarintCodec};
+    use rstest::*;
+
+    const PRIORITY: Extension<i32, SignExtendedVarintCodec> = Extension::new::<100>();
+    const TAGS: Extension<Vec<u32>, VarintCodec> = Extension::packed::<101>();
+    const LIST: Extension<ListValue, LengthDelimitedCodec> = Extension::new::<102>();
+
+    fn decode(bytes: &[u8]) -> ExtensionSet {
+        let mut extensions = ExtensionSet::default();
+        let mut position = 0;
+        while position < bytes.len() {
+            let mut tag = Tag::new_unchecked(0, WireType::Varint);
+            position += tag.from_varint(&bytes[position..]).unwrap();
+            position += extensions.decode_field(&tag, &bytes[position..]).unwrap();
+        }
+        extensions
+    }
+
+    #[rstest]
+    #[case(vec![160, 6, 1, 160, 6, 2], Some(2))]
+    #[case(vec![165, 6, 1, 0, 0, 0], None)]
+    #[case(vec![168, 6, 1], None)]
+    fn test_singular_extension_decoding(#[case] bytes: Vec<u8>, #[case] expected: Option<i32>) {
+        // Arrange
+        let extensions = decode(&bytes);
+
+        // Act
+        let value = extensions.get(&PRIORITY).unwrap();
+
+        // Assert
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn test_repeated_extension_decoding() {
+        // Arrange
+        let extensions = decode(&[168, 6, 1, 170, 6, 2, 2, 3]);
+
+        // Act
+        let values = extensions.get_repeated(&TAGS).unwrap();
+
+        // Assert
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_message_extensions_are_merged() {
+        // Arrange
+        let extensions = decode(&[178, 6, 4, 10, 2, 32, 1, 178, 6, 2, 10, 0]);
+
+        // Act
+        let list = extensions.get(&LIST).unwrap().unwrap();
+
+        // Assert
+        assert_eq!(list.values.len(), 2);
+        assert_eq!(list.values[1], Value::default());
+    }
+
+    #[rstest]
+    #[case(100..102, vec![160, 6, 1, 168, 6, 2])]
+    #[case(101..200, vec![168, 6, 2, 176, 6, 3])]
+    #[case(200..300, vec![])]
+    fn test_extension_ranges_are_written(
+        #[case] numbers: Range<u32>,
+        #[case] expected_buffer: Vec<u8>,
+    ) {
+        // Arrange
+        let extensions = decode(&[176, 6, 3, 160, 6, 1, 168, 6, 2]);
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = extensions.encode_range(numbers, &mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[test]
+    fn test_extensions_are_written_in_field_number_order() {
+        // Arrange
+        let mut extensions = decode(&[178, 6, 0]);
+
+        // Act
+        extensions.set_repeated(&TAGS, &[1, 2]);
+        extensions.set(&PRIORITY, &-1);
+        let cleared = extensions.clear(&LIST);
+        let mut buffer = Buffer::default();
+        let size = extensions.encode(&mut buffer);
+
+        // Assert
+        assert!(cleared);
+        assert_eq!(
+            buffer.to_vec(),
+            vec![160, 6, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1, 170, 6, 2, 1, 2]
+        );
+        assert_eq!(size, 17);
+    }
+
+    #[test]
+    fn test_merged_extensions_match_concatenated_decoding() {
+        // Arrange
+        let first = [160, 6, 1, 168, 6, 1, 178, 6, 4, 10, 2, 32, 1];
+        let second = [160, 6, 2, 170, 6, 2, 2, 3, 178, 6, 2, 10, 0];
+        let mut extensions = decode(&first);
+
+        // Act
+        extensions.merge(decode(&second));
+
+        // Assert
+        let concatenated = decode(&[first, second].concat());
+        assert_eq!(extensions.get(&PRIORITY), concatenated.get(&PRIORITY));
+        assert_eq!(extensions.get(&PRIORITY).unwrap(), Some(2));
+        assert_eq!(extensions.get_repeated(&TAGS).unwrap(), vec![1, 2, 3]);
+        assert_eq!(extensions.get(&LIST).unwrap().unwrap().values.len(), 2);
+    }
+}
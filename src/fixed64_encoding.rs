@@ -37,7 +37,7 @@ pub fn encode_fixed64(value: u64, buffer: &mut Buffer) -> usize {
 /// let (value, len) = decode_fixed64(&buffer).unwrap().unwrap();
 /// ```
 pub fn decode_fixed64(data: &[u8]) -> Result<Option<(u64, usize)>, DecodeError> {
-    if data.len() < 4 {
+    if data.len() < 8 {
         Ok(None)
     } else {
         let value: u64 = data[0] as u64
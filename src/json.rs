@@ -0,0 +1,157 @@
+use crate::{JsonError, Kind, ListValue, NullValue, Struct, Value};
+
+impl From<serde_json::Value> for Value {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::null(),
+            serde_json::Value::Bool(v) => Value::from(v),
+            serde_json::Value::Number(v) => Value::from(v.as_f64().unwrap_or_default()),
+            serde_json::Value::String(v) => Value::from(v),
+            serde_json::Value::Array(v) => Value::from(ListValue::from(v)),
+            serde_json::Value::Object(v) => Value::from(Struct::from(v)),
+        }
+    }
+}
+
+impl TryFrom<&Value> for serde_json::Value {
+    type Error = JsonError;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match &value.kind {
+            Some(Kind::NullValue(NullValue::NullValue)) => Ok(serde_json::Value::Null),
+            Some(Kind::NumberValue(v)) => serde_json::Number::from_f64(*v)
+                .map(serde_json::Value::Number)
+                .ok_or(JsonError::NonFiniteNumber),
+            Some(Kind::StringValue(v)) => Ok(serde_json::Value::String(v.clone())),
+            Some(Kind::BoolValue(v)) => Ok(serde_json::Value::Bool(*v)),
+            Some(Kind::StructValue(v)) => serde_json::Value::try_from(v),
+            Some(Kind::ListValue(v)) => serde_json::Value::try_from(v),
+            None => Err(JsonError::ValueKindNotSet),
+        }
+    }
+}
+
+impl TryFrom<Value> for serde_json::Value {
+    type Error = JsonError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        serde_json::Value::try_from(&value)
+    }
+}
+
+impl From<serde_json::Map<String, serde_json::Value>> for Struct {
+    fn from(fields: serde_json::Map<String, serde_json::Value>) -> Self {
+        fields.into_iter().collect()
+    }
+}
+
+impl TryFrom<serde_json::Value> for Struct {
+    type Error = JsonError;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        match value {
+            serde_json::Value::Object(fields) => Ok(Struct::from(fields)),
+            _ => Err(JsonError::UnexpectedType),
+        }
+    }
+}
+
+impl TryFrom<&Struct> for serde_json::Value {
+    type Error = JsonError;
+
+    fn try_from(value: &Struct) -> Result<Self, Self::Error> {
+        let mut fields = serde_json::Map::new();
+
+        for (key, value) in &value.fields {
+            let _ = fields.insert(key.clone(), serde_json::Value::try_from(value)?);
+        }
+
+        Ok(serde_json::Value::Object(fields))
+    }
+}
+
+impl From<Vec<serde_json::Value>> for ListValue {
+    fn from(values: Vec<serde_json::Value>) -> Self {
+        values.into_iter().collect()
+    }
+}
+
+impl TryFrom<serde_json::Value> for ListValue {
+    type Error = JsonError;
+
+    fn try_from(value: serde_json::Value) -> Result<Self, Self::Error> {
+        match value {
+            serde_json::Value::Array(values) => Ok(ListValue::from(values)),
+            _ => Err(JsonError::UnexpectedType),
+        }
+    }
+}
+
+impl TryFrom<&ListValue> for serde_json::Value {
+    type Error = JsonError;
+
+    fn try_from(value: &ListValue) -> Result<Self, Self::Error> {
+        value
+            .values
+            .iter()
+            .map(serde_json::Value::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map(serde_json::Value::Array)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+    use serde_json::json;
+
+    #[rstest]
+    #[case(json!(null), Value::null())]
+    #[case(json!(1.5), Value::from(1.5))]
+    #[case(json!(7), Value::from(7))]
+    #[case(json!("text"), Value::from("text"))]
+    #[case(json!(true), Value::from(true))]
+    #[case(json!([1, "a"]), Value::from(vec![Value::from(1), Value::from("a")]))]
+    fn test_value_from_json(#[case] json: serde_json::Value, #[case] expected_value: Value) {
+        // Act
+        let value = Value::from(json);
+
+        // Assert
+        assert_eq!(value, expected_value);
+    }
+
+    #[test]
+    fn test_struct_json_round_trip() {
+        // Arrange
+        let json = json!({"name": "test", "tags": ["a", "b"], "nested": {"on": true, "off": null}});
+
+        // Act
+        let value = Struct::try_from(json.clone()).unwrap();
+        let result = serde_json::Value::try_from(&value).unwrap();
+
+        // Assert
+        assert_eq!(result, json);
+    }
+
+    #[rstest]
+    #[case(Value::from(f64::NAN), JsonError::NonFiniteNumber)]
+    #[case(Value::from(f64::INFINITY), JsonError::NonFiniteNumber)]
+    #[case(Value::default(), JsonError::ValueKindNotSet)]
+    fn test_invalid_value_to_json(#[case] value: Value, #[case] expected_error: JsonError) {
+        // Act
+        let result = serde_json::Value::try_from(&value);
+
+        // Assert
+        assert_eq!(result, Err(expected_error));
+    }
+
+    #[test]
+    fn test_struct_from_non_object_json() {
+        // Act
+        let result = Struct::try_from(json!([1, 2]));
+
+        // Assert
+        assert_eq!(result, Err(JsonError::UnexpectedType));
+    }
+}
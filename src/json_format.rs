@@ -0,0 +1,1076 @@
+use crate::{
+    DynamicMessage, Enumeration, FieldDescriptor, FieldMapKey, FieldType, FieldValue, JsonError,
+    MessageDescriptor,
+};
+use serde_json::{Map, Number, Value};
+use std::collections::{BTreeMap, HashSet};
+
+// The range of a Timestamp, from 0001-01-01T00:00:00Z to 9999-12-31T23:59:59Z.
+const MIN_TIMESTAMP_SECONDS: i64 = -62_135_596_800;
+const MAX_TIMESTAMP_SECONDS: i64 = 253_402_300_799;
+
+// The range of a Duration, which is about 10,000 years either way.
+const MAX_DURATION_SECONDS: i64 = 315_576_000_000;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl DynamicMessage {
+    /// Parses a message of the given type from its proto3 JSON form. Fields may be named by their
+    /// JSON name or by their name in the `.proto` file, and the well known types are read from
+    /// their own forms, e.g. a `Timestamp` from an RFC 3339 string.
+    pub fn from_json(descriptor: MessageDescriptor, json: &Value) -> Result<Self, JsonError> {
+        let mut message = Self::new(descriptor);
+        merge_message(&mut message, json)?;
+        Ok(message)
+    }
+
+    /// Parses a message of the given type from JSON text in the proto3 JSON form.
+    pub fn parse_json(descriptor: MessageDescriptor, text: &str) -> Result<Self, JsonError> {
+        let json = serde_json::from_str(text).map_err(|e| JsonError::Syntax(e.to_string()))?;
+        Self::from_json(descriptor, &json)
+    }
+
+    /// Returns the proto3 JSON form of the message. Fields that are not set are left out, fields
+    /// are named by their JSON name, 64 bit integers are written as strings and bytes are written
+    /// in base64.
+    pub fn to_json(&self) -> Result<Value, JsonError> {
+        message_to_json(self)
+    }
+}
+
+fn merge_message(message: &mut DynamicMessage, json: &Value) -> Result<(), JsonError> {
+    let descriptor = message.descriptor().clone();
+    let invalid = |expected: &str| JsonError::InvalidValue {
+        field: descriptor.full_name().to_string(),
+        expected: expected.to_string(),
+    };
+
+    let value = match descriptor.full_name() {
+        "google.protobuf.Any" => return merge_any(message, json),
+        "google.protobuf.Timestamp" => {
+            let (seconds, nanos) = json
+                .as_str()
+                .and_then(parse_timestamp)
+                .ok_or_else(|| invalid("an RFC 3339 date and time"))?;
+            set_field(message, 1, FieldValue::I64(seconds))?;
+            return set_field(message, 2, FieldValue::I32(nanos));
+        }
+        "google.protobuf.Duration" => {
+            let (seconds, nanos) = json
+                .as_str()
+                .and_then(parse_duration)
+                .ok_or_else(|| invalid("a number of seconds ending in s"))?;
+            set_field(message, 1, FieldValue::I64(seconds))?;
+            return set_field(message, 2, FieldValue::I32(nanos));
+        }
+        "google.protobuf.FieldMask" => {
+            let paths = json
+                .as_str()
+                .and_then(parse_field_mask)
+                .ok_or_else(|| invalid("a comma separated list of field paths"))?;
+            return set_field(message, 1, FieldValue::List(paths));
+        }
+        "google.protobuf.Value" => {
+            let (number, value) = match json {
+                Value::Null => (1, FieldValue::EnumNumber(0)),
+                Value::Number(v) => (2, FieldValue::F64(v.as_f64().unwrap_or_default())),
+                Value::String(v) => (3, FieldValue::String(v.clone())),
+                Value::Bool(v) => (4, FieldValue::Bool(*v)),
+                Value::Object(_) => (5, message_field_from_json(&descriptor, 5, json)?),
+                Value::Array(_) => (6, message_field_from_json(&descriptor, 6, json)?),
+            };
+            return set_field(message, number, value);
+        }
+        name if name == "google.protobuf.Struct"
+            || name == "google.protobuf.ListValue"
+            || is_wrapper(name) =>
+        {
+            let field = descriptor.get_field(1).ok_or(JsonError::UnexpectedType)?;
+            field_from_json(&field, json)?
+        }
+        _ => {
+            let object = json.as_object().ok_or_else(|| invalid("an object"))?;
+            return merge_object(message, object, &[]);
+        }
+    };
+
+    set_field(message, 1, value)
+}
+
+// Sets the fields named in the object, leaving out the given keys.
+fn merge_object(
+    message: &mut DynamicMessage,
+    object: &Map<String, Value>,
+    skipped: &[&str],
+) -> Result<(), JsonError> {
+    let descriptor = message.descriptor().clone();
+    let mut numbers = HashSet::new();
+    let mut oneofs = HashSet::new();
+
+    for (key, value) in object
+        .iter()
+        .filter(|(k, _)| !skipped.contains(&k.as_str()))
+    {
+        let field = find_field(&descriptor, key).ok_or_else(|| JsonError::UnknownField {
+            message: descriptor.full_name().to_string(),
+            field: key.clone(),
+        })?;
+        if !numbers.insert(field.number()) {
+            return Err(JsonError::DuplicateField(field.full_name().to_string()));
+        }
+
+        // A null leaves the field unset, unless it is the value of the field.
+        if value.is_null() && (field.is_repeated() || !accepts_null(&field)) {
+            continue;
+        }
+        if let Some(oneof) = field.containing_oneof() {
+            if !oneofs.insert(oneof.full_name().to_string()) {
+                return Err(JsonError::DuplicateField(oneof.full_name().to_string()));
+            }
+        }
+
+        let value = field_from_json(&field, value)?;
+        message
+            .set_field(&field, value)
+            .map_err(|_| JsonError::UnexpectedType)?;
+    }
+
+    Ok(())
+}
+
+fn find_field(descriptor: &MessageDescriptor, key: &str) -> Option<FieldDescriptor> {
+    if let Some(name) = key.strip_prefix('[').and_then(|k| k.strip_suffix(']')) {
+        return descriptor
+            .parent_pool()
+            .get_extension_by_name(name)
+            .filter(|extension| extension.containing_message() == descriptor);
+    }
+
+    descriptor
+        .get_field_by_json_name(key)
+        .or_else(|| descriptor.get_field_by_name(key))
+}
+
+fn field_from_json(field: &FieldDescriptor, json: &Value) -> Result<FieldValue, JsonError> {
+    if field.is_map() {
+        let object = json
+            .as_object()
+            .ok_or_else(|| invalid(field, "an object"))?;
+        let entry = field.message_type();
+        let key_field = entry.as_ref().and_then(|e| e.get_field(1));
+        let value_field = entry.as_ref().and_then(|e| e.get_field(2));
+        let (key_field, value_field) = match (key_field, value_field) {
+            (Some(key), Some(value)) => (key, value),
+            _ => return Err(JsonError::UnexpectedType),
+        };
+
+        let mut entries = BTreeMap::new();
+        for (key, value) in object {
+            let key = map_key(&key_field, key)?;
+            let _ = entries.insert(key, value_from_json(&value_field, value)?);
+        }
+        return Ok(FieldValue::Map(entries));
+    }
+
+    if field.is_repeated() {
+        let values = json.as_array().ok_or_else(|| invalid(field, "an array"))?;
+        return values
+            .iter()
+            .map(|value| value_from_json(field, value))
+            .collect::<Result<Vec<_>, _>>()
+            .map(FieldValue::List);
+    }
+
+    value_from_json(field, json)
+}
+
+// Map keys are always strings in JSON, so numbers and booleans are parsed from the string.
+fn map_key(field: &FieldDescriptor, key: &str) -> Result<FieldMapKey, JsonError> {
+    let value = match field.field_type() {
+        FieldType::Bool => match key {
+            "true" => FieldValue::Bool(true),
+            "false" => FieldValue::Bool(false),
+            _ => return Err(invalid(field, "true or false")),
+        },
+        FieldType::String => FieldValue::String(key.to_string()),
+        _ => value_from_json(field, &Value::String(key.to_string()))?,
+    };
+
+    FieldMapKey::try_from(value).map_err(|_| JsonError::UnexpectedType)
+}
+
+fn value_from_json(field: &FieldDescriptor, json: &Value) -> Result<FieldValue, JsonError> {
+    let value = match field.field_type() {
+        FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => integer(json)
+            .and_then(|v| i32::try_from(v).ok())
+            .map(FieldValue::I32),
+        FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => integer(json)
+            .and_then(|v| i64::try_from(v).ok())
+            .map(FieldValue::I64),
+        FieldType::Uint32 | FieldType::Fixed32 => integer(json)
+            .and_then(|v| u32::try_from(v).ok())
+            .map(FieldValue::U32),
+        FieldType::Uint64 | FieldType::Fixed64 => integer(json)
+            .and_then(|v| u64::try_from(v).ok())
+            .map(FieldValue::U64),
+        FieldType::Double => float(json).map(FieldValue::F64),
+        FieldType::Float => float(json)
+            .filter(|v| !v.is_finite() || v.abs() <= f32::MAX as f64)
+            .map(|v| FieldValue::F32(v as f32)),
+        FieldType::Bool => json.as_bool().map(FieldValue::Bool),
+        FieldType::String => json.as_str().map(|v| FieldValue::String(v.to_string())),
+        FieldType::Bytes => json.as_str().and_then(decode_base64).map(FieldValue::Bytes),
+        FieldType::Enum => {
+            let enumeration = field.enum_type();
+            match json {
+                Value::Null if accepts_null(field) => Some(FieldValue::EnumNumber(0)),
+                Value::String(name) => enumeration
+                    .as_ref()
+                    .and_then(|e| e.get_value_by_name(name))
+                    .map(|v| FieldValue::EnumNumber(v.number())),
+                Value::Number(_) => integer(json)
+                    .and_then(|v| i32::try_from(v).ok())
+                    .map(FieldValue::EnumNumber),
+                _ => None,
+            }
+        }
+        FieldType::Message | FieldType::Group => {
+            let message_type = field.message_type().ok_or(JsonError::UnexpectedType)?;
+            return DynamicMessage::from_json(message_type, json).map(FieldValue::Message);
+        }
+    };
+
+    value.ok_or_else(|| invalid(field, &expected(field)))
+}
+
+fn message_field_from_json(
+    descriptor: &MessageDescriptor,
+    number: u32,
+    json: &Value,
+) -> Result<FieldValue, JsonError> {
+    let field = descriptor
+        .get_field(number)
+        .ok_or(JsonError::UnexpectedType)?;
+    value_from_json(&field, json)
+}
+
+// Reads an Any from the fields of its contents and an `@type` key holding the type URL. The
+// contents of the well known types with their own JSON forms are held by a `value` key.
+fn merge_any(message: &mut DynamicMessage, json: &Value) -> Result<(), JsonError> {
+    let object = json.as_object().ok_or_else(|| JsonError::InvalidValue {
+        field: "google.protobuf.Any".to_string(),
+        expected: "an object".to_string(),
+    })?;
+    if object.is_empty() {
+        return Ok(());
+    }
+
+    let type_url =
+        object
+            .get("@type")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonError::InvalidValue {
+                field: "google.protobuf.Any".to_string(),
+                expected: "an object with an @type".to_string(),
+            })?;
+    let contents_type = resolve_type_url(message.descriptor(), type_url)?;
+
+    let mut contents = DynamicMessage::new(contents_type.clone());
+    if has_own_form(contents_type.full_name()) {
+        if let Some(key) = object.keys().find(|k| *k != "@type" && *k != "value") {
+            return Err(JsonError::UnknownField {
+                message: "google.protobuf.Any".to_string(),
+                field: key.clone(),
+            });
+        }
+        merge_message(&mut contents, object.get("value").unwrap_or(&Value::Null))?;
+    } else {
+        merge_object(&mut contents, object, &["@type"])?;
+    }
+
+    set_field(message, 1, FieldValue::String(type_url.to_string()))?;
+    set_field(message, 2, FieldValue::Bytes(contents.encode_to_vec()))
+}
+
+fn message_to_json(message: &DynamicMessage) -> Result<Value, JsonError> {
+    let descriptor = message.descriptor();
+    let field = |number| {
+        let field = descriptor
+            .get_field(number)
+            .ok_or(JsonError::UnexpectedType)?;
+        Ok::<_, JsonError>((field.clone(), message.get_field(&field).into_owned()))
+    };
+
+    match descriptor.full_name() {
+        "google.protobuf.Any" => any_to_json(message),
+        "google.protobuf.Timestamp" => {
+            let seconds = field(1)?.1.as_i64().unwrap_or_default();
+            let nanos = field(2)?.1.as_i32().unwrap_or_default();
+            format_timestamp(seconds, nanos)
+                .map(Value::String)
+                .ok_or_else(|| JsonError::InvalidValue {
+                    field: descriptor.full_name().to_string(),
+                    expected: "between the years 1 and 9999".to_string(),
+                })
+        }
+        "google.protobuf.Duration" => {
+            let seconds = field(1)?.1.as_i64().unwrap_or_default();
+            let nanos = field(2)?.1.as_i32().unwrap_or_default();
+            format_duration(seconds, nanos)
+                .map(Value::String)
+                .ok_or_else(|| JsonError::InvalidValue {
+                    field: descriptor.full_name().to_string(),
+                    expected: "within 10,000 years, with seconds and nanos of the same sign"
+                        .to_string(),
+                })
+        }
+        "google.protobuf.FieldMask" => {
+            let paths = field(1)?.1;
+            let paths = paths.as_list().unwrap_or_default();
+            paths
+                .iter()
+                .map(|path| path.as_str().and_then(format_field_path))
+                .collect::<Option<Vec<_>>>()
+                .map(|paths| Value::String(paths.join(",")))
+                .ok_or_else(|| JsonError::InvalidValue {
+                    field: descriptor.full_name().to_string(),
+                    expected: "paths of lower case field names".to_string(),
+                })
+        }
+        "google.protobuf.Value" => {
+            let (field, value) = message.fields().next().ok_or(JsonError::ValueKindNotSet)?;
+            match value {
+                FieldValue::F64(v) if !v.is_finite() => Err(JsonError::NonFiniteNumber),
+                FieldValue::F64(v) => Ok(Number::from_f64(*v).map_or(Value::Null, Value::Number)),
+                value => value_to_json(&field, value),
+            }
+        }
+        name if name == "google.protobuf.Struct"
+            || name == "google.protobuf.ListValue"
+            || is_wrapper(name) =>
+        {
+            let (field, value) = field(1)?;
+            field_to_json(&field, &value)
+        }
+        _ => {
+            let mut object = Map::new();
+            for (field, value) in message.fields() {
+                let key = match field.is_extension() {
+                    true => format!("[{}]", field.full_name()),
+                    false => field.json_name().to_string(),
+                };
+                let _ = object.insert(key, field_to_json(&field, value)?);
+            }
+            Ok(Value::Object(object))
+        }
+    }
+}
+
+fn any_to_json(message: &DynamicMessage) -> Result<Value, JsonError> {
+    let type_url = message
+        .get_field_by_number(1)
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let value = message
+        .get_field_by_number(2)
+        .and_then(|v| v.as_bytes().map(<[u8]>::to_vec))
+        .unwrap_or_default();
+    if type_url.is_empty() && value.is_empty() {
+        return Ok(Value::Object(Map::new()));
+    }
+
+    let contents_type = resolve_type_url(message.descriptor(), &type_url)?;
+    let has_own_form = has_own_form(contents_type.full_name());
+    let contents = DynamicMessage::decode(contents_type, &value)?.to_json()?;
+
+    let mut object = Map::new();
+    let _ = object.insert("@type".to_string(), Value::String(type_url));
+    match contents {
+        Value::Object(fields) if !has_own_form => object.extend(fields),
+        contents => {
+            let _ = object.insert("value".to_string(), contents);
+        }
+    }
+    Ok(Value::Object(object))
+}
+
+fn field_to_json(field: &FieldDescriptor, value: &FieldValue) -> Result<Value, JsonError> {
+    match value {
+        FieldValue::List(values) => values
+            .iter()
+            .map(|value| value_to_json(field, value))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::Array),
+        FieldValue::Map(entries) => {
+            let value_field = field
+                .message_type()
+                .and_then(|entry| entry.get_field(2))
+                .ok_or(JsonError::UnexpectedType)?;
+
+            let mut object = Map::new();
+            for (key, value) in entries {
+                let key = match key {
+                    FieldMapKey::Bool(v) => v.to_string(),
+                    FieldMapKey::I32(v) => v.to_string(),
+                    FieldMapKey::I64(v) => v.to_string(),
+                    FieldMapKey::U32(v) => v.to_string(),
+                    FieldMapKey::U64(v) => v.to_string(),
+                    FieldMapKey::String(v) => v.clone(),
+                };
+                let _ = object.insert(key, value_to_json(&value_field, value)?);
+            }
+            Ok(Value::Object(object))
+        }
+        value => value_to_json(field, value),
+    }
+}
+
+fn value_to_json(field: &FieldDescriptor, value: &FieldValue) -> Result<Value, JsonError> {
+    Ok(match value {
+        FieldValue::Bool(v) => Value::Bool(*v),
+        FieldValue::I32(v) => Value::from(*v),
+        FieldValue::U32(v) => Value::from(*v),
+        FieldValue::I64(v) => Value::String(v.to_string()),
+        FieldValue::U64(v) => Value::String(v.to_string()),
+        // Floats are widened through their shortest form, so that 0.1 is not written as
+        // 0.10000000149011612.
+        FieldValue::F32(v) => float_to_json(v.to_string().parse().unwrap_or(*v as f64)),
+        FieldValue::F64(v) => float_to_json(*v),
+        FieldValue::String(v) => Value::String(v.clone()),
+        FieldValue::Bytes(v) => Value::String(encode_base64(v)),
+        FieldValue::EnumNumber(v) => {
+            let enumeration = field.enum_type();
+            match enumeration.as_ref().and_then(|e| e.get_value(*v)) {
+                _ if accepts_null(field) => Value::Null,
+                Some(value) => Value::String(value.name().to_string()),
+                None => Value::from(*v),
+            }
+        }
+        FieldValue::Message(message) => message_to_json(message)?,
+        FieldValue::List(_) | FieldValue::Map(_) => field_to_json(field, value)?,
+    })
+}
+
+fn float_to_json(value: f64) -> Value {
+    match Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None if value.is_nan() => Value::String("NaN".to_string()),
+        None if value > 0.0 => Value::String("Infinity".to_string()),
+        None => Value::String("-Infinity".to_string()),
+    }
+}
+
+fn set_field(
+    message: &mut DynamicMessage,
+    number: u32,
+    value: FieldValue,
+) -> Result<(), JsonError> {
+    match message.descriptor().get_field(number) {
+        Some(field) => message
+            .set_field(&field, value)
+            .map_err(|_| JsonError::UnexpectedType),
+        None => Err(JsonError::UnexpectedType),
+    }
+}
+
+fn resolve_type_url(
+    descriptor: &MessageDescriptor,
+    type_url: &str,
+) -> Result<MessageDescriptor, JsonError> {
+    type_url
+        .rsplit_once('/')
+        .and_then(|(_, name)| descriptor.parent_pool().get_message_by_name(name))
+        .ok_or_else(|| JsonError::UnknownType(type_url.to_string()))
+}
+
+// True for fields that hold null itself, rather than treating it as unset.
+fn accepts_null(field: &FieldDescriptor) -> bool {
+    match field.field_type() {
+        FieldType::Enum => field
+            .enum_type()
+            .is_some_and(|e| e.full_name() == "google.protobuf.NullValue"),
+        FieldType::Message => field
+            .message_type()
+            .is_some_and(|m| m.full_name() == "google.protobuf.Value"),
+        _ => false,
+    }
+}
+
+fn is_wrapper(name: &str) -> bool {
+    matches!(
+        name,
+        "google.protobuf.DoubleValue"
+            | "google.protobuf.FloatValue"
+            | "google.protobuf.Int64Value"
+            | "google.protobuf.UInt64Value"
+            | "google.protobuf.Int32Value"
+            | "google.protobuf.UInt32Value"
+            | "google.protobuf.BoolValue"
+            | "google.protobuf.StringValue"
+            | "google.protobuf.BytesValue"
+    )
+}
+
+// True for the well known types that are not written as JSON objects of their fields.
+fn has_own_form(name: &str) -> bool {
+    is_wrapper(name)
+        || matches!(
+            name,
+            "google.protobuf.Any"
+                | "google.protobuf.Timestamp"
+                | "google.protobuf.Duration"
+                | "google.protobuf.FieldMask"
+                | "google.protobuf.Struct"
+                | "google.protobuf.Value"
+                | "google.protobuf.ListValue"
+        )
+}
+
+fn invalid(field: &FieldDescriptor, expected: &str) -> JsonError {
+    JsonError::InvalidValue {
+        field: field.full_name().to_string(),
+        expected: expected.to_string(),
+    }
+}
+
+fn expected(field: &FieldDescriptor) -> String {
+    match field.field_type() {
+        FieldType::Bool => "true or false".to_string(),
+        FieldType::String => "a string".to_string(),
+        FieldType::Bytes => "a base64 string".to_string(),
+        FieldType::Double | FieldType::Float => "a number".to_string(),
+        FieldType::Enum => match field.enum_type() {
+            Some(enumeration) => format!("a value of {}", enumeration.full_name()),
+            None => "an enum value".to_string(),
+        },
+        FieldType::Message | FieldType::Group => "an object".to_string(),
+        field_type => format!(
+            "a number that fits in {}",
+            field_type.name().trim_start_matches("TYPE_").to_lowercase()
+        ),
+    }
+}
+
+// Integers may be written as numbers or strings, including in exponent form, as long as they
+// have no fractional part.
+fn integer(json: &Value) -> Option<i128> {
+    let integral = |v: f64| {
+        (v.is_finite() && v.fract() == 0.0 && v.abs() < 2f64.powi(64)).then_some(v as i128)
+    };
+
+    match json {
+        Value::Number(v) => v
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| v.as_u64().map(i128::from))
+            .or_else(|| v.as_f64().and_then(integral)),
+        Value::String(v) if v.trim() == v => v
+            .parse::<i128>()
+            .ok()
+            .or_else(|| v.parse::<f64>().ok().and_then(integral)),
+        _ => None,
+    }
+}
+
+fn float(json: &Value) -> Option<f64> {
+    match json {
+        Value::Number(v) => v.as_f64(),
+        Value::String(v) => match v.as_str() {
+            "NaN" => Some(f64::NAN),
+            "Infinity" => Some(f64::INFINITY),
+            "-Infinity" => Some(f64::NEG_INFINITY),
+            v if v.trim() == v => v.parse::<f64>().ok().filter(|v| v.is_finite()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Parses an RFC 3339 date and time, such as `1972-01-01T10:00:20.021+05:30`, into seconds and
+// nanoseconds since the Unix epoch.
+fn parse_timestamp(text: &str) -> Option<(i64, i32)> {
+    let bytes = text.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || bytes[10] != b'T'
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = text.get(range)?;
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    };
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let mut rest = &text[19..];
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        nanos = parse_nanos(&fraction[..digits])?;
+        rest = &fraction[digits..];
+    }
+
+    let offset = match rest.as_bytes() {
+        b"Z" => 0,
+        [sign @ (b'+' | b'-'), _, _, b':', _, _] => {
+            let hours = number(text.len() - 5..text.len() - 3)?;
+            let minutes = number(text.len() - 2..text.len())?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * 3600 + minutes * 60;
+            match sign {
+                b'+' => offset,
+                _ => -offset,
+            }
+        }
+        _ => return None,
+    };
+
+    let seconds =
+        days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    (MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS)
+        .contains(&seconds)
+        .then_some((seconds, nanos))
+}
+
+fn format_timestamp(seconds: i64, nanos: i32) -> Option<String> {
+    if !(MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS).contains(&seconds)
+        || !(0..1_000_000_000).contains(&nanos)
+    {
+        return None;
+    }
+
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    Some(format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        format_nanos(nanos)
+    ))
+}
+
+// Parses a duration such as `-1.5s` into seconds and nanoseconds, which share the same sign.
+fn parse_duration(text: &str) -> Option<(i64, i32)> {
+    let text = text.strip_suffix('s')?;
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+    let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+    if whole.is_empty() || !whole.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let seconds: i64 = whole.parse().ok()?;
+    let nanos = match text.contains('.') {
+        true => parse_nanos(fraction)?,
+        false => 0,
+    };
+    if seconds > MAX_DURATION_SECONDS {
+        return None;
+    }
+
+    match negative {
+        true => Some((-seconds, -nanos)),
+        false => Some((seconds, nanos)),
+    }
+}
+
+fn format_duration(seconds: i64, nanos: i32) -> Option<String> {
+    if seconds.abs() > MAX_DURATION_SECONDS
+        || nanos.abs() >= 1_000_000_000
+        || (seconds < 0 && nanos > 0)
+        || (seconds > 0 && nanos < 0)
+    {
+        return None;
+    }
+
+    let sign = match seconds < 0 || nanos < 0 {
+        true => "-",
+        false => "",
+    };
+    Some(format!(
+        "{}{}{}s",
+        sign,
+        seconds.unsigned_abs(),
+        format_nanos(nanos.abs())
+    ))
+}
+
+fn parse_nanos(digits: &str) -> Option<i32> {
+    if digits.is_empty() || digits.len() > 9 || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    format!("{:0<9}", digits).parse().ok()
+}
+
+// Writes the fraction of a second with 3, 6 or 9 digits, or leaves it out when it is zero.
+fn format_nanos(nanos: i32) -> String {
+    match nanos {
+        0 => String::new(),
+        n if n % 1_000_000 == 0 => format!(".{:03}", n / 1_000_000),
+        n if n % 1_000 == 0 => format!(".{:06}", n / 1_000),
+        n => format!(".{:09}", n),
+    }
+}
+
+// Field mask paths are written in lower camel case in JSON, e.g. `user.displayName`.
+fn parse_field_mask(text: &str) -> Option<Vec<FieldValue>> {
+    if text.is_empty() {
+        return Some(Vec::new());
+    }
+
+    text.split(',')
+        .map(|path| {
+            if path.contains('_') {
+                return None;
+            }
+            let mut snake = String::new();
+            for c in path.chars() {
+                if c.is_ascii_uppercase() {
+                    snake.push('_');
+                    snake.push(c.to_ascii_lowercase());
+                } else {
+                    snake.push(c);
+                }
+            }
+            Some(FieldValue::String(snake))
+        })
+        .collect()
+}
+
+fn format_field_path(path: &str) -> Option<String> {
+    let mut camel = String::new();
+    let mut chars = path.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            'A'..='Z' => return None,
+            '_' => match chars.next() {
+                Some(next @ 'a'..='z') => camel.push(next.to_ascii_uppercase()),
+                _ => return None,
+            },
+            c => camel.push(c),
+        }
+    }
+
+    Some(camel)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The number of days since 1970-01-01 of a date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let bits = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |bits, (i, &b)| bits | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => text.push(BASE64_ALPHABET[(bits >> (18 - 6 * i) & 63) as usize] as char),
+                false => text.push('='),
+            }
+        }
+    }
+
+    text
+}
+
+// Both the standard and the URL safe alphabets are accepted, with or without padding.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut bytes = Vec::new();
+    let mut bits: u32 = 0;
+    let mut count = 0;
+
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+
+        bits = bits << 6 | value as u32;
+        count += 6;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+            bits &= (1 << count) - 1;
+        }
+    }
+
+    // A single character left over can not hold a whole byte.
+    (count != 6).then_some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DescriptorPool, ProtoCompiler};
+    use rstest::*;
+    use serde_json::json;
+
+    const SOURCE: &str = r#"syntax = "proto3";
+package test;
+import "google/protobuf/any.proto";
+import "google/protobuf/duration.proto";
+import "google/protobuf/field_mask.proto";
+import "google/protobuf/struct.proto";
+import "google/protobuf/timestamp.proto";
+import "google/protobuf/wrappers.proto";
+
+enum Colour { RED = 0; GREEN = 1; }
+message Item {
+  string display_name = 1;
+  int32 count = 2;
+  int64 total = 3;
+  uint64 big = 4;
+  float scale = 5;
+  double ratio = 6;
+  bool on = 7;
+  bytes data = 8;
+  Colour colour = 9;
+  repeated Colour colours = 10;
+  map<int32, string> labels = 11;
+  Item child = 12;
+  oneof choice { string text = 13; int32 number = 14; }
+  optional int32 maybe = 15;
+  google.protobuf.Timestamp at = 20;
+  google.protobuf.Duration took = 21;
+  google.protobuf.FieldMask mask = 22;
+  google.protobuf.Struct details = 23;
+  google.protobuf.Value value = 24;
+  google.protobuf.Int64Value wrapped = 25;
+  google.protobuf.Any any = 26;
+}
+"#;
+
+    fn item() -> MessageDescriptor {
+        let mut compiler = ProtoCompiler::new();
+        compiler.include_imports(true);
+        compiler.add_source("test.proto", SOURCE);
+        let set = compiler.compile(&["test.proto"]).unwrap();
+        let pool = DescriptorPool::from_file_descriptor_set(set).unwrap();
+        pool.get_message_by_name("test.Item").unwrap()
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        // Arrange
+        let json = json!({
+            "displayName": "Ada",
+            "count": -5,
+            "total": "9007199254740993",
+            "big": "18446744073709551615",
+            "scale": 0.1,
+            "ratio": "NaN",
+            "on": true,
+            "data": "AP8=",
+            "colour": "GREEN",
+            "colours": ["RED", "GREEN", 7],
+            "labels": {"-1": "minus", "2": "two"},
+            "child": {"count": 1},
+            "number": 0,
+            "maybe": 0,
+            "at": "1972-01-01T10:00:20.021Z",
+            "took": "-1.500s",
+            "mask": "user.displayName,id",
+            "details": {"a": [1.5, null, {"b": false}], "c": "d"},
+            "value": null,
+            "wrapped": "12",
+            "any": {"@type": "type.googleapis.com/google.protobuf.Duration", "value": "3s"}
+        });
+        let item = item();
+
+        // Act
+        let message = DynamicMessage::from_json(item.clone(), &json).unwrap();
+        let result = message.to_json().unwrap();
+
+        // Assert
+        assert_eq!(result, json);
+        assert_eq!(
+            message.get_field_by_name("total").unwrap().as_i64(),
+            Some(9007199254740993)
+        );
+        let decoded = DynamicMessage::decode(item, &message.encode_to_vec()).unwrap();
+        assert_eq!(decoded.to_json().unwrap(), json);
+    }
+
+    #[test]
+    fn test_json_input_forms() {
+        // Arrange
+        let text = r#"{
+            "display_name": "Ada",
+            "count": "1e2",
+            "total": 3.0,
+            "scale": "-Infinity",
+            "data": "-_8",
+            "colour": 1,
+            "labels": {"7": null},
+            "child": null,
+            "at": "1972-01-01T15:00:20.5+05:00",
+            "any": {"@type": "type.googleapis.com/test.Item", "count": 4}
+        }"#;
+
+        // Act
+        let message = DynamicMessage::parse_json(item(), text);
+
+        // Assert
+        assert_eq!(
+            message.map(|m| m.to_json().unwrap()),
+            Err(JsonError::InvalidValue {
+                field: "test.Item.LabelsEntry.value".to_string(),
+                expected: "a string".to_string()
+            })
+        );
+        let text = text.replace(r#""labels": {"7": null},"#, "");
+        let message = DynamicMessage::parse_json(item(), &text).unwrap();
+        assert_eq!(
+            message.to_json().unwrap(),
+            json!({
+                "displayName": "Ada",
+                "count": 100,
+                "total": "3",
+                "scale": "-Infinity",
+                "data": "+/8=",
+                "colour": "GREEN",
+                "at": "1972-01-01T10:00:20.500Z",
+                "any": {"@type": "type.googleapis.com/test.Item", "count": 4}
+            })
+        );
+    }
+
+    #[rstest]
+    #[case(json!({"count": 2147483648_i64}), "The value of test.Item.count must be a number that fits in int32.")]
+    #[case(json!({"count": 1.5}), "The value of test.Item.count must be a number that fits in int32.")]
+    #[case(json!({"scale": 1e39}), "The value of test.Item.scale must be a number.")]
+    #[case(json!({"colour": "BLUE"}), "The value of test.Item.colour must be a value of test.Colour.")]
+    #[case(json!({"data": "a"}), "The value of test.Item.data must be a base64 string.")]
+    #[case(json!({"missing": 1}), "The message test.Item does not have the field missing.")]
+    #[case(json!({"text": "a", "number": 1}), "The field test.Item.choice is set more than once.")]
+    #[case(json!({"displayName": "a", "display_name": "b"}), "The field test.Item.display_name is set more than once.")]
+    #[case(json!({"at": "1972-01-01T10:00:20"}), "The value of google.protobuf.Timestamp must be an RFC 3339 date and time.")]
+    #[case(json!({"at": "10000-01-01T00:00:00Z"}), "The value of google.protobuf.Timestamp must be an RFC 3339 date and time.")]
+    #[case(json!({"took": "315576000001s"}), "The value of google.protobuf.Duration must be a number of seconds ending in s.")]
+    #[case(json!({"mask": "a_b"}), "The value of google.protobuf.FieldMask must be a comma separated list of field paths.")]
+    #[case(json!({"any": {"@type": "type.googleapis.com/test.Missing"}}), "The type type.googleapis.com/test.Missing is not known.")]
+    #[case(json!([]), "The value of test.Item must be an object.")]
+    fn test_json_errors(#[case] json: Value, #[case] expected_message: &str) {
+        // Act
+        let error = DynamicMessage::from_json(item(), &json).unwrap_err();
+
+        // Assert
+        assert_eq!(error.to_string(), expected_message);
+    }
+
+    #[rstest]
+    #[case(0, 0, "1970-01-01T00:00:00Z")]
+    #[case(-1, 999_000_000, "1969-12-31T23:59:59.999Z")]
+    #[case(951_782_400, 1_000, "2000-02-29T00:00:00.000001Z")]
+    #[case(MIN_TIMESTAMP_SECONDS, 0, "0001-01-01T00:00:00Z")]
+    #[case(MAX_TIMESTAMP_SECONDS, 1, "9999-12-31T23:59:59.000000001Z")]
+    fn test_timestamp_forms(#[case] seconds: i64, #[case] nanos: i32, #[case] text: &str) {
+        // Act
+        let formatted = format_timestamp(seconds, nanos);
+        let parsed = parse_timestamp(text);
+
+        // Assert
+        assert_eq!(formatted.as_deref(), Some(text));
+        assert_eq!(parsed, Some((seconds, nanos)));
+    }
+
+    #[rstest]
+    #[case(0, 0, "0s")]
+    #[case(1, 500_000_000, "1.500s")]
+    #[case(0, -1, "-0.000000001s")]
+    #[case(-MAX_DURATION_SECONDS, -10_000, "-315576000000.000010s")]
+    fn test_duration_forms(#[case] seconds: i64, #[case] nanos: i32, #[case] text: &str) {
+        // Act
+        let formatted = format_duration(seconds, nanos);
+        let parsed = parse_duration(text);
+
+        // Assert
+        assert_eq!(formatted.as_deref(), Some(text));
+        assert_eq!(parsed, Some((seconds, nanos)));
+    }
+
+    #[rstest]
+    #[case(vec![], "")]
+    #[case(vec![0], "AA==")]
+    #[case(vec![0, 255], "AP8=")]
+    #[case(vec![1, 2, 3], "AQID")]
+    #[case(b"hello world".to_vec(), "aGVsbG8gd29ybGQ=")]
+    fn test_base64(#[case] bytes: Vec<u8>, #[case] text: &str) {
+        // Act
+        let encoded = encode_base64(&bytes);
+        let decoded = decode_base64(text);
+
+        // Assert
+        assert_eq!(encoded, text);
+        assert_eq!(decoded, Some(bytes));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Buffer, DecodeError, LengthDelimited, Varint};
+use crate::{decode_varint64, Buffer, DecodeError, LengthDelimited, Varint};
 
 #[derive(Debug, Default, Eq, PartialEq)]
 struct Length(u32);
@@ -14,11 +14,23 @@ impl Varint for Length {
         self.0.to_varint(buffer)
     }
 
+    // Unlike 32 bit values, a length is never truncated, as the field would end somewhere else.
     fn from_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
-        self.0.from_varint(buffer)
+        let (length, size) = decode_varint64(buffer)?.ok_or(DecodeError::UnableToDecode)?;
+        self.0 = u32::try_from(length)
+            .map_err(|_| DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)?;
+        Ok(size)
     }
 }
 
+/// Decodes the length written before the value of a length delimited field, returning the length
+/// and the number of bytes it was written in. Lengths which do not fit in 32 bits are rejected.
+pub(crate) fn decode_length(buffer: &[u8]) -> Result<(usize, usize), DecodeError> {
+    let mut length = Length::default();
+    let size = length.from_varint(buffer)?;
+    Ok((length.0 as usize, size))
+}
+
 impl LengthDelimited for Vec<u8> {
     fn to_length_delimited(&self, buffer: &mut Buffer) -> usize {
         let length = Length::from(self.len());
@@ -36,6 +48,10 @@ impl LengthDelimited for Vec<u8> {
         let mut length = Length::default();
 
         let size1 = length.from_varint(buffer)?;
+        if buffer.len() < size1 + length.0 as usize {
+            return Err(DecodeError::BufferOverrun);
+        }
+
         for b in &buffer[size1..size1 + length.0 as usize] {
             self.push(*b);
         }
@@ -116,4 +132,35 @@ mod tests {
         assert_eq!(byte_array, expected_value);
         assert_eq!(size, expected_size);
     }
+
+    #[rstest]
+    #[case(vec![0x82, 0x80, 0x80, 0x80, 0x10, b'h', b'i'])]
+    #[case(vec![0x82, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01, b'h', b'i'])]
+    fn test_lengths_too_large_for_32_bits_are_rejected(#[case] bytes: Vec<u8>) {
+        // Arrange
+        let mut string = String::new();
+
+        // Act
+        let length = decode_length(&bytes);
+        let result = string.from_length_delimited(&bytes);
+
+        // Assert
+        assert_eq!(
+            length,
+            Err(DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)
+        );
+        assert_eq!(
+            result,
+            Err(DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)
+        );
+    }
+
+    #[test]
+    fn test_padded_length_is_decoded() {
+        // Act
+        let length = decode_length(&[0x82, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00]);
+
+        // Assert
+        assert_eq!(length, Ok((2, 10)));
+    }
 }
@@ -3,7 +3,6 @@
 #![warn(rustdoc::missing_doc_code_examples)]
 #![deny(
     bad_style,
-    const_err,
     dead_code,
     improper_ctypes,
     non_shorthand_field_patterns,
@@ -11,7 +10,6 @@
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     unconditional_recursion,
     unused,
     unused_allocation,
@@ -30,24 +28,69 @@
     unused_results
 )]
 
+// Lets the golden generated code used in tests refer to this crate by name.
+#[cfg(test)]
+extern crate self as ks_protobuf_v3;
+
 mod buffer;
+mod codegen;
+mod decode_mode;
+mod descriptor;
+mod descriptor_pool;
+mod dynamic_message;
+mod encode_mode;
 mod encoding_traits;
+mod enum_encoding;
 mod errors;
+mod extension_set;
 mod fixed32_encoding;
 mod fixed64_encoding;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "json")]
+mod json_format;
 mod length_delimited_encoding;
+mod map_encoding;
+mod message;
+mod oneof;
+mod plugin;
+mod proto_compiler;
+mod proto_parser;
+mod raw_decoder;
+mod reflect;
+mod repeated_encoding;
+mod scoped;
 mod tag_encoding;
+mod text_format;
 mod type_encoding;
+mod unknown_fields;
 mod varint_encoding;
+mod well_known_types;
 mod zigzag_encoding;
 
 pub use buffer::*;
+pub use codegen::*;
+pub use decode_mode::*;
+pub use descriptor::*;
+pub use descriptor_pool::*;
+pub use dynamic_message::*;
+pub use encode_mode::*;
 pub use encoding_traits::*;
+pub use enum_encoding::*;
 pub use errors::*;
+pub use extension_set::*;
 pub use fixed32_encoding::*;
 pub use fixed64_encoding::*;
-pub use length_delimited_encoding::*;
+pub use map_encoding::*;
+pub use message::*;
+pub use oneof::*;
+pub use plugin::*;
+pub use proto_compiler::*;
+pub use raw_decoder::*;
+pub use reflect::*;
+pub use repeated_encoding::*;
 pub use tag_encoding::*;
-pub use type_encoding::*;
+pub use unknown_fields::*;
 pub use varint_encoding::*;
+pub use well_known_types::*;
 pub use zigzag_encoding::*;
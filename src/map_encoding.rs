@@ -0,0 +1,270 @@
+use crate::length_delimited_encoding::decode_length;
+use crate::{
+    skip_tagged_field, Buffer, DecodeError, EncodeMode, FieldCodec, LengthDelimited, Tag, Varint,
+    WireType,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+
+/// This trait marks the types that can be used as the key of a map field.
+///
+/// Protocol Buffers only allows integral and string keys; floating point and bytes keys are not
+/// permitted. Keys are ordered so that deterministic encoding can write entries in key order.
+pub trait MapKey: Default + Ord {}
+
+impl MapKey for bool {}
+impl MapKey for u32 {}
+impl MapKey for u64 {}
+impl MapKey for i32 {}
+impl MapKey for i64 {}
+impl MapKey for String {}
+
+/// This trait can be applied to map types to enable them to encode and decode `map<K, V>` fields.
+///
+/// A map field is encoded as a repeated field of entry messages, each holding the key as field 1
+/// and the value as field 2. The codecs select the wire type used for the key and the value.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Buffer, LengthDelimitedCodec, MapField, VarintCodec};
+/// use std::collections::HashMap;
+///
+/// let mut buffer = Buffer::default();
+/// let mut scores: HashMap<String, u32> = HashMap::new();
+/// scores.insert("a".to_string(), 1);
+///
+/// let size = scores.to_map_field::<LengthDelimitedCodec, VarintCodec, 3>(&mut buffer);
+///
+/// let mut decoded: HashMap<String, u32> = HashMap::new();
+/// decoded
+///     .from_map_entry::<LengthDelimitedCodec, VarintCodec>(&buffer.to_vec()[1..])
+///     .unwrap();
+/// ```
+#[allow(clippy::wrong_self_convention)]
+pub trait MapField<K: MapKey, V: Default> {
+    /// This function writes out every entry of the map as a field with the given field number.
+    fn to_map_field<KC: FieldCodec<K>, VC: FieldCodec<V>, const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize;
+
+    /// This function decodes a single entry message and inserts it into the map, replacing any
+    /// existing value with the same key. The buffer starts immediately after the tag.
+    fn from_map_entry<KC: FieldCodec<K>, VC: FieldCodec<V>>(
+        &mut self,
+        buffer: &[u8],
+    ) -> Result<usize, DecodeError>;
+}
+
+impl<K: MapKey + Hash, V: Default> MapField<K, V> for HashMap<K, V> {
+    // The iteration order of a `HashMap` changes from map to map, so deterministic encoding sorts
+    // the entries by key.
+    fn to_map_field<KC: FieldCodec<K>, VC: FieldCodec<V>, const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize {
+        if EncodeMode::is_deterministic() {
+            let mut entries: Vec<_> = self.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+            return entries
+                .into_iter()
+                .map(|(k, v)| encode_map_entry::<K, V, KC, VC, FIELD_NUMBER>(k, v, buffer))
+                .sum();
+        }
+
+        self.iter()
+            .map(|(k, v)| encode_map_entry::<K, V, KC, VC, FIELD_NUMBER>(k, v, buffer))
+            .sum()
+    }
+
+    fn from_map_entry<KC: FieldCodec<K>, VC: FieldCodec<V>>(
+        &mut self,
+        buffer: &[u8],
+    ) -> Result<usize, DecodeError> {
+        let (key, value, size) = decode_map_entry::<K, V, KC, VC>(buffer)?;
+
+        let _ = self.insert(key, value);
+        Ok(size)
+    }
+}
+
+impl<K: MapKey, V: Default> MapField<K, V> for BTreeMap<K, V> {
+    fn to_map_field<KC: FieldCodec<K>, VC: FieldCodec<V>, const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize {
+        self.iter()
+            .map(|(k, v)| encode_map_entry::<K, V, KC, VC, FIELD_NUMBER>(k, v, buffer))
+            .sum()
+    }
+
+    fn from_map_entry<KC: FieldCodec<K>, VC: FieldCodec<V>>(
+        &mut self,
+        buffer: &[u8],
+    ) -> Result<usize, DecodeError> {
+        let (key, value, size) = decode_map_entry::<K, V, KC, VC>(buffer)?;
+
+        let _ = self.insert(key, value);
+        Ok(size)
+    }
+}
+
+/// Writes out a single map entry, including the tag of the map field. The key and value are always
+/// written, even when they hold default values.
+fn encode_map_entry<K, V, KC: FieldCodec<K>, VC: FieldCodec<V>, const FIELD_NUMBER: u32>(
+    key: &K,
+    value: &V,
+    buffer: &mut Buffer,
+) -> usize {
+    let mut entry = Buffer::default();
+    let _ = Tag::new::<1>(KC::WIRE_TYPE).to_varint(&mut entry);
+    let _ = KC::encode(key, &mut entry);
+    let _ = Tag::new::<2>(VC::WIRE_TYPE).to_varint(&mut entry);
+    let _ = VC::encode(value, &mut entry);
+
+    let size = Tag::new::<FIELD_NUMBER>(WireType::LengthDelimited).to_varint(buffer);
+    size + entry.to_vec().to_length_delimited(buffer)
+}
+
+/// Decodes a single map entry. A missing key or value is given its default value and any other
+/// fields within the entry are skipped.
+fn decode_map_entry<K: Default, V: Default, KC: FieldCodec<K>, VC: FieldCodec<V>>(
+    buffer: &[u8],
+) -> Result<(K, V, usize), DecodeError> {
+    let (length, size) = decode_length(buffer)?;
+    let end = size + length;
+    if buffer.len() < end {
+        return Err(DecodeError::BufferOverrun);
+    }
+
+    let mut key = K::default();
+    let mut value = V::default();
+    let mut position = size;
+
+    while position < end {
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
+        position += tag.from_varint(&buffer[position..end])?;
+
+        let entry = &buffer[position..end];
+        position += match (tag.field_number(), tag.wire_type()) {
+            (1, wire_type) if wire_type == KC::WIRE_TYPE => KC::decode(&mut key, entry)?,
+            (2, wire_type) if wire_type == VC::WIRE_TYPE => VC::decode(&mut value, entry)?,
+            _ => skip_tagged_field(&tag, entry)?,
+        };
+    }
+
+    Ok((key, value, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Fixed32Codec, LengthDelimitedCodec, VarintCodec};
+    use rstest::*;
+
+    #[test]
+    fn test_hash_map_encoding() {
+        // Arrange
+        let mut buffer = Buffer::default();
+        let mut map: HashMap<String, u32> = HashMap::new();
+        let _ = map.insert("a".to_string(), 1);
+
+        // Act
+        let size = map.to_map_field::<LengthDelimitedCodec, VarintCodec, 1>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), vec![10, 5, 10, 1, 97, 16, 1]);
+        assert_eq!(size, 7);
+    }
+
+    #[test]
+    fn test_hash_map_deterministic_encoding() {
+        // Arrange
+        let mut buffer = Buffer::default();
+        let map: HashMap<u32, u32> = (0..64).rev().map(|k| (k, 1)).collect();
+
+        // Act
+        let size = EncodeMode::Deterministic
+            .scope(|| map.to_map_field::<VarintCodec, VarintCodec, 1>(&mut buffer));
+
+        // Assert
+        let expected: Vec<u8> = (0..64).flat_map(|k| [10, 4, 8, k, 16, 1]).collect();
+        assert_eq!(buffer.to_vec(), expected);
+        assert_eq!(size, expected.len());
+    }
+
+    #[test]
+    fn test_btree_map_encoding_with_default_values() {
+        // Arrange
+        let mut buffer = Buffer::default();
+        let mut map: BTreeMap<u32, f32> = BTreeMap::new();
+        let _ = map.insert(0, 0.0);
+        let _ = map.insert(2, 1.0);
+
+        // Act
+        let size = map.to_map_field::<VarintCodec, Fixed32Codec, 2>(&mut buffer);
+
+        // Assert
+        assert_eq!(
+            buffer.to_vec(),
+            vec![18, 7, 8, 0, 21, 0, 0, 0, 0, 18, 7, 8, 2, 21, 0, 0, 128, 63]
+        );
+        assert_eq!(size, 18);
+    }
+
+    #[rstest]
+    #[case(vec![5, 10, 1, 97, 16, 1], "a", 1)]
+    #[case(vec![3, 10, 1, 97], "a", 0)]
+    #[case(vec![2, 16, 7], "", 7)]
+    #[case(vec![0], "", 0)]
+    #[case(vec![7, 16, 1, 10, 1, 97, 24, 9], "a", 1)]
+    #[case(vec![6, 16, 1, 16, 2, 16, 3], "", 3)]
+    fn test_map_entry_decoding(
+        #[case] buffer: Vec<u8>,
+        #[case] expected_key: &str,
+        #[case] expected_value: u32,
+    ) {
+        // Arrange
+        let mut map: HashMap<String, u32> = HashMap::new();
+
+        // Act
+        let size = map
+            .from_map_entry::<LengthDelimitedCodec, VarintCodec>(&buffer)
+            .unwrap();
+
+        // Assert
+        assert_eq!(map.get(expected_key), Some(&expected_value));
+        assert_eq!(map.len(), 1);
+        assert_eq!(size, buffer.len());
+    }
+
+    #[test]
+    fn test_duplicate_map_keys_last_one_wins() {
+        // Arrange
+        let mut map: BTreeMap<i64, String> = BTreeMap::new();
+
+        // Act
+        let _ = map
+            .from_map_entry::<VarintCodec, LengthDelimitedCodec>(&[5, 8, 2, 18, 1, 97])
+            .unwrap();
+        let _ = map
+            .from_map_entry::<VarintCodec, LengthDelimitedCodec>(&[5, 8, 2, 18, 1, 98])
+            .unwrap();
+
+        // Assert
+        assert_eq!(map.get(&1), Some(&"b".to_string()));
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_truncated_map_entry_decoding() {
+        // Arrange
+        let mut map: HashMap<String, u32> = HashMap::new();
+
+        // Act
+        let result = map.from_map_entry::<LengthDelimitedCodec, VarintCodec>(&[5, 10, 1]);
+
+        // Assert
+        assert_eq!(result, Err(DecodeError::BufferOverrun));
+    }
+}
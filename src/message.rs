@@ -0,0 +1,800 @@
+use crate::length_delimited_encoding::decode_length;
+use crate::scoped::scoped_set;
+use crate::{
+    decode_fixed32, decode_fixed64, decode_varint64, Buffer, DecodeError, DecodeMode, EncodeMode,
+    Group, GroupField, LengthDelimited, LengthDelimitedField, Presence, Tag, UnknownFields, Varint,
+    WireType,
+};
+use std::cell::Cell;
+use std::collections::HashSet;
+
+/// How deeply messages and groups can be nested within the message being decoded, the default of
+/// the reference implementation. Decoding deeper input fails rather than overflowing the stack.
+pub const RECURSION_LIMIT: usize = 100;
+
+thread_local! {
+    // How deeply the message being decoded on the current thread is nested. The count is per
+    // thread because it bounds the use of the thread's own stack.
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// This trait can be applied to types to enable them to be encoded and decoded as Protocol Buffer
+/// messages.
+///
+/// A message is encoded as a sequence of fields, each of which is prefixed by its tag. Messages
+/// automatically implement `LengthDelimited` so that they can be nested inside other messages.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Buffer, DecodeError, Message, Tag, Varint, VarintField};
+///
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Counter {
+///     count: u32,
+/// }
+///
+/// impl Message for Counter {
+///     fn encode(&self, buffer: &mut Buffer) -> usize {
+///         self.count.to_varint_field::<1>(buffer)
+///     }
+///
+///     fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+///         match tag.field_number() {
+///             1 => self.count.from_varint(buffer).map(Some),
+///             _ => Ok(None),
+///         }
+///     }
+/// }
+///
+/// let bytes = Counter { count: 150 }.encode_to_vec();
+/// let counter = Counter::decode(&bytes).unwrap();
+/// ```
+pub trait Message: Default {
+    /// This function writes out every field of the message to the Protocol Buffer.
+    fn encode(&self, buffer: &mut Buffer) -> usize;
+
+    /// This function decodes a single field of the message. The buffer starts immediately after
+    /// the tag and the number of bytes consumed by the value is returned. `None` is returned when
+    /// the field is not recognised so that it can be skipped.
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError>;
+
+    /// This function encodes the message into a new Vec<u8>.
+    fn encode_to_vec(&self) -> Vec<u8> {
+        let mut buffer = Buffer::default();
+        let _ = self.encode(&mut buffer);
+        buffer.to_vec()
+    }
+
+    /// This function encodes the message into a new Vec<u8>, using the mode to decide whether the
+    /// encoding is deterministic. The mode applies to the current thread only, see [`EncodeMode`].
+    fn encode_to_vec_with_mode(&self, mode: EncodeMode) -> Vec<u8> {
+        mode.scope(|| self.encode_to_vec())
+    }
+
+    /// This function returns an error naming the first proto2 `required` field that is not set,
+    /// searching the message and then the messages within it. Messages without required fields
+    /// use the default, which always succeeds.
+    fn check_required_fields(&self) -> Result<(), DecodeError> {
+        Ok(())
+    }
+
+    /// This function returns true when the field number is that of a field which holds a single
+    /// value, so that strict decoding can reject it being set more than once. Messages which do
+    /// not say use the default, which never rejects a field.
+    fn is_singular_field(&self, _field_number: u32) -> bool {
+        false
+    }
+
+    /// This function returns where the message keeps the fields it does not know, which decoding
+    /// fills with the fields that `decode_field` does not accept. Messages which do not keep them
+    /// use the default, and those fields are skipped.
+    fn unknown_fields_mut(&mut self) -> Option<&mut UnknownFields> {
+        None
+    }
+
+    /// This function decodes a message from the whole of the buffer. Decoding fails when a
+    /// required field is not set.
+    fn decode(buffer: &[u8]) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        let mut message = Self::default();
+        let _ = decode_fields(&mut message, buffer)?;
+        message.check_required_fields()?;
+        Ok(message)
+    }
+
+    /// This function merges the fields in the buffer into the message, as though the buffer had
+    /// followed the message's own encoding. Merging fails when a required field is not set.
+    fn merge_from_bytes(&mut self, buffer: &[u8]) -> Result<(), DecodeError> {
+        let _ = decode_fields(self, buffer)?;
+        self.check_required_fields()
+    }
+
+    /// This function merges the other message into this one. Singular fields which are set in the
+    /// other message replace those in this one, except for messages which are merged, repeated
+    /// fields are appended and map entries replace those with the same key.
+    ///
+    /// The default merges the encoding of the other message, which gives the same result. As that
+    /// encoding is decoded again, it fails when the other message is nested more than
+    /// [`RECURSION_LIMIT`] levels deep. Generated messages merge field by field instead.
+    fn merge(&mut self, other: Self) -> Result<(), DecodeError> {
+        let _ = DecodeMode::Lenient.scope(|| decode_fields(self, &other.encode_to_vec()))?;
+        Ok(())
+    }
+
+    /// This function decodes a message from the whole of the buffer, using the mode to decide
+    /// whether input which is not in its canonical form is accepted. The mode applies to the
+    /// current thread only, see [`DecodeMode`].
+    fn decode_with_mode(buffer: &[u8], mode: DecodeMode) -> Result<Self, DecodeError>
+    where
+        Self: Sized,
+    {
+        mode.scope(|| Self::decode(buffer))
+    }
+}
+
+/// Merges the other value of a singular message field into the field, which takes the other
+/// message when it is not set.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{merge_message, ListValue};
+///
+/// let mut list = Some(ListValue { values: vec![1.into()] });
+///
+/// merge_message(&mut list, Some(ListValue { values: vec!["a".into()] })).unwrap();
+///
+/// assert_eq!(list, Some(ListValue { values: vec![1.into(), "a".into()] }));
+/// ```
+pub fn merge_message<M: Message>(
+    message: &mut Option<M>,
+    other: Option<M>,
+) -> Result<(), DecodeError> {
+    match (message.as_mut(), other) {
+        (Some(current), Some(other)) => return current.merge(other),
+        (None, Some(other)) => *message = Some(other),
+        (_, None) => {}
+    }
+    Ok(())
+}
+
+/// Skips over the value of a field that has been encoded with the given wire type.
+///
+/// The buffer starts immediately after the tag and the number of bytes the value occupies is
+/// returned. Groups can not be skipped by their wire type alone, as their end tag must match the
+/// field number of their start tag, so they are skipped with `skip_group` or `skip_tagged_field`.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{skip_field, WireType};
+///
+/// let buffer: Vec<u8> = vec![3, 1, 2, 3];
+///
+/// let size = skip_field(WireType::LengthDelimited, &buffer).unwrap();
+/// ```
+pub fn skip_field(wire_type: WireType, buffer: &[u8]) -> Result<usize, DecodeError> {
+    match wire_type {
+        WireType::Varint => match decode_varint64(buffer)? {
+            Some((_, s)) => Ok(s),
+            None => Err(DecodeError::BufferOverrun),
+        },
+        WireType::Fixed64 => match decode_fixed64(buffer)? {
+            Some((_, s)) => Ok(s),
+            None => Err(DecodeError::BufferOverrun),
+        },
+        WireType::Fixed32 => match decode_fixed32(buffer)? {
+            Some((_, s)) => Ok(s),
+            None => Err(DecodeError::BufferOverrun),
+        },
+        WireType::LengthDelimited => {
+            let (length, size) = decode_length(buffer)?;
+            if buffer.len() < size + length {
+                return Err(DecodeError::BufferOverrun);
+            }
+            Ok(size + length)
+        }
+        WireType::StartGroup | WireType::EndGroup => Err(DecodeError::UnableToDecode),
+    }
+}
+
+/// Skips over the contents of a group, including any groups nested within it.
+///
+/// The buffer starts immediately after the start group tag and the number of bytes up to and
+/// including the end group tag is returned. An error is returned when an end group tag does not
+/// match the field number of the group it closes.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::skip_group;
+///
+/// let buffer: Vec<u8> = vec![8, 1, 11, 12, 12];
+///
+/// let size = skip_group(1, &buffer).unwrap();
+/// ```
+pub fn skip_group(field_number: u32, buffer: &[u8]) -> Result<usize, DecodeError> {
+    // The field numbers of the groups that are open, innermost last. Nested groups are tracked
+    // here rather than by recursion so that deeply nested input can not overflow the stack.
+    let mut open = vec![field_number];
+    let mut position = 0;
+
+    while let Some(&number) = open.last() {
+        if position >= buffer.len() {
+            return Err(DecodeError::BufferOverrun);
+        }
+
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
+        position += tag.from_varint(&buffer[position..])?;
+
+        match tag.wire_type() {
+            WireType::StartGroup => open.push(tag.field_number()),
+            WireType::EndGroup if tag.field_number() == number => {
+                let _ = open.pop();
+            }
+            WireType::EndGroup => return Err(DecodeError::UnableToDecode),
+            wire_type => position += skip_field(wire_type, &buffer[position..])?,
+        }
+    }
+
+    Ok(position)
+}
+
+/// Skips over the value of a field given its tag, including groups.
+///
+/// The buffer starts immediately after the tag and the number of bytes the value occupies is
+/// returned. An end group tag can not be skipped, as it is only valid when it closes a group.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{skip_tagged_field, Tag, WireType};
+///
+/// let buffer: Vec<u8> = vec![8, 1, 12];
+///
+/// let size = skip_tagged_field(&Tag::new::<1>(WireType::StartGroup), &buffer).unwrap();
+/// ```
+pub fn skip_tagged_field(tag: &Tag, buffer: &[u8]) -> Result<usize, DecodeError> {
+    match tag.wire_type() {
+        WireType::StartGroup => skip_group(tag.field_number(), buffer),
+        wire_type => skip_field(wire_type, buffer),
+    }
+}
+
+/// Decodes every field in the buffer into the message, keeping or skipping any unrecognised
+/// fields.
+pub(crate) fn decode_fields<M: Message>(
+    message: &mut M,
+    buffer: &[u8],
+) -> Result<usize, DecodeError> {
+    let mut position = 0;
+    let mut decoded = DecodedFields::new();
+
+    while position < buffer.len() {
+        let start = position;
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
+        position += tag.from_varint(&buffer[position..])?;
+
+        position += match message.decode_field(&tag, &buffer[position..])? {
+            Some(size) => decoded.check(message, &tag).map(|_| size)?,
+            None => skip_unknown_field(message, &tag, buffer, start, position)?,
+        };
+    }
+
+    Ok(position)
+}
+
+// Skips a field the message does not accept, keeping it when the message keeps unknown fields.
+// The field starts with its tag at the start position and its value at the value position.
+fn skip_unknown_field<M: Message>(
+    message: &mut M,
+    tag: &Tag,
+    buffer: &[u8],
+    start: usize,
+    value: usize,
+) -> Result<usize, DecodeError> {
+    let size = skip_tagged_field(tag, &buffer[value..])?;
+    if let Some(unknown) = message.unknown_fields_mut() {
+        unknown.push_field(&buffer[start..value + size]);
+    }
+    Ok(size)
+}
+
+// Decodes the fields of a group into the message, up to and including the end group tag with the
+// group's field number.
+fn decode_group_fields<M: Message>(
+    message: &mut M,
+    field_number: u32,
+    buffer: &[u8],
+) -> Result<usize, DecodeError> {
+    let mut position = 0;
+    let mut decoded = DecodedFields::new();
+
+    while position < buffer.len() {
+        let start = position;
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
+        position += tag.from_varint(&buffer[position..])?;
+
+        if tag.wire_type() == WireType::EndGroup {
+            return match tag.field_number() == field_number {
+                true => Ok(position),
+                false => Err(DecodeError::UnableToDecode),
+            };
+        }
+
+        position += match message.decode_field(&tag, &buffer[position..])? {
+            Some(size) => decoded.check(message, &tag).map(|_| size)?,
+            None => skip_unknown_field(message, &tag, buffer, start, position)?,
+        };
+    }
+
+    Err(DecodeError::BufferOverrun)
+}
+
+// Decodes a nested message or group one level deeper, failing once the recursion limit would be
+// exceeded.
+pub(crate) fn decode_nested(
+    decode: impl FnOnce() -> Result<usize, DecodeError>,
+) -> Result<usize, DecodeError> {
+    let depth = DEPTH.with(Cell::get);
+    if depth >= RECURSION_LIMIT {
+        return Err(DecodeError::RecursionLimitExceeded);
+    }
+
+    scoped_set(&DEPTH, depth + 1, decode)
+}
+
+// Tracks the singular fields that have been decoded into a message, so that strict decoding can
+// reject a field which is set more than once.
+pub(crate) struct DecodedFields {
+    strict: bool,
+    numbers: HashSet<u32>,
+}
+
+impl DecodedFields {
+    pub(crate) fn new() -> Self {
+        Self {
+            strict: DecodeMode::is_strict(),
+            numbers: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn check<M: Message>(&mut self, message: &M, tag: &Tag) -> Result<(), DecodeError> {
+        self.check_number(tag.field_number(), |n| message.is_singular_field(n))
+    }
+
+    pub(crate) fn check_number(
+        &mut self,
+        number: u32,
+        is_singular: impl FnOnce(u32) -> bool,
+    ) -> Result<(), DecodeError> {
+        match self.strict && is_singular(number) && !self.numbers.insert(number) {
+            true => Err(DecodeError::DuplicateField(number)),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<M: Message> LengthDelimited for M {
+    fn to_length_delimited(&self, buffer: &mut Buffer) -> usize {
+        self.encode_to_vec().to_length_delimited(buffer)
+    }
+
+    fn from_length_delimited(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        let (length, size) = decode_length(buffer)?;
+        if buffer.len() < size + length {
+            return Err(DecodeError::BufferOverrun);
+        }
+
+        let _ = decode_nested(|| decode_fields(self, &buffer[size..size + length]))?;
+        Ok(size + length)
+    }
+}
+
+// Messages have explicit presence, so a message held directly by a field is always written out.
+// Fields that can be unset hold the message in an `Option`.
+impl<M: Message> Presence for M {
+    fn is_present(&self) -> bool {
+        true
+    }
+}
+
+impl<M: Message> LengthDelimitedField for M {}
+
+// Messages can also be written as proto2 groups, which are delimited by tags instead of a length.
+impl<M: Message> Group for M {
+    fn to_group<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        self.encode(buffer) + Tag::new::<FIELD_NUMBER>(WireType::EndGroup).to_varint(buffer)
+    }
+
+    fn from_group(&mut self, field_number: u32, buffer: &[u8]) -> Result<usize, DecodeError> {
+        decode_nested(|| decode_group_fields(self, field_number, buffer))
+    }
+}
+
+impl<M: Message> GroupField for M {}
+
+// Boxed messages let recursive message types hold themselves.
+impl<M: Message> Message for Box<M> {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        M::encode(self, buffer)
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        M::decode_field(self, tag, buffer)
+    }
+
+    fn check_required_fields(&self) -> Result<(), DecodeError> {
+        M::check_required_fields(self)
+    }
+
+    fn is_singular_field(&self, field_number: u32) -> bool {
+        M::is_singular_field(self, field_number)
+    }
+
+    fn unknown_fields_mut(&mut self) -> Option<&mut UnknownFields> {
+        M::unknown_fields_mut(self)
+    }
+
+    fn merge(&mut self, other: Self) -> Result<(), DecodeError> {
+        M::merge(self, *other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Group, GroupField, LengthDelimitedField, VarintField};
+    use rstest::*;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Inner {
+        id: u32,
+    }
+
+    impl Message for Inner {
+        fn encode(&self, buffer: &mut Buffer) -> usize {
+            self.id.to_varint_field::<1>(buffer)
+        }
+
+        fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+            match tag.field_number() {
+                1 => self.id.from_varint(buffer).map(Some),
+                _ => Ok(None),
+            }
+        }
+
+        fn is_singular_field(&self, field_number: u32) -> bool {
+            field_number == 1
+        }
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Outer {
+        name: String,
+        inner: Inner,
+    }
+
+    impl Message for Outer {
+        fn encode(&self, buffer: &mut Buffer) -> usize {
+            self.name.to_length_delimited_field::<1>(buffer)
+                + self.inner.to_length_delimited_field::<2>(buffer)
+        }
+
+        fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+            match tag.field_number() {
+                1 => self.name.from_length_delimited(buffer).map(Some),
+                2 => self.inner.from_length_delimited(buffer).map(Some),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(vec![10, 2, 97, 98, 18, 2, 8, 1], vec![18, 2, 8, 7], ("ab", 7))]
+    #[case(vec![10, 2, 97, 98, 18, 2, 8, 1], vec![10, 1, 99, 18, 0], ("c", 1))]
+    #[case(vec![], vec![10, 1, 99], ("c", 0))]
+    fn test_merge_matches_concatenated_decoding(
+        #[case] first: Vec<u8>,
+        #[case] second: Vec<u8>,
+        #[case] expected: (&str, u32),
+    ) {
+        // Arrange
+        let mut merged = Outer::decode(&first).unwrap();
+        let mut merged_from_bytes = Outer::decode(&first).unwrap();
+        let other = Outer::decode(&second).unwrap();
+
+        // Act
+        merged.merge(other).unwrap();
+        merged_from_bytes.merge_from_bytes(&second).unwrap();
+
+        // Assert
+        let concatenated = Outer::decode(&[first, second].concat()).unwrap();
+        assert_eq!(merged, concatenated);
+        assert_eq!(merged_from_bytes, concatenated);
+        assert_eq!((merged.name.as_str(), merged.inner.id), expected);
+    }
+
+    #[rstest]
+    #[case(None, None, None)]
+    #[case(Some(1), None, Some(1))]
+    #[case(None, Some(2), Some(2))]
+    #[case(Some(1), Some(0), Some(1))]
+    #[case(Some(1), Some(2), Some(2))]
+    fn test_merge_message(
+        #[case] id: Option<u32>,
+        #[case] other_id: Option<u32>,
+        #[case] expected_id: Option<u32>,
+    ) {
+        // Arrange
+        let mut inner = id.map(|id| Inner { id });
+
+        // Act
+        merge_message(&mut inner, other_id.map(|id| Inner { id })).unwrap();
+
+        // Assert
+        assert_eq!(inner, expected_id.map(|id| Inner { id }));
+    }
+
+    #[test]
+    fn test_nested_message_encoding() {
+        // Arrange
+        let message = Outer {
+            name: "ab".to_string(),
+            inner: Inner { id: 150 },
+        };
+
+        // Act
+        let bytes = message.encode_to_vec();
+
+        // Assert
+        assert_eq!(bytes, vec![10, 2, 97, 98, 18, 3, 8, 150, 1]);
+    }
+
+    #[test]
+    fn test_nested_message_decoding() {
+        // Arrange
+        let bytes = vec![10, 2, 97, 98, 18, 3, 8, 150, 1];
+
+        // Act
+        let message = Outer::decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(message.name, "ab");
+        assert_eq!(message.inner, Inner { id: 150 });
+    }
+
+    #[test]
+    fn test_unknown_fields_are_skipped() {
+        // Arrange
+        let bytes = vec![8, 1, 21, 1, 2, 3, 4, 26, 1, 0, 8, 7];
+
+        // Act
+        let message = Inner::decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(message, Inner { id: 7 });
+    }
+
+    #[rstest]
+    #[case(WireType::Varint, vec![150, 1], 2)]
+    #[case(WireType::Fixed32, vec![1, 2, 3, 4], 4)]
+    #[case(WireType::Fixed64, vec![1, 2, 3, 4, 5, 6, 7, 8], 8)]
+    #[case(WireType::LengthDelimited, vec![3, 1, 2, 3], 4)]
+    fn test_skip_field(
+        #[case] wire_type: WireType,
+        #[case] buffer: Vec<u8>,
+        #[case] expected_size: usize,
+    ) {
+        // Act
+        let size = skip_field(wire_type, &buffer).unwrap();
+
+        // Assert
+        assert_eq!(size, expected_size);
+    }
+
+    #[rstest]
+    #[case(vec![12], 1)]
+    #[case(vec![8, 1, 18, 1, 0, 12], 6)]
+    #[case(vec![19, 8, 1, 20, 12, 8, 2], 5)]
+    #[case(vec![11, 12, 19, 20, 12], 5)]
+    fn test_skip_group(#[case] buffer: Vec<u8>, #[case] expected_size: usize) {
+        // Act
+        let size = skip_group(1, &buffer).unwrap();
+
+        // Assert
+        assert_eq!(size, expected_size);
+    }
+
+    #[rstest]
+    #[case(vec![], DecodeError::BufferOverrun)]
+    #[case(vec![8, 1], DecodeError::BufferOverrun)]
+    #[case(vec![19, 12], DecodeError::UnableToDecode)]
+    #[case(vec![20], DecodeError::UnableToDecode)]
+    #[case(vec![18, 5, 12], DecodeError::BufferOverrun)]
+    fn test_invalid_groups_are_not_skipped(
+        #[case] buffer: Vec<u8>,
+        #[case] expected_error: DecodeError,
+    ) {
+        // Act
+        let result = skip_group(1, &buffer);
+
+        // Assert
+        assert_eq!(result, Err(expected_error));
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Chain {
+        child: Option<Box<Chain>>,
+    }
+
+    impl Message for Chain {
+        fn encode(&self, buffer: &mut Buffer) -> usize {
+            self.child.to_length_delimited_field::<1>(buffer)
+        }
+
+        fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+            match (tag.field_number(), tag.wire_type()) {
+                (1, WireType::LengthDelimited) => {
+                    self.child.from_length_delimited(buffer).map(Some)
+                }
+                (1, WireType::StartGroup) => self.child.from_group(1, buffer).map(Some),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    // Builds a chain nested to the given depth from the inside out in reverse, either as length
+    // delimited messages or as groups.
+    fn nested_chain(depth: usize, as_groups: bool) -> Vec<u8> {
+        let mut reversed = Vec::new();
+        for _ in 0..depth {
+            match as_groups {
+                true => {
+                    reversed.insert(0, 12);
+                    reversed.push(11);
+                }
+                false => {
+                    let mut buffer = Buffer::default();
+                    let _ = (reversed.len() as u64).to_varint(&mut buffer);
+                    reversed.extend(buffer.to_vec().into_iter().rev());
+                    reversed.push(10);
+                }
+            }
+        }
+        reversed.into_iter().rev().collect()
+    }
+
+    #[rstest]
+    #[case(RECURSION_LIMIT, false, true)]
+    #[case(RECURSION_LIMIT, true, true)]
+    #[case(RECURSION_LIMIT + 1, false, false)]
+    #[case(RECURSION_LIMIT + 1, true, false)]
+    #[case(100_000, false, false)]
+    #[case(100_000, true, false)]
+    fn test_recursion_limit(#[case] depth: usize, #[case] as_groups: bool, #[case] is_ok: bool) {
+        // Arrange
+        let bytes = nested_chain(depth, as_groups);
+
+        // Act
+        let result = Chain::decode(&bytes);
+
+        // Assert
+        match is_ok {
+            true => assert!(result.is_ok()),
+            false => assert_eq!(result, Err(DecodeError::RecursionLimitExceeded)),
+        }
+    }
+
+    #[rstest]
+    #[case(RECURSION_LIMIT, true)]
+    #[case(RECURSION_LIMIT + 50, false)]
+    fn test_merge_of_deeply_nested_message(#[case] depth: usize, #[case] is_ok: bool) {
+        // Arrange
+        let mut other = Chain::default();
+        for _ in 0..depth {
+            other = Chain {
+                child: Some(Box::new(other)),
+            };
+        }
+        let expected = Chain::decode(&other.encode_to_vec());
+        let mut chain = Chain::default();
+
+        // Act
+        let result = chain.merge(other);
+
+        // Assert
+        match is_ok {
+            true => assert_eq!(Ok(chain), expected),
+            false => assert_eq!(result, Err(DecodeError::RecursionLimitExceeded)),
+        }
+    }
+
+    #[test]
+    fn test_recursion_limit_error_names_the_limit() {
+        // Act
+        let message = DecodeError::RecursionLimitExceeded.to_string();
+
+        // Assert
+        assert_eq!(
+            message,
+            format!("The messages are nested more than {RECURSION_LIMIT} levels deep.")
+        );
+    }
+
+    #[test]
+    fn test_unknown_groups_are_skipped() {
+        // Arrange
+        let bytes = vec![8, 1, 43, 8, 2, 51, 52, 44, 8, 7];
+
+        // Act
+        let message = Inner::decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(message, Inner { id: 7 });
+    }
+
+    #[test]
+    fn test_group_round_trip() {
+        // Arrange
+        let inner = Inner { id: 150 };
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = inner.to_group_field::<3>(&mut buffer);
+        let bytes = buffer.to_vec();
+        let mut decoded = Inner::default();
+        let decoded_size = decoded.from_group(3, &bytes[1..]).unwrap();
+
+        // Assert
+        assert_eq!(bytes, vec![27, 8, 150, 1, 28]);
+        assert_eq!(size, bytes.len());
+        assert_eq!(decoded_size, bytes.len() - 1);
+        assert_eq!(decoded, inner);
+    }
+
+    #[rstest]
+    #[case(vec![8, 1, 36], DecodeError::UnableToDecode)]
+    #[case(vec![8, 1], DecodeError::BufferOverrun)]
+    fn test_invalid_group_decoding(#[case] buffer: Vec<u8>, #[case] expected_error: DecodeError) {
+        // Arrange
+        let mut inner = Inner::default();
+
+        // Act
+        let result = inner.from_group(3, &buffer);
+
+        // Assert
+        assert_eq!(result, Err(expected_error));
+    }
+
+    #[rstest]
+    #[case(vec![8, 1, 8, 2], DecodeMode::Lenient, Ok(Inner { id: 2 }))]
+    #[case(vec![8, 1, 8, 2], DecodeMode::Strict, Err(DecodeError::DuplicateField(1)))]
+    #[case(vec![8, 129, 0], DecodeMode::Lenient, Ok(Inner { id: 1 }))]
+    #[case(vec![8, 129, 0], DecodeMode::Strict, Err(DecodeError::NonCanonicalVarint))]
+    #[case(vec![8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1], DecodeMode::Lenient, Ok(Inner { id: u32::MAX }))]
+    #[case(vec![8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1], DecodeMode::Strict, Err(DecodeError::UnableToDecodeBecauseTheValueWasTooLarge))]
+    #[case(vec![8, 1, 16, 128, 0], DecodeMode::Strict, Err(DecodeError::NonCanonicalVarint))]
+    fn test_decode_modes(
+        #[case] buffer: Vec<u8>,
+        #[case] mode: DecodeMode,
+        #[case] expected_result: Result<Inner, DecodeError>,
+    ) {
+        // Act
+        let result = Inner::decode_with_mode(&buffer, mode);
+
+        // Assert
+        assert_eq!(result, expected_result);
+        assert_eq!(DecodeMode::current(), DecodeMode::Lenient);
+    }
+
+    #[test]
+    fn test_truncated_nested_message_decoding() {
+        // Arrange
+        let bytes = vec![18, 5, 8, 150, 1];
+
+        // Act
+        let result = Outer::decode(&bytes);
+
+        // Assert
+        assert_eq!(result, Err(DecodeError::BufferOverrun));
+    }
+}
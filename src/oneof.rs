@@ -0,0 +1,345 @@
+use crate::{Buffer, DecodeError, Group, LengthDelimited, Message, Tag};
+
+/// This trait can be applied to enums to enable them to encode and decode a `oneof` group.
+///
+/// Each variant of the enum represents a member of the group. Only the member that is set is
+/// written out, and when several members appear on the wire the last one wins. Messages hold the
+/// group as an `Option` of the enum so that the unset state can be represented.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{
+///     decode_oneof_member, Buffer, DecodeError, LengthDelimited, LengthDelimitedField, Oneof, Tag,
+///     Varint, VarintField, WireType,
+/// };
+///
+/// #[derive(Debug, PartialEq)]
+/// enum Id {
+///     Number(u32),
+///     Name(String),
+/// }
+///
+/// impl Oneof for Id {
+///     fn encode(&self, buffer: &mut Buffer) -> usize {
+///         match self {
+///             Id::Number(v) => v.to_varint_field_always::<1>(buffer),
+///             Id::Name(v) => v.to_length_delimited_field_always::<2>(buffer),
+///         }
+///     }
+///
+///     fn decode_field(
+///         oneof: &mut Option<Self>,
+///         tag: &Tag,
+///         buffer: &[u8],
+///     ) -> Result<Option<usize>, DecodeError> {
+///         match (tag.field_number(), tag.wire_type()) {
+///             (1, WireType::Varint) => {
+///                 decode_oneof_member(oneof, buffer, u32::from_varint, Id::Number).map(Some)
+///             }
+///             (2, WireType::LengthDelimited) => {
+///                 decode_oneof_member(oneof, buffer, String::from_length_delimited, Id::Name)
+///                     .map(Some)
+///             }
+///             _ => Ok(None),
+///         }
+///     }
+/// }
+/// ```
+pub trait Oneof: Sized {
+    /// This function writes out the member of the group that is set, including its tag.
+    fn encode(&self, buffer: &mut Buffer) -> usize;
+
+    /// This function decodes a field into the group. The buffer starts immediately after the tag
+    /// and the number of bytes consumed by the value is returned. `None` is returned when the tag
+    /// does not identify a member of the group.
+    fn decode_field(
+        oneof: &mut Option<Self>,
+        tag: &Tag,
+        buffer: &[u8],
+    ) -> Result<Option<usize>, DecodeError>;
+
+    /// This function merges the other member into the group. The default replaces the member,
+    /// groups with message members merge them when both hold the same member.
+    fn merge(&mut self, other: Self) -> Result<(), DecodeError> {
+        *self = other;
+        Ok(())
+    }
+}
+
+/// Merges the other value of a `oneof` group into the group, which takes the other member when it
+/// is not set.
+pub fn merge_oneof<O: Oneof>(oneof: &mut Option<O>, other: Option<O>) -> Result<(), DecodeError> {
+    match (oneof.as_mut(), other) {
+        (Some(current), Some(other)) => return current.merge(other),
+        (None, Some(other)) => *oneof = Some(other),
+        (_, None) => {}
+    }
+    Ok(())
+}
+
+/// Decodes a scalar member of a `oneof` group, replacing whichever member was previously set.
+///
+/// The decode function is one of the `from_*` functions of the encoding traits and the wrap
+/// function builds the variant of the group from the decoded value.
+pub fn decode_oneof_member<O, T, D, W>(
+    oneof: &mut Option<O>,
+    buffer: &[u8],
+    decode: D,
+    wrap: W,
+) -> Result<usize, DecodeError>
+where
+    T: Default,
+    D: FnOnce(&mut T, &[u8]) -> Result<usize, DecodeError>,
+    W: FnOnce(T) -> O,
+{
+    let mut value = T::default();
+    let size = decode(&mut value, buffer)?;
+
+    *oneof = Some(wrap(value));
+    Ok(size)
+}
+
+/// Decodes a message member of a `oneof` group.
+///
+/// When the same member is already set the message on the wire is merged into it, otherwise it
+/// replaces whichever member was previously set. The get function returns the message held by the
+/// group when it is the member being decoded.
+pub fn merge_oneof_message<O, M, G, W>(
+    oneof: &mut Option<O>,
+    buffer: &[u8],
+    get: G,
+    wrap: W,
+) -> Result<usize, DecodeError>
+where
+    M: Message,
+    G: FnOnce(&mut O) -> Option<&mut M>,
+    W: FnOnce(M) -> O,
+{
+    if let Some(current) = oneof.as_mut().and_then(get) {
+        return current.from_length_delimited(buffer);
+    }
+
+    decode_oneof_member(oneof, buffer, M::from_length_delimited, wrap)
+}
+
+/// Decodes a group member of a `oneof` group, which merges in the same way as a message member.
+///
+/// The field number is that of the member, which must also end the group on the wire.
+pub fn merge_oneof_group<O, M, G, W>(
+    oneof: &mut Option<O>,
+    field_number: u32,
+    buffer: &[u8],
+    get: G,
+    wrap: W,
+) -> Result<usize, DecodeError>
+where
+    M: Message,
+    G: FnOnce(&mut O) -> Option<&mut M>,
+    W: FnOnce(M) -> O,
+{
+    if let Some(current) = oneof.as_mut().and_then(get) {
+        return current.from_group(field_number, buffer);
+    }
+
+    decode_oneof_member(
+        oneof,
+        buffer,
+        |v: &mut M, b: &[u8]| v.from_group(field_number, b),
+        wrap,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{GroupField, LengthDelimitedField, Varint, VarintField, WireType};
+    use rstest::*;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Point {
+        x: u32,
+        y: u32,
+    }
+
+    impl Message for Point {
+        fn encode(&self, buffer: &mut Buffer) -> usize {
+            self.x.to_varint_field::<1>(buffer) + self.y.to_varint_field::<2>(buffer)
+        }
+
+        fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+            match (tag.field_number(), tag.wire_type()) {
+                (1, WireType::Varint) => self.x.from_varint(buffer).map(Some),
+                (2, WireType::Varint) => self.y.from_varint(buffer).map(Some),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Shape {
+        Radius(u32),
+        Name(String),
+        Point(Point),
+        Frame(Point),
+    }
+
+    impl Oneof for Shape {
+        fn encode(&self, buffer: &mut Buffer) -> usize {
+            match self {
+                Shape::Radius(v) => v.to_varint_field_always::<1>(buffer),
+                Shape::Name(v) => v.to_length_delimited_field_always::<2>(buffer),
+                Shape::Point(v) => v.to_length_delimited_field_always::<3>(buffer),
+                Shape::Frame(v) => v.to_group_field_always::<4>(buffer),
+            }
+        }
+
+        fn decode_field(
+            oneof: &mut Option<Self>,
+            tag: &Tag,
+            buffer: &[u8],
+        ) -> Result<Option<usize>, DecodeError> {
+            match (tag.field_number(), tag.wire_type()) {
+                (1, WireType::Varint) => {
+                    decode_oneof_member(oneof, buffer, u32::from_varint, Shape::Radius).map(Some)
+                }
+                (2, WireType::LengthDelimited) => {
+                    decode_oneof_member(oneof, buffer, String::from_length_delimited, Shape::Name)
+                        .map(Some)
+                }
+                (3, WireType::LengthDelimited) => merge_oneof_message(
+                    oneof,
+                    buffer,
+                    |v| match v {
+                        Shape::Point(p) => Some(p),
+                        _ => None,
+                    },
+                    Shape::Point,
+                )
+                .map(Some),
+                (4, WireType::StartGroup) => merge_oneof_group(
+                    oneof,
+                    4,
+                    buffer,
+                    |v| match v {
+                        Shape::Frame(p) => Some(p),
+                        _ => None,
+                    },
+                    Shape::Frame,
+                )
+                .map(Some),
+                _ => Ok(None),
+            }
+        }
+
+        fn merge(&mut self, other: Self) -> Result<(), DecodeError> {
+            match (self, other) {
+                (Shape::Point(v), Shape::Point(o)) => v.merge(o),
+                (Shape::Frame(v), Shape::Frame(o)) => v.merge(o),
+                (this, other) => {
+                    *this = other;
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Drawing {
+        id: u32,
+        shape: Option<Shape>,
+    }
+
+    impl Message for Drawing {
+        fn encode(&self, buffer: &mut Buffer) -> usize {
+            let mut size = self.id.to_varint_field::<10>(buffer);
+            if let Some(shape) = &self.shape {
+                size += shape.encode(buffer);
+            }
+            size
+        }
+
+        fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+            match (tag.field_number(), tag.wire_type()) {
+                (10, WireType::Varint) => self.id.from_varint(buffer).map(Some),
+                _ => Shape::decode_field(&mut self.shape, tag, buffer),
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(None, vec![80, 1])]
+    #[case(Some(Shape::Radius(0)), vec![80, 1, 8, 0])]
+    #[case(Some(Shape::Name("a".to_string())), vec![80, 1, 18, 1, 97])]
+    #[case(Some(Shape::Point(Point { x: 1, y: 2 })), vec![80, 1, 26, 4, 8, 1, 16, 2])]
+    fn test_oneof_encoding(#[case] shape: Option<Shape>, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let drawing = Drawing { id: 1, shape };
+
+        // Act
+        let bytes = drawing.encode_to_vec();
+
+        // Assert
+        assert_eq!(bytes, expected_buffer);
+    }
+
+    #[rstest]
+    #[case(vec![8, 5, 18, 1, 97], Some(Shape::Name("a".to_string())))]
+    #[case(vec![18, 1, 97, 8, 5], Some(Shape::Radius(5)))]
+    #[case(vec![26, 2, 8, 1, 8, 5], Some(Shape::Radius(5)))]
+    #[case(vec![26, 2, 8, 1, 26, 2, 16, 2], Some(Shape::Point(Point { x: 1, y: 2 })))]
+    #[case(vec![26, 2, 8, 1, 8, 5, 26, 2, 16, 2], Some(Shape::Point(Point { x: 0, y: 2 })))]
+    #[case(vec![80, 1], None)]
+    fn test_oneof_last_one_wins_decoding(
+        #[case] buffer: Vec<u8>,
+        #[case] expected_shape: Option<Shape>,
+    ) {
+        // Act
+        let drawing = Drawing::decode(&buffer).unwrap();
+
+        // Assert
+        assert_eq!(drawing.shape, expected_shape);
+    }
+
+    #[rstest]
+    #[case(vec![8, 5, 13, 1, 0, 0, 0], Some(Shape::Radius(5)))]
+    #[case(vec![8, 5, 10, 1, 97], Some(Shape::Radius(5)))]
+    #[case(vec![18, 1, 97, 24, 1], Some(Shape::Name("a".to_string())))]
+    #[case(vec![26, 2, 8, 1, 27, 16, 2, 28], Some(Shape::Point(Point { x: 1, y: 0 })))]
+    #[case(vec![34, 2, 8, 1], None)]
+    fn test_oneof_member_with_wrong_wire_type_is_skipped(
+        #[case] buffer: Vec<u8>,
+        #[case] expected_shape: Option<Shape>,
+    ) {
+        // Act
+        let drawing = Drawing::decode(&buffer).unwrap();
+
+        // Assert
+        assert_eq!(drawing.shape, expected_shape);
+    }
+
+    #[rstest]
+    #[case(vec![80, 1], vec![8, 5], Some(Shape::Radius(5)))]
+    #[case(vec![8, 5], vec![80, 2], Some(Shape::Radius(5)))]
+    #[case(vec![8, 5], vec![18, 1, 97], Some(Shape::Name("a".to_string())))]
+    #[case(vec![26, 2, 8, 1], vec![8, 5], Some(Shape::Radius(5)))]
+    #[case(vec![26, 2, 8, 1], vec![26, 2, 16, 2], Some(Shape::Point(Point { x: 1, y: 2 })))]
+    #[case(vec![35, 8, 1, 36], vec![35, 16, 2, 36], Some(Shape::Frame(Point { x: 1, y: 2 })))]
+    #[case(vec![26, 2, 8, 1], vec![35, 16, 2, 36], Some(Shape::Frame(Point { x: 0, y: 2 })))]
+    fn test_merged_oneof_matches_concatenated_decoding(
+        #[case] first: Vec<u8>,
+        #[case] second: Vec<u8>,
+        #[case] expected_shape: Option<Shape>,
+    ) {
+        // Arrange
+        let mut drawing = Drawing::decode(&first).unwrap();
+        let other = Drawing::decode(&second).unwrap();
+        let concatenated = Drawing::decode(&[first, second].concat()).unwrap();
+
+        // Act
+        merge_oneof(&mut drawing.shape, other.shape).unwrap();
+
+        // Assert
+        assert_eq!(drawing.shape, expected_shape);
+        assert_eq!(drawing.shape, concatenated.shape);
+    }
+}
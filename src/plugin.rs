@@ -0,0 +1,267 @@
+use crate::{
+    Buffer, DecodeError, Enumeration, FileDescriptorProto, LengthDelimited, LengthDelimitedCodec,
+    LengthDelimitedField, Message, RepeatedField, SignExtendedVarint, SignExtendedVarintField, Tag,
+    Varint, VarintField, WireType,
+};
+
+/// The version number of the protocol compiler.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CompilerVersion {
+    /// The major version.
+    pub major: Option<i32>,
+
+    /// The minor version.
+    pub minor: Option<i32>,
+
+    /// The patch version.
+    pub patch: Option<i32>,
+
+    /// A suffix for alpha, beta or release candidate versions, e.g. `rc2`.
+    pub suffix: Option<String>,
+}
+
+impl CompilerVersion {
+    /// Returns the value of the `major` field, or zero when it is not set.
+    pub fn major(&self) -> i32 {
+        self.major.unwrap_or_default()
+    }
+
+    /// Returns the value of the `minor` field, or zero when it is not set.
+    pub fn minor(&self) -> i32 {
+        self.minor.unwrap_or_default()
+    }
+
+    /// Returns the value of the `patch` field, or zero when it is not set.
+    pub fn patch(&self) -> i32 {
+        self.patch.unwrap_or_default()
+    }
+
+    /// Returns the value of the `suffix` field, or an empty string when it is not set.
+    pub fn suffix(&self) -> &str {
+        self.suffix.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for CompilerVersion {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.major.to_sign_extended_varint_field::<1>(buffer);
+        size += self.minor.to_sign_extended_varint_field::<2>(buffer);
+        size += self.patch.to_sign_extended_varint_field::<3>(buffer);
+        size += self.suffix.to_length_delimited_field::<4>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => self.major.from_sign_extended_varint(buffer).map(Some),
+            (2, WireType::Varint) => self.minor.from_sign_extended_varint(buffer).map(Some),
+            (3, WireType::Varint) => self.patch.from_sign_extended_varint(buffer).map(Some),
+            (4, WireType::LengthDelimited) => self.suffix.from_length_delimited(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Represents `google.protobuf.compiler.CodeGeneratorRequest`, the request the protocol compiler
+/// writes to the standard input of a plugin.
+///
+/// A plugin is an executable named `protoc-gen-NAME` that `protoc` runs for `--NAME_out`. It
+/// reads a single request from its standard input and writes a single `CodeGeneratorResponse` to
+/// its standard output, both without a length prefix.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CodeGeneratorRequest {
+    /// The `.proto` files that code should be generated for.
+    pub file_to_generate: Vec<String>,
+
+    /// The parameter passed on the command line, e.g. with `--ks_opt`.
+    pub parameter: Option<String>,
+
+    /// The version of the protocol compiler.
+    pub compiler_version: Option<CompilerVersion>,
+
+    /// Every file in `file_to_generate` and every file they import, with each file following the files it imports.
+    pub proto_file: Vec<FileDescriptorProto>,
+}
+
+impl CodeGeneratorRequest {
+    /// Returns the value of the `parameter` field, or an empty string when it is not set.
+    pub fn parameter(&self) -> &str {
+        self.parameter.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for CodeGeneratorRequest {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self
+            .file_to_generate
+            .to_unpacked_field::<LengthDelimitedCodec, 1>(buffer);
+        size += self.parameter.to_length_delimited_field::<2>(buffer);
+        size += self.compiler_version.to_length_delimited_field::<3>(buffer);
+        size += self
+            .proto_file
+            .to_unpacked_field::<LengthDelimitedCodec, 15>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, wire_type) => self
+                .file_to_generate
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            (2, WireType::LengthDelimited) => {
+                self.parameter.from_length_delimited(buffer).map(Some)
+            }
+            (3, WireType::LengthDelimited) => self
+                .compiler_version
+                .from_length_delimited(buffer)
+                .map(Some),
+            (15, wire_type) => self
+                .proto_file
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Represents `google.protobuf.compiler.CodeGeneratorResponse`, the response a plugin writes to
+/// its standard output.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CodeGeneratorResponse {
+    /// The error message when the `.proto` files could not be handled.
+    pub error: Option<String>,
+
+    /// A bitmask of the `Feature` values supported by the plugin.
+    pub supported_features: Option<u64>,
+
+    /// The files generated by the plugin.
+    pub file: Vec<GeneratedFile>,
+}
+
+impl CodeGeneratorResponse {
+    /// Returns the value of the `error` field, or an empty string when it is not set.
+    pub fn error(&self) -> &str {
+        self.error.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `supported_features` field, or zero when it is not set.
+    pub fn supported_features(&self) -> u64 {
+        self.supported_features.unwrap_or_default()
+    }
+}
+
+impl Message for CodeGeneratorResponse {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.error.to_length_delimited_field::<1>(buffer);
+        size += self.supported_features.to_varint_field::<2>(buffer);
+        size += self
+            .file
+            .to_unpacked_field::<LengthDelimitedCodec, 15>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.error.from_length_delimited(buffer).map(Some),
+            (2, WireType::Varint) => self.supported_features.from_varint(buffer).map(Some),
+            (15, wire_type) => self
+                .file
+                .from_repeated_field::<LengthDelimitedCodec>(wire_type, buffer),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A file generated by a plugin.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GeneratedFile {
+    /// The name of the file, relative to the output directory.
+    pub name: Option<String>,
+
+    /// The insertion point in another generated file that the content should be written to.
+    pub insertion_point: Option<String>,
+
+    /// The content of the file.
+    pub content: Option<String>,
+}
+
+impl GeneratedFile {
+    /// Returns the value of the `name` field, or an empty string when it is not set.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `insertion_point` field, or an empty string when it is not set.
+    pub fn insertion_point(&self) -> &str {
+        self.insertion_point.as_deref().unwrap_or_default()
+    }
+
+    /// Returns the value of the `content` field, or an empty string when it is not set.
+    pub fn content(&self) -> &str {
+        self.content.as_deref().unwrap_or_default()
+    }
+}
+
+impl Message for GeneratedFile {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        size += self.name.to_length_delimited_field::<1>(buffer);
+        size += self.insertion_point.to_length_delimited_field::<2>(buffer);
+        size += self.content.to_length_delimited_field::<15>(buffer);
+
+        size
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self.name.from_length_delimited(buffer).map(Some),
+            (2, WireType::LengthDelimited) => {
+                self.insertion_point.from_length_delimited(buffer).map(Some)
+            }
+            (15, WireType::LengthDelimited) => self.content.from_length_delimited(buffer).map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The optional features a plugin can support.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Feature {
+    /// No optional features.
+    NoFeatures = 0,
+
+    /// The plugin supports proto3 `optional` fields.
+    Proto3Optional = 1,
+
+    /// The plugin supports files written with editions.
+    SupportsEditions = 2,
+}
+
+impl Enumeration for Feature {
+    const VALUES: &'static [Self] = &[
+        Feature::NoFeatures,
+        Feature::Proto3Optional,
+        Feature::SupportsEditions,
+    ];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Feature::NoFeatures => "FEATURE_NONE",
+            Feature::Proto3Optional => "FEATURE_PROTO3_OPTIONAL",
+            Feature::SupportsEditions => "FEATURE_SUPPORTS_EDITIONS",
+        }
+    }
+}
@@ -0,0 +1,2250 @@
+use crate::descriptor_pool::to_json_name;
+use crate::proto_parser::{
+    self, EnumDef, FieldDef, ImportKind, Label, Location, MessageDef, OptionDef, OptionValue,
+    ProtoFile, Range, ServiceDef, Span, Syntax, TypeRef, MAX_FIELD_NUMBER,
+};
+use crate::text_format::{add_value, merge_text, scalar_value};
+use crate::{
+    CType, CompileError, DescriptorPool, DescriptorProto, DynamicMessage, EnumDescriptorProto,
+    EnumOptions, EnumReservedRange, EnumType, EnumValueDescriptorProto, EnumValueOptions,
+    Enumeration, ExtensionRange, ExtensionRangeOptions, FeatureSet, FieldDescriptorProto,
+    FieldLabel, FieldOptions, FieldPresence, FieldType, FieldValue, FileDescriptorProto,
+    FileDescriptorSet, FileOptions, IdempotencyLevel, JsType, JsonFormat, Message, MessageEncoding,
+    MessageOptions, MethodDescriptorProto, MethodOptions, NamePart, OneofDescriptorProto,
+    OneofOptions, OpenEnum, OptimizeMode, RepeatedFieldEncoding, ReservedRange,
+    ServiceDescriptorProto, ServiceOptions, SourceCodeInfo, SourceLocation, UninterpretedOption,
+    Utf8Validation,
+};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+// The sources of the well known types, which can be imported without being added or found on an
+// include path.
+const WELL_KNOWN_SOURCES: [(&str, &str); 11] = [
+    (
+        "google/protobuf/any.proto",
+        include_str!("../include/google/protobuf/any.proto"),
+    ),
+    (
+        "google/protobuf/api.proto",
+        include_str!("../include/google/protobuf/api.proto"),
+    ),
+    (
+        "google/protobuf/descriptor.proto",
+        include_str!("../include/google/protobuf/descriptor.proto"),
+    ),
+    (
+        "google/protobuf/duration.proto",
+        include_str!("../include/google/protobuf/duration.proto"),
+    ),
+    (
+        "google/protobuf/empty.proto",
+        include_str!("../include/google/protobuf/empty.proto"),
+    ),
+    (
+        "google/protobuf/field_mask.proto",
+        include_str!("../include/google/protobuf/field_mask.proto"),
+    ),
+    (
+        "google/protobuf/source_context.proto",
+        include_str!("../include/google/protobuf/source_context.proto"),
+    ),
+    (
+        "google/protobuf/struct.proto",
+        include_str!("../include/google/protobuf/struct.proto"),
+    ),
+    (
+        "google/protobuf/timestamp.proto",
+        include_str!("../include/google/protobuf/timestamp.proto"),
+    ),
+    (
+        "google/protobuf/type.proto",
+        include_str!("../include/google/protobuf/type.proto"),
+    ),
+    (
+        "google/protobuf/wrappers.proto",
+        include_str!("../include/google/protobuf/wrappers.proto"),
+    ),
+];
+
+/// Compiles `.proto` files into descriptors, as `protoc --descriptor_set_out` does, without
+/// needing the protocol compiler to be installed.
+///
+/// Type names are resolved to their fully qualified form and the built in options are applied to
+/// the options messages. Custom options, which are defined by extending the options messages, are
+/// set as extension fields of the options and can be read with `get_option` on the descriptors.
+///
+/// Imports are looked up among the added sources first, then in the include paths in the order
+/// they were added, and finally among the `google/protobuf/*.proto` files bundled with the crate.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{DescriptorPool, ProtoCompiler};
+///
+/// let mut compiler = ProtoCompiler::new();
+/// compiler.add_source(
+///     "greeting.proto",
+///     r#"
+///         syntax = "proto3";
+///         package greeting;
+///
+///         message Hello {
+///             string user_name = 1;
+///         }
+///     "#,
+/// );
+///
+/// let set = compiler.compile(&["greeting.proto"]).unwrap();
+/// let pool = DescriptorPool::from_file_descriptor_set(set).unwrap();
+///
+/// assert!(pool.get_message_by_name("greeting.Hello").is_some());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProtoCompiler {
+    sources: HashMap<String, String>,
+    include_paths: Vec<PathBuf>,
+    include_imports: bool,
+    include_source_info: bool,
+}
+
+impl ProtoCompiler {
+    /// Creates a compiler without any sources.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the source of a file, which can then be compiled or imported under the given name.
+    pub fn add_source(&mut self, name: impl Into<String>, source: impl Into<String>) {
+        let _ = self.sources.insert(name.into(), source.into());
+    }
+
+    /// Adds a directory in which the files to compile and import are looked up, as the `-I` option
+    /// of the protocol compiler does.
+    pub fn add_include_path(&mut self, path: impl Into<PathBuf>) {
+        self.include_paths.push(path.into());
+    }
+
+    /// Returns the name a file on disk is compiled and imported by, which is its path relative to
+    /// the first include path that contains it, e.g. `acme/people.proto` for
+    /// `protos/acme/people.proto` when `protos` is an include path.
+    pub fn import_name(&self, file: impl AsRef<Path>) -> String {
+        let file = file.as_ref();
+        self.include_paths
+            .iter()
+            .filter(|include_path| include_path.as_os_str() != ".")
+            .find_map(|include_path| file.strip_prefix(include_path).ok())
+            .unwrap_or(file)
+            .components()
+            .filter(|component| matches!(component, Component::Normal(_)))
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Sets whether the files imported by the compiled files are included in the output, before
+    /// the files that import them.
+    pub fn include_imports(&mut self, include_imports: bool) {
+        self.include_imports = include_imports;
+    }
+
+    /// Sets whether `SourceCodeInfo`, holding the locations and comments of the definitions, is
+    /// included in the output.
+    pub fn include_source_info(&mut self, include_source_info: bool) {
+        self.include_source_info = include_source_info;
+    }
+
+    /// Compiles the named files along with the files they import.
+    pub fn compile(&self, files: &[&str]) -> Result<FileDescriptorSet, CompileError> {
+        let mut compilation = Compilation::default();
+        for file in files {
+            self.compile_file(file, &mut compilation)?;
+        }
+
+        let names: Vec<&str> = match self.include_imports {
+            true => compilation.order.iter().map(String::as_str).collect(),
+            false => files.to_vec(),
+        };
+
+        let mut seen = HashSet::new();
+        Ok(FileDescriptorSet {
+            file: names
+                .into_iter()
+                .filter(|name| seen.insert(*name))
+                .filter_map(|name| compilation.pool.get_file_by_name(name))
+                .map(|file| file.file_descriptor_proto().clone())
+                .collect(),
+        })
+    }
+
+    fn compile_file(&self, name: &str, compilation: &mut Compilation) -> Result<(), CompileError> {
+        if compilation.pool.get_file_by_name(name).is_some() {
+            return Ok(());
+        }
+        if let Some(position) = compilation.loading.iter().position(|f| f == name) {
+            let mut cycle = compilation.loading[position..].to_vec();
+            cycle.push(name.to_string());
+            return Err(CompileError::ImportCycle(cycle.join(" -> ")));
+        }
+
+        let source = self
+            .load_source(name)?
+            .ok_or_else(|| CompileError::FileNotFound(name.to_string()))?;
+        let file = proto_parser::parse(&source).map_err(|e| CompileError::Source {
+            file: name.to_string(),
+            line: e.line + 1,
+            column: e.column + 1,
+            message: e.message,
+        })?;
+
+        compilation.loading.push(name.to_string());
+        for import in &file.imports {
+            match self.compile_file(&import.path, compilation) {
+                Err(CompileError::FileNotFound(path)) if path == import.path => {
+                    let span = import.location.span;
+                    return Err(CompileError::Source {
+                        file: name.to_string(),
+                        line: span.start.0 + 1,
+                        column: span.start.1 + 1,
+                        message: format!("The import {} could not be found.", path),
+                    });
+                }
+                result => result?,
+            }
+        }
+        let _ = compilation.loading.pop();
+
+        let (proto, option_spans) =
+            Lowering::new(name, file.syntax, self.include_source_info).lower_file(&file)?;
+        let proto = resolve_file(&compilation.pool, proto, &option_spans)?;
+        compilation.pool.add_file_descriptor_proto(proto)?;
+        compilation.order.push(name.to_string());
+        Ok(())
+    }
+
+    fn load_source(&self, name: &str) -> Result<Option<Cow<'_, str>>, CompileError> {
+        if let Some(source) = self.sources.get(name) {
+            return Ok(Some(Cow::Borrowed(source)));
+        }
+
+        for include_path in &self.include_paths {
+            let path = include_path.join(name);
+            if path.is_file() {
+                return std::fs::read_to_string(&path)
+                    .map(|source| Some(Cow::Owned(source)))
+                    .map_err(|e| CompileError::Io {
+                        file: path.display().to_string(),
+                        message: e.to_string(),
+                    });
+            }
+        }
+
+        Ok(WELL_KNOWN_SOURCES
+            .iter()
+            .find(|(path, _)| *path == name)
+            .map(|(_, source)| Cow::Borrowed(*source)))
+    }
+}
+
+#[derive(Default)]
+struct Compilation {
+    pool: DescriptorPool,
+    // The compiled files, with every file after the files it imports.
+    order: Vec<String>,
+    // The files whose imports are being compiled.
+    loading: Vec<String>,
+}
+
+// Lowers the syntax tree of a file into a `FileDescriptorProto`, recording the locations of the
+// definitions when source information is wanted.
+struct Lowering<'a> {
+    file: &'a str,
+    syntax: Syntax,
+    locations: Option<Vec<SourceLocation>>,
+    option_spans: OptionSpans,
+}
+
+// The locations of the uninterpreted options of each definition, indexed by the path of the
+// definition as in `SourceCodeInfo`.
+type OptionSpans = HashMap<Vec<i32>, Vec<Span>>;
+
+impl<'a> Lowering<'a> {
+    fn new(file: &'a str, syntax: Syntax, include_source_info: bool) -> Self {
+        Self {
+            file,
+            syntax,
+            locations: include_source_info.then(Vec::new),
+            option_spans: HashMap::new(),
+        }
+    }
+
+    fn error(&self, span: Span, message: impl Into<String>) -> CompileError {
+        CompileError::Source {
+            file: self.file.to_string(),
+            line: span.start.0 + 1,
+            column: span.start.1 + 1,
+            message: message.into(),
+        }
+    }
+
+    fn locate(&mut self, path: &[i32], location: &Location) {
+        if let Some(locations) = &mut self.locations {
+            let comments = &location.comments;
+            locations.push(SourceLocation {
+                path: path.to_vec(),
+                span: location.span.to_source_span(),
+                leading_comments: comments.leading.clone(),
+                trailing_comments: comments.trailing.clone(),
+                leading_detached_comments: comments.detached.clone(),
+            });
+        }
+    }
+
+    fn lower_file(
+        mut self,
+        file: &ProtoFile,
+    ) -> Result<(FileDescriptorProto, OptionSpans), CompileError> {
+        self.locate(
+            &[],
+            &Location {
+                span: file.span,
+                ..Default::default()
+            },
+        );
+        if let Some(location) = &file.syntax_location {
+            match file.syntax {
+                Syntax::Editions => self.locate(&[14], location),
+                Syntax::Proto2 | Syntax::Proto3 => self.locate(&[12], location),
+            }
+        }
+
+        let mut proto = FileDescriptorProto {
+            name: Some(self.file.to_string()),
+            syntax: match file.syntax {
+                Syntax::Proto2 => None,
+                Syntax::Proto3 => Some("proto3".to_string()),
+                Syntax::Editions => Some("editions".to_string()),
+            },
+            edition: file.edition.map(OpenEnum::from),
+            ..Default::default()
+        };
+
+        let package = match &file.package {
+            Some((package, location)) => {
+                self.locate(&[2], location);
+                proto.package = Some(package.clone());
+                package.clone()
+            }
+            None => String::new(),
+        };
+
+        for (index, import) in file.imports.iter().enumerate() {
+            self.locate(&[3, index as i32], &import.location);
+            proto.dependency.push(import.path.clone());
+            match import.kind {
+                ImportKind::Public => proto.public_dependency.push(index as i32),
+                ImportKind::Weak => proto.weak_dependency.push(index as i32),
+                ImportKind::Default => {}
+            }
+        }
+
+        for (index, message) in file.messages.iter().enumerate() {
+            proto
+                .message_type
+                .push(self.lower_message(message, &[4, index as i32], &package)?);
+        }
+        for (index, enumeration) in file.enums.iter().enumerate() {
+            proto
+                .enum_type
+                .push(self.lower_enum(enumeration, &[5, index as i32])?);
+        }
+        for (index, service) in file.services.iter().enumerate() {
+            proto
+                .service
+                .push(self.lower_service(service, &[6, index as i32])?);
+        }
+        for (index, extension) in file.extensions.iter().enumerate() {
+            let (field, _) = self.lower_field(extension, &[7, index as i32])?;
+            proto.extension.push(field);
+        }
+
+        proto.options = self.lower_options(&file.options, &[])?;
+        proto.source_code_info = self
+            .locations
+            .take()
+            .map(|location| SourceCodeInfo { location });
+        Ok((proto, self.option_spans))
+    }
+
+    fn lower_message(
+        &mut self,
+        message: &MessageDef,
+        path: &[i32],
+        scope: &str,
+    ) -> Result<DescriptorProto, CompileError> {
+        self.locate(path, &message.location);
+        let full_name = match scope.is_empty() {
+            true => message.name.clone(),
+            false => format!("{}.{}", scope, message.name),
+        };
+
+        let mut proto = DescriptorProto {
+            name: Some(message.name.clone()),
+            ..Default::default()
+        };
+
+        // Map entry messages are nested in the order their fields appear among the other nested
+        // messages.
+        let mut nested: Vec<((usize, usize), Option<DescriptorProto>, usize)> = Vec::new();
+        for (index, field) in message.fields.iter().enumerate() {
+            let (field_proto, entry) = self.lower_field(field, &child(path, 2, index))?;
+            proto.field.push(field_proto);
+            if let Some(entry) = entry {
+                nested.push((field.location.span.start, Some(entry), 0));
+            }
+        }
+        for (index, nested_message) in message.messages.iter().enumerate() {
+            nested.push((nested_message.location.span.start, None, index));
+        }
+        nested.sort_by_key(|(start, _, _)| *start);
+
+        for (position, (_, entry, index)) in nested.into_iter().enumerate() {
+            proto.nested_type.push(match entry {
+                Some(entry) => entry,
+                None => self.lower_message(
+                    &message.messages[index],
+                    &child(path, 3, position),
+                    &full_name,
+                )?,
+            });
+        }
+
+        for (index, enumeration) in message.enums.iter().enumerate() {
+            proto
+                .enum_type
+                .push(self.lower_enum(enumeration, &child(path, 4, index))?);
+        }
+
+        for statement in &message.extension_ranges {
+            let first = child(path, 5, proto.extension_range.len());
+            let options =
+                self.lower_options::<ExtensionRangeOptions>(&statement.options, &first)?;
+            for range in &statement.ranges {
+                let range_path = child(path, 5, proto.extension_range.len());
+                if let Some(spans) = self.option_spans.get(&first).cloned() {
+                    let _ = self.option_spans.insert(range_path.clone(), spans);
+                }
+                self.locate(&range_path, &statement.location);
+                proto.extension_range.push(ExtensionRange {
+                    start: Some(range.start),
+                    end: Some(exclusive_end(range)),
+                    options: options.clone(),
+                });
+            }
+        }
+
+        for (index, extension) in message.extensions.iter().enumerate() {
+            let (field, _) = self.lower_field(extension, &child(path, 6, index))?;
+            proto.extension.push(field);
+        }
+
+        proto.options = self.lower_options(&message.options, path)?;
+
+        for (index, oneof) in message.oneofs.iter().enumerate() {
+            let oneof_path = child(path, 8, index);
+            self.locate(&oneof_path, &oneof.location);
+            proto.oneof_decl.push(OneofDescriptorProto {
+                name: Some(oneof.name.clone()),
+                options: self.lower_options(&oneof.options, &oneof_path)?,
+            });
+        }
+
+        // Optional proto3 fields are placed in a synthetic oneof, after the real ones, named after
+        // the field with a prefix that makes it unique.
+        let mut names: HashSet<String> = message.fields.iter().map(|f| f.name.clone()).collect();
+        names.extend(message.oneofs.iter().map(|o| o.name.clone()));
+        for field in &mut proto.field {
+            if field.proto3_optional != Some(true) {
+                continue;
+            }
+
+            let mut name = format!("_{}", field.name());
+            while names.contains(&name) {
+                name.insert(0, 'X');
+            }
+            let _ = names.insert(name.clone());
+
+            field.oneof_index = Some(proto.oneof_decl.len() as i32);
+            proto.oneof_decl.push(OneofDescriptorProto {
+                name: Some(name),
+                options: None,
+            });
+        }
+
+        proto.reserved_range = message
+            .reserved_ranges
+            .iter()
+            .map(|range| ReservedRange {
+                start: Some(range.start),
+                end: Some(exclusive_end(range)),
+            })
+            .collect();
+        proto.reserved_name = message.reserved_names.clone();
+
+        Ok(proto)
+    }
+
+    // Lowers a field, along with the entry message of a map field.
+    fn lower_field(
+        &mut self,
+        field: &FieldDef,
+        path: &[i32],
+    ) -> Result<(FieldDescriptorProto, Option<DescriptorProto>), CompileError> {
+        self.locate(path, &field.location);
+
+        let mut proto = FieldDescriptorProto {
+            name: Some(field.name.clone()),
+            number: Some(field.number),
+            label: Some(
+                match field.label {
+                    Some(Label::Repeated) => FieldLabel::Repeated,
+                    Some(Label::Required) => FieldLabel::Required,
+                    Some(Label::Optional) | None => FieldLabel::Optional,
+                }
+                .into(),
+            ),
+            extendee: field.extendee.clone(),
+            oneof_index: field.oneof.map(|i| i as i32),
+            ..Default::default()
+        };
+
+        match &field.field_type {
+            TypeRef::Scalar(field_type) => proto.r#type = Some((*field_type).into()),
+            TypeRef::Named(type_name) => proto.type_name = Some(type_name.clone()),
+            TypeRef::Group(type_name) => {
+                proto.r#type = Some(FieldType::Group.into());
+                proto.type_name = Some(type_name.clone());
+            }
+        }
+
+        let mut entry = None;
+        if let Some((key, value)) = &field.map_types {
+            if !matches!(
+                key,
+                TypeRef::Scalar(
+                    FieldType::Int64
+                        | FieldType::Uint64
+                        | FieldType::Int32
+                        | FieldType::Fixed64
+                        | FieldType::Fixed32
+                        | FieldType::Bool
+                        | FieldType::String
+                        | FieldType::Uint32
+                        | FieldType::Sfixed32
+                        | FieldType::Sfixed64
+                        | FieldType::Sint32
+                        | FieldType::Sint64
+                )
+            ) {
+                return Err(self.error(field.location.span, "The map key type is invalid."));
+            }
+
+            let entry_name = map_entry_name(&field.name);
+            proto.label = Some(FieldLabel::Repeated.into());
+            proto.type_name = Some(entry_name.clone());
+            entry = Some(DescriptorProto {
+                name: Some(entry_name),
+                field: [("key", key), ("value", value)]
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, (name, field_type))| FieldDescriptorProto {
+                        name: Some(name.to_string()),
+                        number: Some(index as i32 + 1),
+                        label: Some(FieldLabel::Optional.into()),
+                        r#type: match field_type {
+                            TypeRef::Scalar(field_type) => Some((*field_type).into()),
+                            TypeRef::Named(_) | TypeRef::Group(_) => None,
+                        },
+                        type_name: match field_type {
+                            TypeRef::Scalar(_) => None,
+                            TypeRef::Named(type_name) | TypeRef::Group(type_name) => {
+                                Some(type_name.clone())
+                            }
+                        },
+                        json_name: Some(name.to_string()),
+                        ..Default::default()
+                    })
+                    .collect(),
+                options: Some(MessageOptions {
+                    map_entry: Some(true),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            });
+        }
+
+        if self.syntax == Syntax::Proto3
+            && field.label == Some(Label::Optional)
+            && field.extendee.is_none()
+        {
+            proto.proto3_optional = Some(true);
+        }
+
+        let mut options = Vec::new();
+        for option in &field.options {
+            match option.simple_name() {
+                Some("default") => {
+                    if self.syntax == Syntax::Proto3 {
+                        return Err(self.error(
+                            option.span,
+                            "Explicit default values are not allowed in proto3.",
+                        ));
+                    }
+                    if field.label == Some(Label::Repeated) || entry.is_some() {
+                        return Err(
+                            self.error(option.span, "Repeated fields can not have a default.")
+                        );
+                    }
+                    proto.default_value = Some(self.default_value(field, option)?);
+                }
+                Some("packed") if self.syntax == Syntax::Editions => {
+                    return Err(self.error(
+                        option.span,
+                        "The packed option is not allowed in editions, use \
+                         features.repeated_field_encoding instead.",
+                    ));
+                }
+                Some("json_name") => {
+                    if field.extendee.is_some() {
+                        return Err(self.error(option.span, "Extensions can not have a JSON name."));
+                    }
+                    proto.json_name =
+                        Some(string_value(option).map_err(|m| self.error(option.span, m))?);
+                }
+                _ => options.push(option.clone()),
+            }
+        }
+        if proto.json_name.is_none() {
+            proto.json_name = Some(to_json_name(&field.name));
+        }
+        proto.options = self.lower_options(&options, path)?;
+
+        Ok((proto, entry))
+    }
+
+    // The default value of a field as text, in the form used by `FieldDescriptorProto`.
+    fn default_value(&self, field: &FieldDef, option: &OptionDef) -> Result<String, CompileError> {
+        let invalid = || {
+            self.error(
+                option.span,
+                format!("The default value of {} is invalid.", field.name),
+            )
+        };
+
+        let field_type = match &field.field_type {
+            TypeRef::Scalar(field_type) => *field_type,
+            // The type is not resolved yet, so any identifier is accepted as an enum value.
+            TypeRef::Group(_) => return Err(invalid()),
+            TypeRef::Named(_) => {
+                return match &option.value {
+                    OptionValue::Identifier(value) if !value.contains('.') => Ok(value.clone()),
+                    _ => Err(invalid()),
+                }
+            }
+        };
+
+        match (field_type, &option.value) {
+            (FieldType::String, OptionValue::String(value)) => {
+                String::from_utf8(value.clone()).map_err(|_| invalid())
+            }
+            (FieldType::Bytes, OptionValue::String(value)) => Ok(c_escape(value)),
+            (FieldType::Bool, OptionValue::Identifier(value))
+                if value == "true" || value == "false" =>
+            {
+                Ok(value.clone())
+            }
+            (FieldType::Float | FieldType::Double, OptionValue::Float(value)) => Ok(match value {
+                v if v.is_nan() => "nan".to_string(),
+                v if v.is_infinite() && *v > 0.0 => "inf".to_string(),
+                v if v.is_infinite() => "-inf".to_string(),
+                v => v.to_string(),
+            }),
+            (FieldType::Float | FieldType::Double, OptionValue::Identifier(value))
+                if value == "inf" || value == "nan" =>
+            {
+                Ok(value.clone())
+            }
+            (FieldType::Float | FieldType::Double, OptionValue::PositiveInt(value)) => {
+                Ok(value.to_string())
+            }
+            (FieldType::Float | FieldType::Double, OptionValue::NegativeInt(value)) => {
+                Ok(value.to_string())
+            }
+            (_, OptionValue::PositiveInt(value)) => {
+                let max = match field_type {
+                    FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => i32::MAX as u64,
+                    FieldType::Uint32 | FieldType::Fixed32 => u32::MAX as u64,
+                    FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => i64::MAX as u64,
+                    FieldType::Uint64 | FieldType::Fixed64 => u64::MAX,
+                    _ => return Err(invalid()),
+                };
+                match *value <= max {
+                    true => Ok(value.to_string()),
+                    false => Err(invalid()),
+                }
+            }
+            (_, OptionValue::NegativeInt(value)) => {
+                let min = match field_type {
+                    FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32 => i32::MIN as i64,
+                    FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64 => i64::MIN,
+                    _ => return Err(invalid()),
+                };
+                match *value >= min {
+                    true => Ok(value.to_string()),
+                    false => Err(invalid()),
+                }
+            }
+            _ => Err(invalid()),
+        }
+    }
+
+    fn lower_enum(
+        &mut self,
+        enumeration: &EnumDef,
+        path: &[i32],
+    ) -> Result<EnumDescriptorProto, CompileError> {
+        self.locate(path, &enumeration.location);
+
+        let mut proto = EnumDescriptorProto {
+            name: Some(enumeration.name.clone()),
+            options: self.lower_options(&enumeration.options, path)?,
+            reserved_range: enumeration
+                .reserved_ranges
+                .iter()
+                .map(|range| EnumReservedRange {
+                    start: Some(range.start),
+                    end: Some(range.end.unwrap_or(i32::MAX)),
+                })
+                .collect(),
+            reserved_name: enumeration.reserved_names.clone(),
+            ..Default::default()
+        };
+
+        for (index, value) in enumeration.values.iter().enumerate() {
+            let value_path = child(path, 2, index);
+            self.locate(&value_path, &value.location);
+            proto.value.push(EnumValueDescriptorProto {
+                name: Some(value.name.clone()),
+                number: Some(value.number),
+                options: self.lower_options(&value.options, &value_path)?,
+            });
+        }
+
+        let allow_alias = proto
+            .options
+            .as_ref()
+            .and_then(|o| o.allow_alias)
+            .unwrap_or_default();
+        let mut numbers = HashSet::new();
+        for value in &enumeration.values {
+            if !numbers.insert(value.number) && !allow_alias {
+                return Err(self.error(
+                    value.location.span,
+                    format!(
+                        "The number {} is used more than once in {}. Set the allow_alias option to allow aliases.",
+                        value.number, enumeration.name
+                    ),
+                ));
+            }
+        }
+
+        Ok(proto)
+    }
+
+    fn lower_service(
+        &mut self,
+        service: &ServiceDef,
+        path: &[i32],
+    ) -> Result<ServiceDescriptorProto, CompileError> {
+        self.locate(path, &service.location);
+
+        let mut proto = ServiceDescriptorProto {
+            name: Some(service.name.clone()),
+            options: self.lower_options(&service.options, path)?,
+            ..Default::default()
+        };
+
+        for (index, method) in service.methods.iter().enumerate() {
+            let method_path = child(path, 2, index);
+            self.locate(&method_path, &method.location);
+            proto.method.push(MethodDescriptorProto {
+                name: Some(method.name.clone()),
+                input_type: Some(method.input_type.clone()),
+                output_type: Some(method.output_type.clone()),
+                options: self.lower_options(&method.options, &method_path)?,
+                client_streaming: method.client_streaming.then_some(true),
+                server_streaming: method.server_streaming.then_some(true),
+            });
+        }
+
+        Ok(proto)
+    }
+
+    // Applies the built in options to a new options message. Options defined by extensions are
+    // kept as uninterpreted options until the file is resolved. `None` is returned when there are
+    // no options.
+    fn lower_options<O: BuiltinOptions>(
+        &mut self,
+        options: &[OptionDef],
+        path: &[i32],
+    ) -> Result<Option<O>, CompileError> {
+        if options.is_empty() {
+            return Ok(None);
+        }
+
+        let mut message = O::default();
+        for option in options {
+            if let [features, feature] = option.name.as_slice() {
+                if features.name == "features" && !features.is_extension && !feature.is_extension {
+                    if self.syntax != Syntax::Editions {
+                        return Err(self.error(
+                            option.span,
+                            "Features are only allowed in files that declare an edition.",
+                        ));
+                    }
+                    let features = message.features().get_or_insert_with(FeatureSet::default);
+                    match set_feature(features, &feature.name, option) {
+                        Ok(true) => continue,
+                        Ok(false) => {
+                            return Err(self.error(
+                                option.span,
+                                format!("The option {} is not known.", option.display_name()),
+                            ))
+                        }
+                        Err(message) => return Err(self.error(option.span, message)),
+                    }
+                }
+            }
+
+            match option.simple_name() {
+                Some(name) => match message.set_builtin(name, option) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        return Err(self.error(
+                            option.span,
+                            format!("The option {} is not known.", option.display_name()),
+                        ))
+                    }
+                    Err(message) => return Err(self.error(option.span, message)),
+                },
+                None => {
+                    message.uninterpreted_options().push(uninterpreted(option));
+                    self.option_spans
+                        .entry(path.to_vec())
+                        .or_default()
+                        .push(option.span);
+                }
+            }
+        }
+
+        Ok(Some(message))
+    }
+}
+
+fn child(path: &[i32], field: i32, index: usize) -> Vec<i32> {
+    let mut path = path.to_vec();
+    path.extend([field, index as i32]);
+    path
+}
+
+fn exclusive_end(range: &Range) -> i32 {
+    range.end.unwrap_or(MAX_FIELD_NUMBER) + 1
+}
+
+// The name of the entry message of a map field, e.g. `foo_bar` has the entry `FooBarEntry`.
+fn map_entry_name(field_name: &str) -> String {
+    let mut name = String::new();
+    let mut upper = true;
+
+    for c in field_name.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                name.push(c.to_ascii_uppercase());
+                upper = false;
+            }
+            c => name.push(c),
+        }
+    }
+
+    name + "Entry"
+}
+
+// Escapes bytes as C does, which is how defaults of bytes fields are written in descriptors.
+fn c_escape(value: &[u8]) -> String {
+    let mut escaped = String::new();
+
+    for &b in value {
+        match b {
+            b'\n' => escaped.push_str("\\n"),
+            b'\r' => escaped.push_str("\\r"),
+            b'\t' => escaped.push_str("\\t"),
+            b'"' => escaped.push_str("\\\""),
+            b'\'' => escaped.push_str("\\'"),
+            b'\\' => escaped.push_str("\\\\"),
+            0x20..=0x7e => escaped.push(b as char),
+            _ => escaped.push_str(&format!("\\{:03o}", b)),
+        }
+    }
+
+    escaped
+}
+
+fn uninterpreted(option: &OptionDef) -> UninterpretedOption {
+    let mut proto = UninterpretedOption {
+        name: option
+            .name
+            .iter()
+            .map(|part| NamePart {
+                name_part: Some(part.name.clone()),
+                is_extension: Some(part.is_extension),
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    match &option.value {
+        OptionValue::Identifier(value) => proto.identifier_value = Some(value.clone()),
+        OptionValue::PositiveInt(value) => proto.positive_int_value = Some(*value),
+        OptionValue::NegativeInt(value) => proto.negative_int_value = Some(*value),
+        OptionValue::Float(value) => proto.double_value = Some(*value),
+        OptionValue::String(value) => proto.string_value = Some(value.clone()),
+        OptionValue::Aggregate(value) => proto.aggregate_value = Some(value.clone()),
+    }
+
+    proto
+}
+
+fn bool_value(option: &OptionDef) -> Result<Option<bool>, String> {
+    match &option.value {
+        OptionValue::Identifier(value) if value == "true" => Ok(Some(true)),
+        OptionValue::Identifier(value) if value == "false" => Ok(Some(false)),
+        _ => Err(format!(
+            "The value of the option {} must be true or false.",
+            option.display_name()
+        )),
+    }
+}
+
+fn string_value(option: &OptionDef) -> Result<String, String> {
+    match &option.value {
+        OptionValue::String(value) => String::from_utf8(value.clone()).map_err(|_| {
+            format!(
+                "The value of the option {} is not UTF-8.",
+                option.display_name()
+            )
+        }),
+        _ => Err(format!(
+            "The value of the option {} must be a string.",
+            option.display_name()
+        )),
+    }
+}
+
+fn enum_value<E: Enumeration>(option: &OptionDef) -> Result<Option<OpenEnum<E>>, String> {
+    match &option.value {
+        OptionValue::Identifier(value) => match E::from_name(value) {
+            Some(value) => Ok(Some(value.into())),
+            None => Err(format!(
+                "The value {} is not valid for the option {}.",
+                value,
+                option.display_name()
+            )),
+        },
+        _ => Err(format!(
+            "The value of the option {} must be an identifier.",
+            option.display_name()
+        )),
+    }
+}
+
+// Sets a feature from an option such as `features.field_presence`, returning false when there is
+// no such feature.
+fn set_feature(features: &mut FeatureSet, name: &str, option: &OptionDef) -> Result<bool, String> {
+    match name {
+        "field_presence" => features.field_presence = enum_value::<FieldPresence>(option)?,
+        "enum_type" => features.enum_type = enum_value::<EnumType>(option)?,
+        "repeated_field_encoding" => {
+            features.repeated_field_encoding = enum_value::<RepeatedFieldEncoding>(option)?
+        }
+        "utf8_validation" => features.utf8_validation = enum_value::<Utf8Validation>(option)?,
+        "message_encoding" => features.message_encoding = enum_value::<MessageEncoding>(option)?,
+        "json_format" => features.json_format = enum_value::<JsonFormat>(option)?,
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+// The options messages, which have fields for the options built into the language.
+trait BuiltinOptions: Default + Clone + Message {
+    // The name of the options message within the `google.protobuf` package.
+    const NAME: &'static str;
+
+    fn features(&mut self) -> &mut Option<FeatureSet>;
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption>;
+
+    fn extensions(&mut self) -> &mut Vec<u8>;
+
+    // Sets the built in option with the given name, returning false when there is no such option.
+    fn set_builtin(&mut self, name: &str, option: &OptionDef) -> Result<bool, String>;
+}
+
+impl BuiltinOptions for FileOptions {
+    const NAME: &'static str = "FileOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, name: &str, option: &OptionDef) -> Result<bool, String> {
+        match name {
+            "java_package" => self.java_package = Some(string_value(option)?),
+            "java_outer_classname" => self.java_outer_classname = Some(string_value(option)?),
+            "optimize_for" => self.optimize_for = enum_value::<OptimizeMode>(option)?,
+            "java_multiple_files" => self.java_multiple_files = bool_value(option)?,
+            "go_package" => self.go_package = Some(string_value(option)?),
+            "cc_generic_services" => self.cc_generic_services = bool_value(option)?,
+            "java_generic_services" => self.java_generic_services = bool_value(option)?,
+            "py_generic_services" => self.py_generic_services = bool_value(option)?,
+            "java_generate_equals_and_hash" => {
+                self.java_generate_equals_and_hash = bool_value(option)?
+            }
+            "deprecated" => self.deprecated = bool_value(option)?,
+            "cc_enable_arenas" => self.cc_enable_arenas = bool_value(option)?,
+            "objc_class_prefix" => self.objc_class_prefix = Some(string_value(option)?),
+            "csharp_namespace" => self.csharp_namespace = Some(string_value(option)?),
+            "swift_prefix" => self.swift_prefix = Some(string_value(option)?),
+            "php_class_prefix" => self.php_class_prefix = Some(string_value(option)?),
+            "php_namespace" => self.php_namespace = Some(string_value(option)?),
+            "php_metadata_namespace" => self.php_metadata_namespace = Some(string_value(option)?),
+            "ruby_package" => self.ruby_package = Some(string_value(option)?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl BuiltinOptions for MessageOptions {
+    const NAME: &'static str = "MessageOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, name: &str, option: &OptionDef) -> Result<bool, String> {
+        match name {
+            "message_set_wire_format" => self.message_set_wire_format = bool_value(option)?,
+            "no_standard_descriptor_accessor" => {
+                self.no_standard_descriptor_accessor = bool_value(option)?
+            }
+            "deprecated" => self.deprecated = bool_value(option)?,
+            "deprecated_legacy_json_field_conflicts" => {
+                self.deprecated_legacy_json_field_conflicts = bool_value(option)?
+            }
+            "map_entry" => {
+                return Err("The map_entry option can not be set explicitly.".to_string())
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl BuiltinOptions for FieldOptions {
+    const NAME: &'static str = "FieldOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, name: &str, option: &OptionDef) -> Result<bool, String> {
+        match name {
+            "ctype" => self.ctype = enum_value::<CType>(option)?,
+            "packed" => self.packed = bool_value(option)?,
+            "deprecated" => self.deprecated = bool_value(option)?,
+            "lazy" => self.lazy = bool_value(option)?,
+            "jstype" => self.jstype = enum_value::<JsType>(option)?,
+            "weak" => self.weak = bool_value(option)?,
+            "unverified_lazy" => self.unverified_lazy = bool_value(option)?,
+            "debug_redact" => self.debug_redact = bool_value(option)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl BuiltinOptions for OneofOptions {
+    const NAME: &'static str = "OneofOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, _: &str, _: &OptionDef) -> Result<bool, String> {
+        Ok(false)
+    }
+}
+
+impl BuiltinOptions for ExtensionRangeOptions {
+    const NAME: &'static str = "ExtensionRangeOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, _: &str, _: &OptionDef) -> Result<bool, String> {
+        Ok(false)
+    }
+}
+
+impl BuiltinOptions for EnumOptions {
+    const NAME: &'static str = "EnumOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, name: &str, option: &OptionDef) -> Result<bool, String> {
+        match name {
+            "allow_alias" => self.allow_alias = bool_value(option)?,
+            "deprecated" => self.deprecated = bool_value(option)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl BuiltinOptions for EnumValueOptions {
+    const NAME: &'static str = "EnumValueOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, name: &str, option: &OptionDef) -> Result<bool, String> {
+        match name {
+            "deprecated" => self.deprecated = bool_value(option)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl BuiltinOptions for ServiceOptions {
+    const NAME: &'static str = "ServiceOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, name: &str, option: &OptionDef) -> Result<bool, String> {
+        match name {
+            "deprecated" => self.deprecated = bool_value(option)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl BuiltinOptions for MethodOptions {
+    const NAME: &'static str = "MethodOptions";
+
+    fn features(&mut self) -> &mut Option<FeatureSet> {
+        &mut self.features
+    }
+
+    fn uninterpreted_options(&mut self) -> &mut Vec<UninterpretedOption> {
+        &mut self.uninterpreted_option
+    }
+
+    fn extensions(&mut self) -> &mut Vec<u8> {
+        &mut self.extensions
+    }
+
+    fn set_builtin(&mut self, name: &str, option: &OptionDef) -> Result<bool, String> {
+        match name {
+            "deprecated" => self.deprecated = bool_value(option)?,
+            "idempotency_level" => self.idempotency_level = enum_value::<IdempotencyLevel>(option)?,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+// Rewrites the type names in a lowered file to their fully qualified form, setting the type of
+// each field that refers to a message or enum, and interprets its custom options. The pool
+// reports any name that can not be resolved.
+fn resolve_file(
+    pool: &DescriptorPool,
+    mut proto: FileDescriptorProto,
+    option_spans: &OptionSpans,
+) -> Result<FileDescriptorProto, CompileError> {
+    let mut scratch = pool.clone();
+    scratch.add_file_descriptor_proto(proto.clone())?;
+
+    let file = proto.name().to_string();
+    let package = proto.package().to_string();
+    let resolver = Resolver {
+        pool: &scratch,
+        file: &file,
+    };
+
+    for message in &mut proto.message_type {
+        resolver.resolve_message(message, &package);
+    }
+    for extension in &mut proto.extension {
+        resolver.resolve_field(extension, &package);
+    }
+    for service in &mut proto.service {
+        for method in &mut service.method {
+            for name in [&mut method.input_type, &mut method.output_type]
+                .into_iter()
+                .flatten()
+            {
+                if let Some((resolved, _)) = resolver.resolve(&package, name) {
+                    *name = resolved;
+                }
+            }
+        }
+    }
+
+    OptionInterpreter {
+        pool: &scratch,
+        file: &file,
+        option_spans,
+    }
+    .interpret_file(&mut proto)?;
+    Ok(proto)
+}
+
+struct Resolver<'a> {
+    pool: &'a DescriptorPool,
+    file: &'a str,
+}
+
+impl Resolver<'_> {
+    fn resolve(&self, scope: &str, type_name: &str) -> Option<(String, FieldType)> {
+        self.pool.resolve_type_name(self.file, scope, type_name)
+    }
+
+    fn resolve_message(&self, message: &mut DescriptorProto, scope: &str) {
+        let full_name = match scope.is_empty() {
+            true => message.name().to_string(),
+            false => format!("{}.{}", scope, message.name()),
+        };
+
+        for field in message.field.iter_mut().chain(message.extension.iter_mut()) {
+            self.resolve_field(field, &full_name);
+        }
+        for nested in &mut message.nested_type {
+            self.resolve_message(nested, &full_name);
+        }
+    }
+
+    fn resolve_field(&self, field: &mut FieldDescriptorProto, scope: &str) {
+        if let Some(type_name) = &field.type_name {
+            if let Some((resolved, field_type)) = self.resolve(scope, type_name) {
+                field.type_name = Some(resolved);
+                if field.r#type.is_none() {
+                    field.r#type = Some(field_type.into());
+                }
+            }
+        }
+        if let Some(extendee) = &field.extendee {
+            if let Some((resolved, _)) = self.resolve(scope, extendee) {
+                field.extendee = Some(resolved);
+            }
+        }
+    }
+}
+
+// Sets the custom options of each definition of a file as extension fields of its options
+// message, resolving the option names from the scope of the definition.
+struct OptionInterpreter<'a> {
+    pool: &'a DescriptorPool,
+    file: &'a str,
+    option_spans: &'a OptionSpans,
+}
+
+impl OptionInterpreter<'_> {
+    fn interpret_file(&self, proto: &mut FileDescriptorProto) -> Result<(), CompileError> {
+        let package = proto.package().to_string();
+        self.interpret(&mut proto.options, &[], &package)?;
+
+        for (index, message) in proto.message_type.iter_mut().enumerate() {
+            self.interpret_message(message, &[4, index as i32], &package)?;
+        }
+        for (index, enumeration) in proto.enum_type.iter_mut().enumerate() {
+            self.interpret_enum(enumeration, &[5, index as i32], &package)?;
+        }
+        for (index, service) in proto.service.iter_mut().enumerate() {
+            let path = [6, index as i32];
+            let full_name = join(&package, service.name());
+            self.interpret(&mut service.options, &path, &full_name)?;
+            for (index, method) in service.method.iter_mut().enumerate() {
+                let scope = join(&full_name, method.name());
+                self.interpret(&mut method.options, &child(&path, 2, index), &scope)?;
+            }
+        }
+        for (index, extension) in proto.extension.iter_mut().enumerate() {
+            let scope = join(&package, extension.name());
+            self.interpret(&mut extension.options, &[7, index as i32], &scope)?;
+        }
+
+        Ok(())
+    }
+
+    fn interpret_message(
+        &self,
+        message: &mut DescriptorProto,
+        path: &[i32],
+        scope: &str,
+    ) -> Result<(), CompileError> {
+        let full_name = join(scope, message.name());
+        self.interpret(&mut message.options, path, &full_name)?;
+
+        for (field_number, fields) in [(2, &mut message.field), (6, &mut message.extension)] {
+            for (index, field) in fields.iter_mut().enumerate() {
+                let scope = join(&full_name, field.name());
+                self.interpret(
+                    &mut field.options,
+                    &child(path, field_number, index),
+                    &scope,
+                )?;
+            }
+        }
+        for (index, nested) in message.nested_type.iter_mut().enumerate() {
+            self.interpret_message(nested, &child(path, 3, index), &full_name)?;
+        }
+        for (index, enumeration) in message.enum_type.iter_mut().enumerate() {
+            self.interpret_enum(enumeration, &child(path, 4, index), &full_name)?;
+        }
+        for (index, range) in message.extension_range.iter_mut().enumerate() {
+            self.interpret(&mut range.options, &child(path, 5, index), &full_name)?;
+        }
+        for (index, oneof) in message.oneof_decl.iter_mut().enumerate() {
+            let scope = join(&full_name, oneof.name());
+            self.interpret(&mut oneof.options, &child(path, 8, index), &scope)?;
+        }
+
+        Ok(())
+    }
+
+    fn interpret_enum(
+        &self,
+        enumeration: &mut EnumDescriptorProto,
+        path: &[i32],
+        scope: &str,
+    ) -> Result<(), CompileError> {
+        let full_name = join(scope, enumeration.name());
+        self.interpret(&mut enumeration.options, path, &full_name)?;
+
+        // Enum values are scoped alongside their enum.
+        for (index, value) in enumeration.value.iter_mut().enumerate() {
+            let scope = join(scope, value.name());
+            self.interpret(&mut value.options, &child(path, 2, index), &scope)?;
+        }
+
+        Ok(())
+    }
+
+    fn interpret<O: BuiltinOptions>(
+        &self,
+        options: &mut Option<O>,
+        path: &[i32],
+        scope: &str,
+    ) -> Result<(), CompileError> {
+        let options = match options {
+            Some(options) => options,
+            None => return Ok(()),
+        };
+        let uninterpreted = std::mem::take(options.uninterpreted_options());
+        if uninterpreted.is_empty() {
+            return Ok(());
+        }
+        let spans = self.option_spans.get(path);
+
+        let extendee = self
+            .pool
+            .get_message_by_name(&format!("google.protobuf.{}", O::NAME));
+        let mut custom = extendee.clone().map(DynamicMessage::new);
+
+        for (index, option) in uninterpreted.iter().enumerate() {
+            let span = spans.and_then(|s| s.get(index)).copied();
+            let error = |message: String| self.error(span.unwrap_or_default(), message);
+            let name = option_name(option);
+            let not_known = || error(format!("The option {} is not known.", name));
+
+            let mut parts = option.name.iter();
+            let extension = match parts.next() {
+                Some(part) if part.is_extension() => self
+                    .pool
+                    .resolve_extension_name(self.file, scope, part.name_part())
+                    .ok_or_else(not_known)?,
+                _ => return Err(not_known()),
+            };
+            let mut target = match custom.as_mut() {
+                Some(custom) if Some(extension.containing_message()) == extendee.as_ref() => custom,
+                _ => {
+                    return Err(error(format!(
+                        "The extension {} does not extend google.protobuf.{}.",
+                        extension.full_name(),
+                        O::NAME
+                    )))
+                }
+            };
+
+            // Each further part of the name selects a field of the message set by the part before.
+            let mut field = extension;
+            for part in parts {
+                if field.is_repeated() || field.message_type().is_none() {
+                    return Err(error(format!(
+                        "The option {} does not have fields, as it is not a message.",
+                        name
+                    )));
+                }
+                target = match target.get_field_mut(&field) {
+                    FieldValue::Message(message) => message,
+                    _ => return Err(not_known()),
+                };
+
+                let descriptor = target.descriptor().clone();
+                field = match part.is_extension() {
+                    true => self
+                        .pool
+                        .resolve_extension_name(self.file, scope, part.name_part())
+                        .filter(|e| e.containing_message() == &descriptor),
+                    false => descriptor.get_field_by_name(part.name_part()),
+                }
+                .ok_or_else(not_known)?;
+            }
+
+            let value = match (field.message_type(), &option.aggregate_value) {
+                (Some(message_type), Some(text)) => {
+                    let mut message = DynamicMessage::new(message_type);
+                    merge_text(&mut message, text).map_err(|e| {
+                        error(format!(
+                            "The value of the option {} is invalid: {}",
+                            name, e.message
+                        ))
+                    })?;
+                    FieldValue::Message(message)
+                }
+                (Some(_), None) => {
+                    return Err(error(format!(
+                        "The value of the option {} must be a message in braces.",
+                        name
+                    )))
+                }
+                (None, _) => scalar_value(&field, &option_value(option)).map_err(|expected| {
+                    error(format!(
+                        "The value of the option {} must be {}.",
+                        name, expected
+                    ))
+                })?,
+            };
+            add_value(target, &field, value).map_err(error)?;
+        }
+
+        if let Some(custom) = custom {
+            options.extensions().extend(custom.encode_to_vec());
+        }
+        Ok(())
+    }
+
+    fn error(&self, span: Span, message: String) -> CompileError {
+        CompileError::Source {
+            file: self.file.to_string(),
+            line: span.start.0 + 1,
+            column: span.start.1 + 1,
+            message,
+        }
+    }
+}
+
+// The name of an uninterpreted option as it was written, e.g. `(acme.rules).max_length`.
+fn option_name(option: &UninterpretedOption) -> String {
+    option
+        .name
+        .iter()
+        .map(|part| match part.is_extension() {
+            true => format!("({})", part.name_part()),
+            false => part.name_part().to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn option_value(option: &UninterpretedOption) -> OptionValue {
+    if let Some(value) = &option.identifier_value {
+        OptionValue::Identifier(value.clone())
+    } else if let Some(value) = option.positive_int_value {
+        OptionValue::PositiveInt(value)
+    } else if let Some(value) = option.negative_int_value {
+        OptionValue::NegativeInt(value)
+    } else if let Some(value) = option.double_value {
+        OptionValue::Float(value)
+    } else if let Some(value) = &option.string_value {
+        OptionValue::String(value.clone())
+    } else {
+        OptionValue::Aggregate(option.aggregate_value.clone().unwrap_or_default())
+    }
+}
+
+fn join(scope: &str, name: &str) -> String {
+    match scope.is_empty() {
+        true => name.to_string(),
+        false => format!("{}.{}", scope, name),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Edition;
+    use rstest::*;
+
+    const PEOPLE: &str = r#"
+syntax = "proto3";
+
+package acme.people;
+
+import "acme/common.proto";
+
+option java_package = "com.acme.people";
+option optimize_for = CODE_SIZE;
+
+// A member of staff.
+message Person {
+  string name = 1;
+  optional int32 age = 2;
+  map<string, common.Address> addresses = 3 [deprecated = true];
+  Kind kind = 4;
+  oneof contact {
+    string phone = 5;
+    string email = 6 [json_name = "mail"];
+  }
+  repeated Person reports = 7;
+  reserved 10 to max;
+
+  enum Kind {
+    KIND_UNSPECIFIED = 0;
+    KIND_STAFF = 1;
+  }
+}
+
+service Directory {
+  rpc Find (Person) returns (stream Person);
+}
+"#;
+
+    const COMMON: &str = r#"
+syntax = "proto3";
+package acme.common;
+
+message Address {
+  string street = 1;
+}
+"#;
+
+    fn compiler() -> ProtoCompiler {
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("acme/people.proto", PEOPLE);
+        compiler.add_source("acme/common.proto", COMMON);
+        compiler
+    }
+
+    #[test]
+    fn test_file_is_lowered() {
+        // Arrange
+        let compiler = compiler();
+
+        // Act
+        let set = compiler.compile(&["acme/people.proto"]).unwrap();
+
+        // Assert
+        assert_eq!(set.file.len(), 1);
+        let file = &set.file[0];
+        assert_eq!(file.package(), "acme.people");
+        assert_eq!(file.syntax(), "proto3");
+        assert_eq!(file.dependency, vec!["acme/common.proto".to_string()]);
+        assert_eq!(
+            file.options.as_ref().unwrap().optimize_for,
+            Some(OptimizeMode::CodeSize.into())
+        );
+
+        let person = &file.message_type[0];
+        let fields: Vec<_> = person
+            .field
+            .iter()
+            .map(|f| (f.name(), f.r#type(), f.type_name(), f.json_name()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                ("name", Some(FieldType::String), "", "name"),
+                ("age", Some(FieldType::Int32), "", "age"),
+                (
+                    "addresses",
+                    Some(FieldType::Message),
+                    ".acme.people.Person.AddressesEntry",
+                    "addresses"
+                ),
+                (
+                    "kind",
+                    Some(FieldType::Enum),
+                    ".acme.people.Person.Kind",
+                    "kind"
+                ),
+                ("phone", Some(FieldType::String), "", "phone"),
+                ("email", Some(FieldType::String), "", "mail"),
+                (
+                    "reports",
+                    Some(FieldType::Message),
+                    ".acme.people.Person",
+                    "reports"
+                ),
+            ]
+        );
+        assert_eq!(person.field[1].proto3_optional, Some(true));
+        assert_eq!(person.field[1].oneof_index, Some(1));
+        assert_eq!(
+            person
+                .oneof_decl
+                .iter()
+                .map(|o| o.name())
+                .collect::<Vec<_>>(),
+            vec!["contact", "_age"]
+        );
+        assert_eq!(
+            person.nested_type[0].field[1].type_name(),
+            ".acme.common.Address"
+        );
+        assert!(person.nested_type[0].options.as_ref().unwrap().map_entry());
+        assert_eq!(
+            person.reserved_range,
+            vec![ReservedRange {
+                start: Some(10),
+                end: Some(536_870_912)
+            }]
+        );
+
+        let method = &file.service[0].method[0];
+        assert_eq!(method.input_type(), ".acme.people.Person");
+        assert_eq!(method.client_streaming, None);
+        assert_eq!(method.server_streaming, Some(true));
+        assert!(file.source_code_info.is_none());
+    }
+
+    #[test]
+    fn test_imports_can_be_included() {
+        // Arrange
+        let mut compiler = compiler();
+        compiler.include_imports(true);
+
+        // Act
+        let set = compiler.compile(&["acme/people.proto"]).unwrap();
+
+        // Assert
+        let names: Vec<_> = set.file.iter().map(|f| f.name()).collect();
+        assert_eq!(names, vec!["acme/common.proto", "acme/people.proto"]);
+    }
+
+    #[test]
+    fn test_source_info_can_be_included() {
+        // Arrange
+        let mut compiler = compiler();
+        compiler.include_source_info(true);
+
+        // Act
+        let set = compiler.compile(&["acme/people.proto"]).unwrap();
+
+        // Assert
+        let info = set.file[0].source_code_info.as_ref().unwrap();
+        let person = info.location.iter().find(|l| l.path == [4, 0]).unwrap();
+        assert_eq!(
+            person.leading_comments.as_deref(),
+            Some(" A member of staff.\n")
+        );
+        assert_eq!(person.span, vec![11, 0, 27, 1]);
+        let kind = info
+            .location
+            .iter()
+            .find(|l| l.path == [4, 0, 4, 0])
+            .unwrap();
+        assert_eq!(kind.span, vec![23, 2, 26, 3]);
+        assert!(info.location.iter().any(|l| l.path == [6, 0, 2, 0]));
+    }
+
+    #[test]
+    fn test_compiled_set_builds_a_pool() {
+        // Arrange
+        let mut compiler = compiler();
+        compiler.include_imports(true);
+        let set = compiler.compile(&["acme/people.proto"]).unwrap();
+
+        // Act
+        let pool = DescriptorPool::decode(&set.encode_to_vec()).unwrap();
+
+        // Assert
+        let person = pool.get_message_by_name("acme.people.Person").unwrap();
+        assert!(person.get_field_by_name("addresses").unwrap().is_map());
+        assert!(person.get_field_by_name("age").unwrap().has_presence());
+    }
+
+    #[test]
+    fn test_well_known_imports_are_bundled() {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.include_imports(true);
+        compiler.add_source(
+            "a.proto",
+            "syntax = \"proto3\";\nimport \"google/protobuf/api.proto\";\nimport \"google/protobuf/descriptor.proto\";\nmessage A { google.protobuf.Api api = 1; google.protobuf.FieldOptions options = 2; }",
+        );
+
+        // Act
+        let set = compiler.compile(&["a.proto"]).unwrap();
+
+        // Assert
+        let names: Vec<_> = set.file.iter().map(|f| f.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "google/protobuf/source_context.proto",
+                "google/protobuf/any.proto",
+                "google/protobuf/type.proto",
+                "google/protobuf/api.proto",
+                "google/protobuf/descriptor.proto",
+                "a.proto"
+            ]
+        );
+        let fields = &set.file[5].message_type[0].field;
+        assert_eq!(fields[0].type_name(), ".google.protobuf.Api");
+        assert_eq!(fields[1].type_name(), ".google.protobuf.FieldOptions");
+    }
+
+    #[test]
+    fn test_all_well_known_sources_compile() {
+        // Arrange
+        let compiler = ProtoCompiler::new();
+        let names: Vec<_> = WELL_KNOWN_SOURCES.iter().map(|(name, _)| *name).collect();
+
+        // Act
+        let set = compiler.compile(&names).unwrap();
+
+        // Assert
+        assert_eq!(set.file.len(), names.len());
+    }
+
+    #[test]
+    fn test_include_paths_are_searched() {
+        // Arrange
+        let root = std::env::temp_dir().join(format!("ks-protoc-include-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("acme")).unwrap();
+        std::fs::write(root.join("acme/common.proto"), COMMON).unwrap();
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_include_path(root.join("missing"));
+        compiler.add_include_path(&root);
+        compiler.add_source("acme/people.proto", PEOPLE);
+
+        // Act
+        let result = compiler.compile(&["acme/common.proto", "acme/people.proto"]);
+
+        // Assert
+        std::fs::remove_dir_all(&root).unwrap();
+        let set = result.unwrap();
+        assert_eq!(set.file[0].message_type[0].name(), "Address");
+    }
+
+    #[test]
+    fn test_added_sources_shadow_bundled_sources() {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source(
+            "google/protobuf/empty.proto",
+            "syntax = \"proto3\";\npackage google.protobuf;\nmessage Empty { int32 x = 1; }",
+        );
+
+        // Act
+        let set = compiler.compile(&["google/protobuf/empty.proto"]).unwrap();
+
+        // Assert
+        assert_eq!(set.file[0].message_type[0].field.len(), 1);
+    }
+
+    #[rstest]
+    #[case("import public", None)]
+    #[case("import", Some("The type C used by B.c could not be resolved."))]
+    #[case("import weak", Some("The type C used by B.c could not be resolved."))]
+    fn test_imported_files_are_visible_through_public_imports(
+        #[case] import: &str,
+        #[case] expected_message: Option<&str>,
+    ) {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("c.proto", "syntax = \"proto3\";\nmessage C {}");
+        compiler.add_source(
+            "a.proto",
+            format!("syntax = \"proto3\";\n{} \"c.proto\";", import),
+        );
+        compiler.add_source(
+            "b.proto",
+            "syntax = \"proto3\";\nimport \"a.proto\";\nmessage B { C c = 1; }",
+        );
+
+        // Act
+        let result = compiler.compile(&["b.proto"]);
+
+        // Assert
+        assert_eq!(
+            result.as_ref().err().map(|e| e.to_string()).as_deref(),
+            expected_message
+        );
+    }
+
+    #[test]
+    fn test_import_kinds_are_recorded() {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source(
+            "a.proto",
+            "syntax = \"proto3\";\nimport \"google/protobuf/any.proto\";\nimport weak \"google/protobuf/empty.proto\";\nimport public \"google/protobuf/duration.proto\";",
+        );
+
+        // Act
+        let set = compiler.compile(&["a.proto"]).unwrap();
+
+        // Assert
+        assert_eq!(set.file[0].weak_dependency, vec![1]);
+        assert_eq!(set.file[0].public_dependency, vec![2]);
+    }
+
+    #[test]
+    fn test_import_cycles_are_reported() {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("a.proto", "syntax = \"proto3\";\nimport \"b.proto\";");
+        compiler.add_source("b.proto", "syntax = \"proto3\";\nimport \"c.proto\";");
+        compiler.add_source("c.proto", "syntax = \"proto3\";\nimport \"a.proto\";");
+
+        // Act
+        let error = compiler.compile(&["a.proto"]).unwrap_err();
+
+        // Assert
+        assert_eq!(
+            error,
+            CompileError::ImportCycle("a.proto -> b.proto -> c.proto -> a.proto".to_string())
+        );
+        assert_eq!(
+            error.to_string(),
+            "The files import each other in a cycle: a.proto -> b.proto -> c.proto -> a.proto."
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "syntax = \"proto3\";\nmessage A { Missing m = 1; }",
+        "The type Missing used by A.m could not be resolved."
+    )]
+    #[case("syntax = \"proto3\";\noption java_package = 5;", "The file a.proto is invalid at line 2, column 1: The value of the option java_package must be a string.")]
+    #[case(
+        "syntax = \"proto3\";\noption unknown = 5;",
+        "The file a.proto is invalid at line 2, column 1: The option unknown is not known."
+    )]
+    #[case(
+        "syntax = \"proto3\";\nimport \"b.proto\";",
+        "The file a.proto is invalid at line 2, column 1: The import b.proto could not be found."
+    )]
+    #[case(
+        "message A {\n  optional int32 a = 1 [default = \"x\"];\n}",
+        "The file a.proto is invalid at line 2, column 25: The default value of a is invalid."
+    )]
+    #[case("enum E {\n  A = 0;\n  B = 0;\n}", "The file a.proto is invalid at line 3, column 3: The number 0 is used more than once in E. Set the allow_alias option to allow aliases.")]
+    #[case(
+        "message A {\n  map<float, int32> m = 1;\n}",
+        "The file a.proto is invalid at line 2, column 3: The map key type is invalid."
+    )]
+    #[case(
+        "message A {\n  extensions 10 to 20;\n}\nextend A { optional int32 x = 5; }",
+        "The number 5 of the extension x is not within an extension range of A."
+    )]
+    #[case(
+        "syntax = \"proto3\";\nmessage A { int32 a = 1 [features.field_presence = EXPLICIT]; }",
+        "The file a.proto is invalid at line 2, column 26: Features are only allowed in files that declare an edition."
+    )]
+    #[case(
+        "edition = \"2023\";\noption features.field_presence = LOUD;",
+        "The file a.proto is invalid at line 2, column 1: The value LOUD is not valid for the option features.field_presence."
+    )]
+    #[case(
+        "edition = \"2023\";\noption features.color = RED;",
+        "The file a.proto is invalid at line 2, column 1: The option features.color is not known."
+    )]
+    #[case(
+        "edition = \"2023\";\nmessage A { repeated int32 a = 1 [packed = false]; }",
+        "The file a.proto is invalid at line 2, column 35: The packed option is not allowed in editions, use features.repeated_field_encoding instead."
+    )]
+    fn test_compile_errors(#[case] source: &str, #[case] expected_message: &str) {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("a.proto", source);
+
+        // Act
+        let error = compiler.compile(&["a.proto"]).unwrap_err();
+
+        // Assert
+        assert_eq!(error.to_string(), expected_message);
+    }
+
+    #[test]
+    fn test_groups_are_lowered() {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source(
+            "a.proto",
+            "message A {\n  repeated group Item = 1 {\n    required string name = 2;\n  }\n}",
+        );
+
+        // Act
+        let set = compiler.compile(&["a.proto"]).unwrap();
+
+        // Assert
+        let message = &set.file[0].message_type[0];
+        assert_eq!(message.field[0].name(), "item");
+        assert_eq!(message.field[0].r#type(), Some(FieldType::Group));
+        assert_eq!(message.field[0].type_name(), ".A.Item");
+        assert_eq!(message.nested_type[0].name(), "Item");
+        assert_eq!(
+            message.nested_type[0].field[0].label(),
+            Some(FieldLabel::Required)
+        );
+    }
+
+    #[test]
+    fn test_editions_are_lowered() {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source(
+            "a.proto",
+            "edition = \"2023\";\noption features.enum_type = CLOSED;\nmessage A {\n  A child = 1 [features.message_encoding = DELIMITED];\n}",
+        );
+
+        // Act
+        let set = compiler.compile(&["a.proto"]).unwrap();
+
+        // Assert
+        let file = &set.file[0];
+        assert_eq!(file.syntax(), "editions");
+        assert_eq!(file.edition(), Some(Edition::Edition2023));
+        let features = file.options.as_ref().and_then(|o| o.features.as_ref());
+        assert_eq!(features.and_then(|f| f.enum_type()), Some(EnumType::Closed));
+        let child = &file.message_type[0].field[0];
+        assert_eq!(child.label(), Some(FieldLabel::Optional));
+        let features = child.options.as_ref().and_then(|o| o.features.as_ref());
+        assert_eq!(
+            features.and_then(|f| f.message_encoding()),
+            Some(MessageEncoding::Delimited)
+        );
+    }
+
+    #[rstest]
+    #[case("optional string s = 1 [default = \"a\\nb\"];", "a\nb")]
+    #[case("optional bytes b = 1 [default = \"\\001a\\\"\"];", "\\001a\\\"")]
+    #[case("optional double d = 1 [default = -inf];", "-inf")]
+    #[case("optional float f = 1 [default = 1.5];", "1.5")]
+    #[case("optional sint32 i = 1 [default = -12];", "-12")]
+    #[case("optional bool b = 1 [default = true];", "true")]
+    fn test_default_values(#[case] field: &str, #[case] expected_default: &str) {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_source("a.proto", format!("message A {{ {} }}", field));
+
+        // Act
+        let set = compiler.compile(&["a.proto"]).unwrap();
+
+        // Assert
+        assert_eq!(
+            set.file[0].message_type[0].field[0].default_value(),
+            expected_default
+        );
+    }
+
+    const RULES: &str = r#"syntax = "proto3";
+package acme;
+import "google/protobuf/descriptor.proto";
+
+enum Sensitivity { PUBLIC = 0; PERSONAL = 1; }
+message Rules {
+  int32 max_length = 1;
+  repeated string patterns = 2;
+  Rules nested = 3;
+}
+extend google.protobuf.FieldOptions {
+  Sensitivity sensitivity = 50000;
+  Rules rules = 50001;
+  repeated string tags = 50002;
+}
+extend google.protobuf.MessageOptions { string table = 50000; }
+"#;
+
+    fn compile_with_rules(source: &str) -> Result<DescriptorPool, CompileError> {
+        let mut compiler = ProtoCompiler::new();
+        compiler.include_imports(true);
+        compiler.add_source("acme/rules.proto", RULES);
+        compiler.add_source("a.proto", source);
+        let set = compiler.compile(&["a.proto"])?;
+        Ok(DescriptorPool::decode(&set.encode_to_vec()).unwrap())
+    }
+
+    #[test]
+    fn test_custom_options_are_interpreted() {
+        // Arrange
+        let source = r#"syntax = "proto3";
+package acme.people;
+import "acme/rules.proto";
+
+message Person {
+  option (acme.table) = "people";
+  string email = 1 [
+    (acme.sensitivity) = PERSONAL,
+    (acme.rules) = { max_length: 254 patterns: ["a", "b"] nested { max_length: 3 } },
+    (acme.tags) = "contact",
+    (acme.tags) = "login"
+  ];
+  string name = 2 [(acme.rules).max_length = 100, (acme.rules).nested.max_length = 5];
+}
+"#;
+
+        // Act
+        let pool = compile_with_rules(source).unwrap();
+
+        // Assert
+        let person = pool.get_message_by_name("acme.people.Person").unwrap();
+        let table = pool.get_extension_by_name("acme.table").unwrap();
+        assert_eq!(
+            person.get_option(&table),
+            Some(FieldValue::String("people".to_string()))
+        );
+
+        let sensitivity = pool.get_extension_by_name("acme.sensitivity").unwrap();
+        let rules = pool.get_extension_by_name("acme.rules").unwrap();
+        let tags = pool.get_extension_by_name("acme.tags").unwrap();
+        let email = person.get_field_by_name("email").unwrap();
+        assert_eq!(
+            email.get_option(&sensitivity),
+            Some(FieldValue::EnumNumber(1))
+        );
+        assert_eq!(
+            email.get_option(&tags),
+            Some(FieldValue::List(vec![
+                FieldValue::String("contact".to_string()),
+                FieldValue::String("login".to_string())
+            ]))
+        );
+
+        let rules_type = rules.message_type().unwrap();
+        let max_length = rules_type.get_field_by_name("max_length").unwrap();
+        let patterns = rules_type.get_field_by_name("patterns").unwrap();
+        let nested = rules_type.get_field_by_name("nested").unwrap();
+        let email_rules = match email.get_option(&rules) {
+            Some(FieldValue::Message(message)) => message,
+            other => panic!("Expected a message but found {:?}", other),
+        };
+        assert_eq!(
+            email_rules.get_field(&max_length).into_owned(),
+            FieldValue::I32(254)
+        );
+        assert_eq!(
+            email_rules.get_field(&patterns).into_owned(),
+            FieldValue::List(vec![
+                FieldValue::String("a".to_string()),
+                FieldValue::String("b".to_string())
+            ])
+        );
+
+        let name = person.get_field_by_name("name").unwrap();
+        let name_rules = match name.get_option(&rules) {
+            Some(FieldValue::Message(message)) => message,
+            other => panic!("Expected a message but found {:?}", other),
+        };
+        assert_eq!(
+            name_rules.get_field(&max_length).into_owned(),
+            FieldValue::I32(100)
+        );
+        match name_rules.get_field(&nested).into_owned() {
+            FieldValue::Message(nested) => {
+                assert_eq!(
+                    nested.get_field(&max_length).into_owned(),
+                    FieldValue::I32(5)
+                )
+            }
+            other => panic!("Expected a message but found {:?}", other),
+        }
+        assert_eq!(name.get_option(&sensitivity), None);
+    }
+
+    #[test]
+    fn test_custom_options_resolve_from_the_scope_of_the_definition() {
+        // Arrange
+        let source = r#"syntax = "proto3";
+package acme.people;
+import "acme/rules.proto";
+
+message Person { string email = 1 [(sensitivity) = PERSONAL]; }
+"#;
+
+        // Act
+        let pool = compile_with_rules(source).unwrap();
+
+        // Assert
+        let sensitivity = pool.get_extension_by_name("acme.sensitivity").unwrap();
+        let person = pool.get_message_by_name("acme.people.Person").unwrap();
+        let email = person.get_field_by_name("email").unwrap();
+        assert_eq!(
+            email.get_option(&sensitivity),
+            Some(FieldValue::EnumNumber(1))
+        );
+        assert!(sensitivity.is_extension());
+    }
+
+    #[rstest]
+    #[case(
+        "message A { int32 a = 1 [(acme.missing) = 1]; }",
+        "line 3, column 26: The option (acme.missing) is not known."
+    )]
+    #[case(
+        "message A { option (acme.sensitivity) = PERSONAL; }",
+        "line 3, column 13: The extension acme.sensitivity does not extend google.protobuf.MessageOptions."
+    )]
+    #[case(
+        "message A { int32 a = 1 [(acme.sensitivity) = SECRET]; }",
+        "line 3, column 26: The value of the option (acme.sensitivity) must be a value of acme.Sensitivity."
+    )]
+    #[case(
+        "message A { int32 a = 1 [(acme.rules).max_length = \"x\"]; }",
+        "line 3, column 26: The value of the option (acme.rules).max_length must be a number that fits in int32."
+    )]
+    #[case(
+        "message A { int32 a = 1 [(acme.rules).missing = 1]; }",
+        "line 3, column 26: The option (acme.rules).missing is not known."
+    )]
+    #[case(
+        "message A { int32 a = 1 [(acme.rules) = { missing: 1 }]; }",
+        "line 3, column 26: The value of the option (acme.rules) is invalid: The field missing of acme.Rules is not known."
+    )]
+    #[case(
+        "message A { int32 a = 1 [(acme.sensitivity) = PUBLIC, (acme.sensitivity) = PERSONAL]; }",
+        "line 3, column 55: The field acme.sensitivity is set more than once."
+    )]
+    fn test_custom_option_errors(#[case] message: &str, #[case] expected_message: &str) {
+        // Arrange
+        let source = format!(
+            "syntax = \"proto3\";\nimport \"acme/rules.proto\";\n{}",
+            message
+        );
+
+        // Act
+        let error = compile_with_rules(&source).unwrap_err();
+
+        // Assert
+        assert_eq!(
+            error.to_string(),
+            format!("The file a.proto is invalid at {}", expected_message)
+        );
+    }
+
+    #[rstest]
+    #[case("a.proto", "a.proto")]
+    #[case("./acme/a.proto", "acme/a.proto")]
+    #[case("protos/acme/a.proto", "acme/a.proto")]
+    #[case("vendor/b.proto", "b.proto")]
+    #[case("other/c.proto", "other/c.proto")]
+    fn test_import_names(#[case] file: &str, #[case] expected_name: &str) {
+        // Arrange
+        let mut compiler = ProtoCompiler::new();
+        compiler.add_include_path(".");
+        compiler.add_include_path("protos");
+        compiler.add_include_path("vendor");
+
+        // Act
+        let name = compiler.import_name(file);
+
+        // Assert
+        assert_eq!(name, expected_name);
+    }
+}
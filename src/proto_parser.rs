@@ -0,0 +1,1600 @@
+use crate::{Edition, FieldType};
+
+// A parser for the `.proto` language, producing a syntax tree that the compiler lowers into
+// descriptors. Lines and columns are counted from zero, as in `SourceCodeInfo`.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct Span {
+    pub(crate) start: (usize, usize),
+    pub(crate) end: (usize, usize),
+}
+
+impl Span {
+    // The span as written in `SourceCodeInfo`, leaving out the end line when it is the start line.
+    pub(crate) fn to_source_span(self) -> Vec<i32> {
+        let mut span = vec![self.start.0 as i32, self.start.1 as i32];
+        if self.end.0 != self.start.0 {
+            span.push(self.end.0 as i32);
+        }
+        span.push(self.end.1 as i32);
+        span
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Comments {
+    pub(crate) leading: Option<String>,
+    pub(crate) trailing: Option<String>,
+    pub(crate) detached: Vec<String>,
+}
+
+// The location of a definition in the source, along with the comments attached to it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Location {
+    pub(crate) span: Span,
+    pub(crate) comments: Comments,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Syntax {
+    Proto2,
+    Proto3,
+    Editions,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ProtoFile {
+    pub(crate) syntax: Syntax,
+    pub(crate) edition: Option<Edition>,
+    pub(crate) syntax_location: Option<Location>,
+    pub(crate) package: Option<(String, Location)>,
+    pub(crate) imports: Vec<Import>,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) messages: Vec<MessageDef>,
+    pub(crate) enums: Vec<EnumDef>,
+    pub(crate) services: Vec<ServiceDef>,
+    pub(crate) extensions: Vec<FieldDef>,
+    pub(crate) span: Span,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ImportKind {
+    Default,
+    Public,
+    Weak,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Import {
+    pub(crate) path: String,
+    pub(crate) kind: ImportKind,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OptionNamePart {
+    pub(crate) name: String,
+    pub(crate) is_extension: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum OptionValue {
+    Identifier(String),
+    PositiveInt(u64),
+    NegativeInt(i64),
+    Float(f64),
+    String(Vec<u8>),
+    Aggregate(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OptionDef {
+    pub(crate) name: Vec<OptionNamePart>,
+    pub(crate) value: OptionValue,
+    pub(crate) span: Span,
+}
+
+impl OptionDef {
+    // The name of a built in option, which is a single part that is not an extension.
+    pub(crate) fn simple_name(&self) -> Option<&str> {
+        match self.name.as_slice() {
+            [part] if !part.is_extension => Some(&part.name),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn display_name(&self) -> String {
+        self.name
+            .iter()
+            .map(|p| match p.is_extension {
+                true => format!("({})", p.name),
+                false => p.name.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Label {
+    Optional,
+    Required,
+    Repeated,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TypeRef {
+    Scalar(FieldType),
+    Named(String),
+    // The name of the message declared by a group field, which is nested alongside the field.
+    Group(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct FieldDef {
+    pub(crate) label: Option<Label>,
+    pub(crate) field_type: TypeRef,
+    pub(crate) map_types: Option<(TypeRef, TypeRef)>,
+    pub(crate) name: String,
+    pub(crate) number: i32,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) oneof: Option<usize>,
+    pub(crate) extendee: Option<String>,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct OneofDef {
+    pub(crate) name: String,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Range {
+    pub(crate) start: i32,
+    // Inclusive, with `None` standing for `max`.
+    pub(crate) end: Option<i32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ExtensionRangeDef {
+    pub(crate) ranges: Vec<Range>,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MessageDef {
+    pub(crate) name: String,
+    pub(crate) fields: Vec<FieldDef>,
+    pub(crate) oneofs: Vec<OneofDef>,
+    pub(crate) messages: Vec<MessageDef>,
+    pub(crate) enums: Vec<EnumDef>,
+    pub(crate) extensions: Vec<FieldDef>,
+    pub(crate) extension_ranges: Vec<ExtensionRangeDef>,
+    pub(crate) reserved_ranges: Vec<Range>,
+    pub(crate) reserved_names: Vec<String>,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EnumValueDef {
+    pub(crate) name: String,
+    pub(crate) number: i32,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct EnumDef {
+    pub(crate) name: String,
+    pub(crate) values: Vec<EnumValueDef>,
+    pub(crate) reserved_ranges: Vec<Range>,
+    pub(crate) reserved_names: Vec<String>,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MethodDef {
+    pub(crate) name: String,
+    pub(crate) input_type: String,
+    pub(crate) output_type: String,
+    pub(crate) client_streaming: bool,
+    pub(crate) server_streaming: bool,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ServiceDef {
+    pub(crate) name: String,
+    pub(crate) methods: Vec<MethodDef>,
+    pub(crate) options: Vec<OptionDef>,
+    pub(crate) location: Location,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ParseError {
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) message: String,
+}
+
+impl ParseError {
+    pub(crate) fn new(span: Span, message: impl Into<String>) -> Self {
+        Self {
+            line: span.start.0,
+            column: span.start.1,
+            message: message.into(),
+        }
+    }
+}
+
+// The largest field number, as the tag holds the field number in 29 bits.
+pub(crate) const MAX_FIELD_NUMBER: i32 = (1 << 29) - 1;
+
+/// Parses the source of a `.proto` file.
+pub(crate) fn parse(source: &str) -> Result<ProtoFile, ParseError> {
+    let tokens = tokenize(source)?;
+    Parser {
+        tokens,
+        position: 0,
+        syntax: Syntax::Proto2,
+    }
+    .parse_file()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum TokenKind {
+    Identifier(String),
+    Integer(u64),
+    Float(f64),
+    String(Vec<u8>),
+    Symbol(char),
+    End,
+}
+
+#[derive(Debug, Clone)]
+struct CommentBlock {
+    text: String,
+    start_line: usize,
+    end_line: usize,
+    is_line_comment: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Token {
+    pub(crate) kind: TokenKind,
+    pub(crate) text: String,
+    pub(crate) span: Span,
+    // The comments between the previous token and this one.
+    comments: Vec<CommentBlock>,
+}
+
+pub(crate) fn tokenize(source: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut comments: Vec<CommentBlock> = Vec::new();
+    let (mut index, mut line, mut column) = (0, 0, 0);
+
+    let error = |line, column, message: &str| ParseError {
+        line,
+        column,
+        message: message.to_string(),
+    };
+
+    while index < chars.len() {
+        let c = chars[index];
+        let start = (line, column);
+
+        if c == '\n' {
+            index += 1;
+            line += 1;
+            column = 0;
+            continue;
+        }
+        if c.is_whitespace() {
+            index += 1;
+            column += 1;
+            continue;
+        }
+
+        if c == '/' && chars.get(index + 1) == Some(&'/') {
+            let end = chars[index..]
+                .iter()
+                .position(|&c| c == '\n')
+                .map_or(chars.len(), |p| index + p);
+            let text: String = chars[index + 2..end].iter().collect();
+            column += end - index;
+            index = end;
+
+            match comments.last_mut() {
+                Some(block) if block.is_line_comment && block.end_line + 1 == line => {
+                    block.text.push_str(&text);
+                    block.text.push('\n');
+                    block.end_line = line;
+                }
+                _ => comments.push(CommentBlock {
+                    text: format!("{}\n", text),
+                    start_line: line,
+                    end_line: line,
+                    is_line_comment: true,
+                }),
+            }
+            continue;
+        }
+
+        if c == '/' && chars.get(index + 1) == Some(&'*') {
+            let mut end = index + 2;
+            while end < chars.len() && !(chars[end] == '*' && chars.get(end + 1) == Some(&'/')) {
+                end += 1;
+            }
+            if end >= chars.len() {
+                return Err(error(line, column, "The block comment is not terminated."));
+            }
+
+            let text: String = chars[index + 2..end].iter().collect();
+            for &c in &chars[index..end + 2] {
+                match c {
+                    '\n' => {
+                        line += 1;
+                        column = 0;
+                    }
+                    _ => column += 1,
+                }
+            }
+            index = end + 2;
+
+            let text = text
+                .lines()
+                .map(|l| {
+                    let trimmed = l.trim_start();
+                    trimmed.strip_prefix('*').unwrap_or(l)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            comments.push(CommentBlock {
+                text,
+                start_line: start.0,
+                end_line: line,
+                is_line_comment: false,
+            });
+            continue;
+        }
+
+        let begin = index;
+        let kind = if c.is_ascii_alphabetic() || c == '_' {
+            while index < chars.len()
+                && (chars[index].is_ascii_alphanumeric() || chars[index] == '_')
+            {
+                index += 1;
+            }
+            TokenKind::Identifier(chars[begin..index].iter().collect())
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(index + 1).is_some_and(|c| c.is_ascii_digit()))
+        {
+            let mut is_float = false;
+            if c == '0' && matches!(chars.get(index + 1), Some('x') | Some('X')) {
+                index += 2;
+                while index < chars.len() && chars[index].is_ascii_hexdigit() {
+                    index += 1;
+                }
+            } else {
+                while index < chars.len() {
+                    let d = chars[index];
+                    if d.is_ascii_digit() {
+                        index += 1;
+                    } else if d == '.' {
+                        is_float = true;
+                        index += 1;
+                    } else if (d == 'e' || d == 'E')
+                        && chars
+                            .get(index + 1)
+                            .is_some_and(|&n| n.is_ascii_digit() || n == '-' || n == '+')
+                    {
+                        is_float = true;
+                        index += 2;
+                    } else {
+                        break;
+                    }
+                }
+            }
+
+            let text: String = chars[begin..index].iter().collect();
+            if is_float {
+                match text.parse::<f64>() {
+                    Ok(value) => TokenKind::Float(value),
+                    Err(_) => return Err(error(line, column, "The number is invalid.")),
+                }
+            } else {
+                let value = match text.strip_prefix("0x").or(text.strip_prefix("0X")) {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None if text.len() > 1 && text.starts_with('0') => {
+                        u64::from_str_radix(&text[1..], 8)
+                    }
+                    None => text.parse::<u64>(),
+                };
+                match value {
+                    Ok(value) => TokenKind::Integer(value),
+                    Err(_) => return Err(error(line, column, "The integer is invalid.")),
+                }
+            }
+        } else if c == '"' || c == '\'' {
+            let (value, end) =
+                unescape(&chars, index + 1, c).map_err(|message| error(line, column, &message))?;
+            index = end;
+            TokenKind::String(value)
+        } else {
+            index += 1;
+            TokenKind::Symbol(c)
+        };
+
+        column += index - begin;
+        tokens.push(Token {
+            kind,
+            text: chars[begin..index].iter().collect(),
+            span: Span {
+                start,
+                end: (line, column),
+            },
+            comments: std::mem::take(&mut comments),
+        });
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        text: String::new(),
+        span: Span {
+            start: (line, column),
+            end: (line, column),
+        },
+        comments,
+    });
+    Ok(tokens)
+}
+
+// Decodes a default value of a bytes field, which is held in the descriptor with C escapes.
+pub(crate) fn unescape_bytes(text: &str) -> Option<Vec<u8>> {
+    let mut chars: Vec<char> = text.chars().collect();
+    chars.push('"');
+    unescape(&chars, 0, '"').ok().map(|(value, _)| value)
+}
+
+// Decodes the body of a string literal, returning the bytes and the index after the closing quote.
+fn unescape(chars: &[char], mut index: usize, quote: char) -> Result<(Vec<u8>, usize), String> {
+    let mut value = Vec::new();
+
+    loop {
+        let c = match chars.get(index) {
+            None | Some('\n') => return Err("The string is not terminated.".to_string()),
+            Some(&c) => c,
+        };
+        index += 1;
+
+        if c == quote {
+            return Ok((value, index));
+        }
+        if c != '\\' {
+            let mut bytes = [0; 4];
+            value.extend_from_slice(c.encode_utf8(&mut bytes).as_bytes());
+            continue;
+        }
+
+        let escape = *chars
+            .get(index)
+            .ok_or_else(|| "The string is not terminated.".to_string())?;
+        index += 1;
+
+        let digits = |index: &mut usize, radix: u32, count: usize| {
+            let mut number = 0u32;
+            let mut read = 0;
+            while read < count {
+                match chars.get(*index).and_then(|c| c.to_digit(radix)) {
+                    Some(digit) => number = number * radix + digit,
+                    None => break,
+                }
+                *index += 1;
+                read += 1;
+            }
+            (number, read)
+        };
+
+        match escape {
+            'a' => value.push(7),
+            'b' => value.push(8),
+            'f' => value.push(12),
+            'n' => value.push(b'\n'),
+            'r' => value.push(b'\r'),
+            't' => value.push(b'\t'),
+            'v' => value.push(11),
+            '\\' | '\'' | '"' | '?' => value.push(escape as u8),
+            'x' | 'X' => match digits(&mut index, 16, 2) {
+                (_, 0) => return Err("The hexadecimal escape is invalid.".to_string()),
+                (number, _) => value.push(number as u8),
+            },
+            '0'..='7' => {
+                index -= 1;
+                let (number, _) = digits(&mut index, 8, 3);
+                value.push(number as u8);
+            }
+            'u' | 'U' => {
+                let count = if escape == 'u' { 4 } else { 8 };
+                let (number, read) = digits(&mut index, 16, count);
+                match char::from_u32(number).filter(|_| read == count) {
+                    Some(c) => {
+                        let mut bytes = [0; 4];
+                        value.extend_from_slice(c.encode_utf8(&mut bytes).as_bytes());
+                    }
+                    None => return Err("The unicode escape is invalid.".to_string()),
+                }
+            }
+            _ => return Err(format!("The escape \\{} is invalid.", escape)),
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    syntax: Syntax,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn peek_at(&self, offset: usize) -> &TokenKind {
+        let index = (self.position + offset).min(self.tokens.len() - 1);
+        &self.tokens[index].kind
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError::new(self.peek().span, message))
+    }
+
+    fn is_symbol(&self, symbol: char) -> bool {
+        self.peek().kind == TokenKind::Symbol(symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(&self.peek().kind, TokenKind::Identifier(i) if i == keyword)
+    }
+
+    fn accept_symbol(&mut self, symbol: char) -> bool {
+        let accepted = self.is_symbol(symbol);
+        if accepted {
+            self.position += 1;
+        }
+        accepted
+    }
+
+    fn accept_keyword(&mut self, keyword: &str) -> bool {
+        let accepted = self.is_keyword(keyword);
+        if accepted {
+            self.position += 1;
+        }
+        accepted
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> Result<(), ParseError> {
+        match self.accept_symbol(symbol) {
+            true => Ok(()),
+            false => self.error(format!(
+                "Expected \"{}\" but found \"{}\".",
+                symbol,
+                self.peek().text
+            )),
+        }
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), ParseError> {
+        match self.accept_keyword(keyword) {
+            true => Ok(()),
+            false => self.error(format!(
+                "Expected \"{}\" but found \"{}\".",
+                keyword,
+                self.peek().text
+            )),
+        }
+    }
+
+    fn expect_identifier(&mut self) -> Result<String, ParseError> {
+        match &self.peek().kind {
+            TokenKind::Identifier(identifier) => {
+                let identifier = identifier.clone();
+                self.position += 1;
+                Ok(identifier)
+            }
+            _ => self.error(format!(
+                "Expected an identifier but found \"{}\".",
+                self.peek().text
+            )),
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<Vec<u8>, ParseError> {
+        let mut value = match &self.peek().kind {
+            TokenKind::String(value) => value.clone(),
+            _ => {
+                return self.error(format!(
+                    "Expected a string but found \"{}\".",
+                    self.peek().text
+                ))
+            }
+        };
+        self.position += 1;
+
+        // Adjacent string literals are concatenated.
+        while let TokenKind::String(next) = &self.peek().kind {
+            value.extend_from_slice(next);
+            self.position += 1;
+        }
+        Ok(value)
+    }
+
+    fn expect_utf8_string(&mut self) -> Result<String, ParseError> {
+        let span = self.peek().span;
+        String::from_utf8(self.expect_string()?)
+            .map_err(|_| ParseError::new(span, "The string is not valid UTF-8."))
+    }
+
+    fn expect_integer(&mut self) -> Result<u64, ParseError> {
+        match self.peek().kind {
+            TokenKind::Integer(value) => {
+                self.position += 1;
+                Ok(value)
+            }
+            _ => self.error(format!(
+                "Expected an integer but found \"{}\".",
+                self.peek().text
+            )),
+        }
+    }
+
+    fn expect_int32(&mut self, allow_negative: bool) -> Result<i32, ParseError> {
+        let negative = allow_negative && self.accept_symbol('-');
+        let span = self.peek().span;
+        let value = self.expect_integer()? as i128;
+        let value = if negative { -value } else { value };
+
+        i32::try_from(value).map_err(|_| ParseError::new(span, "The integer is out of range."))
+    }
+
+    fn expect_field_number(&mut self) -> Result<i32, ParseError> {
+        let span = self.peek().span;
+        match self.expect_int32(false)? {
+            19000..=19999 => Err(ParseError::new(
+                span,
+                "The field numbers 19000 to 19999 are reserved for the Protocol Buffers implementation.",
+            )),
+            number @ 1..=MAX_FIELD_NUMBER => Ok(number),
+            _ => Err(ParseError::new(
+                span,
+                format!(
+                    "The field number must be between 1 and {}.",
+                    MAX_FIELD_NUMBER
+                ),
+            )),
+        }
+    }
+
+    // Parses a name made of dot separated identifiers, which may start with a dot.
+    fn expect_type_name(&mut self) -> Result<String, ParseError> {
+        let mut name = String::new();
+        if self.accept_symbol('.') {
+            name.push('.');
+        }
+
+        name.push_str(&self.expect_identifier()?);
+        while self.accept_symbol('.') {
+            name.push('.');
+            name.push_str(&self.expect_identifier()?);
+        }
+        Ok(name)
+    }
+
+    // Builds the location of the definition starting at the token index. The trailing comment
+    // is taken from after the token at `trailing_index`, which is the opening brace of a block
+    // or the final token of a statement.
+    fn location(&self, start: usize, trailing_index: usize) -> Location {
+        let first = &self.tokens[start];
+        let last = &self.tokens[self.position.max(start + 1) - 1];
+        let mut comments = Comments::default();
+
+        let mut blocks = first.comments.as_slice();
+        if let (Some(block), Some(previous)) = (blocks.first(), start.checked_sub(1)) {
+            if block.start_line == self.tokens[previous].span.end.0 {
+                blocks = &blocks[1..];
+            }
+        }
+        if let Some((last_block, rest)) = blocks.split_last() {
+            match last_block.end_line + 1 >= first.span.start.0 {
+                true => {
+                    comments.leading = Some(last_block.text.clone());
+                    comments.detached = rest.iter().map(|b| b.text.clone()).collect();
+                }
+                false => comments.detached = blocks.iter().map(|b| b.text.clone()).collect(),
+            }
+        }
+
+        let anchor = &self.tokens[trailing_index];
+        if let Some(next) = self.tokens.get(trailing_index + 1) {
+            if let Some(block) = next.comments.first() {
+                if block.start_line == anchor.span.end.0 {
+                    comments.trailing = Some(block.text.clone());
+                }
+            }
+        }
+
+        Location {
+            span: Span {
+                start: first.span.start,
+                end: last.span.end,
+            },
+            comments,
+        }
+    }
+
+    fn parse_file(&mut self) -> Result<ProtoFile, ParseError> {
+        let mut file = ProtoFile {
+            syntax: Syntax::Proto2,
+            edition: None,
+            syntax_location: None,
+            package: None,
+            imports: Vec::new(),
+            options: Vec::new(),
+            messages: Vec::new(),
+            enums: Vec::new(),
+            services: Vec::new(),
+            extensions: Vec::new(),
+            span: Span::default(),
+        };
+
+        if self.is_keyword("syntax") {
+            let start = self.position;
+            self.position += 1;
+            self.expect_symbol('=')?;
+            let span = self.peek().span;
+            file.syntax = match self.expect_utf8_string()?.as_str() {
+                "proto2" => Syntax::Proto2,
+                "proto3" => Syntax::Proto3,
+                other => {
+                    return Err(ParseError::new(
+                        span,
+                        format!("The syntax \"{}\" is not supported.", other),
+                    ))
+                }
+            };
+            self.expect_symbol(';')?;
+            file.syntax_location = Some(self.location(start, self.position - 1));
+            self.syntax = file.syntax;
+        } else if self.is_keyword("edition") {
+            let start = self.position;
+            self.position += 1;
+            self.expect_symbol('=')?;
+            let span = self.peek().span;
+            file.edition = match self.expect_utf8_string()?.as_str() {
+                "2023" => Some(Edition::Edition2023),
+                other => {
+                    return Err(ParseError::new(
+                        span,
+                        format!("The edition \"{}\" is not supported.", other),
+                    ))
+                }
+            };
+            self.expect_symbol(';')?;
+            file.syntax = Syntax::Editions;
+            file.syntax_location = Some(self.location(start, self.position - 1));
+            self.syntax = file.syntax;
+        }
+
+        while self.peek().kind != TokenKind::End {
+            let start = self.position;
+            match &self.peek().kind {
+                TokenKind::Symbol(';') => self.position += 1,
+                TokenKind::Identifier(keyword) => match keyword.as_str() {
+                    "import" => {
+                        self.position += 1;
+                        let kind = match () {
+                            _ if self.accept_keyword("public") => ImportKind::Public,
+                            _ if self.accept_keyword("weak") => ImportKind::Weak,
+                            _ => ImportKind::Default,
+                        };
+                        let path = self.expect_utf8_string()?;
+                        self.expect_symbol(';')?;
+                        file.imports.push(Import {
+                            path,
+                            kind,
+                            location: self.location(start, self.position - 1),
+                        });
+                    }
+                    "package" => {
+                        if file.package.is_some() {
+                            return self.error("The package is declared more than once.");
+                        }
+                        self.position += 1;
+                        let package = self.expect_type_name()?;
+                        self.expect_symbol(';')?;
+                        file.package = Some((package, self.location(start, self.position - 1)));
+                    }
+                    "option" => file.options.push(self.parse_option_statement()?),
+                    "message" => file.messages.push(self.parse_message()?),
+                    "enum" => file.enums.push(self.parse_enum()?),
+                    "service" => file.services.push(self.parse_service()?),
+                    "extend" => self.parse_extend(&mut file.extensions, &mut file.messages)?,
+                    _ => return self.error(format!("Unexpected \"{}\".", keyword)),
+                },
+                _ => return self.error(format!("Unexpected \"{}\".", self.peek().text)),
+            }
+        }
+
+        file.span = Span {
+            start: self.tokens[0].span.start,
+            end: self.tokens[self.position.max(1) - 1].span.end,
+        };
+        Ok(file)
+    }
+
+    fn parse_option_name(&mut self) -> Result<Vec<OptionNamePart>, ParseError> {
+        let mut parts = Vec::new();
+
+        loop {
+            if self.accept_symbol('(') {
+                let name = self.expect_type_name()?;
+                self.expect_symbol(')')?;
+                parts.push(OptionNamePart {
+                    name,
+                    is_extension: true,
+                });
+            } else {
+                parts.push(OptionNamePart {
+                    name: self.expect_identifier()?,
+                    is_extension: false,
+                });
+            }
+
+            if !self.accept_symbol('.') {
+                return Ok(parts);
+            }
+        }
+    }
+
+    fn parse_option_value(&mut self) -> Result<OptionValue, ParseError> {
+        let negative = self.accept_symbol('-');
+        if !negative {
+            let _ = self.accept_symbol('+');
+        }
+
+        let token = self.peek().clone();
+        let value = match token.kind {
+            TokenKind::Integer(value) if negative => match value {
+                0..=0x8000_0000_0000_0000 => {
+                    OptionValue::NegativeInt((value as i64).wrapping_neg())
+                }
+                _ => OptionValue::Float(-(value as f64)),
+            },
+            TokenKind::Integer(value) => OptionValue::PositiveInt(value),
+            TokenKind::Float(value) if negative => OptionValue::Float(-value),
+            TokenKind::Float(value) => OptionValue::Float(value),
+            TokenKind::Identifier(identifier) => match identifier.as_str() {
+                "inf" | "infinity" if negative => OptionValue::Float(f64::NEG_INFINITY),
+                "nan" if negative => OptionValue::Float(f64::NAN),
+                _ if negative => return self.error("Expected a number after \"-\"."),
+                _ => {
+                    return self.expect_type_name().map(OptionValue::Identifier);
+                }
+            },
+            TokenKind::String(_) if !negative => {
+                return self.expect_string().map(OptionValue::String)
+            }
+            TokenKind::Symbol('{') if !negative => return self.parse_aggregate(),
+            _ => {
+                return self.error(format!(
+                    "Expected an option value but found \"{}\".",
+                    token.text
+                ))
+            }
+        };
+
+        self.position += 1;
+        Ok(value)
+    }
+
+    // Aggregate values are kept as text, with the tokens separated by spaces.
+    fn parse_aggregate(&mut self) -> Result<OptionValue, ParseError> {
+        self.expect_symbol('{')?;
+        let mut depth = 1;
+        let mut text = Vec::new();
+
+        loop {
+            let token = self.advance();
+            match token.kind {
+                TokenKind::End => return self.error("The aggregate value is not terminated."),
+                TokenKind::Symbol('{') | TokenKind::Symbol('<') => depth += 1,
+                TokenKind::Symbol('}') | TokenKind::Symbol('>') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(OptionValue::Aggregate(text.join(" ")));
+                    }
+                }
+                _ => {}
+            }
+            text.push(token.text);
+        }
+    }
+
+    fn parse_option_statement(&mut self) -> Result<OptionDef, ParseError> {
+        let start = self.peek().span.start;
+        self.expect_keyword("option")?;
+        let name = self.parse_option_name()?;
+        self.expect_symbol('=')?;
+        let value = self.parse_option_value()?;
+        let end = self.peek().span.end;
+        self.expect_symbol(';')?;
+
+        Ok(OptionDef {
+            name,
+            value,
+            span: Span { start, end },
+        })
+    }
+
+    // Parses the options in square brackets after a field or enum value, if there are any.
+    fn parse_compact_options(&mut self) -> Result<Vec<OptionDef>, ParseError> {
+        let mut options = Vec::new();
+        if !self.accept_symbol('[') {
+            return Ok(options);
+        }
+
+        loop {
+            let start = self.peek().span.start;
+            let name = self.parse_option_name()?;
+            self.expect_symbol('=')?;
+            let value = self.parse_option_value()?;
+            let end = self.tokens[self.position - 1].span.end;
+            options.push(OptionDef {
+                name,
+                value,
+                span: Span { start, end },
+            });
+
+            if !self.accept_symbol(',') {
+                self.expect_symbol(']')?;
+                return Ok(options);
+            }
+        }
+    }
+
+    fn parse_message(&mut self) -> Result<MessageDef, ParseError> {
+        let start = self.position;
+        self.expect_keyword("message")?;
+        let name = self.expect_identifier()?;
+        self.parse_message_body(start, name)
+    }
+
+    // Parses the fields and declarations between the braces of a message or a group.
+    fn parse_message_body(&mut self, start: usize, name: String) -> Result<MessageDef, ParseError> {
+        let open = self.position;
+        self.expect_symbol('{')?;
+
+        let mut message = MessageDef {
+            name,
+            fields: Vec::new(),
+            oneofs: Vec::new(),
+            messages: Vec::new(),
+            enums: Vec::new(),
+            extensions: Vec::new(),
+            extension_ranges: Vec::new(),
+            reserved_ranges: Vec::new(),
+            reserved_names: Vec::new(),
+            options: Vec::new(),
+            location: Location::default(),
+        };
+
+        while !self.accept_symbol('}') {
+            if self.peek().kind == TokenKind::End {
+                return self.error("The message is not terminated.");
+            }
+
+            let is_declaration = matches!(self.peek_at(1), TokenKind::Identifier(_));
+            match &self.peek().kind {
+                TokenKind::Symbol(';') => self.position += 1,
+                TokenKind::Identifier(keyword) => match keyword.as_str() {
+                    "message" if is_declaration => message.messages.push(self.parse_message()?),
+                    "enum" if is_declaration => message.enums.push(self.parse_enum()?),
+                    "extend" if is_declaration => {
+                        self.parse_extend(&mut message.extensions, &mut message.messages)?
+                    }
+                    "option" if is_declaration || *self.peek_at(1) == TokenKind::Symbol('(') => {
+                        message.options.push(self.parse_option_statement()?)
+                    }
+                    "oneof" if is_declaration => self.parse_oneof(&mut message)?,
+                    "extensions" if !is_declaration => {
+                        let start = self.position;
+                        self.position += 1;
+                        let ranges = self.parse_ranges(MAX_FIELD_NUMBER)?;
+                        let options = self.parse_compact_options()?;
+                        self.expect_symbol(';')?;
+                        message.extension_ranges.push(ExtensionRangeDef {
+                            ranges,
+                            options,
+                            location: self.location(start, self.position - 1),
+                        });
+                    }
+                    "reserved" if !is_declaration => {
+                        self.parse_reserved(
+                            &mut message.reserved_ranges,
+                            &mut message.reserved_names,
+                            MAX_FIELD_NUMBER,
+                        )?;
+                    }
+                    _ => {
+                        let field = self.parse_field(None, None, &mut message.messages)?;
+                        message.fields.push(field);
+                    }
+                },
+                _ => return self.error(format!("Unexpected \"{}\".", self.peek().text)),
+            }
+        }
+
+        message.location = self.location(start, open);
+        Ok(message)
+    }
+
+    // Parses a field, adding the message declared by a group field to `messages`.
+    fn parse_field(
+        &mut self,
+        oneof: Option<usize>,
+        extendee: Option<&str>,
+        messages: &mut Vec<MessageDef>,
+    ) -> Result<FieldDef, ParseError> {
+        let start = self.position;
+
+        let label = match () {
+            _ if oneof.is_some() => None,
+            _ if self.accept_keyword("optional") => match self.syntax {
+                Syntax::Proto2 | Syntax::Proto3 => Some(Label::Optional),
+                Syntax::Editions => {
+                    return Err(ParseError::new(
+                        self.tokens[start].span,
+                        "The optional label is not allowed in editions, where fields have \
+                         presence unless features.field_presence says otherwise.",
+                    ))
+                }
+            },
+            _ if self.accept_keyword("repeated") => Some(Label::Repeated),
+            _ if self.accept_keyword("required") => match self.syntax {
+                Syntax::Proto2 => Some(Label::Required),
+                Syntax::Proto3 => {
+                    return Err(ParseError::new(
+                        self.tokens[start].span,
+                        "Required fields are not allowed in proto3.",
+                    ))
+                }
+                Syntax::Editions => {
+                    return Err(ParseError::new(
+                        self.tokens[start].span,
+                        "Required fields are not allowed in editions, use \
+                         features.field_presence = LEGACY_REQUIRED instead.",
+                    ))
+                }
+            },
+            _ => None,
+        };
+
+        let mut map_types = None;
+        let field_type = if self.is_keyword("map") && *self.peek_at(1) == TokenKind::Symbol('<') {
+            if label.is_some() || oneof.is_some() || extendee.is_some() {
+                return self.error("Map fields can not have a label or be a member of a oneof.");
+            }
+            self.position += 2;
+            let key = self.parse_type()?;
+            self.expect_symbol(',')?;
+            let value = self.parse_type()?;
+            self.expect_symbol('>')?;
+            map_types = Some((key, value));
+            TypeRef::Named(String::new())
+        } else {
+            if label.is_none() && oneof.is_none() && self.syntax == Syntax::Proto2 {
+                return self.error("Fields in proto2 must have a label.");
+            }
+            if self.is_keyword("group") && matches!(self.peek_at(1), TokenKind::Identifier(_)) {
+                match self.syntax {
+                    Syntax::Proto2 => {}
+                    Syntax::Proto3 => return self.error("Groups are not allowed in proto3."),
+                    Syntax::Editions => {
+                        return self.error(
+                            "Groups are not allowed in editions, use \
+                             features.message_encoding = DELIMITED instead.",
+                        )
+                    }
+                }
+                self.position += 1;
+                if !self
+                    .peek()
+                    .text
+                    .starts_with(|c: char| c.is_ascii_uppercase())
+                {
+                    return self.error("The name of a group must start with a capital letter.");
+                }
+                TypeRef::Group(self.expect_identifier()?)
+            } else {
+                self.parse_type()?
+            }
+        };
+
+        // A group field is named after its message, in lower case.
+        let name = match &field_type {
+            TypeRef::Group(name) => name.to_ascii_lowercase(),
+            _ => self.expect_identifier()?,
+        };
+        self.expect_symbol('=')?;
+        let number = self.expect_field_number()?;
+        let options = self.parse_compact_options()?;
+        match &field_type {
+            TypeRef::Group(name) => {
+                let message = self.parse_message_body(start, name.clone())?;
+                messages.push(message);
+            }
+            _ => self.expect_symbol(';')?,
+        }
+
+        Ok(FieldDef {
+            label,
+            field_type,
+            map_types,
+            name,
+            number,
+            options,
+            oneof,
+            extendee: extendee.map(str::to_string),
+            location: self.location(start, self.position - 1),
+        })
+    }
+
+    fn parse_type(&mut self) -> Result<TypeRef, ParseError> {
+        let scalar = match &self.peek().kind {
+            TokenKind::Identifier(name) => match name.as_str() {
+                "double" => Some(FieldType::Double),
+                "float" => Some(FieldType::Float),
+                "int64" => Some(FieldType::Int64),
+                "uint64" => Some(FieldType::Uint64),
+                "int32" => Some(FieldType::Int32),
+                "fixed64" => Some(FieldType::Fixed64),
+                "fixed32" => Some(FieldType::Fixed32),
+                "bool" => Some(FieldType::Bool),
+                "string" => Some(FieldType::String),
+                "bytes" => Some(FieldType::Bytes),
+                "uint32" => Some(FieldType::Uint32),
+                "sfixed32" => Some(FieldType::Sfixed32),
+                "sfixed64" => Some(FieldType::Sfixed64),
+                "sint32" => Some(FieldType::Sint32),
+                "sint64" => Some(FieldType::Sint64),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match scalar {
+            Some(scalar) => {
+                self.position += 1;
+                Ok(TypeRef::Scalar(scalar))
+            }
+            None => self.expect_type_name().map(TypeRef::Named),
+        }
+    }
+
+    fn parse_oneof(&mut self, message: &mut MessageDef) -> Result<(), ParseError> {
+        let start = self.position;
+        self.expect_keyword("oneof")?;
+        let name = self.expect_identifier()?;
+        let open = self.position;
+        self.expect_symbol('{')?;
+
+        let index = message.oneofs.len();
+        let mut options = Vec::new();
+        while !self.accept_symbol('}') {
+            match &self.peek().kind {
+                TokenKind::End => return self.error("The oneof is not terminated."),
+                TokenKind::Symbol(';') => self.position += 1,
+                TokenKind::Identifier(keyword) if keyword == "option" => {
+                    options.push(self.parse_option_statement()?)
+                }
+                _ => {
+                    let field = self.parse_field(Some(index), None, &mut message.messages)?;
+                    message.fields.push(field);
+                }
+            }
+        }
+
+        message.oneofs.push(OneofDef {
+            name,
+            options,
+            location: self.location(start, open),
+        });
+        Ok(())
+    }
+
+    fn parse_extend(
+        &mut self,
+        extensions: &mut Vec<FieldDef>,
+        messages: &mut Vec<MessageDef>,
+    ) -> Result<(), ParseError> {
+        self.expect_keyword("extend")?;
+        let extendee = self.expect_type_name()?;
+        self.expect_symbol('{')?;
+
+        while !self.accept_symbol('}') {
+            match &self.peek().kind {
+                TokenKind::End => return self.error("The extend block is not terminated."),
+                TokenKind::Symbol(';') => self.position += 1,
+                _ => {
+                    let field = self.parse_field(None, Some(&extendee), messages)?;
+                    extensions.push(field);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Parses comma separated ranges such as `1, 5 to 10, 100 to max`.
+    fn parse_ranges(&mut self, max: i32) -> Result<Vec<Range>, ParseError> {
+        let mut ranges = Vec::new();
+
+        loop {
+            let span = self.peek().span;
+            let allow_negative = max != MAX_FIELD_NUMBER;
+            let start = self.expect_int32(allow_negative)?;
+            let end = match self.accept_keyword("to") {
+                true if self.accept_keyword("max") => None,
+                true => Some(self.expect_int32(allow_negative)?),
+                false => Some(start),
+            };
+            if end.is_some_and(|end| end < start) {
+                return Err(ParseError::new(
+                    span,
+                    "The end of the range is before its start.",
+                ));
+            }
+            ranges.push(Range { start, end });
+
+            if !self.accept_symbol(',') {
+                return Ok(ranges);
+            }
+        }
+    }
+
+    fn parse_reserved(
+        &mut self,
+        ranges: &mut Vec<Range>,
+        names: &mut Vec<String>,
+        max: i32,
+    ) -> Result<(), ParseError> {
+        self.expect_keyword("reserved")?;
+
+        if matches!(self.peek().kind, TokenKind::String(_)) {
+            loop {
+                names.push(self.expect_utf8_string()?);
+                if !self.accept_symbol(',') {
+                    break;
+                }
+            }
+        } else {
+            ranges.extend(self.parse_ranges(max)?);
+        }
+
+        self.expect_symbol(';')
+    }
+
+    fn parse_enum(&mut self) -> Result<EnumDef, ParseError> {
+        let start = self.position;
+        self.expect_keyword("enum")?;
+        let name = self.expect_identifier()?;
+        let open = self.position;
+        self.expect_symbol('{')?;
+
+        let mut enumeration = EnumDef {
+            name,
+            values: Vec::new(),
+            reserved_ranges: Vec::new(),
+            reserved_names: Vec::new(),
+            options: Vec::new(),
+            location: Location::default(),
+        };
+
+        while !self.accept_symbol('}') {
+            let is_statement = *self.peek_at(1) != TokenKind::Symbol('=');
+            match &self.peek().kind {
+                TokenKind::End => return self.error("The enum is not terminated."),
+                TokenKind::Symbol(';') => self.position += 1,
+                TokenKind::Identifier(keyword) if keyword == "option" && is_statement => {
+                    enumeration.options.push(self.parse_option_statement()?)
+                }
+                TokenKind::Identifier(keyword) if keyword == "reserved" && is_statement => self
+                    .parse_reserved(
+                        &mut enumeration.reserved_ranges,
+                        &mut enumeration.reserved_names,
+                        i32::MAX,
+                    )?,
+                _ => {
+                    let start = self.position;
+                    let name = self.expect_identifier()?;
+                    self.expect_symbol('=')?;
+                    let number = self.expect_int32(true)?;
+                    let options = self.parse_compact_options()?;
+                    self.expect_symbol(';')?;
+                    enumeration.values.push(EnumValueDef {
+                        name,
+                        number,
+                        options,
+                        location: self.location(start, self.position - 1),
+                    });
+                }
+            }
+        }
+
+        if self.syntax == Syntax::Proto3 && enumeration.values.first().map(|v| v.number) != Some(0)
+        {
+            return Err(ParseError::new(
+                self.tokens[start].span,
+                "The first value of a proto3 enum must be zero.",
+            ));
+        }
+
+        enumeration.location = self.location(start, open);
+        Ok(enumeration)
+    }
+
+    fn parse_service(&mut self) -> Result<ServiceDef, ParseError> {
+        let start = self.position;
+        self.expect_keyword("service")?;
+        let name = self.expect_identifier()?;
+        let open = self.position;
+        self.expect_symbol('{')?;
+
+        let mut service = ServiceDef {
+            name,
+            methods: Vec::new(),
+            options: Vec::new(),
+            location: Location::default(),
+        };
+
+        while !self.accept_symbol('}') {
+            match &self.peek().kind {
+                TokenKind::End => return self.error("The service is not terminated."),
+                TokenKind::Symbol(';') => self.position += 1,
+                TokenKind::Identifier(keyword) if keyword == "option" => {
+                    service.options.push(self.parse_option_statement()?)
+                }
+                _ => service.methods.push(self.parse_method()?),
+            }
+        }
+
+        service.location = self.location(start, open);
+        Ok(service)
+    }
+
+    fn parse_method(&mut self) -> Result<MethodDef, ParseError> {
+        let start = self.position;
+        self.expect_keyword("rpc")?;
+        let name = self.expect_identifier()?;
+
+        let parse_type = |parser: &mut Self| -> Result<(String, bool), ParseError> {
+            parser.expect_symbol('(')?;
+            let streaming =
+                parser.is_keyword("stream") && !matches!(parser.peek_at(1), TokenKind::Symbol(_));
+            if streaming {
+                parser.position += 1;
+            }
+            let type_name = parser.expect_type_name()?;
+            parser.expect_symbol(')')?;
+            Ok((type_name, streaming))
+        };
+
+        let (input_type, client_streaming) = parse_type(self)?;
+        self.expect_keyword("returns")?;
+        let (output_type, server_streaming) = parse_type(self)?;
+
+        let mut options = Vec::new();
+        let trailing_index;
+        if self.is_symbol('{') {
+            trailing_index = self.position;
+            self.position += 1;
+            while !self.accept_symbol('}') {
+                match &self.peek().kind {
+                    TokenKind::End => return self.error("The method is not terminated."),
+                    TokenKind::Symbol(';') => self.position += 1,
+                    _ => options.push(self.parse_option_statement()?),
+                }
+            }
+        } else {
+            trailing_index = self.position;
+            self.expect_symbol(';')?;
+        }
+
+        Ok(MethodDef {
+            name,
+            input_type,
+            output_type,
+            client_streaming,
+            server_streaming,
+            options,
+            location: self.location(start, trailing_index),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[test]
+    fn test_message_parsing() {
+        // Arrange
+        let source = r#"
+            syntax = "proto3";
+            package acme.people;
+
+            message Person {
+                string name = 1;
+                repeated int32 ids = 2 [packed = false];
+                map<string, Person> friends = 3;
+                oneof contact {
+                    string phone = 4;
+                    .acme.Address address = 5;
+                }
+                reserved 10 to 20, 30;
+                reserved "old";
+            }
+        "#;
+
+        // Act
+        let file = parse(source).unwrap();
+
+        // Assert
+        let person = &file.messages[0];
+        assert_eq!(file.syntax, Syntax::Proto3);
+        assert_eq!(file.package.as_ref().unwrap().0, "acme.people");
+        assert_eq!(person.fields.len(), 5);
+        assert_eq!(person.fields[1].label, Some(Label::Repeated));
+        assert_eq!(person.fields[1].options[0].simple_name(), Some("packed"));
+        assert_eq!(
+            person.fields[2].map_types,
+            Some((
+                TypeRef::Scalar(FieldType::String),
+                TypeRef::Named("Person".to_string())
+            ))
+        );
+        assert_eq!(person.fields[4].oneof, Some(0));
+        assert_eq!(
+            person.fields[4].field_type,
+            TypeRef::Named(".acme.Address".to_string())
+        );
+        assert_eq!(
+            person.reserved_ranges,
+            vec![
+                Range {
+                    start: 10,
+                    end: Some(20)
+                },
+                Range {
+                    start: 30,
+                    end: Some(30)
+                }
+            ]
+        );
+        assert_eq!(person.reserved_names, vec!["old".to_string()]);
+    }
+
+    #[test]
+    fn test_comments_are_attached() {
+        // Arrange
+        let source = "syntax = \"proto3\";\n\n// Detached.\n\n// The person.\n// Second line.\nmessage Person { // Trailing.\n  /* The name. */\n  string name = 1; // After name.\n}\n";
+
+        // Act
+        let file = parse(source).unwrap();
+
+        // Assert
+        let person = &file.messages[0];
+        assert_eq!(
+            person.location.comments,
+            Comments {
+                leading: Some(" The person.\n Second line.\n".to_string()),
+                trailing: Some(" Trailing.\n".to_string()),
+                detached: vec![" Detached.\n".to_string()],
+            }
+        );
+        assert_eq!(
+            person.fields[0].location.comments.leading.as_deref(),
+            Some(" The name. ")
+        );
+        assert_eq!(
+            person.fields[0].location.comments.trailing.as_deref(),
+            Some(" After name.\n")
+        );
+        assert_eq!(person.location.span.to_source_span(), vec![6, 0, 9, 1]);
+        assert_eq!(
+            person.fields[0].location.span.to_source_span(),
+            vec![8, 2, 18]
+        );
+    }
+
+    #[rstest]
+    #[case("option java_package = \"a\" \"b\";", OptionValue::String(b"ab".to_vec()))]
+    #[case("option (x) = -5;", OptionValue::NegativeInt(-5))]
+    #[case("option (x) = 0x1F;", OptionValue::PositiveInt(31))]
+    #[case("option (x) = -inf;", OptionValue::Float(f64::NEG_INFINITY))]
+    #[case("option (x) = 1.5e3;", OptionValue::Float(1500.0))]
+    #[case("option (x) = SPEED;", OptionValue::Identifier("SPEED".to_string()))]
+    #[case("option (x) = { a: 1 b { c: \"d\" } };", OptionValue::Aggregate("a : 1 b { c : \"d\" }".to_string()))]
+    #[case("option (x) = \"\\x41\\101\\n\";", OptionValue::String(b"AA\n".to_vec()))]
+    fn test_option_values(#[case] source: &str, #[case] expected_value: OptionValue) {
+        // Arrange
+        // Act
+        let file = parse(source).unwrap();
+
+        // Assert
+        assert_eq!(file.options[0].value, expected_value);
+    }
+
+    #[rstest]
+    #[case("syntax = \"proto3\"; message A { required int32 a = 1; }", 0, 31)]
+    #[case("message A { int32 a = 1; }", 0, 12)]
+    #[case("syntax = \"proto3\"; enum E { A = 1; }", 0, 19)]
+    #[case("message A {\n  optional int32 a = 0;\n}", 1, 21)]
+    #[case("message A {\n  optional int32 a = 19000;\n}", 1, 21)]
+    #[case("syntax = \"proto3\"; message A { optional group G = 1 {} }", 0, 40)]
+    #[case("message A { optional group g = 1 {} }", 0, 27)]
+    #[case("edition = \"2024\";", 0, 10)]
+    #[case("edition = \"2023\"; message A { optional int32 a = 1; }", 0, 30)]
+    #[case("edition = \"2023\"; message A { required int32 a = 1; }", 0, 30)]
+    #[case("edition = \"2023\"; message A { repeated group G = 1 {} }", 0, 39)]
+    #[case("message A {", 0, 11)]
+    #[case("message A { string s = 1 [default = \"x]; }", 0, 36)]
+    fn test_parse_errors(
+        #[case] source: &str,
+        #[case] expected_line: usize,
+        #[case] expected_column: usize,
+    ) {
+        // Arrange
+        // Act
+        let error = parse(source).unwrap_err();
+
+        // Assert
+        assert_eq!(
+            (error.line, error.column),
+            (expected_line, expected_column),
+            "{}",
+            error.message
+        );
+    }
+
+    #[test]
+    fn test_service_parsing() {
+        // Arrange
+        let source = "service Chat { rpc Talk (stream Message) returns (stream Reply) { option deprecated = true; } rpc Ping (stream) returns (Reply); }";
+
+        // Act
+        let file = parse(source).unwrap();
+
+        // Assert
+        let methods = &file.services[0].methods;
+        assert!(methods[0].client_streaming && methods[0].server_streaming);
+        assert_eq!(methods[0].options[0].simple_name(), Some("deprecated"));
+        assert_eq!(methods[1].input_type, "stream");
+        assert!(!methods[1].client_streaming);
+    }
+}
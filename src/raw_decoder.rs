@@ -0,0 +1,245 @@
+use crate::length_delimited_encoding::decode_length;
+use crate::text_format::escape;
+use crate::{decode_fixed32, decode_fixed64, decode_varint64, DecodeError, Tag, Varint, WireType};
+use std::fmt::Write;
+
+// How deeply length delimited fields are searched for nested messages, which also bounds the
+// nesting of groups.
+const MAX_DEPTH: usize = 64;
+
+/// Prints the fields of an encoded message without knowing its type, as `protoc --decode_raw`
+/// does.
+///
+/// Each field is keyed by its number. Length delimited fields that can be decoded as a message,
+/// and are not plain text, are printed as nested messages. Otherwise they are printed as strings,
+/// with the bytes that are not valid UTF-8 escaped. Fixed width values are printed in hexadecimal.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::RawDecoder;
+///
+/// let bytes = vec![8, 150, 1, 18, 5, 104, 101, 108, 108, 111, 26, 2, 8, 1];
+///
+/// let text = RawDecoder::new().decode(&bytes).unwrap();
+///
+/// assert_eq!(text, "1: 150\n2: \"hello\"\n3 {\n  1: 1\n}\n");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RawDecoder {
+    show_offsets: bool,
+}
+
+impl RawDecoder {
+    /// Creates a decoder that prints fields without their offsets.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether each field is preceded by the offset of its tag within the buffer.
+    pub fn show_offsets(&mut self, show_offsets: bool) {
+        self.show_offsets = show_offsets;
+    }
+
+    /// Decodes the buffer and returns the fields it holds, one per line. An error is returned when
+    /// the buffer is not a valid encoding of a message.
+    pub fn decode(&self, buffer: &[u8]) -> Result<String, DecodeError> {
+        let (fields, _) = parse_fields(buffer, 0, None, 0)?;
+
+        let mut text = String::new();
+        let width = buffer.len().to_string().len();
+        self.write_fields(&mut text, &fields, 0, width);
+        Ok(text)
+    }
+
+    fn write_fields(&self, text: &mut String, fields: &[RawField], indent: usize, width: usize) {
+        for field in fields {
+            if self.show_offsets {
+                let _ = write!(text, "{:>width$}  ", field.offset, width = width);
+            }
+            let _ = write!(text, "{:indent$}{}", "", field.number, indent = indent);
+
+            match &field.value {
+                RawValue::Varint(value) => {
+                    let _ = writeln!(text, ": {}", value);
+                }
+                RawValue::Fixed64(value) => {
+                    let _ = writeln!(text, ": 0x{:016x}", value);
+                }
+                RawValue::Fixed32(value) => {
+                    let _ = writeln!(text, ": 0x{:08x}", value);
+                }
+                RawValue::Bytes(value) => {
+                    let _ = writeln!(text, ": \"{}\"", escape(value));
+                }
+                RawValue::Message(fields) | RawValue::Group(fields) => {
+                    text.push_str(" {\n");
+                    self.write_fields(text, fields, indent + 2, width);
+                    if self.show_offsets {
+                        let _ = write!(text, "{:width$}  ", "", width = width);
+                    }
+                    let _ = writeln!(text, "{:indent$}}}", "", indent = indent);
+                }
+            }
+        }
+    }
+}
+
+struct RawField {
+    offset: usize,
+    number: u32,
+    value: RawValue,
+}
+
+enum RawValue {
+    Varint(u64),
+    Fixed64(u64),
+    Fixed32(u32),
+    Bytes(Vec<u8>),
+    Message(Vec<RawField>),
+    Group(Vec<RawField>),
+}
+
+// Parses the fields in the buffer, which starts at the offset within the whole message. Within a
+// group, parsing stops after the end group tag with the group's number, and the number of bytes
+// read is returned along with the fields.
+fn parse_fields(
+    buffer: &[u8],
+    offset: usize,
+    group: Option<u32>,
+    depth: usize,
+) -> Result<(Vec<RawField>, usize), DecodeError> {
+    let mut fields = Vec::new();
+    let mut position = 0;
+
+    while position < buffer.len() {
+        let start = position;
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
+        position += tag.from_varint(&buffer[position..])?;
+
+        let remaining = &buffer[position..];
+        let value = match tag.wire_type() {
+            WireType::Varint => {
+                let (value, size) =
+                    decode_varint64(remaining)?.ok_or(DecodeError::BufferOverrun)?;
+                position += size;
+                RawValue::Varint(value)
+            }
+            WireType::Fixed64 => {
+                let (value, size) = decode_fixed64(remaining)?.ok_or(DecodeError::BufferOverrun)?;
+                position += size;
+                RawValue::Fixed64(value)
+            }
+            WireType::Fixed32 => {
+                let (value, size) = decode_fixed32(remaining)?.ok_or(DecodeError::BufferOverrun)?;
+                position += size;
+                RawValue::Fixed32(value)
+            }
+            WireType::LengthDelimited => {
+                let (length, size) = decode_length(remaining)?;
+                let end = size + length;
+                if remaining.len() < end {
+                    return Err(DecodeError::BufferOverrun);
+                }
+                let contents =
+                    length_delimited(&remaining[size..end], offset + position + size, depth);
+                position += end;
+                contents
+            }
+            WireType::StartGroup if depth < MAX_DEPTH => {
+                let (fields, size) = parse_fields(
+                    remaining,
+                    offset + position,
+                    Some(tag.field_number()),
+                    depth + 1,
+                )?;
+                position += size;
+                RawValue::Group(fields)
+            }
+            WireType::EndGroup if group == Some(tag.field_number()) => {
+                return Ok((fields, position));
+            }
+            _ => return Err(DecodeError::UnableToDecode),
+        };
+
+        fields.push(RawField {
+            offset: offset + start,
+            number: tag.field_number(),
+            value,
+        });
+    }
+
+    match group {
+        Some(_) => Err(DecodeError::BufferOverrun),
+        None => Ok((fields, position)),
+    }
+}
+
+// Decodes the contents of a length delimited field as a message when it can be, and as a string
+// otherwise. Text without control characters is kept as a string even when it could be decoded,
+// as short words are often valid messages by chance.
+fn length_delimited(buffer: &[u8], offset: usize, depth: usize) -> RawValue {
+    let is_text = std::str::from_utf8(buffer).is_ok_and(|text| !text.chars().any(char::is_control));
+
+    if !buffer.is_empty() && !is_text && depth < MAX_DEPTH {
+        if let Ok((fields, _)) = parse_fields(buffer, offset, None, depth + 1) {
+            return RawValue::Message(fields);
+        }
+    }
+    RawValue::Bytes(buffer.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(vec![8, 150, 1], "1: 150\n")]
+    #[case(vec![8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1], "1: 18446744073709551615\n")]
+    #[case(vec![21, 0, 0, 128, 63], "2: 0x3f800000\n")]
+    #[case(vec![25, 0, 0, 0, 0, 0, 0, 240, 63], "3: 0x3ff0000000000000\n")]
+    #[case(vec![34, 0], "4: \"\"\n")]
+    #[case(vec![34, 3, 97, 10, 34], "4: \"a\\n\\\"\"\n")]
+    #[case(vec![34, 3, 0xe2, 0x82, 0xac], "4: \"€\"\n")]
+    #[case(vec![34, 2, 0xff, 1], "4: \"\\377\\001\"\n")]
+    #[case(vec![42, 4, 10, 2, 8, 1], "5 {\n  1 {\n    1: 1\n  }\n}\n")]
+    #[case(vec![42, 5, 101, 109, 97, 105, 108], "5: \"email\"\n")]
+    #[case(vec![51, 8, 1, 52, 8, 2], "6 {\n  1: 1\n}\n1: 2\n")]
+    fn test_raw_decoding(#[case] bytes: Vec<u8>, #[case] expected_text: &str) {
+        // Act
+        let text = RawDecoder::new().decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(text, expected_text);
+    }
+
+    #[test]
+    fn test_offsets_are_shown() {
+        // Arrange
+        let bytes = vec![8, 1, 18, 2, 8, 2, 24, 3];
+        let mut decoder = RawDecoder::new();
+        decoder.show_offsets(true);
+
+        // Act
+        let text = decoder.decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(text, "0  1: 1\n2  2 {\n4    1: 2\n   }\n6  3: 3\n");
+    }
+
+    #[rstest]
+    #[case(vec![8], DecodeError::BufferOverrun)]
+    #[case(vec![18, 5, 1], DecodeError::BufferOverrun)]
+    #[case(vec![0, 1], DecodeError::InvalidFieldNumber)]
+    #[case(vec![12], DecodeError::UnableToDecode)]
+    #[case(vec![11, 8, 1], DecodeError::BufferOverrun)]
+    #[case(vec![11, 20], DecodeError::UnableToDecode)]
+    #[case(vec![14], DecodeError::UnknownWireType)]
+    fn test_invalid_buffers(#[case] bytes: Vec<u8>, #[case] expected_error: DecodeError) {
+        // Act
+        let error = RawDecoder::new().decode(&bytes).unwrap_err();
+
+        // Assert
+        assert_eq!(error, expected_error);
+    }
+}
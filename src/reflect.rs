@@ -0,0 +1,349 @@
+use crate::{
+    DecodeError, DynamicMessage, FieldDescriptor, FieldError, FieldValue, MessageDescriptor,
+};
+
+/// This trait can be applied to messages to give tooling access to their fields at runtime.
+///
+/// Every field is read and written through its `FieldDescriptor` as a `FieldValue`, so the same
+/// code works over compiled messages and `DynamicMessage` values. Compiled messages only need to
+/// return their descriptor and convert to and from a `DynamicMessage`, which the `transcode_from`
+/// and `transcode_to` functions of `DynamicMessage` do for any `Message`. The code generator
+/// implements this trait for every message it generates.
+///
+/// For compiled messages each of `has_field`, `get_field`, `set_field` and `clear_field`
+/// transcodes the whole message, so visiting every field one at a time takes time quadratic in the
+/// size of the message. Use `fields` to read every field that is set from a single transcode, or
+/// take one snapshot with `transcode_to_dynamic`, work on it and write it back with
+/// `transcode_from_dynamic`.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{
+///     Buffer, DecodeError, DescriptorPool, DescriptorProto, DynamicMessage, FieldDescriptorProto,
+///     FieldType, FileDescriptorProto, Message, MessageDescriptor, ReflectMessage, Tag, Varint,
+///     VarintField,
+/// };
+/// use std::sync::OnceLock;
+///
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Counter {
+///     count: u32,
+/// }
+///
+/// impl Message for Counter {
+///     fn encode(&self, buffer: &mut Buffer) -> usize {
+///         self.count.to_varint_field::<1>(buffer)
+///     }
+///
+///     fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+///         match tag.field_number() {
+///             1 => self.count.from_varint(buffer).map(Some),
+///             _ => Ok(None),
+///         }
+///     }
+/// }
+///
+/// // Generated code keeps its descriptors in a single pool, as descriptors from different pools
+/// // are never equal.
+/// fn counter_descriptor() -> MessageDescriptor {
+///     static POOL: OnceLock<DescriptorPool> = OnceLock::new();
+///
+///     let pool = POOL.get_or_init(|| {
+///         let mut pool = DescriptorPool::new();
+///         pool.add_file_descriptor_proto(FileDescriptorProto {
+///             name: Some("counter.proto".to_string()),
+///             syntax: Some("proto3".to_string()),
+///             message_type: vec![DescriptorProto {
+///                 name: Some("Counter".to_string()),
+///                 field: vec![FieldDescriptorProto {
+///                     name: Some("count".to_string()),
+///                     number: Some(1),
+///                     r#type: Some(FieldType::Uint32.into()),
+///                     ..Default::default()
+///                 }],
+///                 ..Default::default()
+///             }],
+///             ..Default::default()
+///         })
+///         .unwrap();
+///         pool
+///     });
+///     pool.get_message_by_name("Counter").unwrap()
+/// }
+///
+/// impl ReflectMessage for Counter {
+///     fn descriptor(&self) -> MessageDescriptor {
+///         counter_descriptor()
+///     }
+///
+///     fn transcode_to_dynamic(&self) -> Result<DynamicMessage, DecodeError> {
+///         DynamicMessage::transcode_from(self.descriptor(), self)
+///     }
+///
+///     fn transcode_from_dynamic(&mut self, message: &DynamicMessage) -> Result<(), DecodeError> {
+///         *self = message.transcode_to()?;
+///         Ok(())
+///     }
+/// }
+///
+/// let mut counter = Counter { count: 1 };
+/// let field = counter.descriptor().get_field_by_name("count").unwrap();
+///
+/// counter.set_field(&field, 7u32.into()).unwrap();
+///
+/// assert_eq!(counter.get_field(&field).unwrap().as_u32(), Some(7));
+/// assert_eq!(counter, Counter { count: 7 });
+/// ```
+pub trait ReflectMessage {
+    /// This function returns the type of the message.
+    fn descriptor(&self) -> MessageDescriptor;
+
+    /// This function returns a `DynamicMessage` holding the fields of the message.
+    fn transcode_to_dynamic(&self) -> Result<DynamicMessage, DecodeError>;
+
+    /// This function replaces the fields of the message with the fields of the `DynamicMessage`,
+    /// which must be of the same type.
+    fn transcode_from_dynamic(&mut self, message: &DynamicMessage) -> Result<(), DecodeError>;
+
+    /// This function returns true when the field is set. The default transcodes the whole message.
+    fn has_field(&self, field: &FieldDescriptor) -> Result<bool, FieldError> {
+        Ok(self.transcode_to_dynamic()?.has_field(field))
+    }
+
+    /// This function returns the value of the field, or its default value when it is not set.
+    /// The default transcodes the whole message.
+    fn get_field(&self, field: &FieldDescriptor) -> Result<FieldValue, FieldError> {
+        Ok(self.transcode_to_dynamic()?.get_field(field).into_owned())
+    }
+
+    /// This function returns every field that is set with its value, in field number order. The
+    /// default transcodes the message once, however many fields are set.
+    fn fields(&self) -> Result<Vec<(FieldDescriptor, FieldValue)>, DecodeError> {
+        let message = self.transcode_to_dynamic()?;
+        Ok(message
+            .fields()
+            .map(|(field, value)| (field, value.clone()))
+            .collect())
+    }
+
+    /// This function sets the value of the field. The default transcodes the whole message twice.
+    fn set_field(&mut self, field: &FieldDescriptor, value: FieldValue) -> Result<(), FieldError> {
+        let mut message = self.transcode_to_dynamic()?;
+        message.set_field(field, value)?;
+
+        self.transcode_from_dynamic(&message)?;
+        Ok(())
+    }
+
+    /// This function clears the field. The default transcodes the whole message twice.
+    fn clear_field(&mut self, field: &FieldDescriptor) -> Result<(), FieldError> {
+        let mut message = self.transcode_to_dynamic()?;
+        let _ = message.clear_field(field);
+
+        self.transcode_from_dynamic(&message)?;
+        Ok(())
+    }
+}
+
+impl ReflectMessage for DynamicMessage {
+    fn descriptor(&self) -> MessageDescriptor {
+        DynamicMessage::descriptor(self).clone()
+    }
+
+    fn transcode_to_dynamic(&self) -> Result<DynamicMessage, DecodeError> {
+        Ok(self.clone())
+    }
+
+    fn transcode_from_dynamic(&mut self, message: &DynamicMessage) -> Result<(), DecodeError> {
+        *self = message.clone();
+        Ok(())
+    }
+
+    fn has_field(&self, field: &FieldDescriptor) -> Result<bool, FieldError> {
+        Ok(DynamicMessage::has_field(self, field))
+    }
+
+    fn get_field(&self, field: &FieldDescriptor) -> Result<FieldValue, FieldError> {
+        Ok(DynamicMessage::get_field(self, field).into_owned())
+    }
+
+    fn fields(&self) -> Result<Vec<(FieldDescriptor, FieldValue)>, DecodeError> {
+        Ok(DynamicMessage::fields(self)
+            .map(|(field, value)| (field, value.clone()))
+            .collect())
+    }
+
+    fn set_field(&mut self, field: &FieldDescriptor, value: FieldValue) -> Result<(), FieldError> {
+        DynamicMessage::set_field(self, field, value)
+    }
+
+    fn clear_field(&mut self, field: &FieldDescriptor) -> Result<(), FieldError> {
+        let _ = DynamicMessage::clear_field(self, field);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Buffer, DescriptorPool, DescriptorProto, FieldDescriptorProto, FieldType,
+        FileDescriptorProto, LengthDelimited, LengthDelimitedField, Message, SignExtendedVarint,
+        SignExtendedVarintField, Tag,
+    };
+    use std::sync::OnceLock;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Point {
+        x: i32,
+        label: String,
+    }
+
+    impl Message for Point {
+        fn encode(&self, buffer: &mut Buffer) -> usize {
+            self.x.to_sign_extended_varint_field::<1>(buffer)
+                + self.label.to_length_delimited_field::<2>(buffer)
+        }
+
+        fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+            match tag.field_number() {
+                1 => self.x.from_sign_extended_varint(buffer).map(Some),
+                2 => self.label.from_length_delimited(buffer).map(Some),
+                _ => Ok(None),
+            }
+        }
+    }
+
+    fn point_descriptor() -> MessageDescriptor {
+        static POOL: OnceLock<DescriptorPool> = OnceLock::new();
+
+        let pool = POOL.get_or_init(|| {
+            let field = |name: &str, number, field_type: FieldType| FieldDescriptorProto {
+                name: Some(name.to_string()),
+                number: Some(number),
+                r#type: Some(field_type.into()),
+                ..Default::default()
+            };
+
+            let mut pool = DescriptorPool::new();
+            pool.add_file_descriptor_proto(FileDescriptorProto {
+                name: Some("point.proto".to_string()),
+                syntax: Some("proto3".to_string()),
+                message_type: vec![DescriptorProto {
+                    name: Some("Point".to_string()),
+                    field: vec![
+                        field("x", 1, FieldType::Int32),
+                        field("label", 2, FieldType::String),
+                    ],
+                    ..Default::default()
+                }],
+                ..Default::default()
+            })
+            .unwrap();
+            pool
+        });
+        pool.get_message_by_name("Point").unwrap()
+    }
+
+    impl ReflectMessage for Point {
+        fn descriptor(&self) -> MessageDescriptor {
+            point_descriptor()
+        }
+
+        fn transcode_to_dynamic(&self) -> Result<DynamicMessage, DecodeError> {
+            DynamicMessage::transcode_from(self.descriptor(), self)
+        }
+
+        fn transcode_from_dynamic(&mut self, message: &DynamicMessage) -> Result<(), DecodeError> {
+            *self = message.transcode_to()?;
+            Ok(())
+        }
+    }
+
+    // Walks the fields that are set, the same way for compiled and dynamic messages.
+    fn set_fields<M: ReflectMessage>(message: &M) -> Vec<(String, FieldValue)> {
+        message
+            .descriptor()
+            .fields()
+            .filter(|f| message.has_field(f).unwrap())
+            .map(|f| (f.name().to_string(), message.get_field(&f).unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_compiled_and_dynamic_messages_reflect_the_same_fields() {
+        // Arrange
+        let point = Point {
+            x: -3,
+            label: String::new(),
+        };
+        let dynamic = point.transcode_to_dynamic().unwrap();
+
+        // Act
+        let compiled_fields = set_fields(&point);
+        let dynamic_fields = set_fields(&dynamic);
+
+        // Assert
+        assert_eq!(
+            compiled_fields,
+            vec![("x".to_string(), FieldValue::I32(-3))]
+        );
+        assert_eq!(compiled_fields, dynamic_fields);
+    }
+
+    #[test]
+    fn test_fields_of_compiled_and_dynamic_messages() {
+        // Arrange
+        let point = Point {
+            x: 2,
+            label: "a".to_string(),
+        };
+        let dynamic = point.transcode_to_dynamic().unwrap();
+
+        // Act
+        let compiled_fields = point.fields().unwrap();
+        let dynamic_fields = ReflectMessage::fields(&dynamic).unwrap();
+
+        // Assert
+        let names: Vec<&str> = compiled_fields.iter().map(|(f, _)| f.name()).collect();
+        assert_eq!(names, vec!["x", "label"]);
+        assert_eq!(compiled_fields[1].1, FieldValue::from("a"));
+        assert_eq!(compiled_fields, dynamic_fields);
+    }
+
+    #[test]
+    fn test_set_and_clear_compiled_fields() {
+        // Arrange
+        let mut point = Point::default();
+        let descriptor = point.descriptor();
+        let x = descriptor.get_field_by_name("x").unwrap();
+        let label = descriptor.get_field_by_name("label").unwrap();
+
+        // Act
+        point.set_field(&label, "origin".into()).unwrap();
+        point.set_field(&x, 4.into()).unwrap();
+        point.clear_field(&x).unwrap();
+
+        // Assert
+        assert_eq!(
+            point,
+            Point {
+                x: 0,
+                label: "origin".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_compiled_field_value() {
+        // Arrange
+        let mut point = Point::default();
+        let x = point.descriptor().get_field_by_name("x").unwrap();
+
+        // Act
+        let result = point.set_field(&x, "four".into());
+
+        // Assert
+        assert_eq!(result, Err(FieldError::InvalidType("Point.x".to_string())));
+    }
+}
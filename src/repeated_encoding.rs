@@ -0,0 +1,284 @@
+use crate::length_delimited_encoding::decode_length;
+use crate::{Buffer, DecodeError, FieldCodec, Group, LengthDelimited, Tag, Varint, WireType};
+
+/// This trait can be applied to collections to enable them to encode and decode repeated fields.
+///
+/// Repeated scalar fields can be written packed, where every element is stored in a single length
+/// delimited field, or unpacked, where every element is written as its own field. Decoding accepts
+/// both forms regardless of how the field was declared.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Buffer, RepeatedField, VarintCodec, WireType};
+///
+/// let mut buffer = Buffer::default();
+/// let values: Vec<u32> = vec![3, 270];
+///
+/// let size = values.to_packed_field::<VarintCodec, 4>(&mut buffer);
+///
+/// let mut decoded: Vec<u32> = Vec::new();
+/// decoded
+///     .from_repeated_field::<VarintCodec>(WireType::LengthDelimited, &buffer.to_vec()[1..])
+///     .unwrap();
+/// ```
+#[allow(clippy::wrong_self_convention)]
+pub trait RepeatedField<T> {
+    /// This function writes out every element as a single packed field. Nothing is written when
+    /// there are no elements.
+    fn to_packed_field<C: FieldCodec<T>, const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize;
+
+    /// This function writes out every element as its own field.
+    fn to_unpacked_field<C: FieldCodec<T>, const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize;
+
+    /// This function decodes either a single element or a packed run of elements, appending them
+    /// to the collection. The buffer starts immediately after the tag. `None` is returned when the
+    /// wire type can not hold elements of the field so that it can be skipped.
+    fn from_repeated_field<C: FieldCodec<T>>(
+        &mut self,
+        wire_type: WireType,
+        buffer: &[u8],
+    ) -> Result<Option<usize>, DecodeError>;
+}
+
+impl<T: Default> RepeatedField<T> for Vec<T> {
+    fn to_packed_field<C: FieldCodec<T>, const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let mut elements = Buffer::default();
+        for value in self {
+            let _ = C::encode(value, &mut elements);
+        }
+
+        let size = Tag::new::<FIELD_NUMBER>(WireType::LengthDelimited).to_varint(buffer);
+        size + elements.to_vec().to_length_delimited(buffer)
+    }
+
+    fn to_unpacked_field<C: FieldCodec<T>, const FIELD_NUMBER: u32>(
+        &self,
+        buffer: &mut Buffer,
+    ) -> usize {
+        let mut size = 0;
+
+        for value in self {
+            size += Tag::new::<FIELD_NUMBER>(C::WIRE_TYPE).to_varint(buffer);
+            size += C::encode(value, buffer);
+        }
+
+        size
+    }
+
+    fn from_repeated_field<C: FieldCodec<T>>(
+        &mut self,
+        wire_type: WireType,
+        buffer: &[u8],
+    ) -> Result<Option<usize>, DecodeError> {
+        if wire_type == C::WIRE_TYPE {
+            let mut value = T::default();
+            let size = C::decode(&mut value, buffer)?;
+
+            self.push(value);
+            return Ok(Some(size));
+        }
+
+        if wire_type != WireType::LengthDelimited {
+            return Ok(None);
+        }
+
+        let (length, size) = decode_length(buffer)?;
+        let end = size + length;
+        if buffer.len() < end {
+            return Err(DecodeError::BufferOverrun);
+        }
+
+        let mut position = size;
+        while position < end {
+            let mut value = T::default();
+            position += C::decode(&mut value, &buffer[position..end])?;
+            self.push(value);
+        }
+
+        Ok(Some(end))
+    }
+}
+
+/// This trait can be applied to collections to enable them to encode and decode repeated group
+/// fields, where every element is written between its own start and end group tags.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Buffer, ListValue, RepeatedGroupField};
+///
+/// let mut buffer = Buffer::default();
+/// let values = vec![ListValue::default(), ListValue::default()];
+///
+/// let size = values.to_group_fields::<3>(&mut buffer);
+///
+/// let mut decoded: Vec<ListValue> = Vec::new();
+/// decoded.from_group_field(3, &buffer.to_vec()[1..]).unwrap();
+/// ```
+#[allow(clippy::wrong_self_convention)]
+pub trait RepeatedGroupField {
+    /// This function writes out every element as its own group field.
+    fn to_group_fields<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize;
+
+    /// This function decodes a single group, starting immediately after its start group tag, and
+    /// appends it to the collection.
+    fn from_group_field(&mut self, field_number: u32, buffer: &[u8]) -> Result<usize, DecodeError>;
+}
+
+impl<T: Group + Default> RepeatedGroupField for Vec<T> {
+    fn to_group_fields<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        let mut size = 0;
+
+        for value in self {
+            size += Tag::new::<FIELD_NUMBER>(WireType::StartGroup).to_varint(buffer);
+            size += value.to_group::<FIELD_NUMBER>(buffer);
+        }
+
+        size
+    }
+
+    fn from_group_field(&mut self, field_number: u32, buffer: &[u8]) -> Result<usize, DecodeError> {
+        let mut value = T::default();
+        let size = value.from_group(field_number, buffer)?;
+
+        self.push(value);
+        Ok(size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Fixed32Codec, LengthDelimitedCodec, ListValue, SignExtendedVarintCodec, VarintCodec,
+    };
+    use rstest::*;
+
+    #[rstest]
+    #[case(vec![], vec![])]
+    #[case(vec![3, 270, 86942], vec![34, 6, 3, 142, 2, 158, 167, 5])]
+    fn test_packed_encoding(#[case] values: Vec<u32>, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = values.to_packed_field::<VarintCodec, 4>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[test]
+    fn test_unpacked_encoding() {
+        // Arrange
+        let mut buffer = Buffer::default();
+        let values = vec![0, -1];
+
+        // Act
+        let size = values.to_unpacked_field::<SignExtendedVarintCodec, 1>(&mut buffer);
+
+        // Assert
+        assert_eq!(
+            buffer.to_vec(),
+            vec![8, 0, 8, 255, 255, 255, 255, 255, 255, 255, 255, 255, 1]
+        );
+        assert_eq!(size, 13);
+    }
+
+    #[test]
+    fn test_unpacked_string_encoding() {
+        // Arrange
+        let mut buffer = Buffer::default();
+        let values = vec!["a".to_string(), String::new()];
+
+        // Act
+        let size = values.to_unpacked_field::<LengthDelimitedCodec, 2>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), vec![18, 1, 97, 18, 0]);
+        assert_eq!(size, 5);
+    }
+
+    #[rstest]
+    #[case(WireType::LengthDelimited, vec![6, 3, 142, 2, 158, 167, 5], vec![3, 270, 86942], Some(7))]
+    #[case(WireType::Varint, vec![142, 2], vec![270], Some(2))]
+    #[case(WireType::Fixed32, vec![1, 0, 0, 0], vec![], None)]
+    fn test_repeated_decoding(
+        #[case] wire_type: WireType,
+        #[case] buffer: Vec<u8>,
+        #[case] expected_values: Vec<u32>,
+        #[case] expected_size: Option<usize>,
+    ) {
+        // Arrange
+        let mut values: Vec<u32> = Vec::new();
+
+        // Act
+        let size = values
+            .from_repeated_field::<VarintCodec>(wire_type, &buffer)
+            .unwrap();
+
+        // Assert
+        assert_eq!(values, expected_values);
+        assert_eq!(size, expected_size);
+    }
+
+    #[test]
+    fn test_repeated_decoding_appends() {
+        // Arrange
+        let mut values: Vec<f32> = vec![1.0];
+
+        // Act
+        let _ = values
+            .from_repeated_field::<Fixed32Codec>(WireType::LengthDelimited, &[4, 0, 0, 0, 64])
+            .unwrap();
+        let _ = values
+            .from_repeated_field::<Fixed32Codec>(WireType::Fixed32, &[0, 0, 64, 64])
+            .unwrap();
+
+        // Assert
+        assert_eq!(values, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_repeated_group_round_trip() {
+        // Arrange
+        let mut buffer = Buffer::default();
+        let values = vec![ListValue::default(), ListValue::default()];
+
+        // Act
+        let size = values.to_group_fields::<3>(&mut buffer);
+        let mut decoded: Vec<ListValue> = Vec::new();
+        let decoded_size = decoded.from_group_field(3, &buffer.to_vec()[1..]).unwrap();
+
+        // Assert
+        assert_eq!(buffer.to_vec(), vec![27, 28, 27, 28]);
+        assert_eq!(size, 4);
+        assert_eq!(decoded_size, 1);
+        assert_eq!(decoded, vec![ListValue::default()]);
+    }
+
+    #[test]
+    fn test_truncated_packed_decoding() {
+        // Arrange
+        let mut values: Vec<u32> = Vec::new();
+
+        // Act
+        let result = values.from_repeated_field::<VarintCodec>(WireType::LengthDelimited, &[3, 1]);
+
+        // Assert
+        assert_eq!(result, Err(DecodeError::BufferOverrun));
+    }
+}
@@ -0,0 +1,50 @@
+use std::cell::Cell;
+use std::thread::LocalKey;
+
+/// Runs the function with the thread local set to the value, restoring the previous value
+/// afterwards. The value is restored when the function returns early with an error or panics.
+pub(crate) fn scoped_set<T: Copy + 'static, R>(
+    key: &'static LocalKey<Cell<T>>,
+    value: T,
+    f: impl FnOnce() -> R,
+) -> R {
+    // Restores the previous value when dropped, so that a panic does not leave it set.
+    struct Restore<T: Copy + 'static> {
+        key: &'static LocalKey<Cell<T>>,
+        previous: T,
+    }
+
+    impl<T: Copy + 'static> Drop for Restore<T> {
+        fn drop(&mut self) {
+            self.key.with(|c| c.set(self.previous));
+        }
+    }
+
+    let _restore = Restore {
+        key,
+        previous: key.with(|c| c.replace(value)),
+    };
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic::catch_unwind;
+
+    thread_local! {
+        static VALUE: Cell<u32> = const { Cell::new(1) };
+    }
+
+    #[test]
+    fn test_scoped_set_restores_value() {
+        // Act
+        let inner = scoped_set(&VALUE, 2, || VALUE.with(Cell::get));
+        let panicked = catch_unwind(|| scoped_set(&VALUE, 3, || panic!("failed"))).is_err();
+
+        // Assert
+        assert_eq!(inner, 2);
+        assert!(panicked);
+        assert_eq!(VALUE.with(Cell::get), 1);
+    }
+}
@@ -1,7 +1,8 @@
-use crate::{encode_varint32, Buffer, DecodeError, Varint};
+use crate::{decode_varint64, encode_varint32, Buffer, DecodeError, EncodeError, Varint};
+use std::ops::RangeInclusive;
 
 /// Used in the tag to identify how a field is encoded in Protocol Buffers.
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub enum WireType {
     /// Wire type is used to store values using LEB128
     Varint = 0,
@@ -22,6 +23,38 @@ pub enum WireType {
     Fixed32 = 5,
 }
 
+impl WireType {
+    /// Returns the number of bytes of the payload for wire types with a fixed size.
+    ///
+    /// ```
+    /// # use ks_protobuf_v3::*;
+    /// assert_eq!(WireType::Fixed32.fixed_size(), Some(4));
+    /// assert_eq!(WireType::Varint.fixed_size(), None);
+    /// ```
+    pub const fn fixed_size(self) -> Option<usize> {
+        match self {
+            WireType::Fixed32 => Some(4),
+            WireType::Fixed64 => Some(8),
+            WireType::EndGroup => Some(0),
+            WireType::Varint | WireType::LengthDelimited | WireType::StartGroup => None,
+        }
+    }
+
+    /// Returns whether values of the wire type can be packed into a length delimited field.
+    pub const fn is_packable(self) -> bool {
+        matches!(
+            self,
+            WireType::Varint | WireType::Fixed32 | WireType::Fixed64
+        )
+    }
+}
+
+impl From<WireType> for u32 {
+    fn from(value: WireType) -> Self {
+        value as u32
+    }
+}
+
 impl TryFrom<u32> for WireType {
     type Error = DecodeError;
 
@@ -39,36 +72,189 @@ impl TryFrom<u32> for WireType {
 }
 
 /// This structure is used to represent a tag
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct Tag {
     field_number: u32,
     wire_type: WireType,
 }
 
 impl Tag {
+    /// The smallest field number a field can have.
+    pub const MIN_FIELD_NUMBER: u32 = 1;
+
+    /// The largest field number a field can have, as the tag keeps three bits for the wire type.
+    pub const MAX_FIELD_NUMBER: u32 = (1 << 29) - 1;
+
+    /// The field numbers reserved for the Protocol Buffers implementation.
+    pub const RESERVED_FIELD_NUMBERS: RangeInclusive<u32> = 19000..=19999;
+
     /// Function to allocate a tag
-    pub fn new(field_number: u32, wire_type: WireType) -> Self {
+    ///
+    /// The field number is given as a constant and checked when compiling, so that one which can
+    /// not be used by a field fails to compile rather than overflowing the key. Use
+    /// [`Tag::try_new`] for field numbers which are only known at run time.
+    ///
+    /// ```compile_fail
+    /// # use ks_protobuf_v3::*;
+    /// let tag = Tag::new::<536870912>(WireType::Varint);
+    /// ```
+    pub const fn new<const FIELD_NUMBER: u32>(wire_type: WireType) -> Self {
+        const {
+            assert!(
+                Tag::validate_field_number(FIELD_NUMBER).is_ok(),
+                "the field number can not be used by a field"
+            );
+        }
+        Self::new_unchecked(FIELD_NUMBER, wire_type)
+    }
+
+    /// Allocates a tag after checking that the field number can be used by a field.
+    ///
+    /// ```
+    /// # use ks_protobuf_v3::*;
+    /// assert!(Tag::try_new(1, WireType::Varint).is_ok());
+    /// assert_eq!(
+    ///     Tag::try_new(19000, WireType::Varint).unwrap_err(),
+    ///     EncodeError::ReservedFieldNumber(19000)
+    /// );
+    /// ```
+    pub const fn try_new(field_number: u32, wire_type: WireType) -> Result<Self, EncodeError> {
+        match Self::validate_field_number(field_number) {
+            Ok(()) => Ok(Self::new_unchecked(field_number, wire_type)),
+            Err(error) => Err(error),
+        }
+    }
+
+    // Allocates a tag for a field number which is known to fit, such as one that was decoded or
+    // that comes from a validated descriptor, or 0 for a tag that is about to be decoded.
+    pub(crate) const fn new_unchecked(field_number: u32, wire_type: WireType) -> Self {
         Self {
             field_number,
             wire_type,
         }
     }
+
+    /// Checks that the field number is in range and not reserved.
+    pub const fn validate_field_number(field_number: u32) -> Result<(), EncodeError> {
+        if field_number < Self::MIN_FIELD_NUMBER || field_number > Self::MAX_FIELD_NUMBER {
+            return Err(EncodeError::FieldNumberOutOfRange(field_number));
+        }
+        if field_number >= *Self::RESERVED_FIELD_NUMBERS.start()
+            && field_number <= *Self::RESERVED_FIELD_NUMBERS.end()
+        {
+            return Err(EncodeError::ReservedFieldNumber(field_number));
+        }
+        Ok(())
+    }
+
+    /// Returns the field number identified by the tag.
+    pub const fn field_number(&self) -> u32 {
+        self.field_number
+    }
+
+    /// Returns the wire type used to encode the field identified by the tag.
+    pub const fn wire_type(&self) -> WireType {
+        self.wire_type
+    }
+
+    /// Returns the key, which combines the field number and wire type, written before a field.
+    pub const fn key(&self) -> u32 {
+        (self.field_number << 3) | (self.wire_type as u32)
+    }
+
+    /// Returns the number of bytes used to encode the tag.
+    pub const fn encoded_len(&self) -> usize {
+        match self.key() {
+            0..=0x7F => 1,
+            0x80..=0x3FFF => 2,
+            0x4000..=0x1F_FFFF => 3,
+            0x20_0000..=0xFFF_FFFF => 4,
+            _ => 5,
+        }
+    }
+
+    /// Encodes the tag ahead of time, so that hot paths only need to copy its bytes.
+    ///
+    /// ```
+    /// # use ks_protobuf_v3::*;
+    /// const NAME: EncodedTag = Tag::new::<1000>(WireType::LengthDelimited).encoded();
+    ///
+    /// let mut buffer = Buffer::default();
+    /// let size = NAME.encode(&mut buffer);
+    /// assert_eq!(buffer.to_vec(), vec![194, 62]);
+    /// assert_eq!(size, 2);
+    /// ```
+    pub const fn encoded(&self) -> EncodedTag {
+        let mut bytes = [0; 5];
+        let mut key = self.key();
+        let mut len = 0;
+        while key > 0x7F {
+            bytes[len] = (key & 0x7F) as u8 | 0x80;
+            key >>= 7;
+            len += 1;
+        }
+        bytes[len] = key as u8;
+
+        EncodedTag {
+            bytes,
+            len: len as u8 + 1,
+        }
+    }
+}
+
+impl From<Tag> for u32 {
+    fn from(value: Tag) -> Self {
+        value.key()
+    }
+}
+
+impl TryFrom<u32> for Tag {
+    type Error = DecodeError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        if value >> 3 == 0 {
+            return Err(DecodeError::InvalidFieldNumber);
+        }
+
+        Ok(Self::new_unchecked(
+            value >> 3,
+            WireType::try_from(value & 0x0007)?,
+        ))
+    }
+}
+
+/// A tag which has already been encoded, see [`Tag::encoded`].
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct EncodedTag {
+    bytes: [u8; 5],
+    len: u8,
+}
+
+impl EncodedTag {
+    /// Returns the encoded bytes of the tag.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// Writes the tag to the buffer and returns the number of bytes written.
+    pub fn encode(&self, buffer: &mut Buffer) -> usize {
+        buffer.put_slice(self.as_bytes());
+        self.len as usize
+    }
 }
 
 impl Varint for Tag {
     fn to_varint(&self, buffer: &mut Buffer) -> usize {
-        let tag = (self.field_number << 3) | (self.wire_type as u32);
-
-        encode_varint32(tag, buffer)
+        encode_varint32(self.key(), buffer)
     }
 
+    // Unlike 32 bit values, a tag is never truncated, as its field number would change.
     fn from_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
-        let mut tag: u32 = 0;
-
-        let len = tag.from_varint(buffer)?;
+        let (tag, len) = decode_varint64(buffer)?.ok_or(DecodeError::BufferOverrun)?;
+        let tag = u32::try_from(tag)
+            .map_err(|_| DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)?;
 
-        self.field_number = tag >> 3;
-        self.wire_type = WireType::try_from(tag & 0x0007)?;
+        *self = Tag::try_from(tag)?;
         Ok(len)
     }
 }
@@ -90,7 +276,7 @@ mod tests {
     ) {
         // Arrange
         let mut buffer = Buffer::default();
-        let tag = Tag::new(field_number, wire_type);
+        let tag = Tag::try_new(field_number, wire_type).unwrap();
 
         // Act
         let size = tag.to_varint(&mut buffer);
@@ -111,7 +297,7 @@ mod tests {
         #[case] expected_wire_type: WireType,
     ) {
         // Arrange
-        let mut tag = Tag::new(0, WireType::Varint);
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
 
         // Act
         let result = tag.from_varint(&buffer).unwrap();
@@ -121,4 +307,75 @@ mod tests {
         assert_eq!(tag.wire_type, expected_wire_type);
         assert_eq!(result, buffer.len());
     }
+
+    #[rstest]
+    #[case(1, WireType::Varint, vec![8])]
+    #[case(2, WireType::Fixed32, vec![21])]
+    #[case(1000, WireType::LengthDelimited, vec![194, 62])]
+    #[case(100000, WireType::Fixed64, vec![129, 234, 48])]
+    #[case(536870911, WireType::EndGroup, vec![252, 255, 255, 255, 15])]
+    fn test_encoded_tags_match_varints(
+        #[case] field_number: u32,
+        #[case] wire_type: WireType,
+        #[case] expected_bytes: Vec<u8>,
+    ) {
+        // Arrange
+        let tag = Tag::try_new(field_number, wire_type).unwrap();
+
+        // Act
+        let encoded = tag.encoded();
+
+        // Assert
+        assert_eq!(encoded.as_bytes(), expected_bytes);
+        assert_eq!(tag.encoded_len(), expected_bytes.len());
+    }
+
+    #[test]
+    fn test_tags_convert_to_and_from_keys() {
+        // Arrange
+        let tag = Tag::new::<1000>(WireType::LengthDelimited);
+
+        // Act
+        let key = u32::from(tag);
+        let converted = Tag::try_from(key).unwrap();
+
+        // Assert
+        assert_eq!(key, 8002);
+        assert_eq!(converted, tag);
+    }
+
+    #[rstest]
+    #[case(vec![0], DecodeError::InvalidFieldNumber)]
+    #[case(vec![2], DecodeError::InvalidFieldNumber)]
+    #[case(vec![14], DecodeError::UnknownWireType)]
+    fn test_invalid_tag_decoding(#[case] buffer: Vec<u8>, #[case] expected_error: DecodeError) {
+        // Arrange
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
+
+        // Act
+        let error = tag.from_varint(&buffer).unwrap_err();
+
+        // Assert
+        assert_eq!(error, expected_error);
+    }
+
+    #[rstest]
+    #[case(1, Ok(()))]
+    #[case(18999, Ok(()))]
+    #[case(20000, Ok(()))]
+    #[case(536870911, Ok(()))]
+    #[case(0, Err(EncodeError::FieldNumberOutOfRange(0)))]
+    #[case(19000, Err(EncodeError::ReservedFieldNumber(19000)))]
+    #[case(19999, Err(EncodeError::ReservedFieldNumber(19999)))]
+    #[case(536870912, Err(EncodeError::FieldNumberOutOfRange(536870912)))]
+    fn test_field_numbers_are_validated(
+        #[case] field_number: u32,
+        #[case] expected_result: Result<(), EncodeError>,
+    ) {
+        // Act
+        let result = Tag::try_new(field_number, WireType::Varint).map(|_| ());
+
+        // Assert
+        assert_eq!(result, expected_result);
+    }
 }
@@ -0,0 +1,725 @@
+use crate::proto_parser::{tokenize, OptionValue, ParseError, Span, Token, TokenKind};
+use crate::{
+    DynamicMessage, Enumeration, FieldDescriptor, FieldMapKey, FieldType, FieldValue,
+    MessageDescriptor, TextFormatError,
+};
+use std::borrow::Cow;
+use std::fmt::Write;
+
+impl DynamicMessage {
+    /// Parses a message of the given type from the text format, e.g. `name: "Ada" tags: [1, 2]`.
+    /// Any messages may be written in their expanded form, with the type URL in brackets.
+    pub fn parse_text(descriptor: MessageDescriptor, text: &str) -> Result<Self, TextFormatError> {
+        let mut message = Self::new(descriptor);
+        merge_text(&mut message, text).map_err(|e| TextFormatError::Syntax {
+            line: e.line + 1,
+            column: e.column + 1,
+            message: e.message,
+        })?;
+        Ok(message)
+    }
+
+    /// Returns the fields that are set in the text format, one per line with nested messages
+    /// indented, as `protoc --decode` prints them. Unknown fields are left out.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        write_message(&mut text, self, 0);
+        text
+    }
+}
+
+/// Merges fields written in the text format, such as the aggregate value of a custom option,
+/// into the message.
+pub(crate) fn merge_text(message: &mut DynamicMessage, text: &str) -> Result<(), ParseError> {
+    TextParser {
+        tokens: tokenize(text)?,
+        position: 0,
+    }
+    .parse_fields(message, None)
+}
+
+/// Converts a scalar value, as written in a `.proto` file or in the text format, to a value of
+/// the field. When the value does not fit the field, a description of the values it accepts is
+/// returned instead.
+pub(crate) fn scalar_value(
+    field: &FieldDescriptor,
+    value: &OptionValue,
+) -> Result<FieldValue, String> {
+    let converted = match (field.field_type(), value) {
+        (
+            FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32,
+            OptionValue::PositiveInt(v),
+        ) => i32::try_from(*v).ok().map(FieldValue::I32),
+        (
+            FieldType::Int32 | FieldType::Sint32 | FieldType::Sfixed32,
+            OptionValue::NegativeInt(v),
+        ) => i32::try_from(*v).ok().map(FieldValue::I32),
+        (
+            FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64,
+            OptionValue::PositiveInt(v),
+        ) => i64::try_from(*v).ok().map(FieldValue::I64),
+        (
+            FieldType::Int64 | FieldType::Sint64 | FieldType::Sfixed64,
+            OptionValue::NegativeInt(v),
+        ) => Some(FieldValue::I64(*v)),
+        (FieldType::Uint32 | FieldType::Fixed32, OptionValue::PositiveInt(v)) => {
+            u32::try_from(*v).ok().map(FieldValue::U32)
+        }
+        (FieldType::Uint64 | FieldType::Fixed64, OptionValue::PositiveInt(v)) => {
+            Some(FieldValue::U64(*v))
+        }
+        (FieldType::Double, _) => float_value(value).map(FieldValue::F64),
+        (FieldType::Float, _) => float_value(value).map(|v| FieldValue::F32(v as f32)),
+        (FieldType::Bool, OptionValue::Identifier(v)) => match v.as_str() {
+            "true" | "True" | "t" => Some(FieldValue::Bool(true)),
+            "false" | "False" | "f" => Some(FieldValue::Bool(false)),
+            _ => None,
+        },
+        (FieldType::Bool, OptionValue::PositiveInt(v @ 0..=1)) => Some(FieldValue::Bool(*v == 1)),
+        (FieldType::String, OptionValue::String(v)) => {
+            String::from_utf8(v.clone()).ok().map(FieldValue::String)
+        }
+        (FieldType::Bytes, OptionValue::String(v)) => Some(FieldValue::Bytes(v.clone())),
+        (FieldType::Enum, OptionValue::Identifier(v)) => field
+            .enum_type()
+            .and_then(|e| e.get_value_by_name(v))
+            .map(|v| FieldValue::EnumNumber(v.number())),
+        (FieldType::Enum, OptionValue::PositiveInt(v)) => {
+            i32::try_from(*v).ok().map(FieldValue::EnumNumber)
+        }
+        (FieldType::Enum, OptionValue::NegativeInt(v)) => {
+            i32::try_from(*v).ok().map(FieldValue::EnumNumber)
+        }
+        _ => None,
+    };
+
+    converted.ok_or_else(|| match field.field_type() {
+        FieldType::Bool => "true or false".to_string(),
+        FieldType::String => "a UTF-8 string".to_string(),
+        FieldType::Bytes => "a string".to_string(),
+        FieldType::Enum => match field.enum_type() {
+            Some(enumeration) => format!("a value of {}", enumeration.full_name()),
+            None => "an enum value".to_string(),
+        },
+        FieldType::Message | FieldType::Group => "a message".to_string(),
+        field_type => format!(
+            "a number that fits in {}",
+            field_type.name().trim_start_matches("TYPE_").to_lowercase()
+        ),
+    })
+}
+
+/// Adds a value to a field of the message. Repeated fields have the value appended, map fields
+/// have the entry the value holds inserted and other fields can only be set once.
+pub(crate) fn add_value(
+    message: &mut DynamicMessage,
+    field: &FieldDescriptor,
+    value: FieldValue,
+) -> Result<(), String> {
+    if field.is_map() {
+        let entry = match value {
+            FieldValue::Message(entry) => entry,
+            _ => {
+                return Err(format!(
+                    "The value of {} must be a map entry.",
+                    field.full_name()
+                ))
+            }
+        };
+        let key = entry.get_field_by_number(1).map(Cow::into_owned);
+        let key = key.and_then(|key| FieldMapKey::try_from(key).ok());
+        let value = entry.get_field_by_number(2).map(Cow::into_owned);
+
+        return match (key, value, message.get_field_mut(field)) {
+            (Some(key), Some(value), FieldValue::Map(entries)) => {
+                let _ = entries.insert(key, value);
+                Ok(())
+            }
+            _ => Err(format!("The entry of {} is invalid.", field.full_name())),
+        };
+    }
+
+    if field.is_repeated() {
+        if let FieldValue::List(values) = message.get_field_mut(field) {
+            values.push(value);
+        }
+        return Ok(());
+    }
+
+    if message.has_field(field) {
+        return Err(format!(
+            "The field {} is set more than once.",
+            field.full_name()
+        ));
+    }
+    message.set_field(field, value).map_err(|e| e.to_string())
+}
+
+// Escapes the bytes as they would be written in a `.proto` string. Valid UTF-8 is kept, while the
+// other bytes are written in octal.
+pub(crate) fn escape(bytes: &[u8]) -> String {
+    let mut text = String::new();
+
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            match c {
+                '\n' => text.push_str("\\n"),
+                '\r' => text.push_str("\\r"),
+                '\t' => text.push_str("\\t"),
+                '"' => text.push_str("\\\""),
+                '\'' => text.push_str("\\'"),
+                '\\' => text.push_str("\\\\"),
+                c if c.is_control() => {
+                    for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+                        let _ = write!(text, "\\{:03o}", byte);
+                    }
+                }
+                c => text.push(c),
+            }
+        }
+        for byte in chunk.invalid() {
+            let _ = write!(text, "\\{:03o}", byte);
+        }
+    }
+
+    text
+}
+
+fn write_message(text: &mut String, message: &DynamicMessage, indent: usize) {
+    if let Some((type_url, contents)) = expand_any(message) {
+        let _ = writeln!(text, "{:indent$}[{}] {{", "", type_url, indent = indent);
+        write_message(text, &contents, indent + 2);
+        let _ = writeln!(text, "{:indent$}}}", "", indent = indent);
+        return;
+    }
+
+    for (field, value) in message.fields() {
+        let name = match (field.is_extension(), field.field_type()) {
+            (true, _) => format!("[{}]", field.full_name()),
+            // Groups are written with the name of their message type, which delimited message
+            // fields only share when they are named after it.
+            (false, FieldType::Group) => match field.message_type() {
+                Some(m) if m.name().to_ascii_lowercase() == field.name() => m.name().to_string(),
+                _ => field.name().to_string(),
+            },
+            (false, _) => field.name().to_string(),
+        };
+
+        match value {
+            FieldValue::Map(entries) => {
+                let entry = field.message_type();
+                let key = entry.as_ref().and_then(|e| e.get_field(1));
+                let value = entry.as_ref().and_then(|e| e.get_field(2));
+                let (key, value) = match (key, value) {
+                    (Some(key), Some(value)) => (key, value),
+                    _ => continue,
+                };
+
+                for (k, v) in entries {
+                    let _ = writeln!(text, "{:indent$}{} {{", "", name, indent = indent);
+                    write_value(text, "key", &key, &FieldValue::from(k.clone()), indent + 2);
+                    write_value(text, "value", &value, v, indent + 2);
+                    let _ = writeln!(text, "{:indent$}}}", "", indent = indent);
+                }
+            }
+            FieldValue::List(values) => {
+                for value in values {
+                    write_value(text, &name, &field, value, indent);
+                }
+            }
+            value => write_value(text, &name, &field, value, indent),
+        }
+    }
+}
+
+fn write_value(
+    text: &mut String,
+    name: &str,
+    field: &FieldDescriptor,
+    value: &FieldValue,
+    indent: usize,
+) {
+    let _ = write!(text, "{:indent$}{}", "", name, indent = indent);
+
+    let _ = match value {
+        FieldValue::Message(message) => {
+            text.push_str(" {\n");
+            write_message(text, message, indent + 2);
+            writeln!(text, "{:indent$}}}", "", indent = indent)
+        }
+        FieldValue::Bool(v) => writeln!(text, ": {}", v),
+        FieldValue::I32(v) => writeln!(text, ": {}", v),
+        FieldValue::I64(v) => writeln!(text, ": {}", v),
+        FieldValue::U32(v) => writeln!(text, ": {}", v),
+        FieldValue::U64(v) => writeln!(text, ": {}", v),
+        FieldValue::F32(v) => writeln!(text, ": {}", float_text(*v as f64, v.to_string())),
+        FieldValue::F64(v) => writeln!(text, ": {}", float_text(*v, v.to_string())),
+        FieldValue::String(v) => writeln!(text, ": \"{}\"", escape(v.as_bytes())),
+        FieldValue::Bytes(v) => writeln!(text, ": \"{}\"", escape(v)),
+        FieldValue::EnumNumber(v) => match field.enum_type().and_then(|e| e.get_value(*v)) {
+            Some(value) => writeln!(text, ": {}", value.name()),
+            None => writeln!(text, ": {}", v),
+        },
+        FieldValue::List(_) | FieldValue::Map(_) => writeln!(text, ": []"),
+    };
+}
+
+// Writes a float in the form the parser reads back, using an exponent for very large and very
+// small values rather than writing out every digit.
+fn float_text(value: f64, shortest: String) -> String {
+    match value {
+        v if v.is_nan() => "nan".to_string(),
+        v if v.is_infinite() && v > 0.0 => "inf".to_string(),
+        v if v.is_infinite() => "-inf".to_string(),
+        v if v != 0.0 && !(1e-4..1e16).contains(&v.abs()) => format!("{:e}", v),
+        _ => shortest,
+    }
+}
+
+// Returns the type URL and the decoded contents of an Any message when its type is known to the
+// pool, so that it can be written in its expanded form.
+fn expand_any(message: &DynamicMessage) -> Option<(String, DynamicMessage)> {
+    let descriptor = message.descriptor();
+    if descriptor.full_name() != "google.protobuf.Any" {
+        return None;
+    }
+
+    let type_url = message.get_field_by_number(1)?.as_str()?.to_string();
+    let value = message.get_field_by_number(2)?.as_bytes()?.to_vec();
+    let type_name = type_url.rsplit_once('/')?.1;
+    let contents_type = descriptor.parent_pool().get_message_by_name(type_name)?;
+    let contents = DynamicMessage::decode(contents_type, &value).ok()?;
+    Some((type_url, contents))
+}
+
+fn float_value(value: &OptionValue) -> Option<f64> {
+    match value {
+        OptionValue::PositiveInt(v) => Some(*v as f64),
+        OptionValue::NegativeInt(v) => Some(*v as f64),
+        OptionValue::Float(v) => Some(*v),
+        OptionValue::Identifier(v) => match v.to_ascii_lowercase().as_str() {
+            "inf" | "infinity" => Some(f64::INFINITY),
+            "nan" => Some(f64::NAN),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+struct TextParser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl TextParser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.position]
+    }
+
+    fn advance(&mut self) -> Token {
+        let token = self.tokens[self.position].clone();
+        if token.kind != TokenKind::End {
+            self.position += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError::new(self.peek().span, message))
+    }
+
+    fn accept_symbol(&mut self, symbol: char) -> bool {
+        match self.peek().kind == TokenKind::Symbol(symbol) {
+            true => {
+                self.position += 1;
+                true
+            }
+            false => false,
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: char) -> Result<(), ParseError> {
+        match self.accept_symbol(symbol) {
+            true => Ok(()),
+            false => self.error(format!(
+                "Expected \"{}\" but found \"{}\".",
+                symbol,
+                self.peek().text
+            )),
+        }
+    }
+
+    fn expect_identifier(&mut self) -> Result<String, ParseError> {
+        match &self.peek().kind {
+            TokenKind::Identifier(identifier) => {
+                let identifier = identifier.clone();
+                self.position += 1;
+                Ok(identifier)
+            }
+            _ => self.error(format!(
+                "Expected a field name but found \"{}\".",
+                self.peek().text
+            )),
+        }
+    }
+
+    fn expect_dotted_name(&mut self) -> Result<String, ParseError> {
+        let mut name = self.expect_identifier()?;
+        while self.accept_symbol('.') {
+            name.push('.');
+            name.push_str(&self.expect_identifier()?);
+        }
+        Ok(name)
+    }
+
+    // Accepts the opening brace of a message, returning the symbol that closes it.
+    fn expect_message_start(&mut self) -> Result<char, ParseError> {
+        if self.accept_symbol('{') {
+            Ok('}')
+        } else if self.accept_symbol('<') {
+            Ok('>')
+        } else {
+            self.error(format!(
+                "Expected \"{{\" but found \"{}\".",
+                self.peek().text
+            ))
+        }
+    }
+
+    // Parses the expanded form of an Any message, whose contents are written as a message of the
+    // type named by the URL.
+    fn parse_any(
+        &mut self,
+        message: &mut DynamicMessage,
+        start: Span,
+        type_url: String,
+    ) -> Result<(), ParseError> {
+        let descriptor = message.descriptor().clone();
+        let type_name = type_url.rsplit_once('/').map_or("", |(_, name)| name);
+        let contents_type = descriptor
+            .parent_pool()
+            .get_message_by_name(type_name)
+            .filter(|_| descriptor.full_name() == "google.protobuf.Any")
+            .ok_or_else(|| {
+                ParseError::new(
+                    start,
+                    format!(
+                        "The type {} can not be expanded in {}.",
+                        type_url,
+                        descriptor.full_name()
+                    ),
+                )
+            })?;
+
+        let _ = self.accept_symbol(':');
+        let end = self.expect_message_start()?;
+        let mut contents = DynamicMessage::new(contents_type);
+        self.parse_fields(&mut contents, Some(end))?;
+
+        let values = [
+            FieldValue::String(type_url),
+            FieldValue::Bytes(contents.encode_to_vec()),
+        ];
+        for (field, value) in descriptor.fields().zip(values) {
+            add_value(message, &field, value).map_err(|m| ParseError::new(start, m))?;
+        }
+        Ok(())
+    }
+
+    // Parses fields until the closing symbol, or until the end of the text when there is none.
+    fn parse_fields(
+        &mut self,
+        message: &mut DynamicMessage,
+        end: Option<char>,
+    ) -> Result<(), ParseError> {
+        loop {
+            match (&self.peek().kind, end) {
+                (TokenKind::End, None) => return Ok(()),
+                (TokenKind::End, Some(_)) => return self.error("The message is not terminated."),
+                (TokenKind::Symbol(symbol), Some(end)) if *symbol == end => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+
+            self.parse_field(message)?;
+            if !self.accept_symbol(',') {
+                let _ = self.accept_symbol(';');
+            }
+        }
+    }
+
+    fn parse_field(&mut self, message: &mut DynamicMessage) -> Result<(), ParseError> {
+        let start = self.peek().span;
+        let descriptor = message.descriptor().clone();
+
+        let field = if self.accept_symbol('[') {
+            let name = self.expect_dotted_name()?;
+            if self.accept_symbol('/') {
+                let type_name = self.expect_dotted_name()?;
+                self.expect_symbol(']')?;
+                return self.parse_any(message, start, format!("{}/{}", name, type_name));
+            }
+            self.expect_symbol(']')?;
+
+            descriptor
+                .parent_pool()
+                .get_extension_by_name(&name)
+                .filter(|extension| extension.containing_message() == &descriptor)
+                .ok_or_else(|| {
+                    ParseError::new(
+                        start,
+                        format!(
+                            "The extension {} of {} is not known.",
+                            name,
+                            descriptor.full_name()
+                        ),
+                    )
+                })?
+        } else {
+            // Groups are written with the name of their message type.
+            let name = self.expect_identifier()?;
+            descriptor
+                .get_field_by_name(&name)
+                .or_else(|| {
+                    descriptor.fields().find(|f| {
+                        f.field_type() == FieldType::Group
+                            && f.message_type().is_some_and(|m| m.name() == name)
+                    })
+                })
+                .ok_or_else(|| {
+                    ParseError::new(
+                        start,
+                        format!(
+                            "The field {} of {} is not known.",
+                            name,
+                            descriptor.full_name()
+                        ),
+                    )
+                })?
+        };
+
+        let is_message = matches!(field.field_type(), FieldType::Message | FieldType::Group);
+        if !self.accept_symbol(':') && !is_message {
+            return self.error(format!(
+                "Expected \":\" but found \"{}\".",
+                self.peek().text
+            ));
+        }
+
+        let mut values = Vec::new();
+        if field.is_repeated() && self.accept_symbol('[') {
+            while !self.accept_symbol(']') {
+                if !values.is_empty() {
+                    self.expect_symbol(',')?;
+                }
+                values.push(self.parse_value(&field)?);
+            }
+        } else {
+            values.push(self.parse_value(&field)?);
+        }
+
+        for value in values {
+            add_value(message, &field, value).map_err(|m| ParseError::new(start, m))?;
+        }
+        Ok(())
+    }
+
+    fn parse_value(&mut self, field: &FieldDescriptor) -> Result<FieldValue, ParseError> {
+        let start = self.peek().span;
+
+        if let Some(message_type) = field.message_type() {
+            let end = self.expect_message_start()?;
+            let mut message = DynamicMessage::new(message_type);
+            self.parse_fields(&mut message, Some(end))?;
+            return Ok(FieldValue::Message(message));
+        }
+
+        let value = self.parse_scalar()?;
+        scalar_value(field, &value).map_err(|expected| {
+            ParseError::new(
+                start,
+                format!(
+                    "The value of the field {} must be {}.",
+                    field.full_name(),
+                    expected
+                ),
+            )
+        })
+    }
+
+    fn parse_scalar(&mut self) -> Result<OptionValue, ParseError> {
+        let negative = self.accept_symbol('-');
+        let token = self.advance();
+
+        match token.kind {
+            TokenKind::Integer(value) if negative => Ok(match value {
+                0..=0x8000_0000_0000_0000 => {
+                    OptionValue::NegativeInt((value as i64).wrapping_neg())
+                }
+                _ => OptionValue::Float(-(value as f64)),
+            }),
+            TokenKind::Integer(value) => Ok(OptionValue::PositiveInt(value)),
+            TokenKind::Float(value) if negative => Ok(OptionValue::Float(-value)),
+            TokenKind::Float(value) => Ok(OptionValue::Float(value)),
+            TokenKind::Identifier(identifier) if negative => {
+                match float_value(&OptionValue::Identifier(identifier)) {
+                    Some(value) => Ok(OptionValue::Float(-value)),
+                    None => Err(ParseError::new(
+                        token.span,
+                        format!(
+                            "Expected a number after \"-\" but found \"{}\".",
+                            token.text
+                        ),
+                    )),
+                }
+            }
+            TokenKind::Identifier(identifier) => Ok(OptionValue::Identifier(identifier)),
+            // Adjacent strings are joined together.
+            TokenKind::String(mut value) if !negative => {
+                while let TokenKind::String(more) = &self.peek().kind {
+                    value.extend_from_slice(more);
+                    self.position += 1;
+                }
+                Ok(OptionValue::String(value))
+            }
+            _ => Err(ParseError::new(
+                token.span,
+                format!("Expected a value but found \"{}\".", token.text),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DescriptorPool, ProtoCompiler};
+    use rstest::*;
+
+    const SOURCE: &str = r#"syntax = "proto2";
+package test;
+import "google/protobuf/any.proto";
+
+enum Colour { RED = 0; GREEN = 1; }
+message Item {
+  optional string name = 1;
+  repeated int32 counts = 2;
+  optional Colour colour = 3;
+  optional bytes data = 4;
+  optional double ratio = 5;
+  optional float scale = 6;
+  map<string, Item> children = 7;
+  optional Item extra = 8;
+  optional google.protobuf.Any any = 10;
+  extensions 100 to 200;
+}
+extend Item { optional uint64 size = 100; }
+"#;
+
+    fn item() -> MessageDescriptor {
+        let mut compiler = ProtoCompiler::new();
+        compiler.include_imports(true);
+        compiler.add_source("test.proto", SOURCE);
+        let pool =
+            DescriptorPool::from_file_descriptor_set(compiler.compile(&["test.proto"]).unwrap())
+                .unwrap();
+        pool.get_message_by_name("test.Item").unwrap()
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        // Arrange
+        let text = r#"name: "a\n\"b\"" counts: [1, -2] counts: 3 colour: GREEN
+            data: "\377\001" ratio: 1e300 scale: -inf
+            children { key: "x" value < name: 'y' > }
+            extra { counts: 4 }
+            any { [type.googleapis.com/test.Item] { name: "inner" } }
+            [test.size]: 18446744073709551615"#;
+
+        let item = item();
+
+        // Act
+        let message = DynamicMessage::parse_text(item.clone(), text).unwrap();
+        let printed = message.to_text();
+
+        // Assert
+        assert_eq!(
+            printed,
+            r#"name: "a\n\"b\""
+counts: 1
+counts: -2
+counts: 3
+colour: GREEN
+data: "\377\001"
+ratio: 1e300
+scale: -inf
+children {
+  key: "x"
+  value {
+    name: "y"
+  }
+}
+extra {
+  counts: 4
+}
+any {
+  [type.googleapis.com/test.Item] {
+    name: "inner"
+  }
+}
+[test.size]: 18446744073709551615
+"#
+        );
+        assert_eq!(DynamicMessage::parse_text(item, &printed).unwrap(), message);
+    }
+
+    #[rstest]
+    #[case(
+        "name: 1",
+        1,
+        7,
+        "The value of the field test.Item.name must be a UTF-8 string."
+    )]
+    #[case(
+        "colour: BLUE",
+        1,
+        9,
+        "The value of the field test.Item.colour must be a value of test.Colour."
+    )]
+    #[case(
+        "name: \"a\"\nname: \"b\"",
+        2,
+        1,
+        "The field test.Item.name is set more than once."
+    )]
+    #[case("missing: 1", 1, 1, "The field missing of test.Item is not known.")]
+    #[case("extra { counts: 4", 1, 18, "The message is not terminated.")]
+    #[case("name { }", 1, 6, "Expected \":\" but found \"{\".")]
+    #[case(
+        "any { [type.googleapis.com/test.Missing] {} }",
+        1,
+        7,
+        "The type type.googleapis.com/test.Missing can not be expanded in google.protobuf.Any."
+    )]
+    fn test_text_errors(
+        #[case] text: &str,
+        #[case] expected_line: usize,
+        #[case] expected_column: usize,
+        #[case] expected_message: &str,
+    ) {
+        // Act
+        let error = DynamicMessage::parse_text(item(), text).unwrap_err();
+
+        // Assert
+        assert_eq!(
+            error,
+            TextFormatError::Syntax {
+                line: expected_line,
+                column: expected_column,
+                message: expected_message.to_string()
+            }
+        );
+    }
+}
@@ -1,8 +1,9 @@
 use crate::{
     decode_fixed32, decode_fixed64, decode_varint32, decode_varint64, decode_zigzag32,
     decode_zigzag64, encode_fixed32, encode_fixed64, encode_varint32, encode_varint64,
-    encode_zigzag32, encode_zigzag64, Buffer, DecodeError, Fixed32, Fixed32Field, Fixed64,
-    Fixed64Field, LengthDelimited, LengthDelimitedField, Varint, VarintField,
+    encode_zigzag32, encode_zigzag64, Buffer, DecodeError, DecodeMode, Fixed32, Fixed32Field,
+    Fixed64, Fixed64Field, Group, GroupField, LengthDelimited, LengthDelimitedField, Presence,
+    SignExtendedVarint, SignExtendedVarintField, Varint, VarintField,
 };
 
 impl Varint for bool {
@@ -10,25 +11,17 @@ impl Varint for bool {
         encode_varint32(if *self { 1 } else { 0 }, buffer)
     }
 
+    // Any non-zero value is true, as in the reference implementation, unless decoding is strict.
     fn from_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
-        let result = decode_varint32(&buffer.to_vec()[..]);
-
-        match result {
-            Ok(value) => match value {
-                Some((v, s)) => match v {
-                    0 => {
-                        *self = false;
-                        Ok(s)
-                    }
-                    1 => {
-                        *self = true;
-                        Ok(s)
-                    }
-                    _ => Err(DecodeError::UnableToDecode),
-                },
-                None => Err(DecodeError::UnableToDecode),
-            },
-            Err(e) => Err(e),
+        match decode_varint64(buffer)? {
+            Some((v, _)) if v > 1 && DecodeMode::is_strict() => {
+                Err(DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)
+            }
+            Some((v, s)) => {
+                *self = v != 0;
+                Ok(s)
+            }
+            None => Err(DecodeError::UnableToDecode),
         }
     }
 }
@@ -233,6 +226,56 @@ impl VarintField for i16 {}
 impl VarintField for i32 {}
 impl VarintField for i64 {}
 
+impl SignExtendedVarint for i32 {
+    fn to_sign_extended_varint(&self, buffer: &mut Buffer) -> usize {
+        encode_varint64(*self as i64 as u64, buffer)
+    }
+
+    // Values are truncated to 32 bits, as a negative value will have been sign extended to 64 bits.
+    // Strict decoding rejects values which are not the sign extension of a 32 bit value.
+    fn from_sign_extended_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        let result = decode_varint64(buffer);
+
+        match result {
+            Ok(value) => match value {
+                Some((v, _)) if v as i32 as i64 as u64 != v && DecodeMode::is_strict() => {
+                    Err(DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)
+                }
+                Some((v, s)) => {
+                    *self = v as i32;
+                    Ok(s)
+                }
+                None => Err(DecodeError::UnableToDecode),
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl SignExtendedVarint for i64 {
+    fn to_sign_extended_varint(&self, buffer: &mut Buffer) -> usize {
+        encode_varint64(*self as u64, buffer)
+    }
+
+    fn from_sign_extended_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        let result = decode_varint64(buffer);
+
+        match result {
+            Ok(value) => match value {
+                Some((v, s)) => {
+                    *self = v as i64;
+                    Ok(s)
+                }
+                None => Err(DecodeError::UnableToDecode),
+            },
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl SignExtendedVarintField for i32 {}
+impl SignExtendedVarintField for i64 {}
+
 impl Fixed32 for u32 {
     fn to_fixed32(&self, buffer: &mut Buffer) -> usize {
         encode_fixed32(*self, buffer)
@@ -277,7 +320,7 @@ impl Fixed32 for i32 {
 
 impl Fixed32 for f32 {
     fn to_fixed32(&self, buffer: &mut Buffer) -> usize {
-        encode_fixed32(*self as u32, buffer)
+        encode_fixed32(self.to_bits(), buffer)
     }
 
     fn from_fixed32(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
@@ -286,7 +329,7 @@ impl Fixed32 for f32 {
         match result {
             Ok(value) => match value {
                 Some((v, s)) => {
-                    *self = v as f32;
+                    *self = f32::from_bits(v);
                     Ok(s)
                 }
                 None => Err(DecodeError::UnableToDecode),
@@ -344,7 +387,7 @@ impl Fixed64 for i64 {
 
 impl Fixed64 for f64 {
     fn to_fixed64(&self, buffer: &mut Buffer) -> usize {
-        encode_fixed64(*self as u64, buffer)
+        encode_fixed64(self.to_bits(), buffer)
     }
 
     fn from_fixed64(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
@@ -353,7 +396,7 @@ impl Fixed64 for f64 {
         match result {
             Ok(value) => match value {
                 Some((v, s)) => {
-                    *self = v as f64;
+                    *self = f64::from_bits(v);
                     Ok(s)
                 }
                 None => Err(DecodeError::UnableToDecode),
@@ -378,14 +421,189 @@ impl LengthDelimited for String {
         let mut bytes: Vec<u8> = Vec::new();
         let size = bytes.from_length_delimited(buffer)?;
 
-        // Todo: Error needs to be handled correctly
-        *self = String::from_utf8(bytes).unwrap();
+        *self = String::from_utf8(bytes).map_err(|_| DecodeError::UnableToDecode)?;
 
         Ok(size)
     }
 }
 
 impl LengthDelimitedField for String {}
+impl LengthDelimitedField for Vec<u8> {}
+
+impl Presence for bool {
+    fn is_present(&self) -> bool {
+        *self
+    }
+}
+
+impl Presence for u8 {
+    fn is_present(&self) -> bool {
+        *self != 0
+    }
+}
+
+impl Presence for u16 {
+    fn is_present(&self) -> bool {
+        *self != 0
+    }
+}
+
+impl Presence for u32 {
+    fn is_present(&self) -> bool {
+        *self != 0
+    }
+}
+
+impl Presence for u64 {
+    fn is_present(&self) -> bool {
+        *self != 0
+    }
+}
+
+impl Presence for i8 {
+    fn is_present(&self) -> bool {
+        *self != 0
+    }
+}
+
+impl Presence for i16 {
+    fn is_present(&self) -> bool {
+        *self != 0
+    }
+}
+
+impl Presence for i32 {
+    fn is_present(&self) -> bool {
+        *self != 0
+    }
+}
+
+impl Presence for i64 {
+    fn is_present(&self) -> bool {
+        *self != 0
+    }
+}
+
+// Negative zero is not the default value, so the bits are compared rather than the value.
+impl Presence for f32 {
+    fn is_present(&self) -> bool {
+        self.to_bits() != 0
+    }
+}
+
+impl Presence for f64 {
+    fn is_present(&self) -> bool {
+        self.to_bits() != 0
+    }
+}
+
+impl Presence for String {
+    fn is_present(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl Presence for Vec<u8> {
+    fn is_present(&self) -> bool {
+        !self.is_empty()
+    }
+}
+
+impl<T> Presence for Option<T> {
+    fn is_present(&self) -> bool {
+        self.is_some()
+    }
+}
+
+impl<T: Varint + Default> Varint for Option<T> {
+    fn to_varint(&self, buffer: &mut Buffer) -> usize {
+        match self {
+            Some(v) => v.to_varint(buffer),
+            None => 0,
+        }
+    }
+
+    fn from_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        self.get_or_insert_with(T::default).from_varint(buffer)
+    }
+}
+
+impl<T: SignExtendedVarint + Default> SignExtendedVarint for Option<T> {
+    fn to_sign_extended_varint(&self, buffer: &mut Buffer) -> usize {
+        match self {
+            Some(v) => v.to_sign_extended_varint(buffer),
+            None => 0,
+        }
+    }
+
+    fn from_sign_extended_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        self.get_or_insert_with(T::default)
+            .from_sign_extended_varint(buffer)
+    }
+}
+
+impl<T: Fixed32 + Default> Fixed32 for Option<T> {
+    fn to_fixed32(&self, buffer: &mut Buffer) -> usize {
+        match self {
+            Some(v) => v.to_fixed32(buffer),
+            None => 0,
+        }
+    }
+
+    fn from_fixed32(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        self.get_or_insert_with(T::default).from_fixed32(buffer)
+    }
+}
+
+impl<T: Fixed64 + Default> Fixed64 for Option<T> {
+    fn to_fixed64(&self, buffer: &mut Buffer) -> usize {
+        match self {
+            Some(v) => v.to_fixed64(buffer),
+            None => 0,
+        }
+    }
+
+    fn from_fixed64(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        self.get_or_insert_with(T::default).from_fixed64(buffer)
+    }
+}
+
+// Decoding into a message that is already set merges the fields into it.
+impl<T: LengthDelimited + Default> LengthDelimited for Option<T> {
+    fn to_length_delimited(&self, buffer: &mut Buffer) -> usize {
+        match self {
+            Some(v) => v.to_length_delimited(buffer),
+            None => 0,
+        }
+    }
+
+    fn from_length_delimited(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        self.get_or_insert_with(T::default)
+            .from_length_delimited(buffer)
+    }
+}
+
+// Decoding into a group that is already set merges the fields into it.
+impl<T: Group + Default> Group for Option<T> {
+    fn to_group<const FIELD_NUMBER: u32>(&self, buffer: &mut Buffer) -> usize {
+        match self {
+            Some(v) => v.to_group::<FIELD_NUMBER>(buffer),
+            None => 0,
+        }
+    }
+
+    fn from_group(&mut self, field_number: u32, buffer: &[u8]) -> Result<usize, DecodeError> {
+        self.get_or_insert_with(T::default)
+            .from_group(field_number, buffer)
+    }
+}
+
+impl<T: Varint + Default> VarintField for Option<T> {}
+impl<T: SignExtendedVarint + Default> SignExtendedVarintField for Option<T> {}
+impl<T: Fixed32 + Default> Fixed32Field for Option<T> {}
+impl<T: Fixed64 + Default> Fixed64Field for Option<T> {}
+impl<T: LengthDelimited + Default> LengthDelimitedField for Option<T> {}
+impl<T: Group + Default> GroupField for Option<T> {}
 
 #[cfg(test)]
 mod tests {
@@ -393,6 +611,41 @@ mod tests {
     use crate::Buffer;
     use rstest::*;
 
+    #[rstest]
+    #[case(vec![2], DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)]
+    #[case(vec![129, 0], DecodeError::NonCanonicalVarint)]
+    fn test_bool_strict_decoding(#[case] buffer: Vec<u8>, #[case] expected_error: DecodeError) {
+        // Arrange
+        let mut value = false;
+
+        // Act
+        let error = DecodeMode::Strict
+            .scope(|| value.from_varint(&buffer))
+            .unwrap_err();
+
+        // Assert
+        assert_eq!(error, expected_error);
+    }
+
+    #[rstest]
+    #[case(vec![255, 255, 255, 255, 15], -1)]
+    #[case(vec![128, 128, 128, 128, 16], 0)]
+    fn test_i32_sign_extended_strict_decoding(#[case] buffer: Vec<u8>, #[case] lenient_value: i32) {
+        // Arrange
+        let mut value = 0i32;
+
+        // Act
+        let lenient = value.from_sign_extended_varint(&buffer).map(|_| value);
+        let strict = DecodeMode::Strict.scope(|| value.from_sign_extended_varint(&buffer));
+
+        // Assert
+        assert_eq!(lenient, Ok(lenient_value));
+        assert_eq!(
+            strict,
+            Err(DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)
+        );
+    }
+
     #[rstest]
     #[case(false, vec![0])]
     #[case(true, vec![1])]
@@ -411,6 +664,8 @@ mod tests {
     #[rstest]
     #[case(vec![0], false)]
     #[case(vec![1], true)]
+    #[case(vec![2], true)]
+    #[case(vec![128, 128, 128, 128, 128, 128, 128, 128, 128, 1], true)]
     fn test_bool_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: bool) {
         // Arrange
         //let mut buffer = Buffer::new();
@@ -653,10 +908,10 @@ mod tests {
     #[case(i16::MAX as i64, vec![254, 255, 3])]
     #[case(i32::MIN as i64, vec![255, 255, 255, 255, 15])]
     #[case(i32::MAX as i64, vec![254, 255, 255, 255, 15])]
-    #[case(i64::MIN, vec![128, 128, 128, 128, 240, 255, 255, 255, 255, 1])]
-    #[case(i64::MAX, vec![129, 128, 128, 128, 240, 255, 255, 255, 255, 1])]
-    #[case(i64::MIN + 1, vec![130, 128, 128, 128, 240, 255, 255, 255, 255, 1])]
-    #[case(i64::MAX - 1, vec![131, 128, 128, 128, 240, 255, 255, 255, 255, 1])]
+    #[case(i64::MIN, vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1])]
+    #[case(i64::MAX, vec![254, 255, 255, 255, 255, 255, 255, 255, 255, 1])]
+    #[case(i64::MIN + 1, vec![253, 255, 255, 255, 255, 255, 255, 255, 255, 1])]
+    #[case(i64::MAX - 1, vec![252, 255, 255, 255, 255, 255, 255, 255, 255, 1])]
     fn test_i64_encoding(#[case] value: i64, #[case] expected_buffer: Vec<u8>) {
         // Arrange
         let mut buffer = Buffer::default();
@@ -668,7 +923,7 @@ mod tests {
         assert_eq!(buffer.to_vec(), expected_buffer);
         assert_eq!(size, expected_buffer.len());
     }
-    //
+
     #[rstest]
     #[case(vec![255, 1], i8::MIN as i64)]
     #[case(vec![254, 1], i8::MAX as i64)]
@@ -676,10 +931,10 @@ mod tests {
     #[case(vec![254, 255, 3], i16::MAX as i64)]
     #[case(vec![255, 255, 255, 255, 15], i32::MIN as i64)]
     #[case(vec![254, 255, 255, 255, 15], i32::MAX as i64)]
-    //#[case(vec![128, 128, 128, 128, 240, 255, 255, 255, 255, 1], i64::MIN)]
-    //#[case(vec![129, 128, 128, 128, 240, 255, 255, 255, 255, 1], i64::MAX)]
-    //#[case(vec![130, 128, 128, 128, 240, 255, 255, 255, 255, 1], i64::MIN + 1)]
-    //#[case(vec![131, 128, 128, 128, 240, 255, 255, 255, 255, 1], i64::MAX - 1)]
+    #[case(vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1], i64::MIN)]
+    #[case(vec![254, 255, 255, 255, 255, 255, 255, 255, 255, 1], i64::MAX)]
+    #[case(vec![253, 255, 255, 255, 255, 255, 255, 255, 255, 1], i64::MIN + 1)]
+    #[case(vec![252, 255, 255, 255, 255, 255, 255, 255, 255, 1], i64::MAX - 1)]
     fn test_i64_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: i64) {
         // Arrange
         let mut value: i64 = 0;
@@ -692,6 +947,74 @@ mod tests {
         assert_eq!(result, buffer.len());
     }
 
+    #[rstest]
+    #[case(0, vec![0])]
+    #[case(1, vec![1])]
+    #[case(-1, vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1])]
+    #[case(i32::MIN, vec![128, 128, 128, 128, 248, 255, 255, 255, 255, 1])]
+    #[case(i32::MAX, vec![255, 255, 255, 255, 7])]
+    fn test_i32_sign_extended_encoding(#[case] value: i32, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_sign_extended_varint(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(vec![0], 0)]
+    #[case(vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1], -1)]
+    #[case(vec![255, 255, 255, 255, 15], -1)]
+    #[case(vec![128, 128, 128, 128, 248, 255, 255, 255, 255, 1], i32::MIN)]
+    #[case(vec![255, 255, 255, 255, 7], i32::MAX)]
+    fn test_i32_sign_extended_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: i32) {
+        // Arrange
+        let mut value: i32 = 0;
+
+        // Act
+        let result = value.from_sign_extended_varint(&buffer).unwrap();
+
+        // Assert
+        assert_eq!(value, expected_value);
+        assert_eq!(result, buffer.len());
+    }
+
+    #[rstest]
+    #[case(-1, vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1])]
+    #[case(i64::MIN, vec![128, 128, 128, 128, 128, 128, 128, 128, 128, 1])]
+    #[case(i64::MAX, vec![255, 255, 255, 255, 255, 255, 255, 255, 127])]
+    fn test_i64_sign_extended_encoding(#[case] value: i64, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_sign_extended_varint(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(vec![255, 255, 255, 255, 255, 255, 255, 255, 255, 1], -1)]
+    #[case(vec![128, 128, 128, 128, 128, 128, 128, 128, 128, 1], i64::MIN)]
+    #[case(vec![255, 255, 255, 255, 255, 255, 255, 255, 127], i64::MAX)]
+    fn test_i64_sign_extended_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: i64) {
+        // Arrange
+        let mut value: i64 = 0;
+
+        // Act
+        let result = value.from_sign_extended_varint(&buffer).unwrap();
+
+        // Assert
+        assert_eq!(value, expected_value);
+        assert_eq!(result, buffer.len());
+    }
+
     #[rstest]
     #[case(u32::MIN, vec![0, 0, 0, 0])]
     #[case(u32::MAX, vec![255, 255, 255, 255])]
@@ -752,35 +1075,35 @@ mod tests {
         assert_eq!(result, buffer.len());
     }
 
-    // #[rstest]
-    // #[case(f32::MIN, vec![0, 0, 0, 0])]
-    // #[case(f32::MAX, vec![255, 255, 255, 255])]
-    // fn test_f32_fixed32_encoding(#[case] value: f32, #[case] expected_buffer: Vec<u8>) {
-    //     // Arrange
-    //     let mut buffer = Buffer::default();
-    //
-    //     // Act
-    //     let size = value.to_fixed32(&mut buffer);
-    //
-    //     // Assert
-    //     assert_eq!(buffer.to_vec(), expected_buffer);
-    //     assert_eq!(size, expected_buffer.len());
-    // }
-
-    // #[rstest]
-    // #[case(vec![0, 0, 0, 0], f32::MIN)]
-    // #[case(vec![255, 255, 255, 255], f32::MAX)]
-    // fn test_f32_fixed32_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: f32) {
-    //     // Arrange
-    //     let mut value: f32 = 0.0;
-    //
-    //     // Act
-    //     let result = value.from_fixed32(&buffer).unwrap();
-    //
-    //     // Assert
-    //     assert_eq!(value, expected_value);
-    //     assert_eq!(result, buffer.len());
-    // }
+    #[rstest]
+    #[case(f32::MIN, vec![255, 255, 127, 255])]
+    #[case(f32::MAX, vec![255, 255, 127, 127])]
+    fn test_f32_fixed32_encoding(#[case] value: f32, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_fixed32(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(vec![255, 255, 127, 255], f32::MIN)]
+    #[case(vec![255, 255, 127, 127], f32::MAX)]
+    fn test_f32_fixed32_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: f32) {
+        // Arrange
+        let mut value: f32 = 0.0;
+
+        // Act
+        let result = value.from_fixed32(&buffer).unwrap();
+
+        // Assert
+        assert_eq!(value, expected_value);
+        assert_eq!(result, buffer.len());
+    }
 
     #[rstest]
     #[case(u64::MIN, vec![0, 0, 0, 0, 0, 0, 0, 0])]
@@ -842,35 +1165,35 @@ mod tests {
         assert_eq!(result, buffer.len());
     }
 
-    // #[rstest]
-    // #[case(f64::MIN, vec![0, 0, 0, 0, 0, 0, 0 ,0])]
-    // #[case(f64::MAX, vec![255, 255, 255, 255, 255, 255, 255, 255])]
-    // fn test_f64_fixed64_encoding(#[case] value: f64, #[case] expected_buffer: Vec<u8>) {
-    //     // Arrange
-    //     let mut buffer = Buffer::default();
-    //
-    //     // Act
-    //     let size = value.to_fixed64(&mut buffer);
-    //
-    //     // Assert
-    //     assert_eq!(buffer.to_vec(), expected_buffer);
-    //     assert_eq!(size, expected_buffer.len());
-    // }
-
-    // #[rstest]
-    // #[case(vec![0, 0, 0, 0], f64::MIN)]
-    // #[case(vec![255, 255, 255, 255], f64::MAX)]
-    // fn test_f64_fixed64_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: f64) {
-    //     // Arrange
-    //     let mut value: f64 = 0.0;
-    //
-    //     // Act
-    //     let result = value.from_fixed64(&buffer).unwrap();
-    //
-    //     // Assert
-    //     assert_eq!(value, expected_value);
-    //     assert_eq!(result, buffer.len());
-    // }
+    #[rstest]
+    #[case(f64::MIN, vec![255, 255, 255, 255, 255, 255, 239, 255])]
+    #[case(f64::MAX, vec![255, 255, 255, 255, 255, 255, 239, 127])]
+    fn test_f64_fixed64_encoding(#[case] value: f64, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_fixed64(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(vec![255, 255, 255, 255, 255, 255, 239, 255], f64::MIN)]
+    #[case(vec![255, 255, 255, 255, 255, 255, 239, 127], f64::MAX)]
+    fn test_f64_fixed64_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: f64) {
+        // Arrange
+        let mut value: f64 = 0.0;
+
+        // Act
+        let result = value.from_fixed64(&buffer).unwrap();
+
+        // Assert
+        assert_eq!(value, expected_value);
+        assert_eq!(result, buffer.len());
+    }
 
     #[rstest]
     #[case("this is a test", vec![14, 116, 104, 105, 115, 32, 105, 115, 32, 97, 32, 116, 101, 115, 116])]
@@ -901,4 +1224,97 @@ mod tests {
         assert_eq!(value, expected_value);
         assert_eq!(result, buffer.len());
     }
+
+    #[rstest]
+    #[case(0, vec![])]
+    #[case(150, vec![8, 150, 1])]
+    fn test_u32_field_encoding_skips_default(#[case] value: u32, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_varint_field::<1>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(None, vec![])]
+    #[case(Some(0), vec![8, 0])]
+    #[case(Some(150), vec![8, 150, 1])]
+    fn test_optional_u32_field_encoding(
+        #[case] value: Option<u32>,
+        #[case] expected_buffer: Vec<u8>,
+    ) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_varint_field::<1>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(0.0, vec![])]
+    #[case(-0.0, vec![9, 0, 0, 0, 0, 0, 0, 0, 128])]
+    fn test_f64_field_encoding_skips_default(#[case] value: f64, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_fixed64_field::<1>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(None, vec![])]
+    #[case(Some(String::new()), vec![10, 0])]
+    fn test_optional_string_field_encoding(
+        #[case] value: Option<String>,
+        #[case] expected_buffer: Vec<u8>,
+    ) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.to_length_delimited_field::<1>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[test]
+    fn test_optional_u32_decoding() {
+        // Arrange
+        let mut value: Option<u32> = None;
+
+        // Act
+        let result = value.from_varint(&[0]).unwrap();
+
+        // Assert
+        assert_eq!(value, Some(0));
+        assert_eq!(result, 1);
+    }
+
+    #[test]
+    fn test_field_always_encoding_writes_default() {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = false.to_varint_field_always::<2>(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), vec![16, 0]);
+        assert_eq!(size, 2);
+    }
 }
@@ -0,0 +1,148 @@
+use crate::{skip_tagged_field, Buffer, EncodeError, EncodeMode, Tag, Varint, WireType};
+
+/// Holds the fields of a message that were decoded but are not known to it, so that they are
+/// written out again when the message is encoded.
+///
+/// Fields are kept as they were read, each with its tag. Generated proto2 and editions messages
+/// hold them, along with the values of closed enum fields that are not variants of the enum.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Buffer, UnknownFields};
+///
+/// let mut unknown = UnknownFields::default();
+/// unknown.push_varint(3, 9).unwrap();
+/// unknown.push_field(&[8, 1]);
+///
+/// let mut buffer = Buffer::default();
+/// let size = unknown.encode(&mut buffer);
+///
+/// assert_eq!(buffer.to_vec(), vec![24, 9, 8, 1]);
+/// assert_eq!(size, 4);
+/// ```
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct UnknownFields {
+    fields: Vec<u8>,
+}
+
+impl UnknownFields {
+    /// Returns the fields as they were read.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.fields
+    }
+
+    /// Returns true when no fields are held.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Removes every field.
+    pub fn clear(&mut self) {
+        self.fields.clear();
+    }
+
+    /// Keeps a field that has been read, which must start with its tag.
+    pub fn push_field(&mut self, field: &[u8]) {
+        self.fields.extend_from_slice(field);
+    }
+
+    /// Keeps a varint field, as is done for a value which is not a variant of a closed enum. The
+    /// field is not kept when its field number can not be used by a field.
+    pub fn push_varint(&mut self, field_number: u32, value: u64) -> Result<(), EncodeError> {
+        let tag = Tag::try_new(field_number, WireType::Varint)?;
+        self.push_varint_with_tag(&tag, value);
+        Ok(())
+    }
+
+    // Keeps a varint field with the field number of a tag that has already been decoded, which is
+    // therefore known to be valid.
+    pub(crate) fn push_varint_with_tag(&mut self, tag: &Tag, value: u64) {
+        let mut buffer = Buffer::default();
+        let _ = Tag::new_unchecked(tag.field_number(), WireType::Varint).to_varint(&mut buffer);
+        let _ = value.to_varint(&mut buffer);
+        self.fields.extend(buffer.to_vec());
+    }
+
+    /// Appends the other fields, as though they had been read after these.
+    pub fn merge(&mut self, other: UnknownFields) {
+        self.fields.extend(other.fields);
+    }
+
+    /// This function writes out the fields, in field number order when encoding is
+    /// deterministic.
+    pub fn encode(&self, buffer: &mut Buffer) -> usize {
+        match EncodeMode::is_deterministic() {
+            true => buffer.put_slice(&sort_fields(&self.fields)),
+            false => buffer.put_slice(&self.fields),
+        }
+        self.fields.len()
+    }
+}
+
+// Reorders encoded fields by field number, keeping the order of fields with the same number. The
+// fields have already been decoded once, so they are known to be well formed.
+pub(crate) fn sort_fields(buffer: &[u8]) -> Vec<u8> {
+    let mut fields = Vec::new();
+    let mut position = 0;
+    while position < buffer.len() {
+        let start = position;
+        let mut tag = Tag::new_unchecked(0, WireType::Varint);
+        position += tag.from_varint(&buffer[position..]).unwrap_or(buffer.len());
+        position += skip_tagged_field(&tag, &buffer[position..]).unwrap_or(buffer.len());
+        fields.push((
+            tag.field_number(),
+            &buffer[start..position.min(buffer.len())],
+        ));
+    }
+
+    fields.sort_by_key(|(number, _)| *number);
+    fields
+        .into_iter()
+        .flat_map(|(_, field)| field)
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[rstest]
+    #[case(EncodeMode::Standard, vec![24, 9, 11, 8, 1, 12, 16, 2])]
+    #[case(EncodeMode::Deterministic, vec![11, 8, 1, 12, 16, 2, 24, 9])]
+    fn test_unknown_fields_encoding(#[case] mode: EncodeMode, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut unknown = UnknownFields::default();
+        unknown.push_varint(3, 9).unwrap();
+        unknown.push_field(&[11, 8, 1, 12]);
+        unknown.push_field(&[16, 2]);
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = mode.scope(|| unknown.encode(&mut buffer));
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, 8);
+    }
+
+    #[rstest]
+    #[case(0, EncodeError::FieldNumberOutOfRange(0))]
+    #[case(19000, EncodeError::ReservedFieldNumber(19000))]
+    #[case(1 << 29, EncodeError::FieldNumberOutOfRange(1 << 29))]
+    fn test_invalid_field_numbers_are_rejected(
+        #[case] field_number: u32,
+        #[case] expected_error: EncodeError,
+    ) {
+        // Arrange
+        let mut unknown = UnknownFields::default();
+
+        // Act
+        let result = unknown.push_varint(field_number, 9);
+
+        // Assert
+        assert_eq!(result, Err(expected_error));
+        assert!(unknown.is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Buffer, DecodeError};
+use crate::{Buffer, DecodeError, DecodeMode};
 
 /// Encodes a 32 bit unsigned integer to LEB128
 ///
@@ -84,6 +84,10 @@ pub fn encode_varint64(mut value: u64, buffer: &mut Buffer) -> usize {
 
 /// Decodes a Varint to a 32 bit unsigned integer
 ///
+/// Varints of up to 10 bytes are accepted and truncated to 32 bits, as the reference
+/// implementation does for `int32`, `uint32` and enum values, unless the decoding is strict, see
+/// [`DecodeMode`]. Lengths and tags are never truncated and are decoded without this function.
+///
 /// Basic usage:
 /// ```
 /// use ks_protobuf_v3::{Buffer, decode_varint32};
@@ -93,7 +97,13 @@ pub fn encode_varint64(mut value: u64, buffer: &mut Buffer) -> usize {
 /// let (value, len) = decode_varint32(&buffer).unwrap().unwrap();
 /// ```
 pub fn decode_varint32(data: &[u8]) -> Result<Option<(u32, usize)>, DecodeError> {
-    decode_varint(data)
+    match decode_varint(data)? {
+        Some((value, _)) if value > u32::MAX as u64 && DecodeMode::is_strict() => {
+            Err(DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)
+        }
+        Some((value, size)) => Ok(Some((value as u32, size))),
+        None => Ok(None),
+    }
 }
 
 /// Decodes a Varint to a 64 bit unsigned integer
@@ -110,67 +120,44 @@ pub fn decode_varint64(data: &[u8]) -> Result<Option<(u64, usize)>, DecodeError>
     decode_varint(data)
 }
 
-// pub fn get_varint_encoded_length(buffer: &[u8], start_position: usize) -> usize {
-//     1
-// }
-
 const MAX_VARINT_ENCODED_LEN: usize = 10;
-const MAX_VARINT32_ENCODED_LEN: usize = 5;
-
-trait DecodeVarint {
-    const MAX_ENCODED_LEN: usize;
-    const LAST_BYTE_MAXVALUE: u8;
-
-    fn from_u64(value: u64) -> Self;
-}
-
-impl DecodeVarint for u64 {
-    const MAX_ENCODED_LEN: usize = MAX_VARINT_ENCODED_LEN;
-    const LAST_BYTE_MAXVALUE: u8 = 0x01;
+const LAST_BYTE_MAXVALUE: u8 = 0x01;
 
-    fn from_u64(value: u64) -> Self {
-        value
+fn decode_varint(data: &[u8]) -> Result<Option<(u64, usize)>, DecodeError> {
+    if !data.is_empty() && data[0] < 0x80 {
+        return Ok(Some((data[0] as u64, 1)));
     }
-}
-
-impl DecodeVarint for u32 {
-    const MAX_ENCODED_LEN: usize = MAX_VARINT32_ENCODED_LEN;
-    const LAST_BYTE_MAXVALUE: u8 = 0x0F;
 
-    fn from_u64(value: u64) -> Self {
-        value as u32
-    }
-}
-
-fn decode_varint<D: DecodeVarint>(data: &[u8]) -> Result<Option<(D, usize)>, DecodeError> {
-    if !data.is_empty() && data[0] < 0x80 {
-        let ret = data[0] as u64;
-        let consume = 1;
-        Ok(Some((D::from_u64(ret), consume)))
-    } else if data.len() >= 2 && data[1] < 0x80 {
-        let ret = (data[0] & 0x7f) as u64 | (data[1] as u64) << 7;
-        let consume = 2;
-        Ok(Some((D::from_u64(ret), consume)))
+    let result = if data.len() >= 2 && data[1] < 0x80 {
+        Some(((data[0] & 0x7f) as u64 | (data[1] as u64) << 7, 2))
     } else {
-        decode_varint_slow(data)
+        decode_varint_slow(data)?
+    };
+
+    // A final byte of zero adds nothing to the value, so the varint could have been shorter.
+    match result {
+        Some((_, size)) if data[size - 1] == 0 && DecodeMode::is_strict() => {
+            Err(DecodeError::NonCanonicalVarint)
+        }
+        result => Ok(result),
     }
 }
 
-fn decode_varint_slow<D: DecodeVarint>(data: &[u8]) -> Result<Option<(D, usize)>, DecodeError> {
+fn decode_varint_slow(data: &[u8]) -> Result<Option<(u64, usize)>, DecodeError> {
     let mut r: u64 = 0;
     for (i, &b) in data.iter().enumerate() {
-        if i == D::MAX_ENCODED_LEN - 1 {
-            if b > D::LAST_BYTE_MAXVALUE {
+        if i == MAX_VARINT_ENCODED_LEN - 1 {
+            if b > LAST_BYTE_MAXVALUE {
                 return Err(DecodeError::BufferOverrun);
             }
             let r = r | ((b as u64) << (i as u64 * 7));
 
-            return Ok(Some((D::from_u64(r), i + 1)));
+            return Ok(Some((r, i + 1)));
         }
 
         r |= ((b & 0x7f) as u64) << (i as u64 * 7);
         if b < 0x80 {
-            return Ok(Some((D::from_u64(r), i + 1)));
+            return Ok(Some((r, i + 1)));
         }
     }
 
@@ -0,0 +1,610 @@
+use crate::{
+    decode_oneof_member, merge_oneof_message, Buffer, DecodeError, Enumeration, Fixed64,
+    Fixed64Field, LengthDelimited, LengthDelimitedCodec, LengthDelimitedField, MapField, Message,
+    Oneof, Presence, Tag, Varint, VarintField, WireType,
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Represents `google.protobuf.NullValue`, a singleton enumeration used to represent the null value
+/// of a `Value`.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum NullValue {
+    /// The null value
+    #[default]
+    NullValue = 0,
+}
+
+impl Varint for NullValue {
+    fn to_varint(&self, buffer: &mut Buffer) -> usize {
+        (*self as u32).to_varint(buffer)
+    }
+
+    fn from_varint(&mut self, buffer: &[u8]) -> Result<usize, DecodeError> {
+        let mut value: u64 = 0;
+        let size = value.from_varint(buffer)?;
+
+        *self = NullValue::NullValue;
+        Ok(size)
+    }
+}
+
+impl Presence for NullValue {
+    fn is_present(&self) -> bool {
+        false
+    }
+}
+
+impl VarintField for NullValue {}
+
+impl Enumeration for NullValue {
+    const VALUES: &'static [Self] = &[NullValue::NullValue];
+
+    fn number(self) -> i32 {
+        self as i32
+    }
+
+    fn name(self) -> &'static str {
+        "NULL_VALUE"
+    }
+}
+
+/// Represents `google.protobuf.Struct`, a structured data value consisting of fields which map to
+/// dynamically typed values.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Message, Struct, Value};
+///
+/// let mut metadata = Struct::default();
+/// metadata.insert("region", "eu-west-2");
+/// metadata.insert("replicas", 3);
+///
+/// let bytes = metadata.encode_to_vec();
+/// let decoded = Struct::decode(&bytes).unwrap();
+///
+/// assert_eq!(decoded.get("replicas").and_then(Value::as_f64), Some(3.0));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Struct {
+    /// Unordered map of dynamically typed values.
+    pub fields: HashMap<String, Value>,
+}
+
+impl Struct {
+    /// Inserts a field into the structure, returning the previous value of the field if present.
+    pub fn insert<K: Into<String>, V: Into<Value>>(&mut self, key: K, value: V) -> Option<Value> {
+        self.fields.insert(key.into(), value.into())
+    }
+
+    /// Returns the value of the field with the given name.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.fields.get(key)
+    }
+}
+
+impl Message for Struct {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        self.fields
+            .to_map_field::<LengthDelimitedCodec, LengthDelimitedCodec, 1>(buffer)
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => self
+                .fields
+                .from_map_entry::<LengthDelimitedCodec, LengthDelimitedCodec>(buffer)
+                .map(Some),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// The kind of value held by a `Value`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    /// Represents a null value.
+    NullValue(NullValue),
+
+    /// Represents a double value.
+    NumberValue(f64),
+
+    /// Represents a string value.
+    StringValue(String),
+
+    /// Represents a boolean value.
+    BoolValue(bool),
+
+    /// Represents a structured value.
+    StructValue(Struct),
+
+    /// Represents a repeated `Value`.
+    ListValue(ListValue),
+}
+
+/// Represents `google.protobuf.Value`, a dynamically typed value which can be either null, a
+/// number, a string, a boolean, a recursive struct value, or a list of values.
+///
+/// Basic usage:
+/// ```
+/// use ks_protobuf_v3::{Message, Value};
+///
+/// let value = Value::from(vec!["a", "b"]);
+///
+/// let bytes = value.encode_to_vec();
+/// let decoded = Value::decode(&bytes).unwrap();
+///
+/// assert_eq!(decoded, value);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Value {
+    /// The kind of value, `None` when no kind has been set.
+    pub kind: Option<Kind>,
+}
+
+impl Value {
+    /// Creates a new null value.
+    pub fn null() -> Self {
+        Self {
+            kind: Some(Kind::NullValue(NullValue::NullValue)),
+        }
+    }
+
+    /// Returns true if the value is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self.kind, Some(Kind::NullValue(_)))
+    }
+
+    /// Returns the number held by the value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self.kind {
+            Some(Kind::NumberValue(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the string held by the value.
+    pub fn as_str(&self) -> Option<&str> {
+        match &self.kind {
+            Some(Kind::StringValue(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean held by the value.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self.kind {
+            Some(Kind::BoolValue(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the structure held by the value.
+    pub fn as_struct(&self) -> Option<&Struct> {
+        match &self.kind {
+            Some(Kind::StructValue(v)) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the list held by the value.
+    pub fn as_list(&self) -> Option<&ListValue> {
+        match &self.kind {
+            Some(Kind::ListValue(v)) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl Oneof for Kind {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        match self {
+            Kind::NullValue(v) => v.to_varint_field_always::<1>(buffer),
+            Kind::NumberValue(v) => v.to_fixed64_field_always::<2>(buffer),
+            Kind::StringValue(v) => v.to_length_delimited_field_always::<3>(buffer),
+            Kind::BoolValue(v) => v.to_varint_field_always::<4>(buffer),
+            Kind::StructValue(v) => v.to_length_delimited_field_always::<5>(buffer),
+            Kind::ListValue(v) => v.to_length_delimited_field_always::<6>(buffer),
+        }
+    }
+
+    fn decode_field(
+        oneof: &mut Option<Self>,
+        tag: &Tag,
+        buffer: &[u8],
+    ) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::Varint) => {
+                decode_oneof_member(oneof, buffer, NullValue::from_varint, Kind::NullValue)
+                    .map(Some)
+            }
+            (2, WireType::Fixed64) => {
+                decode_oneof_member(oneof, buffer, f64::from_fixed64, Kind::NumberValue).map(Some)
+            }
+            (3, WireType::LengthDelimited) => decode_oneof_member(
+                oneof,
+                buffer,
+                String::from_length_delimited,
+                Kind::StringValue,
+            )
+            .map(Some),
+            (4, WireType::Varint) => {
+                decode_oneof_member(oneof, buffer, bool::from_varint, Kind::BoolValue).map(Some)
+            }
+            (5, WireType::LengthDelimited) => merge_oneof_message(
+                oneof,
+                buffer,
+                |v| match v {
+                    Kind::StructValue(v) => Some(v),
+                    _ => None,
+                },
+                Kind::StructValue,
+            )
+            .map(Some),
+            (6, WireType::LengthDelimited) => merge_oneof_message(
+                oneof,
+                buffer,
+                |v| match v {
+                    Kind::ListValue(v) => Some(v),
+                    _ => None,
+                },
+                Kind::ListValue,
+            )
+            .map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn merge(&mut self, other: Self) -> Result<(), DecodeError> {
+        match (self, other) {
+            (Kind::StructValue(v), Kind::StructValue(o)) => v.merge(o),
+            (Kind::ListValue(v), Kind::ListValue(o)) => v.merge(o),
+            (this, other) => {
+                *this = other;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Message for Value {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        match &self.kind {
+            Some(kind) => kind.encode(buffer),
+            None => 0,
+        }
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        Kind::decode_field(&mut self.kind, tag, buffer)
+    }
+}
+
+/// Represents `google.protobuf.ListValue`, a repeated field of dynamically typed values.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ListValue {
+    /// Repeated field of dynamically typed values.
+    pub values: Vec<Value>,
+}
+
+impl Message for ListValue {
+    fn encode(&self, buffer: &mut Buffer) -> usize {
+        self.values
+            .iter()
+            .map(|value| value.to_length_delimited_field::<1>(buffer))
+            .sum()
+    }
+
+    fn decode_field(&mut self, tag: &Tag, buffer: &[u8]) -> Result<Option<usize>, DecodeError> {
+        match (tag.field_number(), tag.wire_type()) {
+            (1, WireType::LengthDelimited) => {
+                let mut value = Value::default();
+                let size = value.from_length_delimited(buffer)?;
+
+                self.values.push(value);
+                Ok(Some(size))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+impl From<NullValue> for Value {
+    fn from(value: NullValue) -> Self {
+        Self {
+            kind: Some(Kind::NullValue(value)),
+        }
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self {
+            kind: Some(Kind::NumberValue(value)),
+        }
+    }
+}
+
+impl From<f32> for Value {
+    fn from(value: f32) -> Self {
+        Self::from(value as f64)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Self::from(value as f64)
+    }
+}
+
+impl From<u32> for Value {
+    fn from(value: u32) -> Self {
+        Self::from(value as f64)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Self {
+            kind: Some(Kind::StringValue(value)),
+        }
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Self::from(value.to_string())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Self {
+            kind: Some(Kind::BoolValue(value)),
+        }
+    }
+}
+
+impl From<Struct> for Value {
+    fn from(value: Struct) -> Self {
+        Self {
+            kind: Some(Kind::StructValue(value)),
+        }
+    }
+}
+
+impl From<ListValue> for Value {
+    fn from(value: ListValue) -> Self {
+        Self {
+            kind: Some(Kind::ListValue(value)),
+        }
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        match value {
+            Some(v) => v.into(),
+            None => Self::null(),
+        }
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(values: Vec<T>) -> Self {
+        Self::from(values.into_iter().collect::<ListValue>())
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> From<HashMap<K, V>> for Value {
+    fn from(fields: HashMap<K, V>) -> Self {
+        Self::from(fields.into_iter().collect::<Struct>())
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> From<BTreeMap<K, V>> for Value {
+    fn from(fields: BTreeMap<K, V>) -> Self {
+        Self::from(fields.into_iter().collect::<Struct>())
+    }
+}
+
+impl<T: Into<Value>> FromIterator<T> for ListValue {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self {
+            values: iter.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl IntoIterator for ListValue {
+    type Item = Value;
+    type IntoIter = std::vec::IntoIter<Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Struct {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        Self {
+            fields: iter
+                .into_iter()
+                .map(|(k, v)| (k.into(), v.into()))
+                .collect(),
+        }
+    }
+}
+
+impl IntoIterator for Struct {
+    type Item = (String, Value);
+    type IntoIter = std::collections::hash_map::IntoIter<String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.fields.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::*;
+
+    #[test]
+    fn test_deeply_nested_values_are_rejected() {
+        // Arrange
+        // Values holding lists holding values, built from the inside out in reverse.
+        let mut reversed = Vec::new();
+        for level in 0..80_001 {
+            let mut buffer = Buffer::default();
+            let _ = (reversed.len() as u64).to_varint(&mut buffer);
+            reversed.extend(buffer.to_vec().into_iter().rev());
+            reversed.push(match level % 2 {
+                0 => 50,
+                _ => 10,
+            });
+        }
+        let bytes: Vec<u8> = reversed.into_iter().rev().collect();
+
+        // Act
+        let result = Value::decode(&bytes);
+
+        // Assert
+        assert_eq!(result, Err(DecodeError::RecursionLimitExceeded));
+    }
+
+    #[test]
+    fn test_list_value_with_oversized_length_is_rejected() {
+        // Act
+        let result = ListValue::decode(&[10, 0x82, 0x80, 0x80, 0x80, 0x10, 32, 1]);
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(DecodeError::UnableToDecodeBecauseTheValueWasTooLarge)
+        );
+    }
+
+    #[rstest]
+    #[case(Value::null(), vec![8, 0])]
+    #[case(Value::from(1.0), vec![17, 0, 0, 0, 0, 0, 0, 240, 63])]
+    #[case(Value::from(0.0), vec![17, 0, 0, 0, 0, 0, 0, 0, 0])]
+    #[case(Value::from("hi"), vec![26, 2, 104, 105])]
+    #[case(Value::from(true), vec![32, 1])]
+    #[case(Value::from(vec![true]), vec![50, 4, 10, 2, 32, 1])]
+    #[case(Value::from(Struct::default()), vec![42, 0])]
+    #[case(Value::default(), vec![])]
+    fn test_value_encoding(#[case] value: Value, #[case] expected_buffer: Vec<u8>) {
+        // Arrange
+        let mut buffer = Buffer::default();
+
+        // Act
+        let size = value.encode(&mut buffer);
+
+        // Assert
+        assert_eq!(buffer.to_vec(), expected_buffer);
+        assert_eq!(size, expected_buffer.len());
+    }
+
+    #[rstest]
+    #[case(vec![8, 0], Value::null())]
+    #[case(vec![17, 0, 0, 0, 0, 0, 0, 240, 63], Value::from(1.0))]
+    #[case(vec![26, 2, 104, 105], Value::from("hi"))]
+    #[case(vec![32, 1], Value::from(true))]
+    #[case(vec![50, 4, 10, 2, 32, 1], Value::from(vec![true]))]
+    #[case(vec![42, 0], Value::from(Struct::default()))]
+    #[case(vec![32, 1, 26, 2, 104, 105], Value::from("hi"))]
+    fn test_value_decoding(#[case] buffer: Vec<u8>, #[case] expected_value: Value) {
+        // Act
+        let value = Value::decode(&buffer).unwrap();
+
+        // Assert
+        assert_eq!(value, expected_value);
+    }
+
+    #[test]
+    fn test_value_struct_member_replaced_by_other_member() {
+        // Arrange
+        let bytes = vec![42, 5, 10, 3, 10, 1, 97, 32, 1, 42, 5, 10, 3, 10, 1, 98];
+
+        // Act
+        let value = Value::decode(&bytes).unwrap();
+
+        // Assert
+        let fields = value.as_struct().unwrap();
+        assert_eq!(fields.get("a"), None);
+        assert_eq!(fields.get("b"), Some(&Value::default()));
+    }
+
+    #[test]
+    fn test_value_repeated_struct_members_are_merged() {
+        // Arrange
+        let bytes = vec![42, 5, 10, 3, 10, 1, 97, 42, 5, 10, 3, 10, 1, 98];
+
+        // Act
+        let value = Value::decode(&bytes).unwrap();
+
+        // Assert
+        let fields = value.as_struct().unwrap();
+        assert_eq!(fields.get("a"), Some(&Value::default()));
+        assert_eq!(fields.get("b"), Some(&Value::default()));
+    }
+
+    #[test]
+    fn test_struct_encoding() {
+        // Arrange
+        let mut value = Struct::default();
+        let _ = value.insert("a", true);
+
+        // Act
+        let bytes = value.encode_to_vec();
+
+        // Assert
+        assert_eq!(bytes, vec![10, 7, 10, 1, 97, 18, 2, 32, 1]);
+    }
+
+    #[test]
+    fn test_struct_round_trip() {
+        // Arrange
+        let mut inner = Struct::default();
+        let _ = inner.insert("flag", false);
+        let _ = inner.insert("missing", None::<bool>);
+
+        let mut value = Struct::default();
+        let _ = value.insert("name", "test");
+        let _ = value.insert("count", 3);
+        let _ = value.insert("inner", inner);
+        let _ = value.insert("list", vec![Value::from(1.5), Value::null()]);
+
+        // Act
+        let decoded = Struct::decode(&value.encode_to_vec()).unwrap();
+
+        // Assert
+        assert_eq!(decoded, value);
+    }
+
+    #[test]
+    fn test_struct_decoding_with_missing_entry_value() {
+        // Arrange
+        let bytes = vec![10, 3, 10, 1, 97];
+
+        // Act
+        let value = Struct::decode(&bytes).unwrap();
+
+        // Assert
+        assert_eq!(value.get("a"), Some(&Value::default()));
+    }
+
+    #[test]
+    fn test_value_accessors() {
+        // Arrange
+        let list = Value::from(vec![1, 2]);
+
+        // Assert
+        assert!(Value::null().is_null());
+        assert_eq!(Value::from(2.5).as_f64(), Some(2.5));
+        assert_eq!(Value::from("x").as_str(), Some("x"));
+        assert_eq!(Value::from(false).as_bool(), Some(false));
+        assert_eq!(Value::from("x").as_bool(), None);
+        assert_eq!(list.as_list().map(|l| l.values.len()), Some(2));
+    }
+}
@@ -40,7 +40,7 @@ pub fn decode_zigzag32(value: u32) -> i32 {
 /// let encoded_value = encode_zigzag64(-100);
 /// ```
 pub fn encode_zigzag64(value: i64) -> u64 {
-    ((value << 1) ^ (value >> 31)) as u64
+    ((value << 1) ^ (value >> 63)) as u64
 }
 
 /// Decode a 64 bit zigzag encoded integer to a signed 32bit integer.
@@ -97,6 +97,9 @@ mod tests {
     #[case(0, 0)]
     #[case(1, 2)]
     #[case(10, 20)]
+    #[case(-4294967296, 8589934591)]
+    #[case(i64::MIN, u64::MAX)]
+    #[case(i64::MAX, u64::MAX - 1)]
     fn test_encode_zigzag64(#[case] value: i64, #[case] expected_result: u64) {
         // Act
         let result = encode_zigzag64(value);
@@ -111,6 +114,9 @@ mod tests {
     #[case(0, 0)]
     #[case(2, 1)]
     #[case(20, 10)]
+    #[case(8589934591, -4294967296)]
+    #[case(u64::MAX, i64::MIN)]
+    #[case(u64::MAX - 1, i64::MAX)]
     fn test_decode_zigzag64(#[case] value: u64, #[case] expected_result: i64) {
         // Act
         let result = decode_zigzag64(value);